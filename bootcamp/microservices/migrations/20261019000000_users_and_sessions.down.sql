@@ -0,0 +1,4 @@
+-- Add down migration script here
+DROP TABLE refresh_tokens;
+DROP TABLE sessions;
+DROP TABLE users;
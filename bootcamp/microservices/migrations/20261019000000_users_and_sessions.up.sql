@@ -0,0 +1,29 @@
+-- Add up migration script here
+-- Kept to what both SQLite and Postgres understand, times are in seconds since the Unix epoch
+CREATE TABLE users (
+    user_uuid TEXT PRIMARY KEY,
+    username TEXT NOT NULL UNIQUE,
+    password TEXT NOT NULL
+);
+
+CREATE TABLE sessions (
+    session_token TEXT PRIMARY KEY,
+    user_uuid TEXT NOT NULL,
+    family TEXT NOT NULL,
+    created_at BIGINT NOT NULL,
+    last_used_at BIGINT NOT NULL
+);
+
+CREATE INDEX sessions_user_uuid_idx ON sessions (user_uuid);
+CREATE INDEX sessions_family_idx ON sessions (family);
+
+CREATE TABLE refresh_tokens (
+    refresh_token TEXT PRIMARY KEY,
+    user_uuid TEXT NOT NULL,
+    family TEXT NOT NULL,
+    expires_at BIGINT NOT NULL,
+    used BOOLEAN NOT NULL
+);
+
+CREATE INDEX refresh_tokens_user_uuid_idx ON refresh_tokens (user_uuid);
+CREATE INDEX refresh_tokens_family_idx ON refresh_tokens (family);
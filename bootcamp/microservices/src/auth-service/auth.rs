@@ -1,13 +1,21 @@
-use std::sync::Mutex;
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
 
-use crate::{sessions::Sessions, users::Users};
+use crate::{
+    jwt_sessions::RevokedClaim,
+    sessions::{SessionTokens, Sessions},
+    users::Users,
+};
 
 use tonic::{Request, Response, Status};
 
 use authentication::auth_server::Auth;
 use authentication::{
-    SignInRequest, SignInResponse, SignOutRequest, SignOutResponse, SignUpRequest, SignUpResponse,
-    StatusCode,
+    GetJwksRequest, GetJwksResponse, GetRevocationsRequest, GetRevocationsResponse,
+    ListSessionsRequest, ListSessionsResponse, RefreshSessionRequest, RefreshSessionResponse,
+    SignInRequest, SignInResponse, SignOutEverywhereRequest, SignOutEverywhereResponse,
+    SignOutRequest, SignOutResponse, SignUpRequest, SignUpResponse, StatusCode,
+    ValidateSessionRequest, ValidateSessionResponse,
 };
 
 pub mod authentication {
@@ -19,14 +27,14 @@ pub use authentication::auth_server::AuthServer;
 pub use tonic::transport::Server;
 
 pub struct AuthService {
-    users_service: Box<Mutex<dyn Users + Send + Sync>>,
-    sessions_service: Box<Mutex<dyn Sessions + Send + Sync>>,
+    users_service: Box<dyn Users + Send + Sync>,
+    sessions_service: Arc<dyn Sessions + Send + Sync>,
 }
 
 impl AuthService {
     pub fn new(
-        users_service: Box<Mutex<dyn Users + Send + Sync>>,
-        sessions_service: Box<Mutex<dyn Sessions + Send + Sync>>,
+        users_service: Box<dyn Users + Send + Sync>,
+        sessions_service: Arc<dyn Sessions + Send + Sync>,
     ) -> Self {
         Self {
             users_service,
@@ -45,12 +53,12 @@ impl Auth for AuthService {
 
         let req = request.into_inner();
 
-        // Get user's uuid from `users_service`. Panic if the lock is poisoned.
+        // Get user's uuid from `users_service`.
         let result: Option<String> = self
             .users_service
-            .lock()
-            .expect("Poisoned users service mutex")
-            .get_user_uuid(req.username, req.password);
+            .get_user_uuid(req.username, req.password)
+            .await
+            .map_err(storage_error)?;
 
         // Match on `result`. If `result` is `None` return a SignInResponse with a the `status_code` set to `Failure`
         // and `user_uuid`/`session_token` set to empty strings.
@@ -59,19 +67,21 @@ impl Auth for AuthService {
                 status_code: StatusCode::Failure.into(),
                 user_uuid: String::new(),
                 session_token: String::new(),
+                refresh_token: String::new(),
             },
             Some(uuid) => {
-                // Create new session using `sessions_service`. Panic if the lock is poisoned.
-                let session_token: String = self
+                // Create new session using `sessions_service`.
+                let tokens: SessionTokens = self
                     .sessions_service
-                    .lock()
-                    .expect("Poisoned sessions_service mutex")
-                    .create_session(&uuid);
+                    .create_session(&uuid)
+                    .await
+                    .map_err(storage_error)?;
 
                 SignInResponse {
                     status_code: StatusCode::Success.into(),
                     user_uuid: uuid,
-                    session_token,
+                    session_token: tokens.session_token,
+                    refresh_token: tokens.refresh_token,
                 }
             }
         };
@@ -87,16 +97,16 @@ impl Auth for AuthService {
 
         let req = request.into_inner();
 
-        // Create a new user through `users_service`. Panic if the lock is poisoned.
-        let result: Result<(), String> = self
+        // Create a new user through `users_service`.
+        let created = self
             .users_service
-            .lock()
-            .expect("Poisoned users service mutex")
-            .create_user(req.username, req.password);
+            .create_user(req.username, req.password)
+            .await
+            .map_err(storage_error)?;
 
-        let status_code = match result {
-            Ok(_) => StatusCode::Success,
-            Err(_) => StatusCode::Failure,
+        let status_code = match created {
+            true => StatusCode::Success,
+            false => StatusCode::Failure,
         };
 
         Ok(Response::new(SignUpResponse {
@@ -112,29 +122,244 @@ impl Auth for AuthService {
 
         let req = request.into_inner();
 
-        self.sessions_service
-            .lock()
-            .expect("Poisoned sessions_service mutex")
-            .delete_session(&req.session_token);
+        // Delete the session through `sessions_service`.
+        let deleted = self
+            .sessions_service
+            .delete_session(&req.session_token)
+            .await
+            .map_err(storage_error)?;
+
+        let status_code = match deleted {
+            true => StatusCode::Success,
+            false => StatusCode::Failure,
+        };
 
         let reply = SignOutResponse {
-            status_code: StatusCode::Success.into(),
+            status_code: status_code.into(),
         };
 
         Ok(Response::new(reply))
     }
+
+    async fn refresh_session(
+        &self,
+        request: Request<RefreshSessionRequest>,
+    ) -> Result<Response<RefreshSessionResponse>, Status> {
+        println!("Got a request: {:?}", request);
+
+        let req = request.into_inner();
+
+        // Rotate the refresh token through `sessions_service`.
+        let result: Option<SessionTokens> = self
+            .sessions_service
+            .refresh_session(&req.refresh_token)
+            .await
+            .map_err(storage_error)?;
+
+        let response = match result {
+            None => RefreshSessionResponse {
+                status_code: StatusCode::Failure.into(),
+                session_token: String::new(),
+                refresh_token: String::new(),
+            },
+            Some(tokens) => RefreshSessionResponse {
+                status_code: StatusCode::Success.into(),
+                session_token: tokens.session_token,
+                refresh_token: tokens.refresh_token,
+            },
+        };
+
+        Ok(Response::new(response))
+    }
+
+    async fn sign_out_everywhere(
+        &self,
+        request: Request<SignOutEverywhereRequest>,
+    ) -> Result<Response<SignOutEverywhereResponse>, Status> {
+        println!("Got a request: {:?}", request);
+
+        let req = request.into_inner();
+
+        // Only the owner of a valid session may end the sessions of its user
+        let result = self
+            .sessions_service
+            .renew_session(&req.session_token)
+            .await
+            .map_err(storage_error)?;
+        let response = match result {
+            None => SignOutEverywhereResponse {
+                status_code: StatusCode::Failure.into(),
+                sessions_ended: None,
+            },
+            Some(session) => SignOutEverywhereResponse {
+                status_code: StatusCode::Success.into(),
+                sessions_ended: self
+                    .sessions_service
+                    .delete_user_sessions(&session.user_uuid)
+                    .await
+                    .map_err(storage_error)?
+                    .map(|ended| ended as u32),
+            },
+        };
+
+        Ok(Response::new(response))
+    }
+
+    async fn validate_session(
+        &self,
+        request: Request<ValidateSessionRequest>,
+    ) -> Result<Response<ValidateSessionResponse>, Status> {
+        println!("Got a request: {:?}", request);
+
+        let req = request.into_inner();
+
+        // Get the session from `sessions_service`, validating it counts as using it.
+        let result = self
+            .sessions_service
+            .renew_session(&req.session_token)
+            .await
+            .map_err(storage_error)?;
+
+        let response = match result {
+            None => ValidateSessionResponse {
+                status_code: StatusCode::Failure.into(),
+                user_uuid: String::new(),
+                expires_at: 0,
+            },
+            Some(session) => ValidateSessionResponse {
+                status_code: StatusCode::Success.into(),
+                user_uuid: session.user_uuid,
+                expires_at: unix_seconds(session.expires_at),
+            },
+        };
+
+        Ok(Response::new(response))
+    }
+
+    async fn list_sessions(
+        &self,
+        request: Request<ListSessionsRequest>,
+    ) -> Result<Response<ListSessionsResponse>, Status> {
+        println!("Got a request: {:?}", request);
+
+        let req = request.into_inner();
+
+        // Only the owner of a valid session may list the sessions of its user
+        let result = self
+            .sessions_service
+            .renew_session(&req.session_token)
+            .await
+            .map_err(storage_error)?;
+        let response = match result {
+            None => ListSessionsResponse {
+                status_code: StatusCode::Failure.into(),
+                sessions: Vec::new(),
+            },
+            Some(session) => ListSessionsResponse {
+                status_code: StatusCode::Success.into(),
+                sessions: self
+                    .sessions_service
+                    .get_user_sessions(&session.user_uuid)
+                    .await
+                    .map_err(storage_error)?
+                    .ok_or_else(|| Status::unimplemented("Sessions are not kept by the backend"))?
+                    .into_iter()
+                    .map(|(token, session)| authentication::Session {
+                        created_at: unix_seconds(session.created_at),
+                        expires_at: unix_seconds(session.expires_at),
+                        current: token == req.session_token,
+                    })
+                    .collect(),
+            },
+        };
+
+        Ok(Response::new(response))
+    }
+
+    async fn get_jwks(
+        &self,
+        request: Request<GetJwksRequest>,
+    ) -> Result<Response<GetJwksResponse>, Status> {
+        println!("Got a request: {:?}", request);
+
+        let keys = self
+            .sessions_service
+            .public_keys()
+            .into_iter()
+            .map(|jwk| authentication::Jwk {
+                kty: "OKP".to_owned(),
+                crv: "Ed25519".to_owned(),
+                alg: "EdDSA".to_owned(),
+                kid: jwk.kid,
+                x: jwk.x,
+            })
+            .collect();
+
+        Ok(Response::new(GetJwksResponse { keys }))
+    }
+
+    async fn get_revocations(
+        &self,
+        request: Request<GetRevocationsRequest>,
+    ) -> Result<Response<GetRevocationsResponse>, Status> {
+        println!("Got a request: {:?}", request);
+
+        let revocations = self.sessions_service.revocations();
+        let claims = |claims: Vec<RevokedClaim>| {
+            claims
+                .into_iter()
+                .map(|claim| authentication::RevokedClaim {
+                    value: claim.value,
+                    expires_at: claim.expires_at as i64,
+                })
+                .collect()
+        };
+
+        Ok(Response::new(GetRevocationsResponse {
+            tokens: claims(revocations.tokens),
+            families: claims(revocations.families),
+            users: revocations
+                .users
+                .into_iter()
+                .map(|user| authentication::RevokedUser {
+                    user_uuid: user.user_uuid,
+                    issued_until: user.issued_until,
+                    expires_at: user.expires_at as i64,
+                })
+                .collect(),
+        }))
+    }
+}
+
+// The errors of the backends are logged rather than sent, as they tell about their storage
+fn storage_error(e: String) -> Status {
+    println!("Storage error: {e}");
+    Status::internal("storage error")
+}
+
+// Times are sent as seconds since the Unix epoch
+fn unix_seconds(time: SystemTime) -> i64 {
+    time.duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs() as i64)
+        .unwrap_or_default()
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::{sessions::SessionsImpl, users::UsersImpl};
+    use crate::{
+        clock::SystemClock,
+        database::tests::TestDatabase,
+        jwt_sessions::{tests::generate_key, JwtSessions},
+        sessions::{DatabaseSessions, SessionConfig, SessionsImpl},
+        users::{DatabaseUsers, UsersImpl},
+    };
 
     use super::*;
 
     #[tokio::test]
     async fn sign_in_should_fail_if_user_not_found() {
-        let users_service = Box::new(Mutex::new(UsersImpl::default()));
-        let sessions_service = Box::new(Mutex::new(SessionsImpl::default()));
+        let users_service = Box::new(UsersImpl::default());
+        let sessions_service = Arc::new(SessionsImpl::default());
 
         let auth_service = AuthService::new(users_service, sessions_service);
 
@@ -145,19 +370,22 @@ mod tests {
 
         let result = auth_service.sign_in(request).await.unwrap().into_inner();
 
-        assert_eq!(result.status_code, StatusCode::Failure.into());
+        assert_eq!(result.status_code, i32::from(StatusCode::Failure));
         assert!(result.user_uuid.is_empty());
         assert!(result.session_token.is_empty());
     }
 
     #[tokio::test]
     async fn sign_in_should_fail_if_incorrect_password() {
-        let mut users_service = UsersImpl::default();
+        let users_service = UsersImpl::default();
 
-        let _ = users_service.create_user("123456".to_owned(), "654321".to_owned());
+        users_service
+            .create_user("123456".to_owned(), "654321".to_owned())
+            .await
+            .unwrap();
 
-        let users_service = Box::new(Mutex::new(users_service));
-        let sessions_service = Box::new(Mutex::new(SessionsImpl::default()));
+        let users_service = Box::new(users_service);
+        let sessions_service = Arc::new(SessionsImpl::default());
 
         let auth_service = AuthService::new(users_service, sessions_service);
 
@@ -168,19 +396,22 @@ mod tests {
 
         let result = auth_service.sign_in(request).await.unwrap().into_inner();
 
-        assert_eq!(result.status_code, StatusCode::Failure.into());
+        assert_eq!(result.status_code, i32::from(StatusCode::Failure));
         assert!(result.user_uuid.is_empty());
         assert!(result.session_token.is_empty());
     }
 
     #[tokio::test]
     async fn sign_in_should_succeed() {
-        let mut users_service = UsersImpl::default();
+        let users_service = UsersImpl::default();
 
-        let _ = users_service.create_user("123456".to_owned(), "654321".to_owned());
+        users_service
+            .create_user("123456".to_owned(), "654321".to_owned())
+            .await
+            .unwrap();
 
-        let users_service = Box::new(Mutex::new(users_service));
-        let sessions_service = Box::new(Mutex::new(SessionsImpl::default()));
+        let users_service = Box::new(users_service);
+        let sessions_service = Arc::new(SessionsImpl::default());
 
         let auth_service = AuthService::new(users_service, sessions_service);
 
@@ -191,19 +422,23 @@ mod tests {
 
         let result = auth_service.sign_in(request).await.unwrap().into_inner();
 
-        assert_eq!(result.status_code, StatusCode::Success.into());
+        assert_eq!(result.status_code, i32::from(StatusCode::Success));
         assert!(!result.user_uuid.is_empty());
         assert!(!result.session_token.is_empty());
+        assert!(!result.refresh_token.is_empty());
     }
 
     #[tokio::test]
     async fn sign_up_should_fail_if_username_exists() {
-        let mut users_service = UsersImpl::default();
+        let users_service = UsersImpl::default();
 
-        let _ = users_service.create_user("123456".to_owned(), "654321".to_owned());
+        users_service
+            .create_user("123456".to_owned(), "654321".to_owned())
+            .await
+            .unwrap();
 
-        let users_service = Box::new(Mutex::new(users_service));
-        let sessions_service = Box::new(Mutex::new(SessionsImpl::default()));
+        let users_service = Box::new(users_service);
+        let sessions_service = Arc::new(SessionsImpl::default());
 
         let auth_service = AuthService::new(users_service, sessions_service);
 
@@ -214,13 +449,36 @@ mod tests {
 
         let result = auth_service.sign_up(request).await.unwrap();
 
-        assert_eq!(result.into_inner().status_code, StatusCode::Failure.into());
+        assert_eq!(
+            result.into_inner().status_code,
+            i32::from(StatusCode::Failure)
+        );
+    }
+
+    #[tokio::test]
+    async fn sign_up_should_not_leak_storage_errors() {
+        let database = TestDatabase::sqlite().await;
+        database.pool.close().await;
+        let users_service = Box::new(DatabaseUsers::new(database.pool.clone()));
+        let sessions_service = Arc::new(SessionsImpl::default());
+
+        let auth_service = AuthService::new(users_service, sessions_service);
+
+        let request = tonic::Request::new(SignUpRequest {
+            username: "123456".to_owned(),
+            password: "654321".to_owned(),
+        });
+
+        let status = auth_service.sign_up(request).await.unwrap_err();
+
+        assert_eq!(status.code(), tonic::Code::Internal);
+        assert_eq!(status.message(), "storage error");
     }
 
     #[tokio::test]
     async fn sign_up_should_succeed() {
-        let users_service = Box::new(Mutex::new(UsersImpl::default()));
-        let sessions_service = Box::new(Mutex::new(SessionsImpl::default()));
+        let users_service = Box::new(UsersImpl::default());
+        let sessions_service = Arc::new(SessionsImpl::default());
 
         let auth_service = AuthService::new(users_service, sessions_service);
 
@@ -231,13 +489,16 @@ mod tests {
 
         let result = auth_service.sign_up(request).await.unwrap();
 
-        assert_eq!(result.into_inner().status_code, StatusCode::Success.into());
+        assert_eq!(
+            result.into_inner().status_code,
+            i32::from(StatusCode::Success)
+        );
     }
 
     #[tokio::test]
-    async fn sign_out_should_succeed() {
-        let users_service = Box::new(Mutex::new(UsersImpl::default()));
-        let sessions_service = Box::new(Mutex::new(SessionsImpl::default()));
+    async fn sign_out_should_fail_if_session_not_found() {
+        let users_service = Box::new(UsersImpl::default());
+        let sessions_service = Arc::new(SessionsImpl::default());
 
         let auth_service = AuthService::new(users_service, sessions_service);
 
@@ -247,6 +508,559 @@ mod tests {
 
         let result = auth_service.sign_out(request).await.unwrap();
 
-        assert_eq!(result.into_inner().status_code, StatusCode::Success.into());
+        assert_eq!(
+            result.into_inner().status_code,
+            i32::from(StatusCode::Failure)
+        );
+    }
+
+    #[tokio::test]
+    async fn sign_out_should_succeed() {
+        let sessions_service = SessionsImpl::default();
+
+        let session_token = sessions_service
+            .create_session("123456")
+            .await
+            .unwrap()
+            .session_token;
+        let other_session_token = sessions_service
+            .create_session("123456")
+            .await
+            .unwrap()
+            .session_token;
+
+        let users_service = Box::new(UsersImpl::default());
+        let sessions_service = Arc::new(sessions_service);
+
+        let auth_service = AuthService::new(users_service, sessions_service);
+
+        let request = tonic::Request::new(SignOutRequest {
+            session_token: session_token.clone(),
+        });
+
+        let result = auth_service.sign_out(request).await.unwrap();
+
+        assert_eq!(
+            result.into_inner().status_code,
+            i32::from(StatusCode::Success)
+        );
+
+        let sessions_service = &auth_service.sessions_service;
+        assert!(sessions_service
+            .get_session(&session_token)
+            .await
+            .unwrap()
+            .is_none());
+        assert!(sessions_service
+            .get_session(&other_session_token)
+            .await
+            .unwrap()
+            .is_some());
+    }
+
+    #[tokio::test]
+    async fn refresh_session_should_fail_if_refresh_token_not_found() {
+        let users_service = Box::new(UsersImpl::default());
+        let sessions_service = Arc::new(SessionsImpl::default());
+
+        let auth_service = AuthService::new(users_service, sessions_service);
+
+        let request = tonic::Request::new(RefreshSessionRequest {
+            refresh_token: "unknown".to_owned(),
+        });
+
+        let result = auth_service
+            .refresh_session(request)
+            .await
+            .unwrap()
+            .into_inner();
+
+        assert_eq!(result.status_code, i32::from(StatusCode::Failure));
+        assert!(result.session_token.is_empty());
+        assert!(result.refresh_token.is_empty());
+    }
+
+    #[tokio::test]
+    async fn refresh_session_should_succeed() {
+        let sessions_service = SessionsImpl::default();
+
+        let tokens = sessions_service.create_session("123456").await.unwrap();
+
+        let users_service = Box::new(UsersImpl::default());
+        let sessions_service = Arc::new(sessions_service);
+
+        let auth_service = AuthService::new(users_service, sessions_service);
+
+        let request = tonic::Request::new(RefreshSessionRequest {
+            refresh_token: tokens.refresh_token.clone(),
+        });
+
+        let result = auth_service
+            .refresh_session(request)
+            .await
+            .unwrap()
+            .into_inner();
+
+        assert_eq!(result.status_code, i32::from(StatusCode::Success));
+        assert_ne!(result.session_token, tokens.session_token);
+        assert_ne!(result.refresh_token, tokens.refresh_token);
+
+        let sessions_service = &auth_service.sessions_service;
+        assert!(sessions_service
+            .get_session(&tokens.session_token)
+            .await
+            .unwrap()
+            .is_none());
+        assert_eq!(
+            sessions_service
+                .get_session(&result.session_token)
+                .await
+                .unwrap()
+                .unwrap()
+                .user_uuid,
+            "123456"
+        );
+    }
+
+    #[tokio::test]
+    async fn refresh_session_should_fail_if_refresh_token_reused() {
+        let sessions_service = SessionsImpl::default();
+
+        let tokens = sessions_service.create_session("123456").await.unwrap();
+
+        let users_service = Box::new(UsersImpl::default());
+        let sessions_service = Arc::new(sessions_service);
+
+        let auth_service = AuthService::new(users_service, sessions_service);
+
+        let request = tonic::Request::new(RefreshSessionRequest {
+            refresh_token: tokens.refresh_token.clone(),
+        });
+        let refreshed = auth_service
+            .refresh_session(request)
+            .await
+            .unwrap()
+            .into_inner();
+
+        let request = tonic::Request::new(RefreshSessionRequest {
+            refresh_token: tokens.refresh_token,
+        });
+        let result = auth_service
+            .refresh_session(request)
+            .await
+            .unwrap()
+            .into_inner();
+
+        assert_eq!(result.status_code, i32::from(StatusCode::Failure));
+
+        let sessions_service = &auth_service.sessions_service;
+        assert!(sessions_service
+            .get_session(&refreshed.session_token)
+            .await
+            .unwrap()
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn sign_out_everywhere_should_fail_if_session_not_found() {
+        let users_service = Box::new(UsersImpl::default());
+        let sessions_service = Arc::new(SessionsImpl::default());
+
+        let auth_service = AuthService::new(users_service, sessions_service);
+
+        let request = tonic::Request::new(SignOutEverywhereRequest {
+            session_token: "unknown".to_owned(),
+        });
+
+        let result = auth_service
+            .sign_out_everywhere(request)
+            .await
+            .unwrap()
+            .into_inner();
+
+        assert_eq!(result.status_code, i32::from(StatusCode::Failure));
+        assert_eq!(result.sessions_ended, None);
+    }
+
+    #[tokio::test]
+    async fn sign_out_everywhere_should_succeed() {
+        let sessions_service = SessionsImpl::default();
+
+        let session_token = sessions_service
+            .create_session("123456")
+            .await
+            .unwrap()
+            .session_token;
+        sessions_service.create_session("123456").await.unwrap();
+        let other_user_session_token = sessions_service
+            .create_session("654321")
+            .await
+            .unwrap()
+            .session_token;
+
+        let users_service = Box::new(UsersImpl::default());
+        let sessions_service = Arc::new(sessions_service);
+
+        let auth_service = AuthService::new(users_service, sessions_service);
+
+        let request = tonic::Request::new(SignOutEverywhereRequest { session_token });
+
+        let result = auth_service
+            .sign_out_everywhere(request)
+            .await
+            .unwrap()
+            .into_inner();
+
+        assert_eq!(result.status_code, i32::from(StatusCode::Success));
+        assert_eq!(result.sessions_ended, Some(2));
+
+        let sessions_service = &auth_service.sessions_service;
+        assert!(sessions_service
+            .get_user_sessions("123456")
+            .await
+            .unwrap()
+            .unwrap()
+            .is_empty());
+        assert!(sessions_service
+            .get_session(&other_user_session_token)
+            .await
+            .unwrap()
+            .is_some());
+    }
+
+    #[tokio::test]
+    async fn sign_out_everywhere_should_not_count_signed_sessions() {
+        let sessions_service = JwtSessions::new(
+            vec![generate_key("1")],
+            SessionConfig::default(),
+            Box::new(SystemClock),
+        )
+        .unwrap();
+
+        let session_token = sessions_service
+            .create_session("123456")
+            .await
+            .unwrap()
+            .session_token;
+        let other_session_token = sessions_service
+            .create_session("123456")
+            .await
+            .unwrap()
+            .session_token;
+
+        let users_service = Box::new(UsersImpl::default());
+        let sessions_service = Arc::new(sessions_service);
+
+        let auth_service = AuthService::new(users_service, sessions_service);
+
+        let request = tonic::Request::new(SignOutEverywhereRequest { session_token });
+
+        let result = auth_service
+            .sign_out_everywhere(request)
+            .await
+            .unwrap()
+            .into_inner();
+
+        assert_eq!(result.status_code, i32::from(StatusCode::Success));
+        assert_eq!(result.sessions_ended, None);
+        assert!(auth_service
+            .sessions_service
+            .get_session(&other_session_token)
+            .await
+            .unwrap()
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn validate_session_should_fail_if_session_not_found() {
+        let users_service = Box::new(UsersImpl::default());
+        let sessions_service = Arc::new(SessionsImpl::default());
+
+        let auth_service = AuthService::new(users_service, sessions_service);
+
+        let request = tonic::Request::new(ValidateSessionRequest {
+            session_token: "unknown".to_owned(),
+        });
+
+        let result = auth_service
+            .validate_session(request)
+            .await
+            .unwrap()
+            .into_inner();
+
+        assert_eq!(result.status_code, i32::from(StatusCode::Failure));
+        assert!(result.user_uuid.is_empty());
+        assert_eq!(result.expires_at, 0);
+    }
+
+    #[tokio::test]
+    async fn validate_session_should_not_leak_storage_errors() {
+        let database = TestDatabase::sqlite().await;
+        database.pool.close().await;
+        let users_service = Box::new(UsersImpl::default());
+        let sessions_service = Arc::new(DatabaseSessions::new(
+            database.pool.clone(),
+            SessionConfig::default(),
+            Box::new(SystemClock),
+        ));
+
+        let auth_service = AuthService::new(users_service, sessions_service);
+
+        let request = tonic::Request::new(ValidateSessionRequest {
+            session_token: "123456".to_owned(),
+        });
+
+        let status = auth_service.validate_session(request).await.unwrap_err();
+
+        assert_eq!(status.code(), tonic::Code::Internal);
+        assert_eq!(status.message(), "storage error");
+    }
+
+    #[tokio::test]
+    async fn validate_session_should_succeed() {
+        let sessions_service = SessionsImpl::default();
+
+        let session_token = sessions_service
+            .create_session("123456")
+            .await
+            .unwrap()
+            .session_token;
+
+        let users_service = Box::new(UsersImpl::default());
+        let sessions_service = Arc::new(sessions_service);
+
+        let auth_service = AuthService::new(users_service, sessions_service);
+
+        let request = tonic::Request::new(ValidateSessionRequest { session_token });
+
+        let result = auth_service
+            .validate_session(request)
+            .await
+            .unwrap()
+            .into_inner();
+
+        assert_eq!(result.status_code, i32::from(StatusCode::Success));
+        assert_eq!(result.user_uuid, "123456");
+        assert!(result.expires_at > unix_seconds(SystemTime::now()));
+    }
+
+    #[tokio::test]
+    async fn list_sessions_should_fail_if_session_not_found() {
+        let users_service = Box::new(UsersImpl::default());
+        let sessions_service = Arc::new(SessionsImpl::default());
+
+        let auth_service = AuthService::new(users_service, sessions_service);
+
+        let request = tonic::Request::new(ListSessionsRequest {
+            session_token: "unknown".to_owned(),
+        });
+
+        let result = auth_service
+            .list_sessions(request)
+            .await
+            .unwrap()
+            .into_inner();
+
+        assert_eq!(result.status_code, i32::from(StatusCode::Failure));
+        assert!(result.sessions.is_empty());
+    }
+
+    #[tokio::test]
+    async fn list_sessions_should_succeed() {
+        let sessions_service = SessionsImpl::default();
+
+        sessions_service.create_session("123456").await.unwrap();
+        let session_token = sessions_service
+            .create_session("123456")
+            .await
+            .unwrap()
+            .session_token;
+        sessions_service.create_session("654321").await.unwrap();
+
+        let users_service = Box::new(UsersImpl::default());
+        let sessions_service = Arc::new(sessions_service);
+
+        let auth_service = AuthService::new(users_service, sessions_service);
+
+        let request = tonic::Request::new(ListSessionsRequest { session_token });
+
+        let result = auth_service
+            .list_sessions(request)
+            .await
+            .unwrap()
+            .into_inner();
+
+        assert_eq!(result.status_code, i32::from(StatusCode::Success));
+        assert_eq!(result.sessions.len(), 2);
+        assert_eq!(
+            result
+                .sessions
+                .iter()
+                .filter(|session| session.current)
+                .count(),
+            1
+        );
+        assert!(result.sessions[0].expires_at > result.sessions[0].created_at);
+    }
+
+    #[tokio::test]
+    async fn list_sessions_should_be_unimplemented_with_signed_sessions() {
+        let sessions_service = JwtSessions::new(
+            vec![generate_key("1")],
+            SessionConfig::default(),
+            Box::new(SystemClock),
+        )
+        .unwrap();
+
+        let session_token = sessions_service
+            .create_session("123456")
+            .await
+            .unwrap()
+            .session_token;
+
+        let users_service = Box::new(UsersImpl::default());
+        let sessions_service = Arc::new(sessions_service);
+
+        let auth_service = AuthService::new(users_service, sessions_service);
+
+        let request = tonic::Request::new(ListSessionsRequest { session_token });
+
+        let result = auth_service.list_sessions(request).await.unwrap_err();
+
+        assert_eq!(result.code(), tonic::Code::Unimplemented);
+    }
+
+    #[tokio::test]
+    async fn get_jwks_should_be_empty_if_sessions_not_signed() {
+        let users_service = Box::new(UsersImpl::default());
+        let sessions_service = Arc::new(SessionsImpl::default());
+
+        let auth_service = AuthService::new(users_service, sessions_service);
+
+        let request = tonic::Request::new(GetJwksRequest {});
+
+        let result = auth_service.get_jwks(request).await.unwrap().into_inner();
+
+        assert!(result.keys.is_empty());
+    }
+
+    #[tokio::test]
+    async fn get_jwks_should_succeed() {
+        let sessions_service = JwtSessions::new(
+            vec![generate_key("1"), generate_key("2")],
+            SessionConfig::default(),
+            Box::new(SystemClock),
+        )
+        .unwrap();
+
+        let users_service = Box::new(UsersImpl::default());
+        let sessions_service = Arc::new(sessions_service);
+
+        let auth_service = AuthService::new(users_service, sessions_service);
+
+        let request = tonic::Request::new(GetJwksRequest {});
+
+        let result = auth_service.get_jwks(request).await.unwrap().into_inner();
+
+        assert_eq!(result.keys.len(), 2);
+        assert_eq!(result.keys[0].kid, "1");
+        assert_eq!(result.keys[0].crv, "Ed25519");
+        assert!(!result.keys[0].x.is_empty());
+    }
+
+    #[tokio::test]
+    async fn validate_session_should_succeed_with_signed_sessions() {
+        let sessions_service = JwtSessions::new(
+            vec![generate_key("1")],
+            SessionConfig::default(),
+            Box::new(SystemClock),
+        )
+        .unwrap();
+
+        let session_token = sessions_service
+            .create_session("123456")
+            .await
+            .unwrap()
+            .session_token;
+
+        let users_service = Box::new(UsersImpl::default());
+        let sessions_service = Arc::new(sessions_service);
+
+        let auth_service = AuthService::new(users_service, sessions_service);
+
+        let request = tonic::Request::new(ValidateSessionRequest { session_token });
+
+        let result = auth_service
+            .validate_session(request)
+            .await
+            .unwrap()
+            .into_inner();
+
+        assert_eq!(result.status_code, i32::from(StatusCode::Success));
+        assert_eq!(result.user_uuid, "123456");
+    }
+
+    #[tokio::test]
+    async fn get_revocations_should_be_empty_if_sessions_not_signed() {
+        let users_service = Box::new(UsersImpl::default());
+        let sessions_service = Arc::new(SessionsImpl::default());
+
+        let auth_service = AuthService::new(users_service, sessions_service);
+
+        let request = tonic::Request::new(GetRevocationsRequest {});
+
+        let result = auth_service
+            .get_revocations(request)
+            .await
+            .unwrap()
+            .into_inner();
+
+        assert_eq!(result, GetRevocationsResponse::default());
+    }
+
+    #[tokio::test]
+    async fn get_revocations_should_list_signed_out_sessions() {
+        let sessions_service = JwtSessions::new(
+            vec![generate_key("1")],
+            SessionConfig::default(),
+            Box::new(SystemClock),
+        )
+        .unwrap();
+
+        let session_token = sessions_service
+            .create_session("123456")
+            .await
+            .unwrap()
+            .session_token;
+        let other_session_token = sessions_service
+            .create_session("654321")
+            .await
+            .unwrap()
+            .session_token;
+
+        let users_service = Box::new(UsersImpl::default());
+        let sessions_service = Arc::new(sessions_service);
+
+        let auth_service = AuthService::new(users_service, sessions_service);
+
+        let request = tonic::Request::new(SignOutRequest { session_token });
+        auth_service.sign_out(request).await.unwrap();
+        let request = tonic::Request::new(SignOutEverywhereRequest {
+            session_token: other_session_token,
+        });
+        auth_service.sign_out_everywhere(request).await.unwrap();
+
+        let request = tonic::Request::new(GetRevocationsRequest {});
+
+        let result = auth_service
+            .get_revocations(request)
+            .await
+            .unwrap()
+            .into_inner();
+
+        assert_eq!(result.tokens.len(), 1);
+        assert_eq!(result.families.len(), 1);
+        assert_eq!(result.users.len(), 1);
+        assert_eq!(result.users[0].user_uuid, "654321");
     }
 }
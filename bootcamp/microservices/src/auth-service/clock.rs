@@ -0,0 +1,59 @@
+use std::time::SystemTime;
+
+// Source of the current time, so that expiry can be tested without waiting.
+pub trait Clock {
+    fn now(&self) -> SystemTime;
+}
+
+#[derive(Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> SystemTime {
+        SystemTime::now()
+    }
+}
+
+#[cfg(test)]
+pub use manual::ManualClock;
+
+#[cfg(test)]
+mod manual {
+    use std::sync::{Arc, Mutex};
+    use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+    use super::Clock;
+
+    // Clock which only moves when told to. Clones share the same time.
+    #[derive(Clone)]
+    pub struct ManualClock {
+        now: Arc<Mutex<SystemTime>>,
+    }
+
+    // Starts on a whole second, as databases keep times to the second
+    impl Default for ManualClock {
+        fn default() -> Self {
+            let since_epoch = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .expect("The system clock is before the epoch");
+
+            Self {
+                now: Arc::new(Mutex::new(
+                    UNIX_EPOCH + Duration::from_secs(since_epoch.as_secs()),
+                )),
+            }
+        }
+    }
+
+    impl ManualClock {
+        pub fn advance(&self, duration: Duration) {
+            *self.now.lock().expect("Poisoned clock mutex") += duration;
+        }
+    }
+
+    impl Clock for ManualClock {
+        fn now(&self) -> SystemTime {
+            *self.now.lock().expect("Poisoned clock mutex")
+        }
+    }
+}
@@ -0,0 +1,124 @@
+use sqlx::any::{install_default_drivers, AnyPoolOptions};
+use sqlx::AnyPool;
+
+// Connects to the database of the url, `sqlite://auth.db?mode=rwc` or `postgres://...`, and
+// brings its schema up to date. The migrations are written for both.
+pub async fn connect(url: &str) -> Result<AnyPool, String> {
+    install_default_drivers();
+
+    let pool = AnyPoolOptions::new()
+        .connect(url)
+        .await
+        .map_err(|e| format!("Failed to connect to the database.\n{e:?}"))?;
+    migrate(&pool).await?;
+
+    Ok(pool)
+}
+
+async fn migrate(pool: &AnyPool) -> Result<(), String> {
+    sqlx::migrate!()
+        .run(pool)
+        .await
+        .map_err(|e| format!("Failed to migrate the database.\n{e:?}"))
+}
+
+#[cfg(test)]
+pub mod tests {
+    use std::env;
+    use std::time::Duration;
+
+    use sqlx::{AnyConnection, Connection};
+    use uuid::Uuid;
+
+    use super::*;
+
+    // Database of a single test, on SQLite or Postgres
+    pub struct TestDatabase {
+        pub pool: AnyPool,
+        postgres: Option<(String, String)>,
+    }
+
+    impl TestDatabase {
+        // In memory, on a single connection as every connection has a database of its own
+        pub async fn sqlite() -> Self {
+            install_default_drivers();
+
+            let pool = AnyPoolOptions::new()
+                .max_connections(1)
+                .min_connections(1)
+                .idle_timeout(None::<Duration>)
+                .max_lifetime(None::<Duration>)
+                .connect("sqlite::memory:")
+                .await
+                .unwrap();
+            migrate(&pool).await.unwrap();
+
+            Self {
+                pool,
+                postgres: None,
+            }
+        }
+
+        // A new database on the server of DATABASE_URL. The tests using it are ignored unless
+        // run with `--ignored` or `--include-ignored`.
+        pub async fn postgres() -> Self {
+            let url = env::var("DATABASE_URL")
+                .expect("DATABASE_URL should point to a Postgres server to test against");
+            install_default_drivers();
+
+            let name = format!("auth_test_{}", Uuid::new_v4().simple());
+            let mut admin = AnyConnection::connect(&url).await.unwrap();
+            sqlx::query(&format!("CREATE DATABASE {name}"))
+                .execute(&mut admin)
+                .await
+                .unwrap();
+            admin.close().await.unwrap();
+
+            Self {
+                pool: connect(&with_database(&url, &name)).await.unwrap(),
+                postgres: Some((url, name)),
+            }
+        }
+
+        // Drops the Postgres database, those of failed tests are kept to look into
+        pub async fn close(self) {
+            self.pool.close().await;
+
+            if let Some((url, name)) = self.postgres {
+                let mut admin = AnyConnection::connect(&url).await.unwrap();
+                sqlx::query(&format!("DROP DATABASE {name} WITH (FORCE)"))
+                    .execute(&mut admin)
+                    .await
+                    .unwrap();
+                admin.close().await.unwrap();
+            }
+        }
+    }
+
+    // Replaces the database of a Postgres url
+    fn with_database(url: &str, name: &str) -> String {
+        let (base, query) = url.split_once('?').unwrap_or((url, ""));
+        let authority = base.find("://").map_or(0, |i| i + 3);
+        let base = match base[authority..].find('/') {
+            Some(i) => &base[..authority + i],
+            None => base,
+        };
+
+        match query {
+            "" => format!("{base}/{name}"),
+            query => format!("{base}/{name}?{query}"),
+        }
+    }
+
+    #[test]
+    fn should_replace_database_of_url() {
+        assert_eq!(
+            with_database("postgres://user:pw@localhost:5432", "test"),
+            "postgres://user:pw@localhost:5432/test"
+        );
+        assert_eq!(
+            with_database("postgres://localhost/postgres?sslmode=disable", "test"),
+            "postgres://localhost/test?sslmode=disable"
+        );
+    }
+}
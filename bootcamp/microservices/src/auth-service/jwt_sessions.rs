@@ -0,0 +1,834 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+use std::sync::{Mutex, MutexGuard};
+use std::time::{Duration, UNIX_EPOCH};
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use jsonwebtoken::{
+    decode, decode_header, encode, Algorithm, DecodingKey, EncodingKey, Header, Validation,
+};
+use ring::signature::{Ed25519KeyPair, KeyPair};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::clock::{Clock, SystemClock};
+use crate::sessions::{Session, SessionConfig, SessionTokens, Sessions};
+
+// Public key of a signing key, in the JWK format other services verify tokens with
+#[derive(Clone, Debug, PartialEq)]
+pub struct Jwk {
+    pub kid: String,
+    // Base64url encoded Ed25519 public key
+    pub x: String,
+}
+
+#[derive(Clone)]
+pub struct SigningKey {
+    kid: String,
+    encoding_key: EncodingKey,
+    decoding_key: DecodingKey,
+    jwk: Jwk,
+}
+
+impl SigningKey {
+    // Parses an Ed25519 private key in the PKCS#8 PEM format, as written by
+    // `openssl genpkey -algorithm ed25519`
+    pub fn from_pem(kid: &str, pem: &[u8]) -> Result<Self, String> {
+        let der = pem::parse(pem).map_err(|e| format!("Invalid PEM for key {kid}.\n{e:?}"))?;
+        let key_pair = Ed25519KeyPair::from_pkcs8_maybe_unchecked(der.contents())
+            .map_err(|e| format!("Invalid Ed25519 key {kid}.\n{e:?}"))?;
+        let x = URL_SAFE_NO_PAD.encode(key_pair.public_key().as_ref());
+
+        Ok(Self {
+            kid: kid.to_owned(),
+            encoding_key: EncodingKey::from_ed_der(der.contents()),
+            decoding_key: DecodingKey::from_ed_components(&x)
+                .map_err(|e| format!("Invalid Ed25519 key {kid}.\n{e:?}"))?,
+            jwk: Jwk {
+                kid: kid.to_owned(),
+                x,
+            },
+        })
+    }
+
+    // Loads every `<kid>.pem` file of the directory
+    pub fn from_dir(dir: &Path) -> Result<Vec<Self>, String> {
+        let entries =
+            fs::read_dir(dir).map_err(|e| format!("Failed to read keys from {dir:?}.\n{e:?}"))?;
+
+        let mut keys = Vec::new();
+        for entry in entries {
+            let path = entry
+                .map_err(|e| format!("Failed to read keys from {dir:?}.\n{e:?}"))?
+                .path();
+            if path.extension().is_none_or(|extension| extension != "pem") {
+                continue;
+            }
+            let Some(kid) = path.file_stem().and_then(|stem| stem.to_str()) else {
+                continue;
+            };
+            let pem = fs::read(&path).map_err(|e| format!("Failed to read {path:?}.\n{e:?}"))?;
+            keys.push(Self::from_pem(kid, &pem)?);
+        }
+
+        Ok(keys)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum TokenType {
+    Session,
+    Refresh,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Claims {
+    sub: String,
+    jti: String,
+    // Seconds since the Unix epoch with their fraction, so that a token issued in the same
+    // second as a revocation of its user is told apart from the tokens it revoked
+    iat: f64,
+    exp: u64,
+    typ: TokenType,
+    // Sign in the token descends from
+    fam: String,
+    // Session a refresh token was issued with
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    sid: Option<String>,
+}
+
+// Revokes the tokens carrying a claim value until they expire
+#[derive(Clone, Debug, PartialEq)]
+pub struct RevokedClaim {
+    pub value: String,
+    pub expires_at: u64,
+}
+
+// Revokes the tokens of a user issued up to `issued_until` until they expire
+#[derive(Clone, Debug, PartialEq)]
+pub struct RevokedUser {
+    pub user_uuid: String,
+    pub issued_until: f64,
+    pub expires_at: u64,
+}
+
+// Session tokens revoked before they expire, for the services verifying tokens on their own.
+// A token is revoked when its `jti` is among `tokens`, its `fam` among `families`, or its `sub`
+// among `users` with an `issued_until` no earlier than its `iat`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Revocations {
+    pub tokens: Vec<RevokedClaim>,
+    pub families: Vec<RevokedClaim>,
+    pub users: Vec<RevokedUser>,
+}
+
+// Revocations along with when they can be forgotten, as the tokens they apply to expired
+#[derive(Default)]
+struct RevocationList {
+    tokens: HashMap<String, u64>,
+    // Refresh tokens can only be used once, they are kept to end the family of a reused one
+    used_refresh_tokens: HashMap<String, u64>,
+    families: HashMap<String, u64>,
+    users: HashMap<String, (f64, u64)>,
+}
+
+impl RevocationList {
+    fn revokes(&self, claims: &Claims) -> bool {
+        self.tokens.contains_key(&claims.jti)
+            || self.families.contains_key(&claims.fam)
+            || self
+                .users
+                .get(&claims.sub)
+                .is_some_and(|(issued_until, _)| claims.iat <= *issued_until)
+    }
+
+    fn len(&self) -> usize {
+        self.tokens.len() + self.used_refresh_tokens.len() + self.families.len() + self.users.len()
+    }
+}
+
+// Sessions as signed JWTs which other services can verify on their own, with the keys given out
+// by `GetJwks` and the revocations given out by `GetRevocations`.
+// Tokens are signed with the key of the greatest kid, the other keys are only used to verify
+// tokens signed before a rotation. To rotate, add a key of a greater kid and restart, then
+// remove the previous key once the refresh tokens it signed expired.
+//
+// Sessions are kept in their tokens rather than by the service, which only keeps the signed out
+// sessions, the ended sign ins and the users signed out everywhere until the tokens they apply
+// to expire. So sessions are not renewed by use, only the absolute TTL applies and not the idle
+// TTL, and they can neither be listed nor counted. The revocations are kept in memory: the
+// service must run as a single instance, and tokens revoked before a restart are valid again.
+pub struct JwtSessions {
+    config: SessionConfig,
+    clock: Box<dyn Clock + Send + Sync>,
+    keys: BTreeMap<String, SigningKey>,
+    // Only locked while the revocations are read or updated
+    revocations: Mutex<RevocationList>,
+}
+
+impl JwtSessions {
+    pub fn new(
+        keys: Vec<SigningKey>,
+        config: SessionConfig,
+        clock: Box<dyn Clock + Send + Sync>,
+    ) -> Result<Self, String> {
+        if keys.is_empty() {
+            return Err("At least one signing key is needed".to_owned());
+        }
+
+        Ok(Self {
+            config,
+            clock,
+            keys: keys.into_iter().map(|key| (key.kid.clone(), key)).collect(),
+            revocations: Mutex::new(RevocationList::default()),
+        })
+    }
+
+    pub fn from_dir(dir: &Path) -> Result<Self, String> {
+        Self::new(
+            SigningKey::from_dir(dir)?,
+            SessionConfig::default(),
+            Box::new(SystemClock),
+        )
+    }
+
+    fn revocation_list(&self) -> MutexGuard<'_, RevocationList> {
+        self.revocations.lock().expect("Poisoned revocations mutex")
+    }
+
+    fn now(&self) -> f64 {
+        self.clock
+            .now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_secs_f64())
+            .unwrap_or_default()
+    }
+
+    // No token issued from now on outlives this
+    fn forget_at(&self) -> u64 {
+        let ttl = self.config.refresh_ttl.max(self.config.absolute_ttl);
+
+        self.now() as u64 + ttl.as_secs()
+    }
+
+    fn sign(&self, claims: &Claims) -> String {
+        let (kid, key) = self
+            .keys
+            .last_key_value()
+            .expect("There is at least one signing key");
+        let mut header = Header::new(Algorithm::EdDSA);
+        header.kid = Some(kid.clone());
+
+        encode(&header, claims, &key.encoding_key).expect("Claims are serializable")
+    }
+
+    // Returns the claims of a well signed and unexpired token of the type, revoked or not
+    fn decode(&self, token: &str, typ: TokenType) -> Option<Claims> {
+        let kid = decode_header(token).ok()?.kid?;
+        let key = self.keys.get(&kid)?;
+
+        // Expiry is checked against `clock` rather than the system time
+        let mut validation = Validation::new(Algorithm::EdDSA);
+        validation.validate_exp = false;
+        validation.required_spec_claims = HashSet::new();
+        let claims = decode::<Claims>(token, &key.decoding_key, &validation)
+            .ok()?
+            .claims;
+
+        (claims.typ == typ && claims.exp as f64 > self.now()).then_some(claims)
+    }
+
+    fn verify(&self, token: &str, typ: TokenType) -> Option<Claims> {
+        self.decode(token, typ)
+            .filter(|claims| !self.revocation_list().revokes(claims))
+    }
+
+    fn issue(&self, user_uuid: &str, family: String, refresh_exp: u64) -> SessionTokens {
+        let now = self.now();
+        let session = Claims {
+            sub: user_uuid.to_owned(),
+            jti: Uuid::new_v4().to_string(),
+            iat: now,
+            exp: now as u64 + self.config.absolute_ttl.as_secs(),
+            typ: TokenType::Session,
+            fam: family.clone(),
+            sid: None,
+        };
+        let refresh = Claims {
+            sub: user_uuid.to_owned(),
+            jti: Uuid::new_v4().to_string(),
+            iat: now,
+            exp: refresh_exp,
+            typ: TokenType::Refresh,
+            fam: family,
+            sid: Some(session.jti.clone()),
+        };
+
+        SessionTokens {
+            session_token: self.sign(&session),
+            refresh_token: self.sign(&refresh),
+        }
+    }
+}
+
+#[tonic::async_trait]
+impl Sessions for JwtSessions {
+    async fn create_session(&self, user_uuid: &str) -> Result<SessionTokens, String> {
+        let refresh_exp = self.now() as u64 + self.config.refresh_ttl.as_secs();
+
+        Ok(self.issue(user_uuid, Uuid::new_v4().to_string(), refresh_exp))
+    }
+
+    async fn get_session(&self, session_token: &str) -> Result<Option<Session>, String> {
+        Ok(self
+            .verify(session_token, TokenType::Session)
+            .map(|claims| Session {
+                user_uuid: claims.sub,
+                created_at: UNIX_EPOCH + Duration::from_secs_f64(claims.iat),
+                expires_at: UNIX_EPOCH + Duration::from_secs(claims.exp),
+            }))
+    }
+
+    // Tokens cannot be renewed without issuing new ones, the idle TTL does not apply
+    async fn renew_session(&self, session_token: &str) -> Result<Option<Session>, String> {
+        self.get_session(session_token).await
+    }
+
+    async fn get_user_sessions(
+        &self,
+        _user_uuid: &str,
+    ) -> Result<Option<Vec<(String, Session)>>, String> {
+        Ok(None)
+    }
+
+    async fn refresh_session(&self, refresh_token: &str) -> Result<Option<SessionTokens>, String> {
+        let Some(claims) = self.decode(refresh_token, TokenType::Refresh) else {
+            return Ok(None);
+        };
+
+        {
+            let mut revocations = self.revocation_list();
+            if revocations.revokes(&claims) {
+                return Ok(None);
+            }
+            if revocations.used_refresh_tokens.contains_key(&claims.jti) {
+                revocations
+                    .families
+                    .insert(claims.fam.clone(), self.forget_at());
+                return Ok(None);
+            }
+            revocations
+                .used_refresh_tokens
+                .insert(claims.jti.clone(), claims.exp);
+
+            // The session the refresh token was issued with is replaced by the new one
+            if let Some(sid) = &claims.sid {
+                let session_exp = claims.iat as u64 + self.config.absolute_ttl.as_secs();
+                revocations.tokens.insert(sid.clone(), session_exp);
+            }
+        }
+
+        // Rotating the refresh token does not extend it
+        Ok(Some(self.issue(&claims.sub, claims.fam, claims.exp)))
+    }
+
+    async fn delete_session(&self, session_token: &str) -> Result<bool, String> {
+        let Some(claims) = self.decode(session_token, TokenType::Session) else {
+            return Ok(false);
+        };
+
+        let mut revocations = self.revocation_list();
+        if revocations.revokes(&claims) {
+            return Ok(false);
+        }
+        // Also ends the sign in, so that its refresh token cannot start another session
+        revocations.tokens.insert(claims.jti, claims.exp);
+        revocations.families.insert(claims.fam, self.forget_at());
+
+        Ok(true)
+    }
+
+    async fn delete_user_sessions(&self, user_uuid: &str) -> Result<Option<usize>, String> {
+        let revoked = (self.now(), self.forget_at());
+        self.revocation_list()
+            .users
+            .insert(user_uuid.to_owned(), revoked);
+
+        Ok(None)
+    }
+
+    async fn remove_expired(&self) -> Result<usize, String> {
+        let now = self.now() as u64;
+        let mut revocations = self.revocation_list();
+        let before = revocations.len();
+
+        revocations.tokens.retain(|_, forget_at| *forget_at > now);
+        revocations
+            .used_refresh_tokens
+            .retain(|_, forget_at| *forget_at > now);
+        revocations.families.retain(|_, forget_at| *forget_at > now);
+        revocations
+            .users
+            .retain(|_, (_, forget_at)| *forget_at > now);
+
+        Ok(before - revocations.len())
+    }
+
+    fn public_keys(&self) -> Vec<Jwk> {
+        self.keys.values().map(|key| key.jwk.clone()).collect()
+    }
+
+    fn revocations(&self) -> Revocations {
+        let revocations = self.revocation_list();
+        let claims = |revoked: &HashMap<String, u64>| {
+            let mut claims: Vec<RevokedClaim> = revoked
+                .iter()
+                .map(|(value, expires_at)| RevokedClaim {
+                    value: value.clone(),
+                    expires_at: *expires_at,
+                })
+                .collect();
+            claims.sort_by(|a, b| a.value.cmp(&b.value));
+            claims
+        };
+        let mut users: Vec<RevokedUser> = revocations
+            .users
+            .iter()
+            .map(|(user_uuid, (issued_until, expires_at))| RevokedUser {
+                user_uuid: user_uuid.clone(),
+                issued_until: *issued_until,
+                expires_at: *expires_at,
+            })
+            .collect();
+        users.sort_by(|a, b| a.user_uuid.cmp(&b.user_uuid));
+
+        Revocations {
+            tokens: claims(&revocations.tokens),
+            families: claims(&revocations.families),
+            users,
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use ring::rand::SystemRandom;
+
+    use crate::clock::ManualClock;
+
+    use super::*;
+
+    const MINUTE: Duration = Duration::from_secs(60);
+    const MILLISECOND: Duration = Duration::from_millis(1);
+
+    pub fn generate_key(kid: &str) -> SigningKey {
+        let pkcs8 = Ed25519KeyPair::generate_pkcs8(&SystemRandom::new()).unwrap();
+        let pem = pem::encode(&pem::Pem::new("PRIVATE KEY", pkcs8.as_ref()));
+
+        SigningKey::from_pem(kid, pem.as_bytes()).unwrap()
+    }
+
+    fn sessions_with_clock(keys: Vec<SigningKey>) -> (JwtSessions, ManualClock) {
+        let clock = ManualClock::default();
+        let config = SessionConfig {
+            absolute_ttl: 60 * MINUTE,
+            idle_ttl: 10 * MINUTE,
+            refresh_ttl: 120 * MINUTE,
+        };
+
+        (
+            JwtSessions::new(keys, config, Box::new(clock.clone())).unwrap(),
+            clock,
+        )
+    }
+
+    fn claims(token: &str) -> Claims {
+        let mut validation = Validation::new(Algorithm::EdDSA);
+        validation.insecure_disable_signature_validation();
+        validation.validate_exp = false;
+        validation.required_spec_claims = HashSet::new();
+
+        decode::<Claims>(token, &DecodingKey::from_secret(&[]), &validation)
+            .unwrap()
+            .claims
+    }
+
+    #[test]
+    fn should_need_a_signing_key() {
+        let result = JwtSessions::new(Vec::new(), SessionConfig::default(), Box::new(SystemClock));
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn should_load_keys_from_dir() {
+        let dir = std::env::temp_dir().join(Uuid::new_v4().to_string());
+        fs::create_dir(&dir).unwrap();
+        let pkcs8 = Ed25519KeyPair::generate_pkcs8(&SystemRandom::new()).unwrap();
+        let pem = pem::encode(&pem::Pem::new("PRIVATE KEY", pkcs8.as_ref()));
+        fs::write(dir.join("2026-10-19.pem"), pem).unwrap();
+        fs::write(dir.join("README"), "not a key").unwrap();
+
+        let keys = SigningKey::from_dir(&dir);
+        fs::remove_dir_all(&dir).unwrap();
+
+        let keys = keys.unwrap();
+        assert_eq!(keys.len(), 1);
+        assert_eq!(keys[0].kid, "2026-10-19");
+    }
+
+    #[test]
+    fn should_reject_invalid_key() {
+        assert!(SigningKey::from_pem("bad", b"not a key").is_err());
+    }
+
+    #[tokio::test]
+    async fn should_get_session() {
+        let (session_service, _) = sessions_with_clock(vec![generate_key("1")]);
+        let tokens = session_service.create_session("123456").await.unwrap();
+
+        let session = session_service
+            .get_session(&tokens.session_token)
+            .await
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(session.user_uuid, "123456");
+        assert_eq!(session.expires_at, session.created_at + 60 * MINUTE);
+        assert!(session_service
+            .get_session("unknown")
+            .await
+            .unwrap()
+            .is_none());
+        // Refresh tokens are not sessions
+        assert!(session_service
+            .get_session(&tokens.refresh_token)
+            .await
+            .unwrap()
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn should_expire_session() {
+        let (session_service, clock) = sessions_with_clock(vec![generate_key("1")]);
+        let tokens = session_service.create_session("123456").await.unwrap();
+
+        clock.advance(60 * MINUTE);
+
+        assert!(session_service
+            .get_session(&tokens.session_token)
+            .await
+            .unwrap()
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn should_not_renew_session_by_use() {
+        let (session_service, clock) = sessions_with_clock(vec![generate_key("1")]);
+        let tokens = session_service.create_session("123456").await.unwrap();
+
+        // Unused for longer than the idle TTL, which does not apply
+        clock.advance(30 * MINUTE);
+        let session = session_service
+            .renew_session(&tokens.session_token)
+            .await
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(session.expires_at, session.created_at + 60 * MINUTE);
+    }
+
+    #[tokio::test]
+    async fn should_reject_token_signed_with_unknown_key() {
+        let (other_service, _) = sessions_with_clock(vec![generate_key("1")]);
+        let (session_service, _) = sessions_with_clock(vec![generate_key("1")]);
+        let tokens = other_service.create_session("123456").await.unwrap();
+
+        assert!(session_service
+            .get_session(&tokens.session_token)
+            .await
+            .unwrap()
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn should_verify_tokens_signed_before_rotation() {
+        let old_key = generate_key("1");
+        let (old_service, _) = sessions_with_clock(vec![old_key.clone()]);
+        let (session_service, _) = sessions_with_clock(vec![old_key, generate_key("2")]);
+
+        let old_tokens = old_service.create_session("123456").await.unwrap();
+        let tokens = session_service.create_session("123456").await.unwrap();
+
+        assert!(session_service
+            .get_session(&old_tokens.session_token)
+            .await
+            .unwrap()
+            .is_some());
+        assert_eq!(
+            decode_header(&tokens.session_token).unwrap().kid.unwrap(),
+            "2"
+        );
+        assert!(old_service
+            .get_session(&tokens.session_token)
+            .await
+            .unwrap()
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn should_revoke_signed_out_session() {
+        let (session_service, _) = sessions_with_clock(vec![generate_key("1")]);
+        let tokens = session_service.create_session("123456").await.unwrap();
+        let other = session_service.create_session("123456").await.unwrap();
+
+        assert!(session_service
+            .delete_session(&tokens.session_token)
+            .await
+            .unwrap());
+
+        assert!(!session_service
+            .delete_session(&tokens.session_token)
+            .await
+            .unwrap());
+        assert!(session_service
+            .get_session(&tokens.session_token)
+            .await
+            .unwrap()
+            .is_none());
+        assert!(session_service
+            .refresh_session(&tokens.refresh_token)
+            .await
+            .unwrap()
+            .is_none());
+        assert!(session_service
+            .get_session(&other.session_token)
+            .await
+            .unwrap()
+            .is_some());
+    }
+
+    #[tokio::test]
+    async fn should_revoke_user_sessions() {
+        let (session_service, clock) = sessions_with_clock(vec![generate_key("1")]);
+        let tokens = session_service.create_session("123456").await.unwrap();
+        let other = session_service.create_session("654321").await.unwrap();
+
+        // The number of sessions ended is unknown, as they are not kept
+        clock.advance(MILLISECOND);
+        assert_eq!(
+            session_service
+                .delete_user_sessions("123456")
+                .await
+                .unwrap(),
+            None
+        );
+
+        assert!(session_service
+            .get_session(&tokens.session_token)
+            .await
+            .unwrap()
+            .is_none());
+        assert!(session_service
+            .refresh_session(&tokens.refresh_token)
+            .await
+            .unwrap()
+            .is_none());
+        assert!(session_service
+            .get_session(&other.session_token)
+            .await
+            .unwrap()
+            .is_some());
+    }
+
+    #[tokio::test]
+    async fn should_only_revoke_user_sessions_issued_before() {
+        let (session_service, clock) = sessions_with_clock(vec![generate_key("1")]);
+        let tokens = session_service.create_session("123456").await.unwrap();
+
+        // Signing out everywhere and in again within the same second
+        clock.advance(MILLISECOND);
+        session_service
+            .delete_user_sessions("123456")
+            .await
+            .unwrap();
+        clock.advance(MILLISECOND);
+        let new_tokens = session_service.create_session("123456").await.unwrap();
+
+        assert_eq!(
+            claims(&tokens.session_token).iat as u64,
+            claims(&new_tokens.session_token).iat as u64
+        );
+        assert!(session_service
+            .get_session(&tokens.session_token)
+            .await
+            .unwrap()
+            .is_none());
+        assert!(session_service
+            .get_session(&new_tokens.session_token)
+            .await
+            .unwrap()
+            .is_some());
+        assert!(session_service
+            .refresh_session(&new_tokens.refresh_token)
+            .await
+            .unwrap()
+            .is_some());
+    }
+
+    #[tokio::test]
+    async fn should_refresh_session() {
+        let (session_service, clock) = sessions_with_clock(vec![generate_key("1")]);
+        let tokens = session_service.create_session("123456").await.unwrap();
+
+        clock.advance(30 * MINUTE);
+        let refreshed = session_service
+            .refresh_session(&tokens.refresh_token)
+            .await
+            .unwrap()
+            .unwrap();
+
+        assert!(session_service
+            .get_session(&tokens.session_token)
+            .await
+            .unwrap()
+            .is_none());
+        let session = session_service
+            .get_session(&refreshed.session_token)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(session.user_uuid, "123456");
+        assert_eq!(session.created_at, clock.now());
+        // Rotating the refresh token does not extend it
+        assert_eq!(
+            claims(&refreshed.refresh_token).exp,
+            claims(&tokens.refresh_token).exp
+        );
+    }
+
+    #[tokio::test]
+    async fn should_end_family_when_refresh_token_reused() {
+        let (session_service, _) = sessions_with_clock(vec![generate_key("1")]);
+        let tokens = session_service.create_session("123456").await.unwrap();
+        let other = session_service.create_session("123456").await.unwrap();
+
+        let refreshed = session_service
+            .refresh_session(&tokens.refresh_token)
+            .await
+            .unwrap()
+            .unwrap();
+
+        assert!(session_service
+            .refresh_session(&tokens.refresh_token)
+            .await
+            .unwrap()
+            .is_none());
+        assert!(session_service
+            .get_session(&refreshed.session_token)
+            .await
+            .unwrap()
+            .is_none());
+        assert!(session_service
+            .refresh_session(&refreshed.refresh_token)
+            .await
+            .unwrap()
+            .is_none());
+        assert!(session_service
+            .get_session(&other.session_token)
+            .await
+            .unwrap()
+            .is_some());
+    }
+
+    #[tokio::test]
+    async fn should_not_list_user_sessions() {
+        let (session_service, _) = sessions_with_clock(vec![generate_key("1")]);
+        session_service.create_session("123456").await.unwrap();
+
+        assert!(session_service
+            .get_user_sessions("123456")
+            .await
+            .unwrap()
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn should_give_out_revocations_until_tokens_expire() {
+        let (session_service, clock) = sessions_with_clock(vec![generate_key("1")]);
+        let tokens = session_service.create_session("123456").await.unwrap();
+        let session = claims(&tokens.session_token);
+        assert_eq!(session_service.revocations(), Revocations::default());
+
+        session_service
+            .delete_session(&tokens.session_token)
+            .await
+            .unwrap();
+        session_service
+            .delete_user_sessions("654321")
+            .await
+            .unwrap();
+
+        let now = claims(&tokens.refresh_token).iat;
+        let forget_at = now as u64 + 120 * 60;
+        assert_eq!(
+            session_service.revocations(),
+            Revocations {
+                tokens: vec![RevokedClaim {
+                    value: session.jti,
+                    expires_at: session.exp,
+                }],
+                families: vec![RevokedClaim {
+                    value: session.fam,
+                    expires_at: forget_at,
+                }],
+                users: vec![RevokedUser {
+                    user_uuid: "654321".to_owned(),
+                    issued_until: now,
+                    expires_at: forget_at,
+                }],
+            }
+        );
+
+        // The revoked session expired, then the refresh tokens of the sign in and the user
+        clock.advance(60 * MINUTE);
+        assert_eq!(session_service.remove_expired().await.unwrap(), 1);
+        clock.advance(60 * MINUTE);
+        assert_eq!(session_service.remove_expired().await.unwrap(), 2);
+        assert_eq!(session_service.revocations(), Revocations::default());
+    }
+
+    #[tokio::test]
+    async fn should_forget_used_refresh_tokens_once_expired() {
+        let (session_service, clock) = sessions_with_clock(vec![generate_key("1")]);
+        let tokens = session_service.create_session("123456").await.unwrap();
+
+        session_service
+            .refresh_session(&tokens.refresh_token)
+            .await
+            .unwrap()
+            .unwrap();
+
+        // The replaced session, then the used refresh token
+        clock.advance(60 * MINUTE);
+        assert_eq!(session_service.remove_expired().await.unwrap(), 1);
+        clock.advance(60 * MINUTE);
+        assert_eq!(session_service.remove_expired().await.unwrap(), 1);
+        assert_eq!(session_service.remove_expired().await.unwrap(), 0);
+    }
+
+    #[test]
+    fn should_give_out_public_keys() {
+        let (session_service, _) = sessions_with_clock(vec![generate_key("2"), generate_key("1")]);
+
+        let kids: Vec<String> = session_service
+            .public_keys()
+            .into_iter()
+            .map(|jwk| jwk.kid)
+            .collect();
+
+        assert_eq!(kids, vec!["1", "2"]);
+    }
+}
@@ -1,12 +1,21 @@
-use std::sync::Mutex;
+use std::env;
+use std::path::Path;
+use std::sync::Arc;
 
 mod auth;
+mod clock;
+mod database;
+mod jwt_sessions;
 mod sessions;
 mod users;
 
 use auth::*;
-use sessions::{Sessions, SessionsImpl};
-use users::{Users, UsersImpl};
+use clock::SystemClock;
+use jwt_sessions::JwtSessions;
+use sessions::{
+    run_reaper, DatabaseSessions, SessionConfig, Sessions, SessionsImpl, REAPER_PERIOD,
+};
+use users::{DatabaseUsers, Users, UsersImpl};
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -18,10 +27,45 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // // If using outsite of Docker, use this IP address
     // let addr = "[::1]:50051".parse()?;
 
-    let users_service: Box<Mutex<dyn Users + Send + Sync + 'static>> =
-        Box::new(Mutex::new(UsersImpl::default()));
-    let sessions_service: Box<Mutex<dyn Sessions + Send + Sync + 'static>> =
-        Box::new(Mutex::new(SessionsImpl::default()));
+    // AUTH_DATABASE_URL, such as `sqlite://auth.db?mode=rwc` or `postgres://...`, keeps users
+    // in that database rather than in memory. Its schema is migrated on start.
+    let database = match env::var("AUTH_DATABASE_URL") {
+        Ok(url) => Some(database::connect(&url).await?),
+        Err(_) => None,
+    };
+
+    let users_service: Box<dyn Users + Send + Sync + 'static> = match &database {
+        Some(db) => Box::new(DatabaseUsers::new(db.clone())),
+        None => Box::new(UsersImpl::default()),
+    };
+    // SESSIONS_BACKEND selects how sessions are kept:
+    // - `memory` keeps them in memory, the default without AUTH_DATABASE_URL
+    // - `database` keeps them in the database of AUTH_DATABASE_URL, the default with it
+    // - `jwt` keeps them in signed tokens other services can verify with the keys from `GetJwks`.
+    //   Only revocations are kept, in memory until the tokens expire, and given out by
+    //   `GetRevocations`, so the service runs as a single instance and sessions can't be listed.
+    //   The Ed25519 keys are the `<kid>.pem` files of JWT_KEYS_DIR, `keys` by default, which
+    //   can be generated with `openssl genpkey -algorithm ed25519 -out keys/<kid>.pem`
+    let sessions_service: Arc<dyn Sessions + Send + Sync + 'static> =
+        match (env::var("SESSIONS_BACKEND").as_deref(), &database) {
+            (Err(_), None) | (Ok("memory"), _) => Arc::new(SessionsImpl::default()),
+            (Err(_), Some(db)) | (Ok("database"), Some(db)) => Arc::new(DatabaseSessions::new(
+                db.clone(),
+                SessionConfig::default(),
+                Box::new(SystemClock),
+            )),
+            (Ok("database"), None) => {
+                return Err("The database sessions backend needs AUTH_DATABASE_URL".into())
+            }
+            (Ok("jwt"), _) => {
+                let keys_dir = env::var("JWT_KEYS_DIR").unwrap_or("keys".to_owned());
+                Arc::new(JwtSessions::from_dir(Path::new(&keys_dir))?)
+            }
+            (Ok(backend), _) => return Err(format!("Unknown sessions backend {backend}").into()),
+        };
+
+    // Evict expired sessions in the background
+    tokio::spawn(run_reaper(sessions_service.clone(), REAPER_PERIOD));
 
     let auth_service = AuthService::new(users_service, sessions_service);
 
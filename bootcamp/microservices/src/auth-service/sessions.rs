@@ -1,51 +1,1186 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
+use sqlx::{AnyConnection, AnyPool};
 use uuid::Uuid;
 
+use crate::clock::{Clock, SystemClock};
+use crate::jwt_sessions::{Jwk, Revocations};
+
+// How often the reaper evicts expired sessions and refresh tokens
+pub const REAPER_PERIOD: Duration = Duration::from_secs(60);
+
+pub struct SessionConfig {
+    // How long a session is valid for after signing in or refreshing, however much it is used
+    pub absolute_ttl: Duration,
+    // How long a session is valid for after it was last used
+    pub idle_ttl: Duration,
+    // How long the refresh tokens of a sign in can be used for, rotating them does not extend it
+    pub refresh_ttl: Duration,
+}
+
+impl SessionConfig {
+    fn session(
+        &self,
+        user_uuid: String,
+        created_at: SystemTime,
+        last_used_at: SystemTime,
+    ) -> Session {
+        Session {
+            user_uuid,
+            created_at,
+            expires_at: (created_at + self.absolute_ttl).min(last_used_at + self.idle_ttl),
+        }
+    }
+}
+
+impl Default for SessionConfig {
+    fn default() -> Self {
+        Self {
+            absolute_ttl: Duration::from_secs(24 * 60 * 60),
+            idle_ttl: Duration::from_secs(30 * 60),
+            refresh_ttl: Duration::from_secs(30 * 24 * 60 * 60),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Session {
+    pub user_uuid: String,
+    pub created_at: SystemTime,
+    // The earliest of the absolute and idle expiries
+    pub expires_at: SystemTime,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SessionTokens {
+    pub session_token: String,
+    pub refresh_token: String,
+}
+
+// Sessions are identified by their token, a user may have several at once.
+// Each sign in starts a family of sessions and refresh tokens, each refresh token can be
+// exchanged once for a new session and refresh token of the same family.
+#[tonic::async_trait]
 pub trait Sessions {
-    fn create_session(&mut self, user_uuid: &str) -> String;
-    fn delete_session(&mut self, user_uuid: &str);
+    async fn create_session(&self, user_uuid: &str) -> Result<SessionTokens, String>;
+    // Returns the session of the token unless it expired.
+    async fn get_session(&self, session_token: &str) -> Result<Option<Session>, String>;
+    // Like `get_session`, also counting as a use of the session which delays its idle expiry.
+    async fn renew_session(&self, session_token: &str) -> Result<Option<Session>, String>;
+    // Returns the unexpired sessions of the user along with their tokens, oldest first. Returns
+    // None if the backend does not keep sessions.
+    async fn get_user_sessions(
+        &self,
+        user_uuid: &str,
+    ) -> Result<Option<Vec<(String, Session)>>, String>;
+    // Ends the session of the refresh token and starts a new one. Returns None if the refresh
+    // token is unknown or expired. Reusing a refresh token ends its whole family, as it may
+    // have been stolen.
+    async fn refresh_session(&self, refresh_token: &str) -> Result<Option<SessionTokens>, String>;
+    // Also revokes the refresh token of the session. Returns false if there was no such
+    // session or it had expired.
+    async fn delete_session(&self, session_token: &str) -> Result<bool, String>;
+    // Deletes every session and refresh token of the user, returns how many sessions were
+    // unexpired if the backend keeps sessions.
+    async fn delete_user_sessions(&self, user_uuid: &str) -> Result<Option<usize>, String>;
+    // Evicts expired sessions and refresh tokens, returns how many entries were evicted.
+    async fn remove_expired(&self) -> Result<usize, String>;
+    // Keys other services can verify session tokens with, if the tokens are signed.
+    fn public_keys(&self) -> Vec<Jwk> {
+        Vec::new()
+    }
+    // Session tokens revoked before they expire, if the tokens are signed.
+    fn revocations(&self) -> Revocations {
+        Revocations::default()
+    }
+}
+
+struct SessionRecord {
+    user_uuid: String,
+    family: String,
+    created_at: SystemTime,
+    last_used_at: SystemTime,
+}
+
+struct RefreshRecord {
+    user_uuid: String,
+    family: String,
+    expires_at: SystemTime,
+    // Used refresh tokens are kept until they expire to detect their reuse
+    used: bool,
 }
 
 #[derive(Default)]
+struct SessionIndex {
+    token_to_session: HashMap<String, SessionRecord>,
+    user_to_tokens: HashMap<String, HashSet<String>>,
+    refresh_tokens: HashMap<String, RefreshRecord>,
+}
+
+impl SessionIndex {
+    // Removes the session from both indexes, dropping users left without sessions
+    fn remove_session(&mut self, session_token: &str) -> Option<SessionRecord> {
+        let record = self.token_to_session.remove(session_token)?;
+
+        if let Some(tokens) = self.user_to_tokens.get_mut(&record.user_uuid) {
+            tokens.remove(session_token);
+            if tokens.is_empty() {
+                self.user_to_tokens.remove(&record.user_uuid);
+            }
+        }
+
+        Some(record)
+    }
+
+    fn remove_family_sessions(&mut self, family: &str) {
+        let tokens: Vec<String> = self
+            .token_to_session
+            .iter()
+            .filter(|(_, record)| record.family == family)
+            .map(|(token, _)| token.clone())
+            .collect();
+        for token in tokens {
+            self.remove_session(&token);
+        }
+    }
+}
+
 pub struct SessionsImpl {
-    uuid_to_session: HashMap<String, String>,
+    config: SessionConfig,
+    clock: Box<dyn Clock + Send + Sync>,
+    // Only locked while the indexes are read or updated, never across an await
+    index: Mutex<SessionIndex>,
 }
 
+impl Default for SessionsImpl {
+    fn default() -> Self {
+        Self::new(SessionConfig::default(), Box::new(SystemClock))
+    }
+}
+
+impl SessionsImpl {
+    pub fn new(config: SessionConfig, clock: Box<dyn Clock + Send + Sync>) -> Self {
+        Self {
+            config,
+            clock,
+            index: Mutex::new(SessionIndex::default()),
+        }
+    }
+
+    fn index(&self) -> MutexGuard<'_, SessionIndex> {
+        self.index.lock().expect("Poisoned sessions mutex")
+    }
+
+    fn to_session(&self, record: &SessionRecord) -> Session {
+        self.config.session(
+            record.user_uuid.clone(),
+            record.created_at,
+            record.last_used_at,
+        )
+    }
+
+    fn is_valid(&self, record: &SessionRecord) -> bool {
+        self.to_session(record).expires_at > self.clock.now()
+    }
+
+    // Starts a session and issues a refresh token for it, in the given family
+    fn start_session(
+        &self,
+        index: &mut SessionIndex,
+        user_uuid: &str,
+        family: String,
+        refresh_expires_at: SystemTime,
+    ) -> SessionTokens {
+        let session_token: String = Uuid::new_v4().to_string();
+        let refresh_token: String = Uuid::new_v4().to_string();
+        let now = self.clock.now();
+
+        index.token_to_session.insert(
+            session_token.clone(),
+            SessionRecord {
+                user_uuid: user_uuid.to_owned(),
+                family: family.clone(),
+                created_at: now,
+                last_used_at: now,
+            },
+        );
+        index
+            .user_to_tokens
+            .entry(user_uuid.to_owned())
+            .or_default()
+            .insert(session_token.clone());
+        index.refresh_tokens.insert(
+            refresh_token.clone(),
+            RefreshRecord {
+                user_uuid: user_uuid.to_owned(),
+                family,
+                expires_at: refresh_expires_at,
+                used: false,
+            },
+        );
+
+        SessionTokens {
+            session_token,
+            refresh_token,
+        }
+    }
+}
+
+#[tonic::async_trait]
 impl Sessions for SessionsImpl {
-    fn create_session(&mut self, user_uuid: &str) -> String {
-        let session: String = Uuid::new_v4().to_string();
-        self.uuid_to_session
-            .insert(user_uuid.to_owned(), session.clone());
-        session
+    async fn create_session(&self, user_uuid: &str) -> Result<SessionTokens, String> {
+        let family = Uuid::new_v4().to_string();
+        let refresh_expires_at = self.clock.now() + self.config.refresh_ttl;
+
+        Ok(self.start_session(&mut self.index(), user_uuid, family, refresh_expires_at))
+    }
+
+    async fn get_session(&self, session_token: &str) -> Result<Option<Session>, String> {
+        Ok(self
+            .index()
+            .token_to_session
+            .get(session_token)
+            .filter(|record| self.is_valid(record))
+            .map(|record| self.to_session(record)))
+    }
+
+    async fn renew_session(&self, session_token: &str) -> Result<Option<Session>, String> {
+        let now = self.clock.now();
+        let mut index = self.index();
+        let Some(record) = index
+            .token_to_session
+            .get_mut(session_token)
+            .filter(|record| self.is_valid(record))
+        else {
+            return Ok(None);
+        };
+        record.last_used_at = now;
+
+        Ok(Some(self.to_session(record)))
+    }
+
+    async fn get_user_sessions(
+        &self,
+        user_uuid: &str,
+    ) -> Result<Option<Vec<(String, Session)>>, String> {
+        let index = self.index();
+        let mut sessions: Vec<(String, Session)> = index
+            .user_to_tokens
+            .get(user_uuid)
+            .into_iter()
+            .flatten()
+            .filter_map(|token| {
+                let record = index.token_to_session.get(token)?;
+                self.is_valid(record)
+                    .then(|| (token.clone(), self.to_session(record)))
+            })
+            .collect();
+        sessions.sort_by_key(|(_, session)| session.created_at);
+        Ok(Some(sessions))
+    }
+
+    async fn refresh_session(&self, refresh_token: &str) -> Result<Option<SessionTokens>, String> {
+        let now = self.clock.now();
+        let mut index = self.index();
+        let Some(refresh) = index.refresh_tokens.get_mut(refresh_token) else {
+            return Ok(None);
+        };
+        if refresh.expires_at <= now {
+            return Ok(None);
+        }
+
+        let family = refresh.family.clone();
+        if refresh.used {
+            index
+                .refresh_tokens
+                .retain(|_, refresh| refresh.family != family);
+            index.remove_family_sessions(&family);
+            return Ok(None);
+        }
+        refresh.used = true;
+        let user_uuid = refresh.user_uuid.clone();
+        let refresh_expires_at = refresh.expires_at;
+
+        // The session the refresh token was issued with is replaced by the new one
+        index.remove_family_sessions(&family);
+
+        Ok(Some(self.start_session(
+            &mut index,
+            &user_uuid,
+            family,
+            refresh_expires_at,
+        )))
+    }
+
+    async fn delete_session(&self, session_token: &str) -> Result<bool, String> {
+        let mut index = self.index();
+        let Some(record) = index.remove_session(session_token) else {
+            return Ok(false);
+        };
+
+        index
+            .refresh_tokens
+            .retain(|_, refresh| refresh.family != record.family);
+
+        Ok(self.is_valid(&record))
+    }
+
+    async fn delete_user_sessions(&self, user_uuid: &str) -> Result<Option<usize>, String> {
+        let mut index = self.index();
+        index
+            .refresh_tokens
+            .retain(|_, refresh| refresh.user_uuid != user_uuid);
+
+        let records: Vec<SessionRecord> = index
+            .user_to_tokens
+            .remove(user_uuid)
+            .into_iter()
+            .flatten()
+            .filter_map(|token| index.token_to_session.remove(&token))
+            .collect();
+
+        Ok(Some(
+            records
+                .iter()
+                .filter(|record| self.is_valid(record))
+                .count(),
+        ))
+    }
+
+    async fn remove_expired(&self) -> Result<usize, String> {
+        let now = self.clock.now();
+        let mut index = self.index();
+
+        let expired: Vec<String> = index
+            .token_to_session
+            .iter()
+            .filter(|(_, record)| !self.is_valid(record))
+            .map(|(token, _)| token.clone())
+            .collect();
+        for token in &expired {
+            index.remove_session(token);
+        }
+
+        let refresh_tokens = index.refresh_tokens.len();
+        index
+            .refresh_tokens
+            .retain(|_, refresh| refresh.expires_at > now);
+
+        Ok(expired.len() + refresh_tokens - index.refresh_tokens.len())
+    }
+}
+
+// Sessions and refresh tokens kept in the `sessions` and `refresh_tokens` tables of a SQLite
+// or Postgres database, times to the second.
+pub struct DatabaseSessions {
+    db: AnyPool,
+    config: SessionConfig,
+    clock: Box<dyn Clock + Send + Sync>,
+}
+
+impl DatabaseSessions {
+    pub fn new(db: AnyPool, config: SessionConfig, clock: Box<dyn Clock + Send + Sync>) -> Self {
+        Self { db, config, clock }
+    }
+
+    fn now(&self) -> i64 {
+        to_unix_seconds(self.clock.now())
+    }
+
+    // Sessions are valid when created after the first time and last used after the second
+    fn valid_after(&self) -> (i64, i64) {
+        let now = self.now();
+
+        (
+            now - self.config.absolute_ttl.as_secs() as i64,
+            now - self.config.idle_ttl.as_secs() as i64,
+        )
+    }
+
+    fn session(&self, user_uuid: String, created_at: i64, last_used_at: i64) -> Session {
+        self.config.session(
+            user_uuid,
+            from_unix_seconds(created_at),
+            from_unix_seconds(last_used_at),
+        )
+    }
+
+    // Starts a session and issues a refresh token for it, in the given family
+    async fn start_session(
+        &self,
+        conn: &mut AnyConnection,
+        user_uuid: &str,
+        family: &str,
+        refresh_expires_at: i64,
+    ) -> Result<SessionTokens, sqlx::Error> {
+        let session_token: String = Uuid::new_v4().to_string();
+        let refresh_token: String = Uuid::new_v4().to_string();
+        let now = self.now();
+
+        sqlx::query(
+            r"
+        INSERT INTO sessions ( session_token, user_uuid, family, created_at, last_used_at )
+        VALUES ( $1, $2, $3, $4, $4 )
+        ",
+        )
+        .bind(&session_token)
+        .bind(user_uuid)
+        .bind(family)
+        .bind(now)
+        .execute(&mut *conn)
+        .await?;
+
+        sqlx::query(
+            r"
+        INSERT INTO refresh_tokens ( refresh_token, user_uuid, family, expires_at, used )
+        VALUES ( $1, $2, $3, $4, FALSE )
+        ",
+        )
+        .bind(&refresh_token)
+        .bind(user_uuid)
+        .bind(family)
+        .bind(refresh_expires_at)
+        .execute(&mut *conn)
+        .await?;
+
+        Ok(SessionTokens {
+            session_token,
+            refresh_token,
+        })
+    }
+}
+
+fn failed(action: &str) -> impl FnOnce(sqlx::Error) -> String + '_ {
+    move |e| format!("Failed to {action}.\n{e:?}")
+}
+
+fn to_unix_seconds(time: SystemTime) -> i64 {
+    time.duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs() as i64)
+        .unwrap_or_default()
+}
+
+fn from_unix_seconds(seconds: i64) -> SystemTime {
+    UNIX_EPOCH + Duration::from_secs(seconds.max(0) as u64)
+}
+
+#[tonic::async_trait]
+impl Sessions for DatabaseSessions {
+    async fn create_session(&self, user_uuid: &str) -> Result<SessionTokens, String> {
+        let family = Uuid::new_v4().to_string();
+        let refresh_expires_at = self.now() + self.config.refresh_ttl.as_secs() as i64;
+
+        let mut tx = self.db.begin().await.map_err(failed("create session"))?;
+        let tokens = self
+            .start_session(&mut tx, user_uuid, &family, refresh_expires_at)
+            .await
+            .map_err(failed("create session"))?;
+        tx.commit().await.map_err(failed("create session"))?;
+
+        Ok(tokens)
+    }
+
+    async fn get_session(&self, session_token: &str) -> Result<Option<Session>, String> {
+        let (created_after, used_after) = self.valid_after();
+
+        let session = sqlx::query_as::<_, (String, i64, i64)>(
+            r"
+        SELECT user_uuid, created_at, last_used_at FROM sessions
+        WHERE session_token = $1 AND created_at > $2 AND last_used_at > $3
+        ",
+        )
+        .bind(session_token)
+        .bind(created_after)
+        .bind(used_after)
+        .fetch_optional(&self.db)
+        .await
+        .map_err(failed("get session"))?;
+
+        Ok(session.map(|(user_uuid, created_at, last_used_at)| {
+            self.session(user_uuid, created_at, last_used_at)
+        }))
+    }
+
+    async fn renew_session(&self, session_token: &str) -> Result<Option<Session>, String> {
+        let (created_after, used_after) = self.valid_after();
+
+        let renewed = sqlx::query(
+            r"
+        UPDATE sessions SET last_used_at = $1
+        WHERE session_token = $2 AND created_at > $3 AND last_used_at > $4
+        ",
+        )
+        .bind(self.now())
+        .bind(session_token)
+        .bind(created_after)
+        .bind(used_after)
+        .execute(&self.db)
+        .await
+        .map_err(failed("renew session"))?;
+
+        if renewed.rows_affected() == 0 {
+            return Ok(None);
+        }
+        self.get_session(session_token).await
+    }
+
+    async fn get_user_sessions(
+        &self,
+        user_uuid: &str,
+    ) -> Result<Option<Vec<(String, Session)>>, String> {
+        let (created_after, used_after) = self.valid_after();
+
+        let sessions = sqlx::query_as::<_, (String, String, i64, i64)>(
+            r"
+        SELECT session_token, user_uuid, created_at, last_used_at FROM sessions
+        WHERE user_uuid = $1 AND created_at > $2 AND last_used_at > $3
+        ORDER BY created_at
+        ",
+        )
+        .bind(user_uuid)
+        .bind(created_after)
+        .bind(used_after)
+        .fetch_all(&self.db)
+        .await
+        .map_err(failed("get user sessions"))?;
+
+        Ok(Some(
+            sessions
+                .into_iter()
+                .map(|(token, user_uuid, created_at, last_used_at)| {
+                    (token, self.session(user_uuid, created_at, last_used_at))
+                })
+                .collect(),
+        ))
+    }
+
+    async fn refresh_session(&self, refresh_token: &str) -> Result<Option<SessionTokens>, String> {
+        let mut tx = self.db.begin().await.map_err(failed("refresh session"))?;
+
+        let refresh = sqlx::query_as::<_, (String, String, i64)>(
+            r"
+        SELECT user_uuid, family, expires_at FROM refresh_tokens
+        WHERE refresh_token = $1 AND expires_at > $2
+        ",
+        )
+        .bind(refresh_token)
+        .bind(self.now())
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(failed("refresh session"))?;
+        let Some((user_uuid, family, refresh_expires_at)) = refresh else {
+            return Ok(None);
+        };
+
+        // Of concurrent refreshes with the same token, only the first finds it unused
+        let unused = sqlx::query(
+            "UPDATE refresh_tokens SET used = TRUE WHERE refresh_token = $1 AND NOT used",
+        )
+        .bind(refresh_token)
+        .execute(&mut *tx)
+        .await
+        .map_err(failed("refresh session"))?
+        .rows_affected()
+            == 1;
+
+        // The session the refresh token was issued with is replaced by the new one, or the
+        // whole family ends when the token was reused
+        sqlx::query("DELETE FROM sessions WHERE family = $1")
+            .bind(&family)
+            .execute(&mut *tx)
+            .await
+            .map_err(failed("refresh session"))?;
+
+        let tokens = if unused {
+            let tokens = self
+                .start_session(&mut tx, &user_uuid, &family, refresh_expires_at)
+                .await
+                .map_err(failed("refresh session"))?;
+            Some(tokens)
+        } else {
+            sqlx::query("DELETE FROM refresh_tokens WHERE family = $1")
+                .bind(&family)
+                .execute(&mut *tx)
+                .await
+                .map_err(failed("refresh session"))?;
+            None
+        };
+        tx.commit().await.map_err(failed("refresh session"))?;
+
+        Ok(tokens)
+    }
+
+    async fn delete_session(&self, session_token: &str) -> Result<bool, String> {
+        let (created_after, used_after) = self.valid_after();
+        let mut tx = self.db.begin().await.map_err(failed("delete session"))?;
+
+        let session = sqlx::query_as::<_, (String, i64, i64)>(
+            r"
+        DELETE FROM sessions WHERE session_token = $1
+        RETURNING family, created_at, last_used_at
+        ",
+        )
+        .bind(session_token)
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(failed("delete session"))?;
+        let Some((family, created_at, last_used_at)) = session else {
+            return Ok(false);
+        };
+
+        sqlx::query("DELETE FROM refresh_tokens WHERE family = $1")
+            .bind(&family)
+            .execute(&mut *tx)
+            .await
+            .map_err(failed("delete session"))?;
+        tx.commit().await.map_err(failed("delete session"))?;
+
+        Ok(created_at > created_after && last_used_at > used_after)
+    }
+
+    async fn delete_user_sessions(&self, user_uuid: &str) -> Result<Option<usize>, String> {
+        let (created_after, used_after) = self.valid_after();
+        let mut tx = self
+            .db
+            .begin()
+            .await
+            .map_err(failed("delete user sessions"))?;
+
+        sqlx::query("DELETE FROM refresh_tokens WHERE user_uuid = $1")
+            .bind(user_uuid)
+            .execute(&mut *tx)
+            .await
+            .map_err(failed("delete user sessions"))?;
+
+        let ended = sqlx::query(
+            r"
+        DELETE FROM sessions
+        WHERE user_uuid = $1 AND created_at > $2 AND last_used_at > $3
+        ",
+        )
+        .bind(user_uuid)
+        .bind(created_after)
+        .bind(used_after)
+        .execute(&mut *tx)
+        .await
+        .map_err(failed("delete user sessions"))?;
+
+        sqlx::query("DELETE FROM sessions WHERE user_uuid = $1")
+            .bind(user_uuid)
+            .execute(&mut *tx)
+            .await
+            .map_err(failed("delete user sessions"))?;
+        tx.commit().await.map_err(failed("delete user sessions"))?;
+
+        Ok(Some(ended.rows_affected() as usize))
+    }
+
+    async fn remove_expired(&self) -> Result<usize, String> {
+        let (created_after, used_after) = self.valid_after();
+
+        let sessions =
+            sqlx::query("DELETE FROM sessions WHERE created_at <= $1 OR last_used_at <= $2")
+                .bind(created_after)
+                .bind(used_after)
+                .execute(&self.db)
+                .await
+                .map_err(failed("remove expired sessions"))?;
+
+        let refresh_tokens = sqlx::query("DELETE FROM refresh_tokens WHERE expires_at <= $1")
+            .bind(self.now())
+            .execute(&self.db)
+            .await
+            .map_err(failed("remove expired sessions"))?;
+
+        Ok((sessions.rows_affected() + refresh_tokens.rows_affected()) as usize)
     }
+}
+
+// Evicts expired sessions every `period` until the runtime shuts down
+pub async fn run_reaper(sessions: Arc<dyn Sessions + Send + Sync>, period: Duration) {
+    let mut interval = tokio::time::interval(period);
 
-    fn delete_session(&mut self, user_uuid: &str) {
-        self.uuid_to_session.remove_entry(user_uuid);
+    loop {
+        interval.tick().await;
+
+        match sessions.remove_expired().await {
+            Ok(0) => {}
+            Ok(removed) => println!("Removed {removed} expired sessions and refresh tokens"),
+            Err(e) => println!("Failed to remove expired sessions: {e}"),
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use crate::clock::ManualClock;
+
     use super::*;
 
-    #[test]
-    fn should_create_session() {
-        let mut session_service = SessionsImpl::default();
-        assert_eq!(session_service.uuid_to_session.len(), 0);
-        let session = session_service.create_session("123456");
-        assert_eq!(session_service.uuid_to_session.len(), 1);
+    const MINUTE: Duration = Duration::from_secs(60);
+
+    fn config() -> SessionConfig {
+        SessionConfig {
+            absolute_ttl: 60 * MINUTE,
+            idle_ttl: 10 * MINUTE,
+            refresh_ttl: 120 * MINUTE,
+        }
+    }
+
+    // Runs each test against every backend, with a clock of its own
+    macro_rules! backend_tests {
+        ($($test:ident),* $(,)?) => {
+            mod memory {
+                use crate::clock::ManualClock;
+
+                $(
+                    #[tokio::test]
+                    async fn $test() {
+                        let clock = ManualClock::default();
+                        let session_service =
+                            super::SessionsImpl::new(super::config(), Box::new(clock.clone()));
+                        super::$test(&session_service, clock).await;
+                    }
+                )*
+            }
+
+            mod sqlite {
+                use crate::clock::ManualClock;
+                use crate::database::tests::TestDatabase;
+
+                $(
+                    #[tokio::test]
+                    async fn $test() {
+                        let database = TestDatabase::sqlite().await;
+                        let clock = ManualClock::default();
+                        let session_service = super::DatabaseSessions::new(
+                            database.pool.clone(),
+                            super::config(),
+                            Box::new(clock.clone()),
+                        );
+                        super::$test(&session_service, clock).await;
+                        database.close().await;
+                    }
+                )*
+            }
+
+            mod postgres {
+                use crate::clock::ManualClock;
+                use crate::database::tests::TestDatabase;
+
+                $(
+                    #[tokio::test]
+                    #[ignore = "needs a Postgres server at DATABASE_URL"]
+                    async fn $test() {
+                        let database = TestDatabase::postgres().await;
+                        let clock = ManualClock::default();
+                        let session_service = super::DatabaseSessions::new(
+                            database.pool.clone(),
+                            super::config(),
+                            Box::new(clock.clone()),
+                        );
+                        super::$test(&session_service, clock).await;
+                        database.close().await;
+                    }
+                )*
+            }
+        };
+    }
+
+    backend_tests!(
+        should_create_session,
+        should_delete_session,
+        should_fail_to_delete_unknown_session,
+        should_get_session,
+        should_expire_idle_session,
+        should_delay_idle_expiry_when_renewed,
+        should_expire_session_after_absolute_ttl_however_used,
+        should_keep_concurrent_sessions,
+        should_delete_user_sessions,
+        should_refresh_session,
+        should_not_refresh_after_refresh_ttl,
+        should_end_family_when_refresh_token_reused,
+        should_not_refresh_signed_out_session,
+        should_remove_expired,
+    );
+
+    async fn should_create_session(session_service: &dyn Sessions, _: ManualClock) {
+        let tokens = session_service.create_session("123456").await.unwrap();
+
+        assert_ne!(tokens.session_token, tokens.refresh_token);
         assert_eq!(
-            session_service.uuid_to_session.get("123456").unwrap(),
-            &session
+            session_service
+                .get_session(&tokens.session_token)
+                .await
+                .unwrap()
+                .unwrap()
+                .user_uuid,
+            "123456"
+        );
+        assert_eq!(
+            session_service
+                .get_user_sessions("123456")
+                .await
+                .unwrap()
+                .unwrap()[0]
+                .0,
+            tokens.session_token
         );
     }
 
-    #[test]
-    fn should_delete_session() {
-        let mut session_service = SessionsImpl::default();
-        session_service.create_session("123456");
-        session_service.delete_session("123456");
-        assert_eq!(session_service.uuid_to_session.len(), 0);
+    async fn should_delete_session(session_service: &dyn Sessions, _: ManualClock) {
+        let tokens = session_service.create_session("123456").await.unwrap();
+
+        assert!(session_service
+            .delete_session(&tokens.session_token)
+            .await
+            .unwrap());
+        assert!(session_service
+            .get_session(&tokens.session_token)
+            .await
+            .unwrap()
+            .is_none());
+        assert!(session_service
+            .get_user_sessions("123456")
+            .await
+            .unwrap()
+            .unwrap()
+            .is_empty());
+    }
+
+    async fn should_fail_to_delete_unknown_session(session_service: &dyn Sessions, _: ManualClock) {
+        let tokens = session_service.create_session("123456").await.unwrap();
+
+        assert!(!session_service.delete_session("123456").await.unwrap());
+        assert!(session_service
+            .delete_session(&tokens.session_token)
+            .await
+            .unwrap());
+        assert!(!session_service
+            .delete_session(&tokens.session_token)
+            .await
+            .unwrap());
+    }
+
+    async fn should_get_session(session_service: &dyn Sessions, _: ManualClock) {
+        let tokens = session_service.create_session("123456").await.unwrap();
+
+        let session = session_service
+            .get_session(&tokens.session_token)
+            .await
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(session.user_uuid, "123456");
+        assert_eq!(session.expires_at, session.created_at + 10 * MINUTE);
+        assert!(session_service
+            .get_session("unknown")
+            .await
+            .unwrap()
+            .is_none());
+    }
+
+    async fn should_expire_idle_session(session_service: &dyn Sessions, clock: ManualClock) {
+        let tokens = session_service.create_session("123456").await.unwrap();
+
+        clock.advance(10 * MINUTE);
+
+        assert!(session_service
+            .get_session(&tokens.session_token)
+            .await
+            .unwrap()
+            .is_none());
+        assert!(session_service
+            .get_user_sessions("123456")
+            .await
+            .unwrap()
+            .unwrap()
+            .is_empty());
+        assert!(session_service
+            .renew_session(&tokens.session_token)
+            .await
+            .unwrap()
+            .is_none());
+        assert!(!session_service
+            .delete_session(&tokens.session_token)
+            .await
+            .unwrap());
+    }
+
+    async fn should_delay_idle_expiry_when_renewed(
+        session_service: &dyn Sessions,
+        clock: ManualClock,
+    ) {
+        let tokens = session_service.create_session("123456").await.unwrap();
+
+        clock.advance(9 * MINUTE);
+        let session = session_service
+            .renew_session(&tokens.session_token)
+            .await
+            .unwrap()
+            .unwrap();
+        clock.advance(9 * MINUTE);
+
+        assert_eq!(session.expires_at, session.created_at + 19 * MINUTE);
+        assert!(session_service
+            .get_session(&tokens.session_token)
+            .await
+            .unwrap()
+            .is_some());
+    }
+
+    async fn should_expire_session_after_absolute_ttl_however_used(
+        session_service: &dyn Sessions,
+        clock: ManualClock,
+    ) {
+        let tokens = session_service.create_session("123456").await.unwrap();
+
+        for _ in 0..6 {
+            clock.advance(9 * MINUTE);
+            assert!(session_service
+                .renew_session(&tokens.session_token)
+                .await
+                .unwrap()
+                .is_some());
+        }
+        clock.advance(6 * MINUTE);
+
+        assert!(session_service
+            .get_session(&tokens.session_token)
+            .await
+            .unwrap()
+            .is_none());
+    }
+
+    async fn should_keep_concurrent_sessions(session_service: &dyn Sessions, _: ManualClock) {
+        let first = session_service
+            .create_session("123456")
+            .await
+            .unwrap()
+            .session_token;
+        let second = session_service
+            .create_session("123456")
+            .await
+            .unwrap()
+            .session_token;
+        session_service.create_session("654321").await.unwrap();
+
+        let sessions = session_service
+            .get_user_sessions("123456")
+            .await
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(sessions.len(), 2);
+        assert!(sessions.iter().any(|(token, _)| token == &first));
+        assert!(sessions.iter().any(|(token, _)| token == &second));
+
+        session_service.delete_session(&first).await.unwrap();
+
+        assert!(session_service.get_session(&first).await.unwrap().is_none());
+        assert!(session_service
+            .get_session(&second)
+            .await
+            .unwrap()
+            .is_some());
+    }
+
+    async fn should_delete_user_sessions(session_service: &dyn Sessions, _: ManualClock) {
+        session_service.create_session("123456").await.unwrap();
+        let tokens = session_service.create_session("123456").await.unwrap();
+        let other = session_service.create_session("654321").await.unwrap();
+
+        assert_eq!(
+            session_service
+                .delete_user_sessions("123456")
+                .await
+                .unwrap(),
+            Some(2)
+        );
+        assert!(session_service
+            .get_user_sessions("123456")
+            .await
+            .unwrap()
+            .unwrap()
+            .is_empty());
+        assert!(session_service
+            .refresh_session(&tokens.refresh_token)
+            .await
+            .unwrap()
+            .is_none());
+        assert!(session_service
+            .get_session(&other.session_token)
+            .await
+            .unwrap()
+            .is_some());
+        assert_eq!(
+            session_service
+                .delete_user_sessions("123456")
+                .await
+                .unwrap(),
+            Some(0)
+        );
+    }
+
+    async fn should_refresh_session(session_service: &dyn Sessions, clock: ManualClock) {
+        let tokens = session_service.create_session("123456").await.unwrap();
+
+        clock.advance(30 * MINUTE);
+        let refreshed = session_service
+            .refresh_session(&tokens.refresh_token)
+            .await
+            .unwrap()
+            .unwrap();
+
+        assert_ne!(refreshed, tokens);
+        assert!(session_service
+            .get_session(&tokens.session_token)
+            .await
+            .unwrap()
+            .is_none());
+        let session = session_service
+            .get_session(&refreshed.session_token)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(session.user_uuid, "123456");
+        assert_eq!(session.created_at, clock.now());
+    }
+
+    async fn should_not_refresh_after_refresh_ttl(
+        session_service: &dyn Sessions,
+        clock: ManualClock,
+    ) {
+        let tokens = session_service.create_session("123456").await.unwrap();
+
+        // Rotating does not extend the refresh tokens of a sign in
+        clock.advance(100 * MINUTE);
+        let refreshed = session_service
+            .refresh_session(&tokens.refresh_token)
+            .await
+            .unwrap()
+            .unwrap();
+        clock.advance(20 * MINUTE);
+
+        assert!(session_service
+            .refresh_session(&refreshed.refresh_token)
+            .await
+            .unwrap()
+            .is_none());
+        assert!(session_service
+            .refresh_session("unknown")
+            .await
+            .unwrap()
+            .is_none());
+    }
+
+    async fn should_end_family_when_refresh_token_reused(
+        session_service: &dyn Sessions,
+        _: ManualClock,
+    ) {
+        let tokens = session_service.create_session("123456").await.unwrap();
+        let other = session_service.create_session("123456").await.unwrap();
+
+        let refreshed = session_service
+            .refresh_session(&tokens.refresh_token)
+            .await
+            .unwrap()
+            .unwrap();
+
+        assert!(session_service
+            .refresh_session(&tokens.refresh_token)
+            .await
+            .unwrap()
+            .is_none());
+        assert!(session_service
+            .get_session(&refreshed.session_token)
+            .await
+            .unwrap()
+            .is_none());
+        assert!(session_service
+            .refresh_session(&refreshed.refresh_token)
+            .await
+            .unwrap()
+            .is_none());
+        // Other sign ins of the user are left alone
+        assert!(session_service
+            .get_session(&other.session_token)
+            .await
+            .unwrap()
+            .is_some());
+    }
+
+    async fn should_not_refresh_signed_out_session(session_service: &dyn Sessions, _: ManualClock) {
+        let tokens = session_service.create_session("123456").await.unwrap();
+
+        session_service
+            .delete_session(&tokens.session_token)
+            .await
+            .unwrap();
+
+        assert!(session_service
+            .refresh_session(&tokens.refresh_token)
+            .await
+            .unwrap()
+            .is_none());
+    }
+
+    async fn should_remove_expired(session_service: &dyn Sessions, clock: ManualClock) {
+        let tokens = session_service.create_session("123456").await.unwrap();
+        session_service.create_session("654321").await.unwrap();
+
+        clock.advance(10 * MINUTE);
+        let refreshed = session_service
+            .refresh_session(&tokens.refresh_token)
+            .await
+            .unwrap()
+            .unwrap();
+
+        // The expired session of 654321
+        assert_eq!(session_service.remove_expired().await.unwrap(), 1);
+        assert_eq!(
+            session_service
+                .get_user_sessions("123456")
+                .await
+                .unwrap()
+                .unwrap()
+                .len(),
+            1
+        );
+
+        // The refreshed session, then the refresh tokens, used or not
+        clock.advance(10 * MINUTE);
+        assert_eq!(session_service.remove_expired().await.unwrap(), 1);
+        clock.advance(100 * MINUTE);
+        assert_eq!(session_service.remove_expired().await.unwrap(), 3);
+        assert_eq!(session_service.remove_expired().await.unwrap(), 0);
+        assert!(session_service
+            .refresh_session(&refreshed.refresh_token)
+            .await
+            .unwrap()
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn should_keep_indexes_in_step() {
+        let session_service = SessionsImpl::default();
+        let tokens = session_service.create_session("123456").await.unwrap();
+        assert_eq!(session_service.index().token_to_session.len(), 1);
+        assert!(session_service.index().user_to_tokens["123456"].contains(&tokens.session_token));
+        assert!(session_service
+            .index()
+            .refresh_tokens
+            .contains_key(&tokens.refresh_token));
+
+        session_service
+            .delete_session(&tokens.session_token)
+            .await
+            .unwrap();
+
+        assert_eq!(session_service.index().token_to_session.len(), 0);
+        assert_eq!(session_service.index().user_to_tokens.len(), 0);
+        assert_eq!(session_service.index().refresh_tokens.len(), 0);
     }
 }
@@ -2,15 +2,44 @@ use pbkdf2::{
     password_hash::{rand_core::OsRng, PasswordHash, PasswordHasher, PasswordVerifier, SaltString},
     Pbkdf2,
 };
+use sqlx::AnyPool;
 use uuid::Uuid;
 
 use std::collections::HashMap;
+use std::sync::{Mutex, MutexGuard};
 
 #[allow(dead_code)]
+#[tonic::async_trait]
 pub trait Users {
-    fn create_user(&mut self, username: String, password: String) -> Result<(), String>;
-    fn get_user_uuid(&self, username: String, password: String) -> Option<String>;
-    fn delete_user(&mut self, user_uuid: String);
+    // Returns false if the username is already taken.
+    async fn create_user(&self, username: String, password: String) -> Result<bool, String>;
+    async fn get_user_uuid(
+        &self,
+        username: String,
+        password: String,
+    ) -> Result<Option<String>, String>;
+    async fn delete_user(&self, user_uuid: String) -> Result<(), String>;
+}
+
+// Hashes the password with a random salt.
+fn hash_password(password: &str) -> Result<String, String> {
+    let salt = SaltString::generate(&mut OsRng);
+
+    Ok(Pbkdf2
+        .hash_password(password.as_bytes(), &salt)
+        .map_err(|e| format!("Failed to hash password.\n{e:?}"))?
+        .to_string())
+}
+
+// Verify passed in password matches the user's hashed password.
+fn verify_password(hashed_password: &str, password: &str) -> bool {
+    let Ok(parsed_hash) = PasswordHash::new(hashed_password) else {
+        return false;
+    };
+
+    Pbkdf2
+        .verify_password(password.as_bytes(), &parsed_hash)
+        .is_ok()
 }
 
 #[derive(Clone)]
@@ -21,24 +50,27 @@ pub struct User {
 }
 
 #[derive(Default)]
-pub struct UsersImpl {
+struct UserIndex {
     uuid_to_user: HashMap<String, User>,
     username_to_user: HashMap<String, User>,
 }
 
-impl Users for UsersImpl {
-    fn create_user(&mut self, username: String, password: String) -> Result<(), String> {
-        // Check if username already exist
-        if self.username_to_user.contains_key(&username) {
-            return Err(format!("User {username} already exists"));
-        }
+#[derive(Default)]
+pub struct UsersImpl {
+    // Only locked while the indexes are read or updated, never while hashing
+    index: Mutex<UserIndex>,
+}
 
-        let salt = SaltString::generate(&mut OsRng);
+impl UsersImpl {
+    fn index(&self) -> MutexGuard<'_, UserIndex> {
+        self.index.lock().expect("Poisoned users mutex")
+    }
+}
 
-        let hashed_password = Pbkdf2
-            .hash_password(password.as_bytes(), &salt)
-            .map_err(|e| format!("Failed to hash password.\n{e:?}"))?
-            .to_string();
+#[tonic::async_trait]
+impl Users for UsersImpl {
+    async fn create_user(&self, username: String, password: String) -> Result<bool, String> {
+        let hashed_password = hash_password(&password)?;
 
         let user_uuid = Uuid::new_v4().to_string();
 
@@ -49,34 +81,102 @@ impl Users for UsersImpl {
             password: hashed_password,
         };
 
-        self.username_to_user.insert(username, user.clone());
-        self.uuid_to_user.insert(user_uuid, user);
+        // Check if username already exist
+        let mut index = self.index();
+        if index.username_to_user.contains_key(&username) {
+            return Ok(false);
+        }
 
-        Ok(())
+        index.username_to_user.insert(username, user.clone());
+        index.uuid_to_user.insert(user_uuid, user);
+
+        Ok(true)
     }
 
     //  If the username and password passed in matches the user's username and password return the user's uuid.
-    fn get_user_uuid(&self, username: String, password: String) -> Option<String> {
-        if let Some(user) = self.username_to_user.get(&username) {
-            // Get user's password as `PasswordHash` instance.
-            let hashed_password = user.password.clone();
-            let parsed_hash = PasswordHash::new(&hashed_password).ok()?;
-
-            // Verify passed in password matches user's password.
-            let result = Pbkdf2.verify_password(password.as_bytes(), &parsed_hash);
-            if result.is_ok() {
-                return Some(user.user_uuid.clone());
-            }
-        }
+    async fn get_user_uuid(
+        &self,
+        username: String,
+        password: String,
+    ) -> Result<Option<String>, String> {
+        let user = self.index().username_to_user.get(&username).cloned();
 
-        None
+        Ok(user
+            .filter(|user| verify_password(&user.password, &password))
+            .map(|user| user.user_uuid))
     }
 
     //  Remove user from `username_to_user` and `uuid_to_user`.
-    fn delete_user(&mut self, user_uuid: String) {
-        if let Some(user) = self.uuid_to_user.remove(&user_uuid) {
-            self.username_to_user.remove(&user.username);
+    async fn delete_user(&self, user_uuid: String) -> Result<(), String> {
+        let mut index = self.index();
+        if let Some(user) = index.uuid_to_user.remove(&user_uuid) {
+            index.username_to_user.remove(&user.username);
         }
+
+        Ok(())
+    }
+}
+
+// Users kept in the `users` table of a SQLite or Postgres database.
+pub struct DatabaseUsers {
+    db: AnyPool,
+}
+
+impl DatabaseUsers {
+    pub fn new(db: AnyPool) -> Self {
+        Self { db }
+    }
+}
+
+#[tonic::async_trait]
+impl Users for DatabaseUsers {
+    async fn create_user(&self, username: String, password: String) -> Result<bool, String> {
+        let hashed_password = hash_password(&password)?;
+
+        // The unique username settles concurrent sign ups
+        let created = sqlx::query(
+            r"
+        INSERT INTO users ( user_uuid, username, password )
+        VALUES ( $1, $2, $3 )
+        ON CONFLICT ( username ) DO NOTHING
+        ",
+        )
+        .bind(Uuid::new_v4().to_string())
+        .bind(&username)
+        .bind(hashed_password)
+        .execute(&self.db)
+        .await
+        .map_err(|e| format!("Failed to create user {username}.\n{e:?}"))?;
+
+        Ok(created.rows_affected() == 1)
+    }
+
+    async fn get_user_uuid(
+        &self,
+        username: String,
+        password: String,
+    ) -> Result<Option<String>, String> {
+        let user = sqlx::query_as::<_, (String, String)>(
+            "SELECT user_uuid, password FROM users WHERE username = $1",
+        )
+        .bind(&username)
+        .fetch_optional(&self.db)
+        .await
+        .map_err(|e| format!("Failed to get user {username}.\n{e:?}"))?;
+
+        Ok(user
+            .filter(|(_, hashed_password)| verify_password(hashed_password, &password))
+            .map(|(user_uuid, _)| user_uuid))
+    }
+
+    async fn delete_user(&self, user_uuid: String) -> Result<(), String> {
+        sqlx::query("DELETE FROM users WHERE user_uuid = $1")
+            .bind(&user_uuid)
+            .execute(&self.db)
+            .await
+            .map_err(|e| format!("Failed to delete user {user_uuid}.\n{e:?}"))?;
+
+        Ok(())
     }
 }
 
@@ -84,67 +184,171 @@ impl Users for UsersImpl {
 mod tests {
     use super::*;
 
-    #[test]
-    fn should_create_user() {
-        let mut user_service = UsersImpl::default();
+    // Runs each test against every backend
+    macro_rules! backend_tests {
+        ($($test:ident),* $(,)?) => {
+            mod memory {
+                $(
+                    #[tokio::test]
+                    async fn $test() {
+                        super::$test(&super::UsersImpl::default()).await;
+                    }
+                )*
+            }
+
+            mod sqlite {
+                use crate::database::tests::TestDatabase;
+
+                $(
+                    #[tokio::test]
+                    async fn $test() {
+                        let database = TestDatabase::sqlite().await;
+                        super::$test(&super::DatabaseUsers::new(database.pool.clone())).await;
+                        database.close().await;
+                    }
+                )*
+            }
+
+            mod postgres {
+                use crate::database::tests::TestDatabase;
+
+                $(
+                    #[tokio::test]
+                    #[ignore = "needs a Postgres server at DATABASE_URL"]
+                    async fn $test() {
+                        let database = TestDatabase::postgres().await;
+                        super::$test(&super::DatabaseUsers::new(database.pool.clone())).await;
+                        database.close().await;
+                    }
+                )*
+            }
+        };
+    }
+
+    backend_tests!(
+        should_create_user,
+        should_fail_creating_user_with_existing_username,
+        should_create_user_once_when_signing_up_concurrently,
+        should_retrieve_user_uuid,
+        should_fail_to_retrieve_user_uuid_with_incorrect_password,
+        should_fail_to_retrieve_user_uuid_of_unknown_user,
+        should_delete_user,
+    );
+
+    async fn should_create_user(user_service: &dyn Users) {
         user_service
             .create_user("username".to_owned(), "password".to_owned())
+            .await
             .expect("should create user");
 
-        assert_eq!(user_service.uuid_to_user.len(), 1);
-        assert_eq!(user_service.username_to_user.len(), 1);
+        let other_user = user_service
+            .create_user("other username".to_owned(), "password".to_owned())
+            .await
+            .unwrap();
+
+        assert!(other_user);
     }
 
-    #[test]
-    fn should_fail_creating_user_with_existing_username() {
-        let mut user_service = UsersImpl::default();
+    async fn should_fail_creating_user_with_existing_username(user_service: &dyn Users) {
         user_service
             .create_user("username".to_owned(), "password".to_owned())
+            .await
             .expect("should create user");
 
-        let result = user_service.create_user("username".to_owned(), "password".to_owned());
+        let created = user_service
+            .create_user("username".to_owned(), "password".to_owned())
+            .await
+            .unwrap();
+
+        assert!(!created);
+    }
+
+    async fn should_create_user_once_when_signing_up_concurrently(user_service: &dyn Users) {
+        let (first, second) = tokio::join!(
+            user_service.create_user("username".to_owned(), "password".to_owned()),
+            user_service.create_user("username".to_owned(), "other password".to_owned()),
+        );
 
-        assert!(result.is_err());
+        assert!(first.unwrap() != second.unwrap());
     }
 
-    #[test]
-    fn should_retrieve_user_uuid() {
-        let mut user_service = UsersImpl::default();
+    async fn should_retrieve_user_uuid(user_service: &dyn Users) {
         user_service
             .create_user("username".to_owned(), "password".to_owned())
+            .await
             .expect("should create user");
 
         assert!(user_service
             .get_user_uuid("username".to_owned(), "password".to_owned())
+            .await
+            .unwrap()
             .is_some());
     }
 
-    #[test]
-    fn should_fail_to_retrieve_user_uuid_with_incorrect_password() {
-        let mut user_service = UsersImpl::default();
+    async fn should_fail_to_retrieve_user_uuid_with_incorrect_password(user_service: &dyn Users) {
         user_service
             .create_user("username".to_owned(), "password".to_owned())
+            .await
             .expect("should create user");
 
         assert!(user_service
             .get_user_uuid("username".to_owned(), "incorrect password".to_owned())
+            .await
+            .unwrap()
+            .is_none());
+    }
+
+    async fn should_fail_to_retrieve_user_uuid_of_unknown_user(user_service: &dyn Users) {
+        assert!(user_service
+            .get_user_uuid("username".to_owned(), "password".to_owned())
+            .await
+            .unwrap()
             .is_none());
     }
 
-    #[test]
-    fn should_delete_user() {
-        let mut user_service = UsersImpl::default();
+    async fn should_delete_user(user_service: &dyn Users) {
         user_service
             .create_user("username".to_owned(), "password".to_owned())
+            .await
             .expect("should create user");
 
         let user_uuid = user_service
             .get_user_uuid("username".to_owned(), "password".to_owned())
+            .await
+            .unwrap()
             .unwrap();
 
-        user_service.delete_user(user_uuid);
+        user_service.delete_user(user_uuid).await.unwrap();
+
+        assert!(user_service
+            .get_user_uuid("username".to_owned(), "password".to_owned())
+            .await
+            .unwrap()
+            .is_none());
+        // The username can be taken again
+        assert!(user_service
+            .create_user("username".to_owned(), "password".to_owned())
+            .await
+            .unwrap());
+    }
+
+    #[tokio::test]
+    async fn should_keep_indexes_in_step() {
+        let user_service = UsersImpl::default();
+        user_service
+            .create_user("username".to_owned(), "password".to_owned())
+            .await
+            .expect("should create user");
+
+        assert_eq!(user_service.index().uuid_to_user.len(), 1);
+        assert_eq!(user_service.index().username_to_user.len(), 1);
+
+        let user_uuid = user_service.index().username_to_user["username"]
+            .user_uuid
+            .clone();
+        user_service.delete_user(user_uuid).await.unwrap();
 
-        assert_eq!(user_service.uuid_to_user.len(), 0);
-        assert_eq!(user_service.username_to_user.len(), 0);
+        assert_eq!(user_service.index().uuid_to_user.len(), 0);
+        assert_eq!(user_service.index().username_to_user.len(), 0);
     }
 }
@@ -2,10 +2,17 @@ use clap::{Parser, Subcommand};
 use std::env;
 
 use authentication::auth_client::AuthClient;
-use authentication::{SignInRequest, SignOutRequest, SignUpRequest};
+use authentication::{
+    GetJwksRequest, GetRevocationsRequest, ListSessionsRequest, RefreshSessionRequest,
+    SignInRequest, SignOutEverywhereRequest, SignOutRequest, SignUpRequest, ValidateSessionRequest,
+};
 use tonic::{Request, Response};
 
-use crate::authentication::{SignInResponse, SignOutResponse, SignUpResponse};
+use crate::authentication::{
+    GetJwksResponse, GetRevocationsResponse, ListSessionsResponse, RefreshSessionResponse,
+    SignInResponse, SignOutEverywhereResponse, SignOutResponse, SignUpResponse,
+    ValidateSessionResponse,
+};
 
 pub mod authentication {
     tonic::include_proto!("authentication");
@@ -37,6 +44,24 @@ enum Commands {
         #[arg(short, long)]
         session_token: String,
     },
+    RefreshSession {
+        #[arg(short, long)]
+        refresh_token: String,
+    },
+    SignOutEverywhere {
+        #[arg(short, long)]
+        session_token: String,
+    },
+    ValidateSession {
+        #[arg(short, long)]
+        session_token: String,
+    },
+    ListSessions {
+        #[arg(short, long)]
+        session_token: String,
+    },
+    GetJwks,
+    GetRevocations,
 }
 
 #[tokio::main]
@@ -77,6 +102,61 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
             println!("{:?}", response.into_inner());
         }
+        Some(Commands::RefreshSession { refresh_token }) => {
+            let request: Request<RefreshSessionRequest> = Request::new(RefreshSessionRequest {
+                refresh_token: refresh_token.to_owned(),
+            });
+
+            let response: Response<RefreshSessionResponse> =
+                client.refresh_session(request).await?;
+
+            println!("{:?}", response.into_inner());
+        }
+        Some(Commands::SignOutEverywhere { session_token }) => {
+            let request: Request<SignOutEverywhereRequest> =
+                Request::new(SignOutEverywhereRequest {
+                    session_token: session_token.to_owned(),
+                });
+
+            let response: Response<SignOutEverywhereResponse> =
+                client.sign_out_everywhere(request).await?;
+
+            println!("{:?}", response.into_inner());
+        }
+        Some(Commands::ValidateSession { session_token }) => {
+            let request: Request<ValidateSessionRequest> = Request::new(ValidateSessionRequest {
+                session_token: session_token.to_owned(),
+            });
+
+            let response: Response<ValidateSessionResponse> =
+                client.validate_session(request).await?;
+
+            println!("{:?}", response.into_inner());
+        }
+        Some(Commands::ListSessions { session_token }) => {
+            let request: Request<ListSessionsRequest> = Request::new(ListSessionsRequest {
+                session_token: session_token.to_owned(),
+            });
+
+            let response: Response<ListSessionsResponse> = client.list_sessions(request).await?;
+
+            println!("{:?}", response.into_inner());
+        }
+        Some(Commands::GetJwks) => {
+            let request: Request<GetJwksRequest> = Request::new(GetJwksRequest {});
+
+            let response: Response<GetJwksResponse> = client.get_jwks(request).await?;
+
+            println!("{:?}", response.into_inner());
+        }
+        Some(Commands::GetRevocations) => {
+            let request: Request<GetRevocationsRequest> = Request::new(GetRevocationsRequest {});
+
+            let response: Response<GetRevocationsResponse> =
+                client.get_revocations(request).await?;
+
+            println!("{:?}", response.into_inner());
+        }
         None => {}
     }
 
@@ -0,0 +1,141 @@
+//! Throughput of the handler layer through the in-process router, database included.
+//! Every run asks its questions in a space of its own, so that earlier runs do not change
+//! the size of what is listed.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use axum_test::TestServer;
+use criterion::{criterion_group, criterion_main, Criterion};
+use sqlx::postgres::PgPoolOptions;
+use stackoverflow::{
+    app, app_state,
+    config::Config,
+    models::{Question, QuestionDetail, QuestionId, Space},
+};
+use tokio::runtime::Runtime;
+
+/// Questions listed by the listing benchmarks
+const LISTED_QUESTIONS: usize = 50;
+const ADMIN_TOKEN: &str = "bench-admin";
+
+/// Server with a fresh open space holding `LISTED_QUESTIONS` questions,
+/// returns the path prefix of the space
+async fn server(config: Config) -> (TestServer, String) {
+    dotenvy::dotenv().ok();
+    let pool = PgPoolOptions::new()
+        .max_connections(5)
+        .connect(&std::env::var("DATABASE_URL").expect("DATABASE_URL must be set"))
+        .await
+        .expect("Failed to create Postgres connection pool");
+    let config = Config {
+        admin_token: Some(ADMIN_TOKEN.to_owned()),
+        ..config
+    };
+    let server = TestServer::new(app(app_state(pool, config))).unwrap();
+
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_nanos();
+    let slug = format!("bench-{nanos}");
+    server
+        .post("/spaces")
+        .authorization_bearer(ADMIN_TOKEN)
+        .json(&Space {
+            slug: slug.clone(),
+            name: "Benchmarks".to_owned(),
+            open: true,
+        })
+        .await
+        .assert_status_ok();
+
+    let prefix = format!("/spaces/{slug}");
+    for i in 0..LISTED_QUESTIONS {
+        server
+            .post(&format!("{prefix}/question"))
+            .json(&question(i))
+            .await
+            .assert_status_ok();
+    }
+
+    (server, prefix)
+}
+
+fn question(i: usize) -> Question {
+    Question {
+        title: format!("Benchmark question {i}"),
+        description: "Some `code` and a [link](https://example.com)".to_owned(),
+    }
+}
+
+fn list_questions(c: &mut Criterion) {
+    let rt = Runtime::new().unwrap();
+    let mut group = c.benchmark_group("list_questions");
+
+    let uncached = Config {
+        cache_capacity: 0,
+        ..Config::default()
+    };
+    for (name, config) in [("cached", Config::default()), ("uncached", uncached)] {
+        let (server, prefix) = rt.block_on(server(config));
+        let path = format!("{prefix}/questions");
+        group.bench_function(name, |b| {
+            b.to_async(&rt)
+                .iter(|| async { server.get(&path).await.assert_status_ok() })
+        });
+    }
+
+    group.finish();
+}
+
+fn read_question(c: &mut Criterion) {
+    let rt = Runtime::new().unwrap();
+    let (server, prefix) = rt.block_on(server(Config::default()));
+    let question = rt.block_on(async {
+        server
+            .post(&format!("{prefix}/question"))
+            .json(&question(LISTED_QUESTIONS))
+            .await
+            .json::<QuestionDetail>()
+    });
+    let path = format!("{prefix}/questions/{}", question.question_uuid);
+
+    c.bench_function("read_question", |b| {
+        b.to_async(&rt)
+            .iter(|| async { server.get(&path).await.assert_status_ok() })
+    });
+}
+
+/// Deleting what was created keeps the number of listed questions constant
+fn create_and_delete_question(c: &mut Criterion) {
+    let rt = Runtime::new().unwrap();
+    let (server, prefix) = rt.block_on(server(Config::default()));
+    let path = format!("{prefix}/question");
+
+    c.bench_function("create_and_delete_question", |b| {
+        b.to_async(&rt).iter(|| async {
+            let question = server
+                .post(&path)
+                .add_header("X-User-Id", "bench")
+                .json(&question(0))
+                .await
+                .json::<QuestionDetail>();
+            server
+                .delete(&path)
+                .add_header("X-User-Id", "bench")
+                .json(&QuestionId {
+                    question_uuid: question.question_uuid,
+                })
+                .await
+                .assert_status_ok();
+        })
+    });
+}
+
+criterion_group!(
+    benches,
+    list_questions,
+    read_question,
+    create_and_delete_question
+);
+criterion_main!(benches);
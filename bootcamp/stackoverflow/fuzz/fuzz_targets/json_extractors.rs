@@ -0,0 +1,53 @@
+//! Feeds arbitrary bodies to the JSON extractors of the request models, run with
+//! `cargo +nightly fuzz run json_extractors` from the `stackoverflow` directory.
+//! Bodies must either be rejected as the client's fault or read as values which survive
+//! a round trip through JSON.
+
+#![no_main]
+
+use std::sync::OnceLock;
+
+use axum::{
+    body::Body,
+    extract::FromRequest,
+    http::{header, Request},
+    Json,
+};
+use libfuzzer_sys::fuzz_target;
+use serde::{de::DeserializeOwned, Serialize};
+use stackoverflow::models::{Answer, AnswerId, Question, QuestionId};
+use tokio::runtime::{Builder, Runtime};
+
+fn runtime() -> &'static Runtime {
+    static RUNTIME: OnceLock<Runtime> = OnceLock::new();
+    RUNTIME.get_or_init(|| Builder::new_current_thread().build().unwrap())
+}
+
+async fn extract<T: Serialize + DeserializeOwned>(body: &[u8]) {
+    let request = Request::post("/")
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::from(body.to_vec()))
+        .unwrap();
+
+    match Json::<T>::from_request(request, &()).await {
+        Ok(Json(value)) => {
+            let json = serde_json::to_vec(&value).unwrap();
+            let read_back =
+                serde_json::from_slice::<T>(&json).expect("Written values are read back");
+            assert_eq!(serde_json::to_vec(&read_back).unwrap(), json);
+        }
+        Err(rejection) => {
+            let status = axum::response::IntoResponse::into_response(rejection).status();
+            assert!(status.is_client_error(), "Rejected with {status}");
+        }
+    }
+}
+
+fuzz_target!(|body: &[u8]| {
+    runtime().block_on(async {
+        extract::<Question>(body).await;
+        extract::<Answer>(body).await;
+        extract::<QuestionId>(body).await;
+        extract::<AnswerId>(body).await;
+    });
+});
@@ -0,0 +1,26 @@
+-- Add up migration script here
+
+CREATE TABLE IF NOT EXISTS webhooks (
+    webhook_uuid uuid PRIMARY KEY DEFAULT gen_random_uuid(),
+    url VARCHAR(2048) NOT NULL,
+    events TEXT[] NOT NULL,
+    secret VARCHAR(64) NOT NULL DEFAULT encode(sha256(gen_random_uuid()::text::bytea), 'hex'),
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+-- One row per (event, subscribed webhook), written in the same transaction as the event itself
+CREATE TABLE IF NOT EXISTS webhook_deliveries (
+    delivery_uuid uuid PRIMARY KEY DEFAULT gen_random_uuid(),
+    webhook_uuid uuid NOT NULL REFERENCES webhooks (webhook_uuid) ON DELETE CASCADE,
+    event VARCHAR(64) NOT NULL,
+    payload JSONB NOT NULL,
+    status VARCHAR(16) NOT NULL DEFAULT 'pending',
+    attempts INTEGER NOT NULL DEFAULT 0,
+    last_error TEXT,
+    next_attempt_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS webhook_deliveries_pending_idx
+    ON webhook_deliveries (next_attempt_at)
+    WHERE status = 'pending';
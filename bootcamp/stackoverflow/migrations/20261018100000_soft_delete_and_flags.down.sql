@@ -0,0 +1,6 @@
+-- Add down migration script here
+
+DROP TABLE IF EXISTS flags;
+
+ALTER TABLE answers DROP COLUMN IF EXISTS deleted_at, DROP COLUMN IF EXISTS deleted_by;
+ALTER TABLE questions DROP COLUMN IF EXISTS deleted_at, DROP COLUMN IF EXISTS deleted_by;
@@ -0,0 +1,26 @@
+-- Add up migration script here
+
+ALTER TABLE questions
+    ADD COLUMN IF NOT EXISTS deleted_at TIMESTAMP,
+    ADD COLUMN IF NOT EXISTS deleted_by VARCHAR(255);
+
+ALTER TABLE answers
+    ADD COLUMN IF NOT EXISTS deleted_at TIMESTAMP,
+    ADD COLUMN IF NOT EXISTS deleted_by VARCHAR(255);
+
+CREATE TABLE IF NOT EXISTS flags (
+    flag_uuid uuid PRIMARY KEY DEFAULT gen_random_uuid(),
+    question_uuid uuid REFERENCES questions (question_uuid) ON DELETE CASCADE,
+    answer_uuid uuid REFERENCES answers (answer_uuid) ON DELETE CASCADE,
+    reason VARCHAR(255) NOT NULL,
+    reported_by VARCHAR(255) NOT NULL,
+    status VARCHAR(16) NOT NULL DEFAULT 'open',
+    resolution VARCHAR(16),
+    resolved_by VARCHAR(255),
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    resolved_at TIMESTAMP,
+    -- A flag targets exactly one post
+    CHECK ((question_uuid IS NULL) <> (answer_uuid IS NULL))
+);
+
+CREATE INDEX IF NOT EXISTS flags_open_idx ON flags (created_at) WHERE status = 'open';
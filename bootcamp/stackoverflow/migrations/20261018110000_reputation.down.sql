@@ -0,0 +1,6 @@
+-- Add down migration script here
+
+DROP TABLE IF EXISTS reputation_events;
+
+ALTER TABLE answers DROP COLUMN IF EXISTS author;
+ALTER TABLE questions DROP COLUMN IF EXISTS author;
@@ -0,0 +1,18 @@
+-- Add up migration script here
+
+ALTER TABLE questions ADD COLUMN IF NOT EXISTS author VARCHAR(255) NOT NULL DEFAULT 'anonymous';
+ALTER TABLE answers ADD COLUMN IF NOT EXISTS author VARCHAR(255) NOT NULL DEFAULT 'anonymous';
+
+-- Append-only ledger, a user's reputation is the sum of their points
+CREATE TABLE IF NOT EXISTS reputation_events (
+    event_uuid uuid PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id VARCHAR(255) NOT NULL,
+    reason VARCHAR(64) NOT NULL,
+    points INTEGER NOT NULL,
+    post_uuid uuid,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS reputation_events_user_idx ON reputation_events (user_id);
+CREATE INDEX IF NOT EXISTS questions_author_idx ON questions (author);
+CREATE INDEX IF NOT EXISTS answers_author_idx ON answers (author);
@@ -0,0 +1,9 @@
+-- Add down migration script here
+
+ALTER TABLE questions DROP COLUMN IF EXISTS closed_by;
+ALTER TABLE questions DROP COLUMN IF EXISTS duplicate_of;
+
+DROP INDEX IF EXISTS questions_description_trgm_idx;
+DROP INDEX IF EXISTS questions_title_trgm_idx;
+
+DROP EXTENSION IF EXISTS pg_trgm;
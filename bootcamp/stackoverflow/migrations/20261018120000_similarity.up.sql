@@ -0,0 +1,10 @@
+-- Add up migration script here
+
+CREATE EXTENSION IF NOT EXISTS pg_trgm;
+
+CREATE INDEX IF NOT EXISTS questions_title_trgm_idx ON questions USING GIN (title gin_trgm_ops);
+CREATE INDEX IF NOT EXISTS questions_description_trgm_idx ON questions USING GIN (description gin_trgm_ops);
+
+-- Questions closed as a duplicate point to the question that should be read instead
+ALTER TABLE questions ADD COLUMN IF NOT EXISTS duplicate_of uuid REFERENCES questions(question_uuid);
+ALTER TABLE questions ADD COLUMN IF NOT EXISTS closed_by VARCHAR(255);
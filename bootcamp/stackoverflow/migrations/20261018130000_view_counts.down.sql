@@ -0,0 +1,5 @@
+-- Add down migration script here
+
+DROP TABLE IF EXISTS question_views;
+
+ALTER TABLE questions DROP COLUMN IF EXISTS view_count;
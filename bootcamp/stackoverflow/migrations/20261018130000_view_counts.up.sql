@@ -0,0 +1,13 @@
+-- Add up migration script here
+
+ALTER TABLE questions ADD COLUMN IF NOT EXISTS view_count BIGINT NOT NULL DEFAULT 0;
+
+-- Views per question and hour, trending scores decay them with their age
+CREATE TABLE IF NOT EXISTS question_views (
+    question_uuid uuid NOT NULL REFERENCES questions (question_uuid) ON DELETE CASCADE,
+    hour TIMESTAMP NOT NULL,
+    views BIGINT NOT NULL,
+    PRIMARY KEY (question_uuid, hour)
+);
+
+CREATE INDEX IF NOT EXISTS question_views_hour_idx ON question_views (hour);
@@ -0,0 +1,5 @@
+-- Add down migration script here
+
+DROP TABLE IF EXISTS inbox_items;
+DROP TABLE IF EXISTS bookmarks;
+DROP TABLE IF EXISTS follows;
@@ -0,0 +1,28 @@
+-- Add up migration script here
+
+CREATE TABLE IF NOT EXISTS follows (
+    user_id VARCHAR(255) NOT NULL,
+    question_uuid uuid NOT NULL REFERENCES questions (question_uuid) ON DELETE CASCADE,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (user_id, question_uuid)
+);
+
+CREATE INDEX IF NOT EXISTS follows_question_idx ON follows (question_uuid);
+
+CREATE TABLE IF NOT EXISTS bookmarks (
+    user_id VARCHAR(255) NOT NULL,
+    question_uuid uuid NOT NULL REFERENCES questions (question_uuid) ON DELETE CASCADE,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (user_id, question_uuid)
+);
+
+-- One row per follower of a question for every new answer, written with the answer
+CREATE TABLE IF NOT EXISTS inbox_items (
+    item_uuid uuid PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id VARCHAR(255) NOT NULL,
+    answer_uuid uuid NOT NULL REFERENCES answers (answer_uuid) ON DELETE CASCADE,
+    read_at TIMESTAMP,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS inbox_items_unread_idx ON inbox_items (user_id) WHERE read_at IS NULL;
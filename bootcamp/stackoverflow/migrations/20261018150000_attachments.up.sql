@@ -0,0 +1,16 @@
+-- Add up migration script here
+
+-- Contents live in the blob store under their SHA-256 digest, identical files share a blob
+CREATE TABLE IF NOT EXISTS attachments (
+    attachment_uuid uuid PRIMARY KEY DEFAULT gen_random_uuid(),
+    question_uuid uuid NOT NULL REFERENCES questions (question_uuid) ON DELETE CASCADE,
+    file_name VARCHAR(255) NOT NULL,
+    content_type VARCHAR(255) NOT NULL,
+    size BIGINT NOT NULL,
+    sha256 CHAR(64) NOT NULL,
+    uploaded_by VARCHAR(255) NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS attachments_question_idx ON attachments (question_uuid);
+CREATE INDEX IF NOT EXISTS attachments_sha256_idx ON attachments (sha256);
@@ -0,0 +1,7 @@
+-- Add down migration script here
+
+ALTER TABLE webhooks DROP COLUMN IF EXISTS space_id;
+DROP INDEX IF EXISTS questions_space_idx;
+ALTER TABLE questions DROP COLUMN IF EXISTS space_id;
+DROP TABLE IF EXISTS space_members;
+DROP TABLE IF EXISTS spaces;
@@ -0,0 +1,32 @@
+-- Add up migration script here
+
+CREATE TABLE IF NOT EXISTS spaces (
+    space_id uuid PRIMARY KEY DEFAULT gen_random_uuid(),
+    slug VARCHAR(64) NOT NULL UNIQUE,
+    name VARCHAR(255) NOT NULL,
+    -- Anyone may read and write an open space, members or not
+    open BOOLEAN NOT NULL DEFAULT FALSE,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+-- Every question asked before spaces existed belongs to the default space
+INSERT INTO spaces ( space_id, slug, name, open )
+VALUES ( '00000000-0000-0000-0000-000000000001', 'default', 'Default', TRUE )
+ON CONFLICT DO NOTHING;
+
+CREATE TABLE IF NOT EXISTS space_members (
+    space_id uuid NOT NULL REFERENCES spaces (space_id) ON DELETE CASCADE,
+    user_id VARCHAR(255) NOT NULL,
+    role VARCHAR(16) NOT NULL CHECK (role IN ('reader', 'writer')),
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (space_id, user_id)
+);
+
+ALTER TABLE questions ADD COLUMN IF NOT EXISTS space_id uuid NOT NULL
+    DEFAULT '00000000-0000-0000-0000-000000000001' REFERENCES spaces (space_id);
+
+CREATE INDEX IF NOT EXISTS questions_space_idx ON questions (space_id, created_at);
+
+-- Webhooks only receive the events of their own space
+ALTER TABLE webhooks ADD COLUMN IF NOT EXISTS space_id uuid NOT NULL
+    DEFAULT '00000000-0000-0000-0000-000000000001' REFERENCES spaces (space_id) ON DELETE CASCADE;
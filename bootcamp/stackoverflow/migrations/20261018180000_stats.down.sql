@@ -0,0 +1,5 @@
+-- Add down migration script here
+
+DROP MATERIALIZED VIEW IF EXISTS stats_refreshes;
+DROP MATERIALIZED VIEW IF EXISTS daily_posts;
+DROP MATERIALIZED VIEW IF EXISTS question_stats;
@@ -0,0 +1,42 @@
+-- Add up migration script here
+
+-- Statistics are read from materialised views refreshed in the background, so that
+-- reading them does not scan every post. Deleted posts are left out.
+
+-- One row per question, with its first answer
+CREATE MATERIALIZED VIEW IF NOT EXISTS question_stats AS
+SELECT q.question_uuid, q.space_id, q.created_at,
+    q.duplicate_of IS NOT NULL AS closed,
+    COUNT(a.answer_uuid) AS answer_count,
+    MIN(a.created_at) AS first_answer_at
+FROM questions q
+LEFT JOIN answers a ON a.question_uuid = q.question_uuid AND a.deleted_at IS NULL
+WHERE q.deleted_at IS NULL
+GROUP BY q.question_uuid;
+
+-- Concurrent refreshes, which do not block reads, need a unique index
+CREATE UNIQUE INDEX IF NOT EXISTS question_stats_uuid_idx ON question_stats (question_uuid);
+CREATE INDEX IF NOT EXISTS question_stats_space_idx ON question_stats (space_id);
+
+-- Questions and answers posted per space and day
+CREATE MATERIALIZED VIEW IF NOT EXISTS daily_posts AS
+SELECT space_id, day, SUM(questions)::BIGINT AS questions, SUM(answers)::BIGINT AS answers
+FROM (
+    SELECT space_id, created_at::DATE AS day, 1 AS questions, 0 AS answers
+    FROM questions
+    WHERE deleted_at IS NULL
+    UNION ALL
+    SELECT q.space_id, a.created_at::DATE AS day, 0 AS questions, 1 AS answers
+    FROM answers a
+    JOIN questions q ON q.question_uuid = a.question_uuid
+    WHERE a.deleted_at IS NULL AND q.deleted_at IS NULL
+) posts
+GROUP BY space_id, day;
+
+CREATE UNIQUE INDEX IF NOT EXISTS daily_posts_space_day_idx ON daily_posts (space_id, day);
+
+-- When the views above were last refreshed
+CREATE MATERIALIZED VIEW IF NOT EXISTS stats_refreshes AS
+SELECT LOCALTIMESTAMP AS refreshed_at;
+
+CREATE UNIQUE INDEX IF NOT EXISTS stats_refreshes_idx ON stats_refreshes (refreshed_at);
@@ -0,0 +1,6 @@
+-- Add down migration script here
+
+DROP TABLE IF EXISTS job_runs;
+DROP TABLE IF EXISTS questions_archive;
+DROP TABLE IF EXISTS needs_attention;
+ALTER TABLE questions DROP COLUMN IF EXISTS closed_at;
@@ -0,0 +1,37 @@
+-- Add up migration script here
+
+-- Questions closed by the maintenance jobs for being inactive, they may not be answered anymore
+ALTER TABLE questions ADD COLUMN IF NOT EXISTS closed_at TIMESTAMP;
+
+-- Unanswered questions brought to the attention of those who could answer them
+CREATE TABLE IF NOT EXISTS needs_attention (
+    question_uuid uuid PRIMARY KEY REFERENCES questions (question_uuid) ON DELETE CASCADE,
+    added_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+-- Questions past the retention period, moved out of the live tables together with
+-- their answers. Rows are kept as JSON so that the archive outlives schema changes.
+CREATE TABLE IF NOT EXISTS questions_archive (
+    question_uuid uuid PRIMARY KEY,
+    space_id uuid NOT NULL,
+    question JSONB NOT NULL,
+    answers JSONB NOT NULL,
+    archived_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS questions_archive_space_idx ON questions_archive (space_id);
+
+-- Runs of the maintenance jobs, in UTC. A job runs at most once per scheduled time
+-- whichever the number of instances.
+CREATE TABLE IF NOT EXISTS job_runs (
+    run_id BIGSERIAL PRIMARY KEY,
+    job VARCHAR(64) NOT NULL,
+    scheduled_for TIMESTAMP NOT NULL,
+    started_at TIMESTAMP NOT NULL,
+    finished_at TIMESTAMP NOT NULL,
+    succeeded BOOLEAN NOT NULL,
+    -- Questions closed, flagged or archived by the run
+    affected BIGINT NOT NULL DEFAULT 0,
+    error TEXT,
+    UNIQUE (job, scheduled_for)
+);
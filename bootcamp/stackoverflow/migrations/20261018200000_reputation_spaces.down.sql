@@ -0,0 +1,4 @@
+-- Add down migration script here
+
+DROP INDEX IF EXISTS reputation_events_space_user_idx;
+ALTER TABLE reputation_events DROP COLUMN IF EXISTS space_id;
@@ -0,0 +1,14 @@
+-- Add up migration script here
+
+-- Reputation is earned and spent within a single space
+ALTER TABLE reputation_events ADD COLUMN IF NOT EXISTS space_id uuid NOT NULL
+    DEFAULT '00000000-0000-0000-0000-000000000001' REFERENCES spaces (space_id) ON DELETE CASCADE;
+
+UPDATE reputation_events SET space_id = questions.space_id
+FROM questions WHERE questions.question_uuid = reputation_events.post_uuid;
+
+UPDATE reputation_events SET space_id = questions.space_id
+FROM answers JOIN questions ON questions.question_uuid = answers.question_uuid
+WHERE answers.answer_uuid = reputation_events.post_uuid;
+
+CREATE INDEX IF NOT EXISTS reputation_events_space_user_idx ON reputation_events (space_id, user_id);
@@ -0,0 +1,178 @@
+use std::{
+    ops::Range,
+    sync::Arc,
+    time::{Duration, SystemTime},
+};
+
+use log::error;
+
+use crate::{
+    models::DBError,
+    persistance::{attachments_dao::AttachmentsDao, blob_store::BlobStore},
+};
+
+/// Largest file accepted as an attachment
+pub const MAX_ATTACHMENT_SIZE: usize = 10 * 1024 * 1024;
+
+/// Screenshots, documents and logs. Nothing a browser would run is accepted,
+/// since attachments are served back with the type they were uploaded with.
+pub const ALLOWED_CONTENT_TYPES: &[&str] = &[
+    "image/png",
+    "image/jpeg",
+    "image/gif",
+    "image/webp",
+    "application/pdf",
+    "application/json",
+    "text/plain",
+];
+
+#[derive(Debug, Clone)]
+pub struct SweepConfig {
+    pub interval: Duration,
+    /// Attachments of deleted questions are kept this long so that restored questions get them back
+    pub retention: Duration,
+    /// Unreferenced blobs stored more recently than this are kept, they may belong to an upload
+    /// whose attachment is not recorded yet
+    pub grace_period: Duration,
+}
+
+impl Default for SweepConfig {
+    fn default() -> Self {
+        SweepConfig {
+            interval: Duration::from_secs(60 * 60),
+            retention: Duration::from_secs(30 * 24 * 60 * 60),
+            grace_period: Duration::from_secs(60 * 60),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct RangeNotSatisfiable;
+
+/// Bytes requested by a `Range` header of a blob of `size` bytes, `None` for the whole blob.
+/// Only single byte ranges are served, other ranges are answered with the whole blob.
+pub fn parse_range(
+    header: Option<&str>,
+    size: u64,
+) -> Result<Option<Range<u64>>, RangeNotSatisfiable> {
+    let Some(spec) = header.and_then(|header| header.trim().strip_prefix("bytes=")) else {
+        return Ok(None);
+    };
+    if spec.contains(',') {
+        return Ok(None);
+    }
+    let Some((start, end)) = spec.split_once('-') else {
+        return Ok(None);
+    };
+
+    let range = match (start.trim(), end.trim()) {
+        ("", suffix) => {
+            let suffix: u64 = suffix.parse().map_err(|_| RangeNotSatisfiable)?;
+            if suffix == 0 {
+                return Err(RangeNotSatisfiable);
+            }
+            size.saturating_sub(suffix)..size
+        }
+        (start, "") => start.parse().map_err(|_| RangeNotSatisfiable)?..size,
+        (start, end) => {
+            let start: u64 = start.parse().map_err(|_| RangeNotSatisfiable)?;
+            let end: u64 = end.parse().map_err(|_| RangeNotSatisfiable)?;
+            if end < start {
+                return Err(RangeNotSatisfiable);
+            }
+            start..end.saturating_add(1).min(size)
+        }
+    };
+
+    if range.start >= size {
+        return Err(RangeNotSatisfiable);
+    }
+    Ok(Some(range))
+}
+
+/// Forgets the attachments of long deleted questions, then deletes the blobs no attachment
+/// refers to anymore. Returns the number of deleted blobs.
+pub async fn sweep(
+    attachments_dao: &(dyn AttachmentsDao + Send + Sync),
+    blob_store: &(dyn BlobStore + Send + Sync),
+    config: &SweepConfig,
+) -> Result<usize, DBError> {
+    attachments_dao
+        .purge_attachments_of_deleted_questions(config.retention)
+        .await?;
+
+    let cutoff = SystemTime::now() - config.grace_period;
+    let candidates: Vec<String> = blob_store
+        .list()
+        .await
+        .map_err(|e| DBError::Other(Box::new(e)))?
+        .into_iter()
+        .filter(|blob| blob.modified <= cutoff)
+        .map(|blob| blob.digest)
+        .collect();
+    if candidates.is_empty() {
+        return Ok(0);
+    }
+
+    let referenced = attachments_dao
+        .get_referenced_digests(candidates.clone())
+        .await?;
+    let mut deleted = 0;
+    // Blobs stored again since they were listed are newer than the cutoff, and kept
+    for digest in candidates.iter().filter(|d| !referenced.contains(d)) {
+        match blob_store.delete(digest, cutoff).await {
+            Ok(true) => deleted += 1,
+            Ok(false) => {}
+            Err(e) => error!("Failed to delete blob {}: {:?}", digest, e),
+        }
+    }
+
+    Ok(deleted)
+}
+
+/// Background task sweeping orphaned blobs until the runtime shuts down
+pub async fn run_blob_sweeper(
+    attachments_dao: Arc<dyn AttachmentsDao + Send + Sync>,
+    blob_store: Arc<dyn BlobStore + Send + Sync>,
+    config: SweepConfig,
+) {
+    let mut interval = tokio::time::interval(config.interval);
+
+    loop {
+        interval.tick().await;
+        if let Err(e) = sweep(attachments_dao.as_ref(), blob_store.as_ref(), &config).await {
+            error!("Failed to sweep orphaned blobs: {:?}", e);
+        }
+    }
+}
+
+// ***********************************************************
+//                           Tests
+// ***********************************************************
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_range_should_serve_single_byte_ranges() {
+        assert_eq!(parse_range(None, 10), Ok(None));
+        assert_eq!(parse_range(Some("bytes=2-4"), 10), Ok(Some(2..5)));
+        assert_eq!(parse_range(Some("bytes=2-"), 10), Ok(Some(2..10)));
+        assert_eq!(parse_range(Some("bytes=-3"), 10), Ok(Some(7..10)));
+        assert_eq!(parse_range(Some("bytes=-30"), 10), Ok(Some(0..10)));
+        assert_eq!(parse_range(Some("bytes=5-100"), 10), Ok(Some(5..10)));
+        // Multiple ranges and other units are answered with the whole blob
+        assert_eq!(parse_range(Some("bytes=0-1,4-5"), 10), Ok(None));
+        assert_eq!(parse_range(Some("lines=1-2"), 10), Ok(None));
+    }
+
+    #[test]
+    fn parse_range_should_reject_unsatisfiable_ranges() {
+        assert_eq!(parse_range(Some("bytes=10-"), 10), Err(RangeNotSatisfiable));
+        assert_eq!(parse_range(Some("bytes=4-2"), 10), Err(RangeNotSatisfiable));
+        assert_eq!(parse_range(Some("bytes=-0"), 10), Err(RangeNotSatisfiable));
+        assert_eq!(parse_range(Some("bytes=a-b"), 10), Err(RangeNotSatisfiable));
+        assert_eq!(parse_range(Some("bytes=0-"), 0), Err(RangeNotSatisfiable));
+    }
+}
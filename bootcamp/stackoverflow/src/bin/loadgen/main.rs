@@ -0,0 +1,328 @@
+use std::{
+    collections::BTreeMap,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use clap::Parser;
+use stackoverflow::{client::QaClient, models::Question};
+use tokio::{
+    sync::Mutex,
+    time::{Interval, MissedTickBehavior},
+};
+
+#[derive(Parser)]
+#[command(
+    version,
+    about = "Drives a mix of requests against a running stackoverflow API and reports their latencies",
+    long_about = None
+)]
+struct Cli {
+    /// Base URL of the API
+    #[arg(long, env = "SO_API_URL", default_value = "http://127.0.0.1:8000")]
+    url: String,
+    /// Identity the questions are asked and deleted with
+    #[arg(short, long, env = "SO_USER_ID", default_value = "loadgen")]
+    user: String,
+    /// Requests in flight at once
+    #[arg(short, long, default_value_t = 8, value_parser = clap::value_parser!(u16).range(1..))]
+    concurrency: u16,
+    /// Requests started per second across all workers, as many as possible when not set
+    #[arg(short, long, value_parser = parse_rate)]
+    rate: Option<f64>,
+    /// How long to send requests for, in seconds
+    #[arg(short, long, default_value_t = 10)]
+    duration: u64,
+    /// Relative weights of the operations
+    #[arg(short, long, default_value = "create=1,list=8,delete=1", value_parser = parse_mix)]
+    mix: Schedule,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Operation {
+    Create,
+    List,
+    Delete,
+}
+
+impl Operation {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Operation::Create => "create",
+            Operation::List => "list",
+            Operation::Delete => "delete",
+        }
+    }
+}
+
+/// Operations repeated in turn, each as many times as its weight
+#[derive(Debug, Clone, PartialEq)]
+struct Schedule(Vec<Operation>);
+
+/// Parses comma separated `operation=weight` pairs, like `create=1,list=8,delete=1`
+fn parse_mix(mix: &str) -> Result<Schedule, String> {
+    let mut schedule = Vec::new();
+    for pair in mix
+        .split(',')
+        .map(str::trim)
+        .filter(|pair| !pair.is_empty())
+    {
+        let (operation, weight) = pair
+            .split_once('=')
+            .ok_or_else(|| format!("Expected operation=weight, got {pair}"))?;
+        let operation = match operation.trim() {
+            "create" => Operation::Create,
+            "list" => Operation::List,
+            "delete" => Operation::Delete,
+            other => return Err(format!("Unknown operation {other}")),
+        };
+        let weight: usize = weight
+            .trim()
+            .parse()
+            .map_err(|e| format!("Invalid weight of {}: {e}", operation.as_str()))?;
+        schedule.extend(std::iter::repeat_n(operation, weight));
+    }
+
+    if schedule.is_empty() {
+        return Err("The mix must give a weight to at least one operation".to_owned());
+    }
+    Ok(Schedule(schedule))
+}
+
+fn parse_rate(rate: &str) -> Result<f64, String> {
+    match rate.parse::<f64>() {
+        Ok(rate) if rate.is_finite() && rate > 0.0 => Ok(rate),
+        _ => Err(format!("The rate must be a positive number, got {rate}")),
+    }
+}
+
+struct Sample {
+    operation: Operation,
+    latency: Duration,
+    ok: bool,
+}
+
+/// Questions created by the workers, deleted by later `delete` operations
+type Created = Arc<Mutex<Vec<String>>>;
+
+#[tokio::main]
+async fn main() {
+    let cli = Cli::parse();
+    let client = QaClient::new(cli.url).with_user_id(cli.user);
+    let schedule = Arc::new(cli.mix);
+    let created = Created::default();
+
+    // A single pacer shared by the workers so that the rate is the total one
+    let pacer = cli.rate.map(|rate| {
+        let mut interval = tokio::time::interval(Duration::from_secs_f64(1.0 / rate));
+        // Requests that could not be sent on time are sent as soon as possible
+        interval.set_missed_tick_behavior(MissedTickBehavior::Burst);
+        Arc::new(Mutex::new(interval))
+    });
+
+    let started = Instant::now();
+    let deadline = started + Duration::from_secs(cli.duration);
+    let workers = (0..usize::from(cli.concurrency))
+        .map(|index| {
+            tokio::spawn(worker(
+                index,
+                client.clone(),
+                schedule.clone(),
+                created.clone(),
+                pacer.clone(),
+                deadline,
+            ))
+        })
+        .collect::<Vec<_>>();
+
+    let mut samples = Vec::new();
+    for worker in workers {
+        samples.extend(worker.await.expect("Workers do not panic"));
+    }
+
+    print!("{}", report(&samples, started.elapsed()));
+}
+
+async fn worker(
+    index: usize,
+    client: QaClient,
+    schedule: Arc<Schedule>,
+    created: Created,
+    pacer: Option<Arc<Mutex<Interval>>>,
+    deadline: Instant,
+) -> Vec<Sample> {
+    let mut samples = Vec::new();
+
+    // Workers start at different points of the schedule so that the mix holds at any time
+    for operation in schedule.0.iter().cycle().skip(index) {
+        // Paced requests are timed from when they were due, so that a server slowing down
+        // shows in the latencies rather than in requests being sent late
+        let due = match &pacer {
+            Some(pacer) => pacer.lock().await.tick().await.into_std(),
+            None => Instant::now(),
+        };
+        if due >= deadline {
+            break;
+        }
+
+        let (operation, ok) = run(*operation, &client, &created).await;
+        samples.push(Sample {
+            operation,
+            latency: due.elapsed(),
+            ok,
+        });
+    }
+
+    samples
+}
+
+/// Returns the operation that was actually run, deletes turn into creates when
+/// there is nothing left to delete
+async fn run(operation: Operation, client: &QaClient, created: &Created) -> (Operation, bool) {
+    match operation {
+        Operation::Create => (Operation::Create, create(client, created).await),
+        Operation::List => (Operation::List, client.list_questions().await.is_ok()),
+        Operation::Delete => {
+            let question_uuid = created.lock().await.pop();
+            match question_uuid {
+                Some(question_uuid) => (
+                    Operation::Delete,
+                    client.delete_question(&question_uuid).await.is_ok(),
+                ),
+                None => (Operation::Create, create(client, created).await),
+            }
+        }
+    }
+}
+
+async fn create(client: &QaClient, created: &Created) -> bool {
+    let question = Question {
+        title: "Load test question".to_owned(),
+        description: "Asked by `loadgen`, safe to delete".to_owned(),
+    };
+
+    match client.create_question(&question).await {
+        Ok(question) => {
+            created.lock().await.push(question.question_uuid);
+            true
+        }
+        Err(_) => false,
+    }
+}
+
+/// Latency below which `percent` percent of the sorted latencies are, by nearest rank
+fn percentile(sorted: &[Duration], percent: f64) -> Duration {
+    if sorted.is_empty() {
+        return Duration::ZERO;
+    }
+    let rank = (percent / 100.0 * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+fn report(samples: &[Sample], elapsed: Duration) -> String {
+    let mut by_operation: BTreeMap<&str, Vec<&Sample>> = BTreeMap::new();
+    for sample in samples {
+        by_operation
+            .entry(sample.operation.as_str())
+            .or_default()
+            .push(sample);
+    }
+    let rows = by_operation
+        .iter()
+        .map(|(operation, samples)| (*operation, samples.as_slice()))
+        .chain([("all", &samples.iter().collect::<Vec<_>>()[..])])
+        .map(|(operation, samples)| report_row(operation, samples))
+        .collect::<String>();
+
+    format!(
+        "{:<8}{:>10}{:>9}{:>10}{:>10}{:>10}{:>10}\n{rows}\n{} requests in {:.1}s, {:.1} requests/s\n",
+        "",
+        "REQUESTS",
+        "ERRORS",
+        "P50",
+        "P90",
+        "P99",
+        "MAX",
+        samples.len(),
+        elapsed.as_secs_f64(),
+        samples.len() as f64 / elapsed.as_secs_f64().max(f64::EPSILON),
+    )
+}
+
+fn report_row(operation: &str, samples: &[&Sample]) -> String {
+    let mut latencies = samples.iter().map(|s| s.latency).collect::<Vec<_>>();
+    latencies.sort();
+    let errors = samples.iter().filter(|s| !s.ok).count();
+    let error_rate = 100.0 * errors as f64 / samples.len().max(1) as f64;
+    let millis = |latency: Duration| format!("{:.1}ms", latency.as_secs_f64() * 1000.0);
+
+    format!(
+        "{:<8}{:>10}{:>9}{:>10}{:>10}{:>10}{:>10}\n",
+        operation,
+        samples.len(),
+        format!("{error_rate:.1}%"),
+        millis(percentile(&latencies, 50.0)),
+        millis(percentile(&latencies, 90.0)),
+        millis(percentile(&latencies, 99.0)),
+        millis(latencies.last().copied().unwrap_or_default()),
+    )
+}
+
+// ***********************************************************
+//                           Tests
+// ***********************************************************
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_mix_should_repeat_operations_by_weight() {
+        assert_eq!(
+            parse_mix("create=1, list=2,delete=0"),
+            Ok(Schedule(vec![
+                Operation::Create,
+                Operation::List,
+                Operation::List
+            ]))
+        );
+    }
+
+    #[test]
+    fn parse_mix_should_reject_malformed_mixes() {
+        for mix in ["", "list=0", "list", "list=-1", "update=1"] {
+            assert!(parse_mix(mix).is_err(), "{mix} was accepted");
+        }
+    }
+
+    #[test]
+    fn percentile_should_use_the_nearest_rank() {
+        let latencies = (1..=10).map(Duration::from_millis).collect::<Vec<_>>();
+
+        assert_eq!(percentile(&latencies, 50.0), Duration::from_millis(5));
+        assert_eq!(percentile(&latencies, 99.0), Duration::from_millis(10));
+        assert_eq!(percentile(&latencies, 0.0), Duration::from_millis(1));
+        assert_eq!(percentile(&[], 50.0), Duration::ZERO);
+    }
+
+    #[test]
+    fn report_should_count_errors_per_operation() {
+        let sample = |operation, ok| Sample {
+            operation,
+            latency: Duration::from_millis(2),
+            ok,
+        };
+        let samples = vec![
+            sample(Operation::List, true),
+            sample(Operation::List, false),
+            sample(Operation::Create, true),
+        ];
+
+        let report = report(&samples, Duration::from_secs(1));
+
+        assert!(report.contains("list             2    50.0%"), "{report}");
+        assert!(report.contains("create           1     0.0%"), "{report}");
+        assert!(report.contains("all              3    33.3%"), "{report}");
+        assert!(report.ends_with("3 requests in 1.0s, 3.0 requests/s\n"));
+    }
+}
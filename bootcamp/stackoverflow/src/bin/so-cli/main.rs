@@ -0,0 +1,255 @@
+use clap::{ArgGroup, Parser, Subcommand, ValueEnum};
+use serde::Serialize;
+use stackoverflow::{
+    client::QaClient,
+    models::{Answer, AnswerDetail, Question, QuestionDetail},
+};
+
+#[derive(Parser)]
+#[command(version, about = "Command-line client for the stackoverflow API", long_about = None)]
+struct Cli {
+    /// Base URL of the API
+    #[arg(long, env = "SO_API_URL", default_value = "http://127.0.0.1:8000")]
+    url: String,
+    /// Identity sent with every request, anonymous if not set
+    #[arg(short, long, env = "SO_USER_ID")]
+    user: Option<String>,
+    #[arg(short, long, value_enum, default_value_t = Output::Table)]
+    output: Output,
+    #[command(subcommand)]
+    command: Commands,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Output {
+    Table,
+    Json,
+}
+
+#[derive(Subcommand)]
+enum Commands {
+    /// Ask a new question
+    Ask {
+        #[arg(short, long)]
+        title: String,
+        /// Markdown description
+        #[arg(short, long)]
+        description: String,
+    },
+    /// List all questions
+    List,
+    /// List the questions with the most recent views and answers
+    Trending,
+    /// Answer a question
+    Answer {
+        #[arg(short, long)]
+        question_uuid: String,
+        /// Markdown content
+        #[arg(short, long)]
+        content: String,
+    },
+    /// Show a question and its answers
+    Show {
+        #[arg(short, long)]
+        question_uuid: String,
+    },
+    /// Delete a question or an answer
+    #[command(group(ArgGroup::new("post").required(true)))]
+    Delete {
+        #[arg(short, long, group = "post")]
+        question_uuid: Option<String>,
+        #[arg(short, long, group = "post")]
+        answer_uuid: Option<String>,
+    },
+}
+
+#[derive(Serialize)]
+struct Thread {
+    question: QuestionDetail,
+    answers: Vec<AnswerDetail>,
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let cli = Cli::parse();
+    let client = match cli.user {
+        Some(user) => QaClient::new(cli.url).with_user_id(user),
+        None => QaClient::new(cli.url),
+    };
+
+    match cli.command {
+        Commands::Ask { title, description } => {
+            let question = client
+                .create_question(&Question { title, description })
+                .await?;
+            print(cli.output, &question, || {
+                questions_table(std::slice::from_ref(&question))
+            })?;
+        }
+        Commands::List => {
+            let questions = client.list_questions().await?;
+            print(cli.output, &questions, || questions_table(&questions))?;
+        }
+        Commands::Trending => {
+            let questions = client.trending_questions().await?;
+            print(cli.output, &questions, || questions_table(&questions))?;
+        }
+        Commands::Answer {
+            question_uuid,
+            content,
+        } => {
+            let answer = client
+                .create_answer(&Answer {
+                    question_uuid,
+                    content,
+                })
+                .await?;
+            print(cli.output, &answer, || {
+                answers_table(std::slice::from_ref(&answer))
+            })?;
+        }
+        Commands::Show { question_uuid } => {
+            let question = client.get_question(&question_uuid).await?;
+            let answers = client.list_answers(&question.question_uuid).await?;
+            let thread = Thread { question, answers };
+            print(cli.output, &thread, || thread_text(&thread))?;
+        }
+        Commands::Delete {
+            question_uuid,
+            answer_uuid,
+        } => match (question_uuid, answer_uuid) {
+            (Some(question_uuid), _) => {
+                client.delete_question(&question_uuid).await?;
+                eprintln!("Deleted question {question_uuid}");
+            }
+            (None, Some(answer_uuid)) => {
+                client.delete_answer(&answer_uuid).await?;
+                eprintln!("Deleted answer {answer_uuid}");
+            }
+            (None, None) => unreachable!("clap requires one of the post UUIDs"),
+        },
+    }
+
+    Ok(())
+}
+
+fn print<T: Serialize>(
+    output: Output,
+    value: &T,
+    table: impl FnOnce() -> String,
+) -> Result<(), serde_json::Error> {
+    match output {
+        Output::Json => println!("{}", serde_json::to_string_pretty(value)?),
+        Output::Table => print!("{}", table()),
+    }
+    Ok(())
+}
+
+fn questions_table(questions: &[QuestionDetail]) -> String {
+    let rows = questions
+        .iter()
+        .map(|q| {
+            vec![
+                q.question_uuid.clone(),
+                q.author.clone(),
+                q.title.clone(),
+                q.view_count.to_string(),
+                q.created_at.clone(),
+            ]
+        })
+        .collect::<Vec<_>>();
+    table(&["UUID", "AUTHOR", "TITLE", "VIEWS", "CREATED AT"], &rows)
+}
+
+fn answers_table(answers: &[AnswerDetail]) -> String {
+    let rows = answers
+        .iter()
+        .map(|a| {
+            vec![
+                a.answer_uuid.clone(),
+                a.author.clone(),
+                first_line(&a.content),
+                a.created_at.clone(),
+            ]
+        })
+        .collect::<Vec<_>>();
+    table(&["UUID", "AUTHOR", "CONTENT", "CREATED AT"], &rows)
+}
+
+fn thread_text(thread: &Thread) -> String {
+    let question = &thread.question;
+    format!(
+        "{}\nasked by {} at {} ({})\n\n{}\n\n{} answer(s)\n{}",
+        question.title,
+        question.author,
+        question.created_at,
+        question.question_uuid,
+        question.description,
+        thread.answers.len(),
+        answers_table(&thread.answers),
+    )
+}
+
+fn first_line(text: &str) -> String {
+    let line = text.lines().next().unwrap_or_default();
+    if text.lines().nth(1).is_some() {
+        format!("{line} ...")
+    } else {
+        line.to_owned()
+    }
+}
+
+/// Left aligned columns sized to their widest cell
+fn table(headers: &[&str], rows: &[Vec<String>]) -> String {
+    let mut widths: Vec<usize> = headers.iter().map(|h| h.chars().count()).collect();
+    for row in rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    let format_row = |cells: Vec<&str>| {
+        let line = cells
+            .iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!("{cell:<width$}"))
+            .collect::<Vec<_>>()
+            .join("  ");
+        format!("{}\n", line.trim_end())
+    };
+
+    let mut table = format_row(headers.to_vec());
+    for row in rows {
+        table.push_str(&format_row(row.iter().map(String::as_str).collect()));
+    }
+    table
+}
+
+// ***********************************************************
+//                           Tests
+// ***********************************************************
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn table_should_align_columns() {
+        let rows = vec![
+            vec!["1".to_owned(), "a long title".to_owned()],
+            vec!["23".to_owned(), "short".to_owned()],
+        ];
+
+        assert_eq!(
+            table(&["ID", "TITLE"], &rows),
+            "ID  TITLE\n1   a long title\n23  short\n"
+        );
+    }
+
+    #[test]
+    fn first_line_should_mark_truncated_text() {
+        assert_eq!(first_line("one line"), "one line");
+        assert_eq!(first_line("first\nsecond"), "first ...");
+        assert_eq!(first_line(""), "");
+    }
+}
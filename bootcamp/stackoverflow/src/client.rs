@@ -0,0 +1,343 @@
+use reqwest::{RequestBuilder, Response, StatusCode};
+use serde::de::DeserializeOwned;
+use thiserror::Error;
+
+use crate::models::{
+    Answer, AnswerDetail, AnswerEdit, AnswerId, DuplicateOf, HandlerError, Question,
+    QuestionDetail, QuestionEdit, QuestionId, SimilarQuestion, UserProfile,
+};
+
+pub const USER_ID_HEADER: &str = "X-User-Id";
+
+#[derive(Error, Debug)]
+pub enum ClientError {
+    /// The server could not be reached or responded with an unexpected body
+    #[error("Request failed: {0}")]
+    Request(#[from] reqwest::Error),
+    /// The server rejected the request
+    #[error(transparent)]
+    Handler(#[from] HandlerError),
+}
+
+/// Async client of the Q&A REST API.
+/// Requests and responses are the server's own models so that both sides cannot drift apart.
+#[derive(Clone)]
+pub struct QaClient {
+    http: reqwest::Client,
+    base_url: String,
+    user_id: Option<String>,
+}
+
+impl QaClient {
+    pub fn new(base_url: impl Into<String>) -> Self {
+        QaClient {
+            http: reqwest::Client::new(),
+            base_url: base_url.into().trim_end_matches('/').to_owned(),
+            user_id: None,
+        }
+    }
+
+    /// Identity sent with every request, requests are anonymous otherwise
+    pub fn with_user_id(mut self, user_id: impl Into<String>) -> Self {
+        self.user_id = Some(user_id.into());
+        self
+    }
+
+    pub async fn create_question(
+        &self,
+        question: &Question,
+    ) -> Result<QuestionDetail, ClientError> {
+        self.send_json(self.http.post(self.url("/question")).json(question))
+            .await
+    }
+
+    pub async fn list_questions(&self) -> Result<Vec<QuestionDetail>, ClientError> {
+        self.send_json(self.http.get(self.url("/questions"))).await
+    }
+
+    /// Follows the redirect of questions closed as a duplicate
+    pub async fn get_question(&self, question_uuid: &str) -> Result<QuestionDetail, ClientError> {
+        self.send_json(
+            self.http
+                .get(self.url(&format!("/questions/{question_uuid}"))),
+        )
+        .await
+    }
+
+    /// Open questions with the most recent views and answers, highest score first
+    pub async fn trending_questions(&self) -> Result<Vec<QuestionDetail>, ClientError> {
+        self.send_json(self.http.get(self.url("/questions/trending")))
+            .await
+    }
+
+    pub async fn update_question(
+        &self,
+        question_edit: &QuestionEdit,
+    ) -> Result<QuestionDetail, ClientError> {
+        self.send_json(self.http.put(self.url("/question")).json(question_edit))
+            .await
+    }
+
+    pub async fn delete_question(&self, question_uuid: &str) -> Result<(), ClientError> {
+        let question_id = QuestionId {
+            question_uuid: question_uuid.to_owned(),
+        };
+        self.send(self.http.delete(self.url("/question")).json(&question_id))
+            .await
+            .map(|_| ())
+    }
+
+    pub async fn related_questions(
+        &self,
+        question_uuid: &str,
+    ) -> Result<Vec<SimilarQuestion>, ClientError> {
+        self.send_json(
+            self.http
+                .get(self.url(&format!("/questions/{question_uuid}/related"))),
+        )
+        .await
+    }
+
+    pub async fn similar_questions(
+        &self,
+        question: &Question,
+    ) -> Result<Vec<SimilarQuestion>, ClientError> {
+        self.send_json(
+            self.http
+                .post(self.url("/questions/similar"))
+                .json(question),
+        )
+        .await
+    }
+
+    pub async fn close_as_duplicate(
+        &self,
+        question_uuid: &str,
+        duplicate_of: &str,
+    ) -> Result<QuestionDetail, ClientError> {
+        let duplicate_of = DuplicateOf {
+            duplicate_of: duplicate_of.to_owned(),
+        };
+        self.send_json(
+            self.http
+                .post(self.url(&format!("/questions/{question_uuid}/duplicate")))
+                .json(&duplicate_of),
+        )
+        .await
+    }
+
+    pub async fn create_answer(&self, answer: &Answer) -> Result<AnswerDetail, ClientError> {
+        self.send_json(self.http.post(self.url("/answer")).json(answer))
+            .await
+    }
+
+    pub async fn list_answers(
+        &self,
+        question_uuid: &str,
+    ) -> Result<Vec<AnswerDetail>, ClientError> {
+        let question_id = QuestionId {
+            question_uuid: question_uuid.to_owned(),
+        };
+        self.send_json(self.http.get(self.url("/answers")).json(&question_id))
+            .await
+    }
+
+    pub async fn update_answer(
+        &self,
+        answer_edit: &AnswerEdit,
+    ) -> Result<AnswerDetail, ClientError> {
+        self.send_json(self.http.put(self.url("/answer")).json(answer_edit))
+            .await
+    }
+
+    pub async fn delete_answer(&self, answer_uuid: &str) -> Result<(), ClientError> {
+        let answer_id = AnswerId {
+            answer_uuid: answer_uuid.to_owned(),
+        };
+        self.send(self.http.delete(self.url("/answer")).json(&answer_id))
+            .await
+            .map(|_| ())
+    }
+
+    pub async fn get_user(&self, user_id: &str) -> Result<UserProfile, ClientError> {
+        self.send_json(self.http.get(self.url(&format!("/users/{user_id}"))))
+            .await
+    }
+
+    fn url(&self, path: &str) -> String {
+        format!("{}{}", self.base_url, path)
+    }
+
+    async fn send(&self, request: RequestBuilder) -> Result<Response, ClientError> {
+        let request = match &self.user_id {
+            Some(user_id) => request.header(USER_ID_HEADER, user_id),
+            None => request,
+        };
+
+        let response = request.send().await?;
+        let status = response.status();
+        if status.is_success() {
+            return Ok(response);
+        }
+
+        let message = response.text().await?;
+        Err(ClientError::Handler(handler_error(status, message)))
+    }
+
+    async fn send_json<T: DeserializeOwned>(
+        &self,
+        request: RequestBuilder,
+    ) -> Result<T, ClientError> {
+        Ok(self.send(request).await?.json::<T>().await?)
+    }
+}
+
+/// Inverse of the `IntoResponse` implementation of `HandlerError`.
+/// Rejections of malformed requests by axum's extractors are reported as bad requests.
+fn handler_error(status: StatusCode, message: String) -> HandlerError {
+    match status {
+        StatusCode::UNAUTHORIZED => HandlerError::Unauthorized(message),
+        StatusCode::FORBIDDEN => HandlerError::Forbidden(message),
+        status if status.is_client_error() => HandlerError::BadRequest(message),
+        _ => HandlerError::InternalError(message),
+    }
+}
+
+// ***********************************************************
+//                           Tests
+// ***********************************************************
+
+#[cfg(all(test, feature = "server"))]
+mod tests {
+    use super::*;
+    use crate::{app, app_state, config::Config};
+    use sqlx::PgPool;
+
+    /// Serves `app()` on an ephemeral port and returns its base URL
+    async fn serve(pool: PgPool) -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let app = app(app_state(pool, Config::default()));
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        format!("http://{addr}")
+    }
+
+    #[test]
+    fn handler_error_should_map_status_codes() {
+        let error = |status| handler_error(status, "oops".to_owned());
+
+        assert_eq!(
+            error(StatusCode::BAD_REQUEST),
+            HandlerError::BadRequest("oops".to_owned())
+        );
+        assert_eq!(
+            error(StatusCode::UNPROCESSABLE_ENTITY),
+            HandlerError::BadRequest("oops".to_owned())
+        );
+        assert_eq!(
+            error(StatusCode::UNAUTHORIZED),
+            HandlerError::Unauthorized("oops".to_owned())
+        );
+        assert_eq!(
+            error(StatusCode::FORBIDDEN),
+            HandlerError::Forbidden("oops".to_owned())
+        );
+        assert_eq!(
+            error(StatusCode::INTERNAL_SERVER_ERROR),
+            HandlerError::InternalError("oops".to_owned())
+        );
+    }
+
+    #[sqlx::test]
+    async fn client_should_round_trip_questions_and_answers(pool: PgPool) {
+        let client = QaClient::new(serve(pool).await).with_user_id("toto");
+
+        let question = client
+            .create_question(&Question {
+                title: "Toto title".to_owned(),
+                description: "Toto description".to_owned(),
+            })
+            .await
+            .unwrap();
+        assert_eq!(question.author, "toto");
+        assert_eq!(
+            client.list_questions().await.unwrap(),
+            vec![question.clone()]
+        );
+        assert_eq!(
+            client.get_question(&question.question_uuid).await.unwrap(),
+            question
+        );
+
+        let answer = client
+            .create_answer(&Answer {
+                question_uuid: question.question_uuid.clone(),
+                content: "Answer content".to_owned(),
+            })
+            .await
+            .unwrap();
+        assert_eq!(
+            client.list_answers(&question.question_uuid).await.unwrap(),
+            vec![answer.clone()]
+        );
+
+        let answer = client
+            .update_answer(&AnswerEdit {
+                answer_uuid: answer.answer_uuid.clone(),
+                content: "*Edited*".to_owned(),
+            })
+            .await
+            .unwrap();
+        assert_eq!(answer.content_html, "<p><em>Edited</em></p>\n");
+
+        let profile = client.get_user("toto").await.unwrap();
+        assert_eq!(profile.question_count, 1);
+        assert_eq!(profile.answer_count, 1);
+
+        client.delete_answer(&answer.answer_uuid).await.unwrap();
+        client
+            .delete_question(&question.question_uuid)
+            .await
+            .unwrap();
+        assert!(client.list_questions().await.unwrap().is_empty());
+    }
+
+    #[sqlx::test]
+    async fn client_should_map_error_bodies_to_handler_errors(pool: PgPool) {
+        let base_url = serve(pool).await;
+        let author = QaClient::new(&base_url).with_user_id("toto");
+        let anonymous = QaClient::new(&base_url);
+
+        let question = author
+            .create_question(&Question {
+                title: "Toto title".to_owned(),
+                description: "Toto description".to_owned(),
+            })
+            .await
+            .unwrap();
+
+        let result = anonymous.delete_question(&question.question_uuid).await;
+        assert!(matches!(
+            result,
+            Err(ClientError::Handler(HandlerError::Forbidden(_)))
+        ));
+
+        let result = author.get_question("malformed").await;
+        assert!(matches!(
+            result,
+            Err(ClientError::Handler(HandlerError::BadRequest(_)))
+        ));
+
+        let result = author
+            .create_answer(&Answer {
+                question_uuid: "b068cd2f-edac-479e-98f1-c5f91008dcbd".to_owned(),
+                content: "Answer content".to_owned(),
+            })
+            .await;
+        assert!(matches!(
+            result,
+            Err(ClientError::Handler(HandlerError::BadRequest(_)))
+        ));
+    }
+}
@@ -0,0 +1,233 @@
+use std::{collections::HashMap, path::PathBuf, str::FromStr, time::Duration};
+
+use cron::Schedule;
+
+const DEFAULT_BLOB_DIR: &str = "blobs";
+const DEFAULT_CACHE_CAPACITY: usize = 1024;
+const DEFAULT_CACHE_TTL: Duration = Duration::from_secs(30);
+/// Every day at 03:00 UTC
+const DEFAULT_CLOSE_INACTIVE_SCHEDULE: &str = "0 3 * * *";
+/// Every hour
+const DEFAULT_FLAG_UNANSWERED_SCHEDULE: &str = "0 * * * *";
+const DEFAULT_INACTIVE_AFTER_DAYS: u64 = 90;
+const DEFAULT_UNANSWERED_AFTER_HOURS: u64 = 24;
+const DEFAULT_ARCHIVE_AFTER_DAYS: u64 = 730;
+
+/// Runtime settings of the server, read from the environment
+#[derive(Debug, Clone)]
+pub struct Config {
+    /// Bearer token granting access to moderation routes, these are disabled when neither it
+    /// nor `admin_tokens` is set. Its holders are all recorded as `moderator`.
+    pub admin_token: Option<String>,
+    /// Admins by bearer token, each recorded under their own name in the audit log
+    pub admin_tokens: HashMap<String, String>,
+    /// Users by bearer token. When empty, callers are identified by the `X-User-Id` header.
+    pub user_tokens: HashMap<String, String>,
+    /// Directory of the local blob store holding attachments
+    pub blob_dir: PathBuf,
+    /// Reads of questions and answers kept in memory, nothing is cached when zero
+    pub cache_capacity: usize,
+    /// Longest time a read is served from the cache
+    pub cache_ttl: Duration,
+    /// Whether writes are announced to other instances with Postgres `NOTIFY`,
+    /// required when several instances serve the same database
+    pub cache_notify: bool,
+    /// Whether webhooks may target loopback, link-local and private addresses, as when
+    /// developing against a local receiver
+    pub webhook_private_targets: bool,
+    /// Jobs closing, flagging and archiving questions, each instance runs the scheduler
+    pub maintenance: MaintenanceConfig,
+}
+
+/// Schedules of the maintenance jobs and the questions they act upon
+#[derive(Debug, Clone)]
+pub struct MaintenanceConfig {
+    /// Jobs without a schedule never run, archiving does not unless it is given one
+    pub close_inactive: Option<Schedule>,
+    pub flag_unanswered: Option<Schedule>,
+    pub archive: Option<Schedule>,
+    /// Open questions without a new answer for this long are closed
+    pub inactive_after: Duration,
+    /// Open questions without any answer for this long need attention
+    pub unanswered_after: Duration,
+    /// Questions asked this long ago are archived
+    pub archive_after: Duration,
+}
+
+impl Default for MaintenanceConfig {
+    fn default() -> Self {
+        MaintenanceConfig {
+            close_inactive: parse_schedule(DEFAULT_CLOSE_INACTIVE_SCHEDULE)
+                .expect("The default schedule is valid"),
+            flag_unanswered: parse_schedule(DEFAULT_FLAG_UNANSWERED_SCHEDULE)
+                .expect("The default schedule is valid"),
+            archive: None,
+            inactive_after: days(DEFAULT_INACTIVE_AFTER_DAYS),
+            unanswered_after: hours(DEFAULT_UNANSWERED_AFTER_HOURS),
+            archive_after: days(DEFAULT_ARCHIVE_AFTER_DAYS),
+        }
+    }
+}
+
+impl MaintenanceConfig {
+    /// Panics on invalid schedules, so that a job does not silently run at the wrong times
+    pub fn from_env() -> Self {
+        let defaults = MaintenanceConfig::default();
+        let number = |var: &str| std::env::var(var).ok().and_then(|n| n.parse().ok());
+
+        MaintenanceConfig {
+            close_inactive: schedule_from_env("CLOSE_INACTIVE_SCHEDULE", defaults.close_inactive),
+            flag_unanswered: schedule_from_env(
+                "FLAG_UNANSWERED_SCHEDULE",
+                defaults.flag_unanswered,
+            ),
+            archive: schedule_from_env("ARCHIVE_SCHEDULE", defaults.archive),
+            inactive_after: number("CLOSE_INACTIVE_AFTER_DAYS")
+                .map_or(defaults.inactive_after, days),
+            unanswered_after: number("UNANSWERED_AFTER_HOURS")
+                .map_or(defaults.unanswered_after, hours),
+            archive_after: number("ARCHIVE_AFTER_DAYS").map_or(defaults.archive_after, days),
+        }
+    }
+}
+
+fn days(days: u64) -> Duration {
+    hours(days * 24)
+}
+
+fn hours(hours: u64) -> Duration {
+    Duration::from_secs(hours * 60 * 60)
+}
+
+fn schedule_from_env(var: &str, default: Option<Schedule>) -> Option<Schedule> {
+    match std::env::var(var) {
+        Ok(expression) => {
+            parse_schedule(&expression).unwrap_or_else(|e| panic!("Invalid {var}: {e}"))
+        }
+        Err(_) => default,
+    }
+}
+
+/// Parses a cron expression in UTC, either the five fields of crontab like `0 3 * * *`
+/// or six fields starting with the seconds. Empty and `off` expressions disable the job.
+fn parse_schedule(expression: &str) -> Result<Option<Schedule>, String> {
+    let expression = expression.trim();
+    if expression.is_empty() || expression == "off" {
+        return Ok(None);
+    }
+
+    let expression = match expression.split_whitespace().count() {
+        5 => format!("0 {expression}"),
+        _ => expression.to_owned(),
+    };
+    Schedule::from_str(&expression)
+        .map(Some)
+        .map_err(|e| format!("{expression}: {e}"))
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            admin_token: None,
+            admin_tokens: HashMap::new(),
+            user_tokens: HashMap::new(),
+            blob_dir: PathBuf::from(DEFAULT_BLOB_DIR),
+            cache_capacity: DEFAULT_CACHE_CAPACITY,
+            cache_ttl: DEFAULT_CACHE_TTL,
+            cache_notify: false,
+            webhook_private_targets: false,
+            maintenance: MaintenanceConfig::default(),
+        }
+    }
+}
+
+impl Config {
+    /// Whether anonymous callers may modify anonymous posts, only when no identity provider
+    /// is configured and callers cannot prove who they are anyway
+    pub fn anonymous_posts_open(&self) -> bool {
+        self.user_tokens.is_empty()
+    }
+
+    pub fn from_env() -> Self {
+        Config {
+            admin_token: std::env::var("ADMIN_TOKEN").ok().filter(|t| !t.is_empty()),
+            admin_tokens: std::env::var("ADMIN_TOKENS")
+                .map(|tokens| parse_user_tokens(&tokens))
+                .unwrap_or_default(),
+            user_tokens: std::env::var("USER_TOKENS")
+                .map(|tokens| parse_user_tokens(&tokens))
+                .unwrap_or_default(),
+            blob_dir: std::env::var("BLOB_DIR")
+                .ok()
+                .filter(|dir| !dir.is_empty())
+                .map_or_else(|| PathBuf::from(DEFAULT_BLOB_DIR), PathBuf::from),
+            cache_capacity: std::env::var("CACHE_CAPACITY")
+                .ok()
+                .and_then(|capacity| capacity.parse().ok())
+                .unwrap_or(DEFAULT_CACHE_CAPACITY),
+            cache_ttl: std::env::var("CACHE_TTL_SECS")
+                .ok()
+                .and_then(|secs| secs.parse().ok())
+                .map_or(DEFAULT_CACHE_TTL, Duration::from_secs),
+            cache_notify: std::env::var("CACHE_NOTIFY").is_ok_and(|notify| notify == "true"),
+            webhook_private_targets: std::env::var("WEBHOOK_PRIVATE_TARGETS")
+                .is_ok_and(|allow| allow == "true"),
+            maintenance: MaintenanceConfig::from_env(),
+        }
+    }
+}
+
+/// Parses comma separated `token=user` pairs, malformed pairs are skipped
+fn parse_user_tokens(tokens: &str) -> HashMap<String, String> {
+    tokens
+        .split(',')
+        .filter_map(|pair| pair.trim().split_once('='))
+        .filter(|(token, user)| !token.is_empty() && !user.is_empty())
+        .map(|(token, user)| (token.to_owned(), user.to_owned()))
+        .collect()
+}
+
+// ***********************************************************
+//                           Tests
+// ***********************************************************
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_user_tokens_should_skip_malformed_pairs() {
+        assert_eq!(
+            parse_user_tokens("abc=toto, def=titi,ghi,=tata,jkl="),
+            HashMap::from([
+                ("abc".to_owned(), "toto".to_owned()),
+                ("def".to_owned(), "titi".to_owned()),
+            ])
+        );
+    }
+
+    #[test]
+    fn parse_schedule_should_accept_crontab_and_seconds_expressions() {
+        let schedule = parse_schedule("0 3 * * *").unwrap().unwrap();
+        assert_eq!(
+            Some(schedule.clone()),
+            parse_schedule("0 0 3 * * *").unwrap()
+        );
+
+        let after = "2026-10-19T10:00:00Z"
+            .parse::<chrono::DateTime<chrono::Utc>>()
+            .unwrap();
+        assert_eq!(
+            schedule.after(&after).next().unwrap().to_rfc3339(),
+            "2026-10-20T03:00:00+00:00"
+        );
+    }
+
+    #[test]
+    fn parse_schedule_should_disable_empty_and_off_expressions() {
+        assert_eq!(parse_schedule("").unwrap(), None);
+        assert_eq!(parse_schedule(" off ").unwrap(), None);
+        assert!(parse_schedule("every day").is_err());
+        assert!(parse_schedule("0 25 * * *").is_err());
+    }
+}
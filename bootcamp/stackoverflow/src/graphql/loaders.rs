@@ -0,0 +1,217 @@
+use std::{collections::HashMap, sync::Arc};
+
+use async_graphql::dataloader::Loader;
+use log::error;
+
+use crate::{
+    models::{AnswerDetail, DBError, HandlerError, QuestionDetail},
+    persistance::{answers_dao::AnswersDao, questions_dao::QuestionsDao},
+};
+
+fn handler_error(err: DBError) -> HandlerError {
+    error!("Failed to load a batch: {:?}", err);
+
+    match err {
+        DBError::InvalidUUID(s) => HandlerError::BadRequest(s),
+        _ => HandlerError::InternalError(err.to_string()),
+    }
+}
+
+/// Questions by UUID, deleted questions are missing from the batch
+pub struct QuestionLoader {
+    questions_dao: Arc<dyn QuestionsDao + Send + Sync>,
+}
+
+impl QuestionLoader {
+    pub fn new(questions_dao: Arc<dyn QuestionsDao + Send + Sync>) -> Self {
+        QuestionLoader { questions_dao }
+    }
+}
+
+impl Loader<String> for QuestionLoader {
+    type Value = QuestionDetail;
+    type Error = HandlerError;
+
+    async fn load(
+        &self,
+        question_uuids: &[String],
+    ) -> Result<HashMap<String, Self::Value>, Self::Error> {
+        let questions = self
+            .questions_dao
+            .get_questions_by_uuids(question_uuids.to_vec())
+            .await
+            .map_err(handler_error)?;
+
+        Ok(questions
+            .into_iter()
+            .map(|question| (question.question_uuid.clone(), question))
+            .collect())
+    }
+}
+
+/// Answers of a question by question UUID, oldest first
+pub struct AnswersLoader {
+    answers_dao: Arc<dyn AnswersDao + Send + Sync>,
+}
+
+impl AnswersLoader {
+    pub fn new(answers_dao: Arc<dyn AnswersDao + Send + Sync>) -> Self {
+        AnswersLoader { answers_dao }
+    }
+}
+
+impl Loader<String> for AnswersLoader {
+    type Value = Vec<AnswerDetail>;
+    type Error = HandlerError;
+
+    async fn load(
+        &self,
+        question_uuids: &[String],
+    ) -> Result<HashMap<String, Self::Value>, Self::Error> {
+        let answers = self
+            .answers_dao
+            .get_answers_of_questions(question_uuids.to_vec())
+            .await
+            .map_err(handler_error)?;
+
+        let mut answers_by_question: HashMap<String, Self::Value> = question_uuids
+            .iter()
+            .map(|question_uuid| (question_uuid.clone(), Vec::new()))
+            .collect();
+        for answer in answers {
+            answers_by_question
+                .entry(answer.question_uuid.clone())
+                .or_default()
+                .push(answer);
+        }
+
+        Ok(answers_by_question)
+    }
+}
+
+// ***********************************************************
+//                           Tests
+// ***********************************************************
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{models::Answer, persistance::unit_of_work::UnitOfWork};
+    use async_graphql::dataloader::DataLoader;
+    use async_trait::async_trait;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use tokio::sync::Mutex;
+
+    /// Answers every question with a single answer and counts the queries
+    #[derive(Default)]
+    struct CountingAnswersDao {
+        queries: AtomicUsize,
+        create_answer_response: Mutex<Option<Result<AnswerDetail, DBError>>>,
+        get_answer_response: Mutex<Option<Result<AnswerDetail, DBError>>>,
+        lock_answer_response: Mutex<Option<Result<AnswerDetail, DBError>>>,
+        update_answer_response: Mutex<Option<Result<AnswerDetail, DBError>>>,
+        delete_answer_response: Mutex<Option<Result<(), DBError>>>,
+        get_answers_response: Mutex<Option<Result<Vec<AnswerDetail>, DBError>>>,
+    }
+
+    #[async_trait]
+    impl AnswersDao for CountingAnswersDao {
+        async fn create_answer(
+            &self,
+            _: &mut dyn UnitOfWork,
+            _: Answer,
+            _: String,
+        ) -> Result<AnswerDetail, DBError> {
+            self.create_answer_response
+                .lock()
+                .await
+                .take()
+                .expect("create_answer_response should not be None.")
+        }
+        async fn get_answer(&self, _: String) -> Result<AnswerDetail, DBError> {
+            self.get_answer_response
+                .lock()
+                .await
+                .take()
+                .expect("get_answer_response should not be None.")
+        }
+        async fn lock_answer(
+            &self,
+            _: &mut dyn UnitOfWork,
+            _: String,
+        ) -> Result<AnswerDetail, DBError> {
+            self.lock_answer_response
+                .lock()
+                .await
+                .take()
+                .expect("lock_answer_response should not be None.")
+        }
+        async fn update_answer(
+            &self,
+            _: &mut dyn UnitOfWork,
+            _: String,
+            _: String,
+        ) -> Result<AnswerDetail, DBError> {
+            self.update_answer_response
+                .lock()
+                .await
+                .take()
+                .expect("update_answer_response should not be None.")
+        }
+        async fn delete_answer(
+            &self,
+            _: &mut dyn UnitOfWork,
+            _: String,
+            _: String,
+        ) -> Result<(), DBError> {
+            self.delete_answer_response
+                .lock()
+                .await
+                .take()
+                .expect("delete_answer_response should not be None.")
+        }
+        async fn get_answers(&self, _: String) -> Result<Vec<AnswerDetail>, DBError> {
+            self.get_answers_response
+                .lock()
+                .await
+                .take()
+                .expect("get_answers_response should not be None.")
+        }
+        async fn get_answers_of_questions(
+            &self,
+            question_uuids: Vec<String>,
+        ) -> Result<Vec<AnswerDetail>, DBError> {
+            self.queries.fetch_add(1, Ordering::SeqCst);
+
+            Ok(question_uuids
+                .into_iter()
+                .filter(|question_uuid| question_uuid != "unanswered")
+                .map(|question_uuid| AnswerDetail {
+                    answer_uuid: format!("answer of {question_uuid}"),
+                    question_uuid,
+                    content: "content".to_owned(),
+                    content_html: "<p>content</p>\n".to_owned(),
+                    author: "user".to_owned(),
+                    created_at: "now".to_owned(),
+                })
+                .collect())
+        }
+    }
+
+    #[tokio::test]
+    async fn answers_loader_should_batch_concurrent_loads() {
+        let answers_dao = Arc::new(CountingAnswersDao::default());
+        let loader = DataLoader::new(AnswersLoader::new(answers_dao.clone()), tokio::spawn);
+
+        let (first, second, unanswered) = tokio::join!(
+            loader.load_one("first".to_owned()),
+            loader.load_one("second".to_owned()),
+            loader.load_one("unanswered".to_owned()),
+        );
+
+        assert_eq!(answers_dao.queries.load(Ordering::SeqCst), 1);
+        assert_eq!(first.unwrap().unwrap()[0].answer_uuid, "answer of first");
+        assert_eq!(second.unwrap().unwrap()[0].answer_uuid, "answer of second");
+        assert_eq!(unanswered.unwrap(), Some(Vec::new()));
+    }
+}
@@ -0,0 +1,72 @@
+//! GraphQL API over questions and answers.
+//! Nested fields are resolved through DataLoaders, so a page of questions with their answers
+//! costs a fixed number of SQL queries whatever the size of the page.
+
+mod loaders;
+mod schema;
+
+use std::sync::OnceLock;
+
+use async_graphql::{
+    dataloader::DataLoader, http::GraphiQLSource, EmptySubscription, ErrorExtensions, Schema,
+};
+use axum::{
+    extract::State,
+    response::{Html, IntoResponse},
+    Json,
+};
+
+use crate::{
+    handlers::extractors::{Caller, RequestId},
+    models::HandlerError,
+    AppState,
+};
+use loaders::{AnswersLoader, QuestionLoader};
+use schema::{MutationRoot, QueryRoot};
+
+pub type QaSchema = Schema<QueryRoot, MutationRoot, EmptySubscription>;
+
+fn schema() -> &'static QaSchema {
+    static SCHEMA: OnceLock<QaSchema> = OnceLock::new();
+    SCHEMA.get_or_init(|| Schema::build(QueryRoot, MutationRoot, EmptySubscription).finish())
+}
+
+/// Errors carry the same classification as the status codes of the REST API
+impl ErrorExtensions for HandlerError {
+    fn extend(&self) -> async_graphql::Error {
+        let code = match self {
+            HandlerError::BadRequest(_) => "BAD_REQUEST",
+            HandlerError::Unauthorized(_) => "UNAUTHORIZED",
+            HandlerError::Forbidden(_) => "FORBIDDEN",
+            HandlerError::InternalError(_) => "INTERNAL_SERVER_ERROR",
+        };
+        async_graphql::Error::new(self.to_string()).extend_with(|_, e| e.set("code", code))
+    }
+}
+
+pub async fn graphql(
+    State(state): State<AppState>,
+    caller: Caller,
+    request_id: RequestId,
+    Json(request): Json<async_graphql::Request>,
+) -> Json<async_graphql::Response> {
+    // Loaders are created per request so that batches never mix the data of different callers
+    let request = request
+        .data(DataLoader::new(
+            QuestionLoader::new(state.questions_dao.clone()),
+            tokio::spawn,
+        ))
+        .data(DataLoader::new(
+            AnswersLoader::new(state.answers_dao.clone()),
+            tokio::spawn,
+        ))
+        .data(caller)
+        .data(request_id)
+        .data(state);
+
+    Json(schema().execute(request).await)
+}
+
+pub async fn graphiql() -> impl IntoResponse {
+    Html(GraphiQLSource::build().endpoint("/graphql").finish())
+}
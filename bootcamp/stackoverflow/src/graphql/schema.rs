@@ -0,0 +1,371 @@
+use async_graphql::{
+    connection::{query, Connection, Edge},
+    dataloader::DataLoader,
+    Context, ErrorExtensions, Object, OutputType, Result,
+};
+
+use super::loaders::{AnswersLoader, QuestionLoader};
+use crate::{
+    handlers::{
+        extractors::{Caller, RequestId},
+        handlers_inner,
+    },
+    models::{
+        Answer, AnswerDetail, AnswerEdit, AnswerId, HandlerError, Question, QuestionDetail,
+        QuestionEdit, QuestionId,
+    },
+    AppState,
+};
+
+const DEFAULT_PAGE_SIZE: usize = 20;
+const MAX_PAGE_SIZE: usize = 100;
+
+/// Cursors are offsets in the list, pages start right after the `after` cursor
+fn page_bounds(after: Option<usize>, first: Option<usize>) -> (usize, usize) {
+    let start = after.map_or(0, |after| after + 1);
+    let size = first.unwrap_or(DEFAULT_PAGE_SIZE).min(MAX_PAGE_SIZE);
+    (start, size)
+}
+
+/// Builds a page from the nodes following `start`, one more than the page size
+/// may be given to tell whether there is a next page
+fn page<T: OutputType>(start: usize, size: usize, nodes: Vec<T>) -> Connection<usize, T> {
+    let mut connection = Connection::new(start > 0, nodes.len() > size);
+    connection.edges.extend(
+        nodes
+            .into_iter()
+            .take(size)
+            .enumerate()
+            .map(|(i, node)| Edge::new(start + i, node)),
+    );
+    connection
+}
+
+fn state_and_caller<'a>(ctx: &Context<'a>) -> (&'a AppState, String) {
+    let Caller(caller) = ctx.data_unchecked::<Caller>();
+    (ctx.data_unchecked::<AppState>(), caller.clone())
+}
+
+fn request_id(ctx: &Context<'_>) -> Option<String> {
+    let RequestId(request_id) = ctx.data_unchecked::<RequestId>();
+    request_id.clone()
+}
+
+pub struct QuestionNode(QuestionDetail);
+
+#[Object(name = "Question")]
+impl QuestionNode {
+    async fn question_uuid(&self) -> &str {
+        &self.0.question_uuid
+    }
+
+    async fn title(&self) -> &str {
+        &self.0.title
+    }
+
+    /// Markdown source
+    async fn description(&self) -> &str {
+        &self.0.description
+    }
+
+    /// Sanitised HTML rendering of `description`
+    async fn description_html(&self) -> &str {
+        &self.0.description_html
+    }
+
+    async fn author(&self) -> &str {
+        &self.0.author
+    }
+
+    /// UUID of the question this one was closed as a duplicate of
+    async fn duplicate_of(&self) -> Option<&str> {
+        self.0.duplicate_of.as_deref()
+    }
+
+    /// Views counted so far, recent views may not be included yet
+    async fn view_count(&self) -> i64 {
+        self.0.view_count
+    }
+
+    async fn created_at(&self) -> &str {
+        &self.0.created_at
+    }
+
+    /// Answers oldest first, loaded together with the answers of sibling questions
+    async fn answers(
+        &self,
+        ctx: &Context<'_>,
+        after: Option<String>,
+        first: Option<i32>,
+    ) -> Result<Connection<usize, AnswerNode>> {
+        let answers = ctx
+            .data_unchecked::<DataLoader<AnswersLoader>>()
+            .load_one(self.0.question_uuid.clone())
+            .await
+            .map_err(|e| e.extend())?
+            .unwrap_or_default();
+
+        query(after, None, first, None, |after, _, first, _| async move {
+            let (start, size) = page_bounds(after, first);
+            let answers = answers
+                .into_iter()
+                .skip(start)
+                .take(size + 1)
+                .map(AnswerNode)
+                .collect();
+            Ok::<_, async_graphql::Error>(page(start, size, answers))
+        })
+        .await
+    }
+}
+
+pub struct AnswerNode(AnswerDetail);
+
+#[Object(name = "Answer")]
+impl AnswerNode {
+    async fn answer_uuid(&self) -> &str {
+        &self.0.answer_uuid
+    }
+
+    /// Markdown source
+    async fn content(&self) -> &str {
+        &self.0.content
+    }
+
+    /// Sanitised HTML rendering of `content`
+    async fn content_html(&self) -> &str {
+        &self.0.content_html
+    }
+
+    async fn author(&self) -> &str {
+        &self.0.author
+    }
+
+    async fn created_at(&self) -> &str {
+        &self.0.created_at
+    }
+
+    async fn question(&self, ctx: &Context<'_>) -> Result<Option<QuestionNode>> {
+        let question = ctx
+            .data_unchecked::<DataLoader<QuestionLoader>>()
+            .load_one(self.0.question_uuid.clone())
+            .await
+            .map_err(|e| e.extend())?;
+
+        Ok(question.map(QuestionNode))
+    }
+}
+
+pub struct QueryRoot;
+
+#[Object]
+impl QueryRoot {
+    /// Questions that were not deleted, oldest first
+    async fn questions(
+        &self,
+        ctx: &Context<'_>,
+        after: Option<String>,
+        first: Option<i32>,
+    ) -> Result<Connection<usize, QuestionNode>> {
+        let (state, _) = state_and_caller(ctx);
+
+        query(after, None, first, None, |after, _, first, _| async move {
+            let (start, size) = page_bounds(after, first);
+            let questions = state
+                .questions_dao
+                .get_questions_page(start as i64, size as i64 + 1)
+                .await
+                .map_err(|err| HandlerError::InternalError(err.to_string()).extend())?;
+
+            let questions = questions.into_iter().map(QuestionNode).collect();
+            Ok::<_, async_graphql::Error>(page(start, size, questions))
+        })
+        .await
+    }
+
+    /// Unlike the REST API, questions closed as a duplicate are returned as is
+    async fn question(
+        &self,
+        ctx: &Context<'_>,
+        question_uuid: String,
+    ) -> Result<Option<QuestionNode>> {
+        let question = ctx
+            .data_unchecked::<DataLoader<QuestionLoader>>()
+            .load_one(question_uuid)
+            .await
+            .map_err(|e| e.extend())?;
+
+        Ok(question.map(QuestionNode))
+    }
+}
+
+/// Mirrors the CRUD routes of the REST API, with the same permission checks
+pub struct MutationRoot;
+
+#[Object]
+impl MutationRoot {
+    async fn create_question(
+        &self,
+        ctx: &Context<'_>,
+        title: String,
+        description: String,
+    ) -> Result<QuestionNode> {
+        let (state, caller) = state_and_caller(ctx);
+
+        handlers_inner::create_question(
+            Question { title, description },
+            caller,
+            request_id(ctx),
+            state.database.as_ref(),
+            state.audit_dao.as_ref(),
+            state.questions_dao.as_ref(),
+        )
+        .await
+        .map(QuestionNode)
+        .map_err(|e| e.extend())
+    }
+
+    async fn update_question(
+        &self,
+        ctx: &Context<'_>,
+        question_uuid: String,
+        title: String,
+        description: String,
+    ) -> Result<QuestionNode> {
+        let (state, caller) = state_and_caller(ctx);
+
+        handlers_inner::update_question(
+            QuestionEdit {
+                question_uuid,
+                title,
+                description,
+            },
+            caller,
+            state.config.anonymous_posts_open(),
+            request_id(ctx),
+            state.database.as_ref(),
+            state.audit_dao.as_ref(),
+            state.questions_dao.as_ref(),
+            state.reputation_dao.as_ref(),
+        )
+        .await
+        .map(QuestionNode)
+        .map_err(|e| e.extend())
+    }
+
+    async fn delete_question(&self, ctx: &Context<'_>, question_uuid: String) -> Result<bool> {
+        let (state, caller) = state_and_caller(ctx);
+
+        handlers_inner::delete_question(
+            QuestionId { question_uuid },
+            caller,
+            state.config.anonymous_posts_open(),
+            request_id(ctx),
+            state.database.as_ref(),
+            state.audit_dao.as_ref(),
+            state.questions_dao.as_ref(),
+            state.reputation_dao.as_ref(),
+        )
+        .await
+        .map(|_| true)
+        .map_err(|e| e.extend())
+    }
+
+    async fn create_answer(
+        &self,
+        ctx: &Context<'_>,
+        question_uuid: String,
+        content: String,
+    ) -> Result<AnswerNode> {
+        let (state, caller) = state_and_caller(ctx);
+
+        handlers_inner::create_answer(
+            Answer {
+                question_uuid,
+                content,
+            },
+            caller,
+            request_id(ctx),
+            state.database.as_ref(),
+            state.audit_dao.as_ref(),
+            state.answers_dao.as_ref(),
+        )
+        .await
+        .map(AnswerNode)
+        .map_err(|e| e.extend())
+    }
+
+    async fn update_answer(
+        &self,
+        ctx: &Context<'_>,
+        answer_uuid: String,
+        content: String,
+    ) -> Result<AnswerNode> {
+        let (state, caller) = state_and_caller(ctx);
+
+        handlers_inner::update_answer(
+            AnswerEdit {
+                answer_uuid,
+                content,
+            },
+            caller,
+            state.config.anonymous_posts_open(),
+            request_id(ctx),
+            state.database.as_ref(),
+            state.audit_dao.as_ref(),
+            state.answers_dao.as_ref(),
+            state.reputation_dao.as_ref(),
+        )
+        .await
+        .map(AnswerNode)
+        .map_err(|e| e.extend())
+    }
+
+    async fn delete_answer(&self, ctx: &Context<'_>, answer_uuid: String) -> Result<bool> {
+        let (state, caller) = state_and_caller(ctx);
+
+        handlers_inner::delete_answer(
+            AnswerId { answer_uuid },
+            caller,
+            state.config.anonymous_posts_open(),
+            request_id(ctx),
+            state.database.as_ref(),
+            state.audit_dao.as_ref(),
+            state.answers_dao.as_ref(),
+            state.reputation_dao.as_ref(),
+        )
+        .await
+        .map(|_| true)
+        .map_err(|e| e.extend())
+    }
+}
+
+// ***********************************************************
+//                           Tests
+// ***********************************************************
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn page_bounds_should_start_after_the_cursor() {
+        assert_eq!(page_bounds(None, None), (0, DEFAULT_PAGE_SIZE));
+        assert_eq!(page_bounds(Some(4), Some(2)), (5, 2));
+        assert_eq!(page_bounds(None, Some(1000)), (0, MAX_PAGE_SIZE));
+    }
+
+    #[test]
+    fn page_should_report_next_page_from_the_extra_node() {
+        let last_page = page(5, 2, vec![1, 2]);
+        assert!(last_page.has_previous_page);
+        assert!(!last_page.has_next_page);
+        assert_eq!(last_page.edges.len(), 2);
+        assert_eq!(last_page.edges[1].cursor, 6);
+
+        let first_page = page(0, 2, vec![1, 2, 3]);
+        assert!(!first_page.has_previous_page);
+        assert!(first_page.has_next_page);
+        assert_eq!(first_page.edges.len(), 2);
+    }
+}
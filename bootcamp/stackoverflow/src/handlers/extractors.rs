@@ -0,0 +1,247 @@
+use std::{collections::HashMap, convert::Infallible};
+
+use axum::{
+    extract::{FromRequestParts, Path},
+    http::{header::AUTHORIZATION, request::Parts},
+};
+use sqlx::types::Uuid;
+
+use super::handlers_inner::{self, HandlerError, MODERATOR};
+use crate::{
+    models::{SpaceRole, ANONYMOUS},
+    AppState,
+};
+
+pub const USER_ID_HEADER: &str = "x-user-id";
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+/// Longest request id accepted from a caller, longer ones are replaced
+pub const MAX_REQUEST_ID_LEN: usize = 128;
+
+/// Identity of the caller as told by the configured `IdentityProvider`, anonymous when it
+/// cannot tell
+pub struct Caller(pub String);
+
+impl FromRequestParts<AppState> for Caller {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &AppState,
+    ) -> Result<Self, Self::Rejection> {
+        let user_id = state
+            .identity
+            .identify(parts)
+            .unwrap_or_else(|| ANONYMOUS.to_owned());
+
+        Ok(Caller(user_id))
+    }
+}
+
+fn bearer_token(parts: &Parts) -> Option<&str> {
+    parts
+        .headers
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+}
+
+/// Tells who is calling, the server is configured with one of the implementations below
+pub trait IdentityProvider: Send + Sync {
+    fn identify(&self, parts: &Parts) -> Option<String>;
+}
+
+/// Trusts the `X-User-Id` header, for deployments behind an authenticating proxy
+pub struct HeaderIdentity;
+
+impl IdentityProvider for HeaderIdentity {
+    fn identify(&self, parts: &Parts) -> Option<String> {
+        parts
+            .headers
+            .get(USER_ID_HEADER)
+            .and_then(|value| value.to_str().ok())
+            .filter(|value| !value.is_empty())
+            .map(str::to_owned)
+    }
+}
+
+/// Looks the bearer token up among the tokens issued to users
+pub struct BearerIdentity {
+    users_by_token: HashMap<String, String>,
+}
+
+impl BearerIdentity {
+    pub fn new(users_by_token: HashMap<String, String>) -> Self {
+        BearerIdentity { users_by_token }
+    }
+}
+
+impl IdentityProvider for BearerIdentity {
+    fn identify(&self, parts: &Parts) -> Option<String> {
+        bearer_token(parts).and_then(|token| self.users_by_token.get(token).cloned())
+    }
+}
+
+/// Identity of a caller acting on their own data, anonymous callers are rejected
+pub struct Identity(pub String);
+
+impl FromRequestParts<AppState> for Identity {
+    type Rejection = HandlerError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &AppState,
+    ) -> Result<Self, Self::Rejection> {
+        state
+            .identity
+            .identify(parts)
+            .filter(|user_id| user_id != ANONYMOUS)
+            .map(Identity)
+            .ok_or_else(|| HandlerError::Unauthorized("The caller must identify".to_owned()))
+    }
+}
+
+/// Id of the request, set by the `request_id` middleware, recorded with the mutations it makes
+pub struct RequestId(pub Option<String>);
+
+impl<S: Send + Sync> FromRequestParts<S> for RequestId {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _: &S) -> Result<Self, Self::Rejection> {
+        let request_id = parts
+            .headers
+            .get(REQUEST_ID_HEADER)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_owned);
+
+        Ok(RequestId(request_id))
+    }
+}
+
+/// Guard of moderation routes, the bearer token must be one of the configured admin tokens.
+/// Holds the name of the admin, recorded as the actor of the changes they make.
+pub struct Admin(pub String);
+
+impl FromRequestParts<AppState> for Admin {
+    type Rejection = HandlerError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &AppState,
+    ) -> Result<Self, Self::Rejection> {
+        let token = bearer_token(parts);
+        if let Some(name) = token.and_then(|token| state.config.admin_tokens.get(token)) {
+            return Ok(Admin(name.clone()));
+        }
+
+        match (token, state.config.admin_token.as_deref()) {
+            (Some(token), Some(admin_token)) if token == admin_token => {
+                Ok(Admin(MODERATOR.to_owned()))
+            }
+            _ => Err(HandlerError::Unauthorized(
+                "A valid admin token is required".to_owned(),
+            )),
+        }
+    }
+}
+
+/// State of the space named by the `slug` path parameter, once the caller was let in with
+/// `access`. Outside of `/spaces/{slug}`, the state of the default space as is.
+async fn space_state(
+    parts: &mut Parts,
+    state: &AppState,
+    access: SpaceRole,
+) -> Result<AppState, HandlerError> {
+    let slug = Path::<HashMap<String, String>>::from_request_parts(parts, state)
+        .await
+        .ok()
+        .and_then(|Path(mut params)| params.remove("slug"));
+    let Some(slug) = slug else {
+        return Ok(state.clone());
+    };
+
+    let admin = Admin::from_request_parts(parts, state).await.is_ok();
+    let user_id = state.identity.identify(parts);
+    let space =
+        handlers_inner::enter_space(slug, user_id, admin, access, state.spaces_dao.as_ref())
+            .await?;
+    let space_id =
+        Uuid::parse_str(&space.space_id).map_err(|e| HandlerError::InternalError(e.to_string()))?;
+
+    Ok(state.in_space(space_id))
+}
+
+/// State of the space of the request, for callers allowed to read it
+pub struct SpaceReader(pub AppState);
+
+impl FromRequestParts<AppState> for SpaceReader {
+    type Rejection = HandlerError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &AppState,
+    ) -> Result<Self, Self::Rejection> {
+        space_state(parts, state, SpaceRole::Reader)
+            .await
+            .map(SpaceReader)
+    }
+}
+
+/// State of the space of the request, for callers allowed to write to it
+pub struct SpaceWriter(pub AppState);
+
+impl FromRequestParts<AppState> for SpaceWriter {
+    type Rejection = HandlerError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &AppState,
+    ) -> Result<Self, Self::Rejection> {
+        space_state(parts, state, SpaceRole::Writer)
+            .await
+            .map(SpaceWriter)
+    }
+}
+
+// ***********************************************************
+//                           Tests
+// ***********************************************************
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::Request;
+
+    fn parts(header: &str, value: &str) -> Parts {
+        Request::builder()
+            .header(header, value)
+            .body(())
+            .unwrap()
+            .into_parts()
+            .0
+    }
+
+    #[test]
+    fn header_identity_should_ignore_empty_headers() {
+        assert_eq!(
+            HeaderIdentity.identify(&parts(USER_ID_HEADER, "toto")),
+            Some("toto".to_owned())
+        );
+        assert_eq!(HeaderIdentity.identify(&parts(USER_ID_HEADER, "")), None);
+    }
+
+    #[test]
+    fn bearer_identity_should_only_accept_issued_tokens() {
+        let identity = BearerIdentity::new(HashMap::from([("abc".to_owned(), "toto".to_owned())]));
+
+        assert_eq!(
+            identity.identify(&parts("authorization", "Bearer abc")),
+            Some("toto".to_owned())
+        );
+        assert_eq!(
+            identity.identify(&parts("authorization", "Bearer def")),
+            None
+        );
+        assert_eq!(identity.identify(&parts(USER_ID_HEADER, "toto")), None);
+    }
+}
@@ -1,26 +1,149 @@
+use std::ops::Range;
+
 use crate::{
-    models::{Answer, AnswerDetail, AnswerId, DBError, Question, QuestionDetail, QuestionId},
-    persistance::{answers_dao::AnswersDao, questions_dao::QuestionsDao},
+    attachments::{ALLOWED_CONTENT_TYPES, MAX_ATTACHMENT_SIZE},
+    models::{
+        Answer, AnswerDetail, AnswerEdit, AnswerId, Attachment, AttachmentDetail, AuditAction,
+        AuditEntry, AuditQuery, AuditRecord, DBError, DuplicateOf, Flag, FlagDetail,
+        FlagResolution, InboxItem, JobRun, MarkRead, Page, PageQuery, PostKind, Privilege,
+        Question, QuestionDetail, QuestionEdit, QuestionId, SimilarQuestion, Space, SpaceDetail,
+        SpaceMember, SpaceMemberDetail, SpaceRole, Stats, StatsQuery, Upload, UserProfile, Webhook,
+        WebhookDetail, ANONYMOUS,
+    },
+    persistance::{
+        answers_dao::AnswersDao,
+        attachments_dao::AttachmentsDao,
+        audit_dao::AuditDao,
+        blob_store::BlobStore,
+        flags_dao::FlagsDao,
+        follows_dao::FollowsDao,
+        jobs_dao::JobsDao,
+        questions_dao::QuestionsDao,
+        reputation_dao::ReputationDao,
+        spaces_dao::SpacesDao,
+        stats_dao::StatsDao,
+        unit_of_work::{Database, UnitOfWork},
+        webhooks_dao::WebhooksDao,
+    },
+    views::ViewCounter,
+    webhooks,
 };
 use log::error;
+use serde::Serialize;
 
-#[derive(Debug, PartialEq, Deserialize)]
-pub enum HandlerError {
-    BadRequest(String),
-    InternalError(String),
-}
+pub use crate::models::HandlerError;
+
+/// Name of the admins using the shared admin token rather than a named one
+pub const MODERATOR: &str = "moderator";
 
-use serde::Deserialize;
 use HandlerError::*;
+
+async fn begin(
+    database: &(dyn Database + Send + Sync),
+) -> Result<Box<dyn UnitOfWork>, HandlerError> {
+    database.begin().await.map_err(|e| {
+        error!("Failed to begin unit of work: {:?}", e);
+        InternalError(e.to_string())
+    })
+}
+
+async fn commit(uow: Box<dyn UnitOfWork>) -> Result<(), HandlerError> {
+    uow.commit().await.map_err(|e| {
+        error!("Failed to commit unit of work: {:?}", e);
+        InternalError(e.to_string())
+    })
+}
+
+/// Serialises the state of a resource for the audit log
+fn snapshot<T: Serialize>(resource: &T) -> Option<serde_json::Value> {
+    serde_json::to_value(resource).ok()
+}
+
+/// Appends `entry` to the audit log, in the unit of work of the mutation it records
+async fn audit(
+    uow: &mut dyn UnitOfWork,
+    audit_dao: &(dyn AuditDao + Send + Sync),
+    entry: AuditEntry,
+) -> Result<(), HandlerError> {
+    audit_dao.record(uow, entry).await.map_err(|e| {
+        error!("Failed to record audit entry: {:?}", e);
+        InternalError(e.to_string())
+    })
+}
+
+/// Fails unless `user_id` has enough reputation to use `privilege`
+async fn require_privilege(
+    user_id: &str,
+    privilege: Privilege,
+    reputation_dao: &(dyn ReputationDao + Send + Sync),
+) -> Result<(), HandlerError> {
+    if user_id == ANONYMOUS {
+        return Err(Forbidden(format!("Anonymous users cannot {privilege:?}")));
+    }
+
+    let reputation = reputation_dao
+        .get_reputation(user_id.to_owned())
+        .await
+        .map_err(|e| {
+            error!("Failed to read reputation: {:?}", e);
+            InternalError(e.to_string())
+        })?;
+
+    if reputation < privilege.min_reputation() {
+        return Err(Forbidden(format!(
+            "{} reputation is required to {privilege:?}, {user_id} has {reputation}",
+            privilege.min_reputation()
+        )));
+    }
+
+    Ok(())
+}
+
+/// Users may always modify their own posts, other posts require `privilege`.
+/// Anonymous callers own nothing, unless `anonymous_posts_open`: deployments without a
+/// configured identity provider keep anonymous posts open to anonymous callers, as all posts
+/// were before they had owners.
+async fn require_owner_or_privilege(
+    user_id: &str,
+    author: &str,
+    anonymous_posts_open: bool,
+    privilege: Privilege,
+    reputation_dao: &(dyn ReputationDao + Send + Sync),
+) -> Result<(), HandlerError> {
+    if user_id == author && (user_id != ANONYMOUS || anonymous_posts_open) {
+        return Ok(());
+    }
+
+    require_privilege(user_id, privilege, reputation_dao).await
+}
+
 pub async fn create_question(
     question: Question,
-    // We are using a trait object here so that inner handlers do not depend on concrete DAO implementations
+    author: String,
+    request_id: Option<String>,
+    // We are using trait objects here so that inner handlers do not depend on concrete DAO implementations
+    database: &(dyn Database + Send + Sync),
+    audit_dao: &(dyn AuditDao + Send + Sync),
     questions_dao: &(dyn QuestionsDao + Sync + Send),
 ) -> Result<QuestionDetail, HandlerError> {
-    let question = questions_dao.create_question(question).await;
+    let mut uow = begin(database).await?;
+    let question = questions_dao
+        .create_question(uow.as_mut(), question, author.clone())
+        .await;
 
     match question {
-        Ok(question) => Ok(question), // return question
+        Ok(question) => {
+            let entry = AuditEntry {
+                actor: author,
+                action: AuditAction::QuestionCreated,
+                resource_id: question.question_uuid.clone(),
+                before: None,
+                after: snapshot(&question),
+                request_id,
+            };
+            audit(uow.as_mut(), audit_dao, entry).await?;
+            commit(uow).await.map(|_| question) // return question
+        }
         Err(err) => {
             error!("Failed to create question: {:?}", err);
             Err(InternalError(err.to_string()))
@@ -42,29 +165,402 @@ pub async fn read_questions(
     }
 }
 
+/// Questions listed by default, and at most, in a page
+pub const DEFAULT_PAGE_SIZE: i64 = 20;
+pub const MAX_PAGE_SIZE: i64 = 100;
+
+fn page_bounds(query: PageQuery) -> Result<(i64, i64), HandlerError> {
+    let offset = query.offset.unwrap_or(0);
+    let limit = query.limit.unwrap_or(DEFAULT_PAGE_SIZE);
+    if offset < 0 {
+        return Err(BadRequest(format!(
+            "The offset must not be negative, got {offset}"
+        )));
+    }
+    if !(1..=MAX_PAGE_SIZE).contains(&limit) {
+        return Err(BadRequest(format!(
+            "The limit must be between 1 and {MAX_PAGE_SIZE}, got {limit}"
+        )));
+    }
+    Ok((offset, limit))
+}
+
+/// Page of `items`, read with one more item than the `limit` to tell whether there is a next page
+fn page_of<T>(mut items: Vec<T>, offset: i64, limit: i64) -> Page<T> {
+    let next_offset = (items.len() as i64 > limit).then_some(offset + limit);
+    items.truncate(limit as usize);
+    Page { items, next_offset }
+}
+
+pub async fn read_questions_page(
+    query: PageQuery,
+    questions_dao: &(dyn QuestionsDao + Sync + Send),
+) -> Result<Page<QuestionDetail>, HandlerError> {
+    let (offset, limit) = page_bounds(query)?;
+
+    let questions = questions_dao.get_questions_page(offset, limit + 1).await;
+
+    match questions {
+        Ok(questions) => Ok(page_of(questions, offset, limit)),
+        Err(err) => {
+            error!("Failed to read questions: {:?}", err);
+            Err(InternalError(err.to_string()))
+        }
+    }
+}
+
+pub async fn read_needs_attention(
+    query: PageQuery,
+    questions_dao: &(dyn QuestionsDao + Sync + Send),
+) -> Result<Page<QuestionDetail>, HandlerError> {
+    let (offset, limit) = page_bounds(query)?;
+
+    let questions = questions_dao
+        .get_questions_needing_attention(offset, limit + 1)
+        .await;
+
+    match questions {
+        Ok(questions) => Ok(page_of(questions, offset, limit)),
+        Err(err) => {
+            error!("Failed to read questions needing attention: {:?}", err);
+            Err(InternalError(err.to_string()))
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub async fn update_question(
+    question_edit: QuestionEdit,
+    edited_by: String,
+    anonymous_posts_open: bool,
+    request_id: Option<String>,
+    database: &(dyn Database + Send + Sync),
+    audit_dao: &(dyn AuditDao + Send + Sync),
+    questions_dao: &(dyn QuestionsDao + Sync + Send),
+    reputation_dao: &(dyn ReputationDao + Send + Sync),
+) -> Result<QuestionDetail, HandlerError> {
+    // The post stays locked until the unit ends, so its author cannot change under the check
+    let mut uow = begin(database).await?;
+    let before = questions_dao
+        .lock_question(uow.as_mut(), question_edit.question_uuid.clone())
+        .await
+        .map_err(|err| match err {
+            DBError::InvalidUUID(s) => BadRequest(s),
+            _ => InternalError(err.to_string()),
+        })?;
+
+    require_owner_or_privilege(
+        &edited_by,
+        &before.author,
+        anonymous_posts_open,
+        Privilege::EditOthersPosts,
+        reputation_dao,
+    )
+    .await?;
+
+    let question = questions_dao
+        .update_question(
+            uow.as_mut(),
+            question_edit.question_uuid,
+            Question {
+                title: question_edit.title,
+                description: question_edit.description,
+            },
+        )
+        .await;
+
+    match question {
+        Ok(question) => {
+            let entry = AuditEntry {
+                actor: edited_by,
+                action: AuditAction::QuestionUpdated,
+                resource_id: question.question_uuid.clone(),
+                before: snapshot(&before),
+                after: snapshot(&question),
+                request_id,
+            };
+            audit(uow.as_mut(), audit_dao, entry).await?;
+            commit(uow).await.map(|_| question)
+        }
+        Err(err) => {
+            error!("Failed to update question: {:?}", err);
+            match err {
+                DBError::InvalidUUID(s) => Err(BadRequest(s)),
+                _ => Err(InternalError(err.to_string())),
+            }
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
 pub async fn delete_question(
     question_uuid: QuestionId,
+    deleted_by: String,
+    anonymous_posts_open: bool,
+    request_id: Option<String>,
+    database: &(dyn Database + Send + Sync),
+    audit_dao: &(dyn AuditDao + Send + Sync),
     questions_dao: &(dyn QuestionsDao + Sync + Send),
+    reputation_dao: &(dyn ReputationDao + Send + Sync),
 ) -> Result<(), HandlerError> {
+    let mut uow = begin(database).await?;
+    let question = questions_dao
+        .lock_question(uow.as_mut(), question_uuid.question_uuid.clone())
+        .await
+        .map_err(|err| match err {
+            DBError::InvalidUUID(s) => BadRequest(s),
+            _ => InternalError(err.to_string()),
+        })?;
+
+    require_owner_or_privilege(
+        &deleted_by,
+        &question.author,
+        anonymous_posts_open,
+        Privilege::DeleteOthersPosts,
+        reputation_dao,
+    )
+    .await?;
+
     let result = questions_dao
-        .delete_question(question_uuid.question_uuid)
+        .delete_question(
+            uow.as_mut(),
+            question_uuid.question_uuid,
+            deleted_by.clone(),
+        )
         .await; // delete question using `questions_dao`
 
     if let Err(e) = result {
         return Err(InternalError(e.to_string()));
     }
 
-    Ok(())
+    let entry = AuditEntry {
+        actor: deleted_by,
+        action: AuditAction::QuestionDeleted,
+        resource_id: question.question_uuid.clone(),
+        before: snapshot(&question),
+        after: None,
+        request_id,
+    };
+    audit(uow.as_mut(), audit_dao, entry).await?;
+
+    commit(uow).await
+}
+
+/// Restored by an admin, recorded as such in the audit log
+pub async fn restore_question(
+    question_uuid: QuestionId,
+    admin: String,
+    request_id: Option<String>,
+    database: &(dyn Database + Send + Sync),
+    audit_dao: &(dyn AuditDao + Send + Sync),
+    questions_dao: &(dyn QuestionsDao + Sync + Send),
+) -> Result<QuestionDetail, HandlerError> {
+    let mut uow = begin(database).await?;
+    let question = questions_dao
+        .restore_question(uow.as_mut(), question_uuid.question_uuid)
+        .await;
+
+    match question {
+        Ok(question) => {
+            let entry = AuditEntry {
+                actor: admin,
+                action: AuditAction::QuestionRestored,
+                resource_id: question.question_uuid.clone(),
+                before: None,
+                after: snapshot(&question),
+                request_id,
+            };
+            audit(uow.as_mut(), audit_dao, entry).await?;
+            commit(uow).await.map(|_| question)
+        }
+        Err(err) => {
+            error!("Failed to restore question: {:?}", err);
+
+            match err {
+                DBError::InvalidUUID(s) => Err(BadRequest(s)),
+                _ => Err(InternalError(err.to_string())),
+            }
+        }
+    }
+}
+
+/// Counts a view unless the question is a duplicate, whose readers are sent to the original
+pub async fn read_question(
+    question_uuid: QuestionId,
+    questions_dao: &(dyn QuestionsDao + Sync + Send),
+    views: &ViewCounter,
+) -> Result<QuestionDetail, HandlerError> {
+    let question = questions_dao
+        .get_question(question_uuid.question_uuid)
+        .await;
+
+    match question {
+        Ok(question) => {
+            if question.duplicate_of.is_none() {
+                views.record(&question.question_uuid);
+            }
+            Ok(question)
+        }
+        Err(err) => {
+            error!("Failed to read question: {:?}", err);
+
+            match err {
+                DBError::InvalidUUID(s) => Err(BadRequest(s)),
+                _ => Err(InternalError(err.to_string())),
+            }
+        }
+    }
+}
+
+/// Number of questions listed as trending
+pub const TRENDING_QUESTIONS_LIMIT: i64 = 20;
+
+pub async fn read_trending_questions(
+    questions_dao: &(dyn QuestionsDao + Sync + Send),
+) -> Result<Vec<QuestionDetail>, HandlerError> {
+    let questions = questions_dao
+        .get_trending_questions(TRENDING_QUESTIONS_LIMIT)
+        .await;
+
+    match questions {
+        Ok(questions) => Ok(questions),
+        Err(err) => {
+            error!("Failed to read trending questions: {:?}", err);
+            Err(InternalError(err.to_string()))
+        }
+    }
+}
+
+/// Maximum number of questions suggested as related or similar
+pub const SIMILAR_QUESTIONS_LIMIT: i64 = 10;
+
+pub async fn read_related_questions(
+    question_uuid: QuestionId,
+    questions_dao: &(dyn QuestionsDao + Sync + Send),
+) -> Result<Vec<SimilarQuestion>, HandlerError> {
+    let questions = questions_dao
+        .get_related_questions(question_uuid.question_uuid, SIMILAR_QUESTIONS_LIMIT)
+        .await;
+
+    match questions {
+        Ok(questions) => Ok(questions),
+        Err(err) => {
+            error!("Failed to read related questions: {:?}", err);
+
+            match err {
+                DBError::InvalidUUID(s) => Err(BadRequest(s)),
+                _ => Err(InternalError(err.to_string())),
+            }
+        }
+    }
+}
+
+pub async fn read_similar_questions(
+    question: Question,
+    questions_dao: &(dyn QuestionsDao + Sync + Send),
+) -> Result<Vec<SimilarQuestion>, HandlerError> {
+    let questions = questions_dao
+        .get_similar_questions(question, SIMILAR_QUESTIONS_LIMIT)
+        .await;
+
+    match questions {
+        Ok(questions) => Ok(questions),
+        Err(err) => {
+            error!("Failed to read similar questions: {:?}", err);
+            Err(InternalError(err.to_string()))
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub async fn close_as_duplicate(
+    question_uuid: QuestionId,
+    duplicate_of: DuplicateOf,
+    closed_by: String,
+    anonymous_posts_open: bool,
+    request_id: Option<String>,
+    database: &(dyn Database + Send + Sync),
+    audit_dao: &(dyn AuditDao + Send + Sync),
+    questions_dao: &(dyn QuestionsDao + Sync + Send),
+    reputation_dao: &(dyn ReputationDao + Send + Sync),
+) -> Result<QuestionDetail, HandlerError> {
+    let mut uow = begin(database).await?;
+    let before = questions_dao
+        .lock_question(uow.as_mut(), question_uuid.question_uuid.clone())
+        .await
+        .map_err(|err| match err {
+            DBError::InvalidUUID(s) => BadRequest(s),
+            _ => InternalError(err.to_string()),
+        })?;
+
+    require_owner_or_privilege(
+        &closed_by,
+        &before.author,
+        anonymous_posts_open,
+        Privilege::CloseOthersQuestions,
+        reputation_dao,
+    )
+    .await?;
+
+    let question = questions_dao
+        .close_as_duplicate(
+            uow.as_mut(),
+            question_uuid.question_uuid,
+            duplicate_of.duplicate_of,
+            closed_by.clone(),
+        )
+        .await;
+
+    match question {
+        Ok(question) => {
+            let entry = AuditEntry {
+                actor: closed_by,
+                action: AuditAction::QuestionClosedAsDuplicate,
+                resource_id: question.question_uuid.clone(),
+                before: snapshot(&before),
+                after: snapshot(&question),
+                request_id,
+            };
+            audit(uow.as_mut(), audit_dao, entry).await?;
+            commit(uow).await.map(|_| question)
+        }
+        Err(err) => {
+            error!("Failed to close question as duplicate: {:?}", err);
+
+            match err {
+                DBError::InvalidUUID(s) => Err(BadRequest(s)),
+                _ => Err(InternalError(err.to_string())),
+            }
+        }
+    }
 }
 
 pub async fn create_answer(
     answer: Answer,
+    author: String,
+    request_id: Option<String>,
+    database: &(dyn Database + Send + Sync),
+    audit_dao: &(dyn AuditDao + Send + Sync),
     answers_dao: &(dyn AnswersDao + Send + Sync),
 ) -> Result<AnswerDetail, HandlerError> {
-    let answer = answers_dao.create_answer(answer).await;
+    let mut uow = begin(database).await?;
+    let answer = answers_dao
+        .create_answer(uow.as_mut(), answer, author.clone())
+        .await;
 
     match answer {
-        Ok(answer) => Ok(answer),
+        Ok(answer) => {
+            let entry = AuditEntry {
+                actor: author,
+                action: AuditAction::AnswerCreated,
+                resource_id: answer.answer_uuid.clone(),
+                before: None,
+                after: snapshot(&answer),
+                request_id,
+            };
+            audit(uow.as_mut(), audit_dao, entry).await?;
+            commit(uow).await.map(|_| answer)
+        }
         Err(err) => {
             error!("Failed to create answer: {:?}", err);
 
@@ -91,208 +587,3967 @@ pub async fn read_answers(
     }
 }
 
+/// Answers are few enough per question to be read at once and paged in memory
+pub async fn read_answers_page(
+    question_uuid: QuestionId,
+    query: PageQuery,
+    answers_dao: &(dyn AnswersDao + Send + Sync),
+) -> Result<Page<AnswerDetail>, HandlerError> {
+    let (offset, limit) = page_bounds(query)?;
+
+    let answers = answers_dao.get_answers(question_uuid.question_uuid).await;
+
+    match answers {
+        Ok(answers) => {
+            let next_offset = (answers.len() as i64 > offset + limit).then_some(offset + limit);
+            Ok(Page {
+                items: answers
+                    .into_iter()
+                    .skip(offset as usize)
+                    .take(limit as usize)
+                    .collect(),
+                next_offset,
+            })
+        }
+        Err(err) => {
+            error!("Failed to read answers: {:?}", err);
+
+            match err {
+                DBError::InvalidUUID(s) => Err(BadRequest(s)),
+                _ => Err(InternalError(err.to_string())),
+            }
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub async fn update_answer(
+    answer_edit: AnswerEdit,
+    edited_by: String,
+    anonymous_posts_open: bool,
+    request_id: Option<String>,
+    database: &(dyn Database + Send + Sync),
+    audit_dao: &(dyn AuditDao + Send + Sync),
+    answers_dao: &(dyn AnswersDao + Send + Sync),
+    reputation_dao: &(dyn ReputationDao + Send + Sync),
+) -> Result<AnswerDetail, HandlerError> {
+    let mut uow = begin(database).await?;
+    let before = answers_dao
+        .lock_answer(uow.as_mut(), answer_edit.answer_uuid.clone())
+        .await
+        .map_err(|err| match err {
+            DBError::InvalidUUID(s) => BadRequest(s),
+            _ => InternalError(err.to_string()),
+        })?;
+
+    require_owner_or_privilege(
+        &edited_by,
+        &before.author,
+        anonymous_posts_open,
+        Privilege::EditOthersPosts,
+        reputation_dao,
+    )
+    .await?;
+
+    let answer = answers_dao
+        .update_answer(uow.as_mut(), answer_edit.answer_uuid, answer_edit.content)
+        .await;
+
+    match answer {
+        Ok(answer) => {
+            let entry = AuditEntry {
+                actor: edited_by,
+                action: AuditAction::AnswerUpdated,
+                resource_id: answer.answer_uuid.clone(),
+                before: snapshot(&before),
+                after: snapshot(&answer),
+                request_id,
+            };
+            audit(uow.as_mut(), audit_dao, entry).await?;
+            commit(uow).await.map(|_| answer)
+        }
+        Err(err) => {
+            error!("Failed to update answer: {:?}", err);
+            match err {
+                DBError::InvalidUUID(s) => Err(BadRequest(s)),
+                _ => Err(InternalError(err.to_string())),
+            }
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
 pub async fn delete_answer(
     answer_uuid: AnswerId,
+    deleted_by: String,
+    anonymous_posts_open: bool,
+    request_id: Option<String>,
+    database: &(dyn Database + Send + Sync),
+    audit_dao: &(dyn AuditDao + Send + Sync),
     answers_dao: &(dyn AnswersDao + Send + Sync),
+    reputation_dao: &(dyn ReputationDao + Send + Sync),
 ) -> Result<(), HandlerError> {
-    let result = answers_dao.delete_answer(answer_uuid.answer_uuid).await;
+    let mut uow = begin(database).await?;
+    let answer = answers_dao
+        .lock_answer(uow.as_mut(), answer_uuid.answer_uuid.clone())
+        .await
+        .map_err(|err| match err {
+            DBError::InvalidUUID(s) => BadRequest(s),
+            _ => InternalError(err.to_string()),
+        })?;
+
+    require_owner_or_privilege(
+        &deleted_by,
+        &answer.author,
+        anonymous_posts_open,
+        Privilege::DeleteOthersPosts,
+        reputation_dao,
+    )
+    .await?;
+
+    let result = answers_dao
+        .delete_answer(uow.as_mut(), answer_uuid.answer_uuid, deleted_by.clone())
+        .await;
 
     if let Err(e) = result {
         return Err(InternalError(e.to_string()));
     }
 
-    Ok(())
-}
+    let entry = AuditEntry {
+        actor: deleted_by,
+        action: AuditAction::AnswerDeleted,
+        resource_id: answer.answer_uuid.clone(),
+        before: snapshot(&answer),
+        after: None,
+        request_id,
+    };
+    audit(uow.as_mut(), audit_dao, entry).await?;
 
-// ***********************************************************
-//                           Tests
-// ***********************************************************
+    commit(uow).await
+}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+pub async fn create_flag(
+    flag: Flag,
+    reported_by: String,
+    request_id: Option<String>,
+    database: &(dyn Database + Send + Sync),
+    audit_dao: &(dyn AuditDao + Send + Sync),
+    flags_dao: &(dyn FlagsDao + Send + Sync),
+) -> Result<FlagDetail, HandlerError> {
+    if flag.reason.trim().is_empty() {
+        return Err(BadRequest("A flag must give a reason".to_owned()));
+    }
 
-    use async_trait::async_trait;
-    use tokio::sync::Mutex;
+    let mut uow = begin(database).await?;
+    let flag = flags_dao
+        .create_flag(uow.as_mut(), flag, reported_by.clone())
+        .await;
 
-    struct QuestionsDaoMock {
-        create_question_response: Mutex<Option<Result<QuestionDetail, DBError>>>,
-        delete_question_response: Mutex<Option<Result<(), DBError>>>,
-        get_questions_response: Mutex<Option<Result<Vec<QuestionDetail>, DBError>>>,
-    }
+    match flag {
+        Ok(flag) => {
+            let entry = AuditEntry {
+                actor: reported_by,
+                action: AuditAction::FlagCreated,
+                resource_id: flag.flag_uuid.clone(),
+                before: None,
+                after: snapshot(&flag),
+                request_id,
+            };
+            audit(uow.as_mut(), audit_dao, entry).await?;
+            commit(uow).await.map(|_| flag)
+        }
+        Err(err) => {
+            error!("Failed to create flag: {:?}", err);
 
-    impl QuestionsDaoMock {
-        pub fn new() -> Self {
-            QuestionsDaoMock {
-                create_question_response: Mutex::new(None),
-                delete_question_response: Mutex::new(None),
-                get_questions_response: Mutex::new(None),
+            match err {
+                DBError::InvalidUUID(s) => Err(BadRequest(s)),
+                _ => Err(InternalError(err.to_string())),
             }
         }
-        pub fn mock_create_question(&mut self, response: Result<QuestionDetail, DBError>) {
-            self.create_question_response = Mutex::new(Some(response));
-        }
-        pub fn mock_delete_question(&mut self, response: Result<(), DBError>) {
-            self.delete_question_response = Mutex::new(Some(response));
-        }
-        pub fn mock_get_questions(&mut self, response: Result<Vec<QuestionDetail>, DBError>) {
-            self.get_questions_response = Mutex::new(Some(response));
-        }
     }
+}
 
-    #[async_trait]
-    impl QuestionsDao for QuestionsDaoMock {
-        async fn create_question(&self, _: Question) -> Result<QuestionDetail, DBError> {
-            self.create_question_response
-                .lock()
-                .await
-                .take()
-                .expect("create_question_response should not be None.")
-        }
-        async fn delete_question(&self, _: String) -> Result<(), DBError> {
-            self.delete_question_response
-                .lock()
-                .await
-                .take()
-                .expect("delete_question_response should not be None.")
-        }
-        async fn get_questions(&self) -> Result<Vec<QuestionDetail>, DBError> {
-            self.get_questions_response
-                .lock()
-                .await
-                .take()
-                .expect("get_questions_response should not be None.")
+pub async fn read_flags(
+    flags_dao: &(dyn FlagsDao + Send + Sync),
+) -> Result<Vec<FlagDetail>, HandlerError> {
+    let flags = flags_dao.get_open_flags().await;
+
+    match flags {
+        Ok(flags) => Ok(flags),
+        Err(err) => {
+            error!("Failed to read flags: {:?}", err);
+            Err(InternalError(err.to_string()))
         }
     }
+}
 
-    struct AnswersDaoMock {
-        create_answer_response: Mutex<Option<Result<AnswerDetail, DBError>>>,
-        delete_answer_response: Mutex<Option<Result<(), DBError>>>,
-        get_answers_response: Mutex<Option<Result<Vec<AnswerDetail>, DBError>>>,
-    }
+#[allow(clippy::too_many_arguments)]
+pub async fn resolve_flag(
+    flag_uuid: String,
+    resolution: FlagResolution,
+    admin: String,
+    request_id: Option<String>,
+    database: &(dyn Database + Send + Sync),
+    audit_dao: &(dyn AuditDao + Send + Sync),
+    flags_dao: &(dyn FlagsDao + Send + Sync),
+    questions_dao: &(dyn QuestionsDao + Send + Sync),
+    answers_dao: &(dyn AnswersDao + Send + Sync),
+    reputation_dao: &(dyn ReputationDao + Send + Sync),
+) -> Result<FlagDetail, HandlerError> {
+    // The flag is only closed if the post and its author's penalty are dealt with too
+    let mut uow = begin(database).await?;
+    let flag = flags_dao
+        .resolve_flag(uow.as_mut(), flag_uuid, resolution, admin.clone())
+        .await
+        .map_err(|err| {
+            error!("Failed to resolve flag: {:?}", err);
 
-    impl AnswersDaoMock {
-        pub fn new() -> Self {
-            AnswersDaoMock {
-                create_answer_response: Mutex::new(None),
-                delete_answer_response: Mutex::new(None),
-                get_answers_response: Mutex::new(None),
+            match err {
+                DBError::InvalidUUID(s) => BadRequest(s),
+                _ => InternalError(err.to_string()),
+            }
+        })?;
+
+    if resolution == FlagResolution::DeletePost {
+        let post_uuid = flag.post_uuid.clone();
+        let (action, before) = match flag.post_kind {
+            PostKind::Question => (
+                AuditAction::QuestionDeleted,
+                questions_dao
+                    .get_question(post_uuid.clone())
+                    .await
+                    .map(|question| snapshot(&question)),
+            ),
+            PostKind::Answer => (
+                AuditAction::AnswerDeleted,
+                answers_dao
+                    .get_answer(post_uuid.clone())
+                    .await
+                    .map(|answer| snapshot(&answer)),
+            ),
+        };
+        // A post that is already deleted has no state left to record
+        let before = match before {
+            Ok(before) => before,
+            Err(DBError::InvalidUUID(_)) => None,
+            Err(e) => {
+                error!("Failed to read flagged post: {:?}", e);
+                return Err(InternalError(e.to_string()));
+            }
+        };
+
+        let result = match flag.post_kind {
+            PostKind::Question => {
+                questions_dao
+                    .delete_question(uow.as_mut(), post_uuid.clone(), admin.clone())
+                    .await
             }
+            PostKind::Answer => {
+                let result = answers_dao
+                    .delete_answer(uow.as_mut(), post_uuid.clone(), admin.clone())
+                    .await;
+                match result {
+                    Ok(()) => {
+                        reputation_dao
+                            .record_answer_deleted_by_moderator(uow.as_mut(), post_uuid.clone())
+                            .await
+                    }
+                    Err(e) => Err(e),
+                }
+            }
+        };
+
+        if let Err(e) = result {
+            error!("Failed to delete flagged post: {:?}", e);
+            return Err(InternalError(e.to_string()));
         }
-        pub fn mock_create_answer(&mut self, response: Result<AnswerDetail, DBError>) {
-            self.create_answer_response = Mutex::new(Some(response));
+
+        let entry = AuditEntry {
+            actor: admin.clone(),
+            action,
+            resource_id: post_uuid,
+            before,
+            after: None,
+            request_id: request_id.clone(),
+        };
+        audit(uow.as_mut(), audit_dao, entry).await?;
+    }
+
+    let entry = AuditEntry {
+        actor: admin,
+        action: AuditAction::FlagResolved,
+        resource_id: flag.flag_uuid.clone(),
+        before: None,
+        after: snapshot(&flag),
+        request_id,
+    };
+    audit(uow.as_mut(), audit_dao, entry).await?;
+
+    commit(uow).await.map(|_| flag)
+}
+
+pub async fn read_user(
+    user_id: String,
+    reputation_dao: &(dyn ReputationDao + Send + Sync),
+) -> Result<UserProfile, HandlerError> {
+    let profile = reputation_dao.get_user_profile(user_id).await;
+
+    match profile {
+        Ok(profile) => Ok(profile),
+        Err(err) => {
+            error!("Failed to read user profile: {:?}", err);
+            Err(InternalError(err.to_string()))
         }
-        pub fn mock_delete_answer(&mut self, response: Result<(), DBError>) {
-            self.delete_answer_response = Mutex::new(Some(response));
+    }
+}
+
+/// Keeps the last component of the file name, some browsers send the full path
+fn attachment_file_name(file_name: &str) -> String {
+    let name = file_name
+        .rsplit(['/', '\\'])
+        .next()
+        .unwrap_or_default()
+        .trim();
+    if name.is_empty() {
+        "attachment".to_owned()
+    } else {
+        name.chars().take(255).collect()
+    }
+}
+
+/// Only the author of the question or users allowed to edit it can attach files
+#[allow(clippy::too_many_arguments)]
+pub async fn create_attachment(
+    question_uuid: QuestionId,
+    upload: Upload,
+    uploaded_by: String,
+    anonymous_posts_open: bool,
+    request_id: Option<String>,
+    database: &(dyn Database + Send + Sync),
+    audit_dao: &(dyn AuditDao + Send + Sync),
+    questions_dao: &(dyn QuestionsDao + Sync + Send),
+    attachments_dao: &(dyn AttachmentsDao + Send + Sync),
+    reputation_dao: &(dyn ReputationDao + Send + Sync),
+    blob_store: &(dyn BlobStore + Send + Sync),
+) -> Result<AttachmentDetail, HandlerError> {
+    if upload.content.is_empty() {
+        return Err(BadRequest("An attachment cannot be empty".to_owned()));
+    }
+    if upload.content.len() > MAX_ATTACHMENT_SIZE {
+        return Err(BadRequest(format!(
+            "Attachments are limited to {MAX_ATTACHMENT_SIZE} bytes"
+        )));
+    }
+    let content_type = upload
+        .content_type
+        .split(';')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_ascii_lowercase();
+    if !ALLOWED_CONTENT_TYPES.contains(&content_type.as_str()) {
+        return Err(BadRequest(format!(
+            "Unsupported attachment type: {content_type}"
+        )));
+    }
+
+    let question = questions_dao
+        .get_question(question_uuid.question_uuid)
+        .await
+        .map_err(|err| match err {
+            DBError::InvalidUUID(s) => BadRequest(s),
+            _ => InternalError(err.to_string()),
+        })?;
+
+    require_owner_or_privilege(
+        &uploaded_by,
+        &question.author,
+        anonymous_posts_open,
+        Privilege::EditOthersPosts,
+        reputation_dao,
+    )
+    .await?;
+
+    let sha256 = blob_store.put(&upload.content).await.map_err(|err| {
+        error!("Failed to store attachment: {:?}", err);
+        InternalError(err.to_string())
+    })?;
+
+    // Should this fail, the blob is left unreferenced until the sweeper deletes it
+    let mut uow = begin(database).await?;
+    let attachment = attachments_dao
+        .create_attachment(
+            uow.as_mut(),
+            Attachment {
+                question_uuid: question.question_uuid,
+                file_name: attachment_file_name(&upload.file_name),
+                content_type,
+                size: upload.content.len() as i64,
+                sha256,
+            },
+            uploaded_by.clone(),
+        )
+        .await;
+
+    match attachment {
+        Ok(attachment) => {
+            let entry = AuditEntry {
+                actor: uploaded_by,
+                action: AuditAction::AttachmentCreated,
+                resource_id: attachment.attachment_uuid.clone(),
+                before: None,
+                after: snapshot(&attachment),
+                request_id,
+            };
+            audit(uow.as_mut(), audit_dao, entry).await?;
+            commit(uow).await.map(|_| attachment)
         }
-        pub fn mock_get_answers(&mut self, response: Result<Vec<AnswerDetail>, DBError>) {
-            self.get_answers_response = Mutex::new(Some(response));
+        Err(err) => {
+            error!("Failed to create attachment: {:?}", err);
+
+            match err {
+                DBError::InvalidUUID(s) => Err(BadRequest(s)),
+                _ => Err(InternalError(err.to_string())),
+            }
         }
     }
+}
 
-    #[async_trait]
-    impl AnswersDao for AnswersDaoMock {
-        async fn create_answer(&self, _: Answer) -> Result<AnswerDetail, DBError> {
-            self.create_answer_response
-                .lock()
-                .await
-                .take()
-                .expect("create_answer_response should not be None.")
+pub async fn read_attachments(
+    question_uuid: QuestionId,
+    attachments_dao: &(dyn AttachmentsDao + Send + Sync),
+) -> Result<Vec<AttachmentDetail>, HandlerError> {
+    let attachments = attachments_dao
+        .get_attachments(question_uuid.question_uuid)
+        .await;
+
+    match attachments {
+        Ok(attachments) => Ok(attachments),
+        Err(err) => {
+            error!("Failed to read attachments: {:?}", err);
+
+            match err {
+                DBError::InvalidUUID(s) => Err(BadRequest(s)),
+                _ => Err(InternalError(err.to_string())),
+            }
         }
-        async fn delete_answer(&self, _: String) -> Result<(), DBError> {
-            self.delete_answer_response
-                .lock()
-                .await
-                .take()
-                .expect("delete_answer_response should not be None.")
+    }
+}
+
+/// Attachments are only found through the question they are attached to
+pub async fn read_attachment(
+    question_uuid: QuestionId,
+    attachment_uuid: String,
+    attachments_dao: &(dyn AttachmentsDao + Send + Sync),
+) -> Result<AttachmentDetail, HandlerError> {
+    let attachment = attachments_dao.get_attachment(attachment_uuid).await;
+
+    match attachment {
+        Ok(attachment) if attachment.question_uuid == question_uuid.question_uuid => Ok(attachment),
+        Ok(attachment) => Err(BadRequest(format!(
+            "Attachment {} is not attached to question {}",
+            attachment.attachment_uuid, question_uuid.question_uuid
+        ))),
+        Err(err) => {
+            error!("Failed to read attachment: {:?}", err);
+
+            match err {
+                DBError::InvalidUUID(s) => Err(BadRequest(s)),
+                _ => Err(InternalError(err.to_string())),
+            }
         }
-        async fn get_answers(&self, _: String) -> Result<Vec<AnswerDetail>, DBError> {
-            self.get_answers_response
-                .lock()
-                .await
-                .take()
-                .expect("get_answers_response should not be None.")
+    }
+}
+
+pub async fn read_attachment_content(
+    attachment: &AttachmentDetail,
+    range: Option<Range<u64>>,
+    blob_store: &(dyn BlobStore + Send + Sync),
+) -> Result<Vec<u8>, HandlerError> {
+    blob_store
+        .get(&attachment.sha256, range)
+        .await
+        .map_err(|err| {
+            error!("Failed to read attachment content: {:?}", err);
+            InternalError(err.to_string())
+        })
+}
+
+pub async fn follow_question(
+    question_uuid: QuestionId,
+    user_id: String,
+    request_id: Option<String>,
+    database: &(dyn Database + Send + Sync),
+    audit_dao: &(dyn AuditDao + Send + Sync),
+    follows_dao: &(dyn FollowsDao + Send + Sync),
+) -> Result<(), HandlerError> {
+    let mut uow = begin(database).await?;
+    let result = follows_dao
+        .follow_question(
+            uow.as_mut(),
+            user_id.clone(),
+            question_uuid.question_uuid.clone(),
+        )
+        .await;
+
+    match result {
+        Ok(()) => {
+            let entry = AuditEntry {
+                actor: user_id,
+                action: AuditAction::QuestionFollowed,
+                resource_id: question_uuid.question_uuid,
+                before: None,
+                after: None,
+                request_id,
+            };
+            audit(uow.as_mut(), audit_dao, entry).await?;
+            commit(uow).await
+        }
+        Err(err) => {
+            error!("Failed to follow question: {:?}", err);
+
+            match err {
+                DBError::InvalidUUID(s) => Err(BadRequest(s)),
+                _ => Err(InternalError(err.to_string())),
+            }
         }
     }
+}
 
-    #[tokio::test]
-    async fn create_question_should_return_question() {
-        let question = Question {
-            title: "test title".to_owned(),
-            description: "test description".to_owned(),
-        };
+pub async fn unfollow_question(
+    question_uuid: QuestionId,
+    user_id: String,
+    request_id: Option<String>,
+    database: &(dyn Database + Send + Sync),
+    audit_dao: &(dyn AuditDao + Send + Sync),
+    follows_dao: &(dyn FollowsDao + Send + Sync),
+) -> Result<(), HandlerError> {
+    let mut uow = begin(database).await?;
+    let result = follows_dao
+        .unfollow_question(
+            uow.as_mut(),
+            user_id.clone(),
+            question_uuid.question_uuid.clone(),
+        )
+        .await;
 
-        let question_detail = QuestionDetail {
-            question_uuid: "123".to_owned(),
-            title: question.title.clone(),
-            description: question.description.clone(),
-            created_at: "now".to_owned(),
-        };
+    match result {
+        Ok(()) => {
+            let entry = AuditEntry {
+                actor: user_id,
+                action: AuditAction::QuestionUnfollowed,
+                resource_id: question_uuid.question_uuid,
+                before: None,
+                after: None,
+                request_id,
+            };
+            audit(uow.as_mut(), audit_dao, entry).await?;
+            commit(uow).await
+        }
+        Err(err) => {
+            error!("Failed to unfollow question: {:?}", err);
+
+            match err {
+                DBError::InvalidUUID(s) => Err(BadRequest(s)),
+                _ => Err(InternalError(err.to_string())),
+            }
+        }
+    }
+}
+
+pub async fn bookmark_question(
+    question_uuid: QuestionId,
+    user_id: String,
+    request_id: Option<String>,
+    database: &(dyn Database + Send + Sync),
+    audit_dao: &(dyn AuditDao + Send + Sync),
+    follows_dao: &(dyn FollowsDao + Send + Sync),
+) -> Result<(), HandlerError> {
+    let mut uow = begin(database).await?;
+    let result = follows_dao
+        .bookmark_question(
+            uow.as_mut(),
+            user_id.clone(),
+            question_uuid.question_uuid.clone(),
+        )
+        .await;
+
+    match result {
+        Ok(()) => {
+            let entry = AuditEntry {
+                actor: user_id,
+                action: AuditAction::QuestionBookmarked,
+                resource_id: question_uuid.question_uuid,
+                before: None,
+                after: None,
+                request_id,
+            };
+            audit(uow.as_mut(), audit_dao, entry).await?;
+            commit(uow).await
+        }
+        Err(err) => {
+            error!("Failed to bookmark question: {:?}", err);
+
+            match err {
+                DBError::InvalidUUID(s) => Err(BadRequest(s)),
+                _ => Err(InternalError(err.to_string())),
+            }
+        }
+    }
+}
+
+pub async fn remove_bookmark(
+    question_uuid: QuestionId,
+    user_id: String,
+    request_id: Option<String>,
+    database: &(dyn Database + Send + Sync),
+    audit_dao: &(dyn AuditDao + Send + Sync),
+    follows_dao: &(dyn FollowsDao + Send + Sync),
+) -> Result<(), HandlerError> {
+    let mut uow = begin(database).await?;
+    let result = follows_dao
+        .remove_bookmark(
+            uow.as_mut(),
+            user_id.clone(),
+            question_uuid.question_uuid.clone(),
+        )
+        .await;
+
+    match result {
+        Ok(()) => {
+            let entry = AuditEntry {
+                actor: user_id,
+                action: AuditAction::QuestionBookmarkRemoved,
+                resource_id: question_uuid.question_uuid,
+                before: None,
+                after: None,
+                request_id,
+            };
+            audit(uow.as_mut(), audit_dao, entry).await?;
+            commit(uow).await
+        }
+        Err(err) => {
+            error!("Failed to remove bookmark: {:?}", err);
+
+            match err {
+                DBError::InvalidUUID(s) => Err(BadRequest(s)),
+                _ => Err(InternalError(err.to_string())),
+            }
+        }
+    }
+}
+
+pub async fn read_bookmarks(
+    user_id: String,
+    follows_dao: &(dyn FollowsDao + Send + Sync),
+) -> Result<Vec<QuestionDetail>, HandlerError> {
+    let bookmarks = follows_dao.get_bookmarks(user_id).await;
+
+    match bookmarks {
+        Ok(bookmarks) => Ok(bookmarks),
+        Err(err) => {
+            error!("Failed to read bookmarks: {:?}", err);
+            Err(InternalError(err.to_string()))
+        }
+    }
+}
+
+pub async fn read_inbox(
+    user_id: String,
+    follows_dao: &(dyn FollowsDao + Send + Sync),
+) -> Result<Vec<InboxItem>, HandlerError> {
+    let inbox = follows_dao.get_inbox(user_id).await;
+
+    match inbox {
+        Ok(inbox) => Ok(inbox),
+        Err(err) => {
+            error!("Failed to read inbox: {:?}", err);
+            Err(InternalError(err.to_string()))
+        }
+    }
+}
+
+pub async fn mark_inbox_read(
+    mark_read: MarkRead,
+    user_id: String,
+    request_id: Option<String>,
+    database: &(dyn Database + Send + Sync),
+    audit_dao: &(dyn AuditDao + Send + Sync),
+    follows_dao: &(dyn FollowsDao + Send + Sync),
+) -> Result<(), HandlerError> {
+    let mut uow = begin(database).await?;
+    let result = follows_dao
+        .mark_inbox_read(uow.as_mut(), user_id.clone(), mark_read.item_uuids.clone())
+        .await;
+
+    match result {
+        // Nothing changed, so there is nothing to record
+        Ok(0) => Ok(()),
+        Ok(_) => {
+            let entry = AuditEntry {
+                actor: user_id.clone(),
+                action: AuditAction::InboxRead,
+                resource_id: user_id,
+                before: None,
+                after: snapshot(&mark_read),
+                request_id,
+            };
+            audit(uow.as_mut(), audit_dao, entry).await?;
+            commit(uow).await
+        }
+        Err(err) => {
+            error!("Failed to mark inbox items as read: {:?}", err);
+
+            match err {
+                DBError::InvalidUUID(s) => Err(BadRequest(s)),
+                _ => Err(InternalError(err.to_string())),
+            }
+        }
+    }
+}
+
+/// Audit snapshot of a webhook. The secret is only ever given to the creator of the webhook.
+fn webhook_snapshot(webhook: &WebhookDetail) -> Option<serde_json::Value> {
+    let mut snapshot = snapshot(webhook);
+    if let Some(serde_json::Value::Object(fields)) = snapshot.as_mut() {
+        fields.remove("secret");
+    }
+    snapshot
+}
+
+/// Subscribes to events of the space. Unless `allow_private_targets`, the url may not target
+/// loopback, link-local or private addresses, so that webhooks cannot reach internal hosts.
+pub async fn create_webhook(
+    webhook: Webhook,
+    allow_private_targets: bool,
+    created_by: String,
+    request_id: Option<String>,
+    database: &(dyn Database + Send + Sync),
+    audit_dao: &(dyn AuditDao + Send + Sync),
+    webhooks_dao: &(dyn WebhooksDao + Send + Sync),
+) -> Result<WebhookDetail, HandlerError> {
+    webhooks::parse_url(&webhook.url, allow_private_targets).map_err(BadRequest)?;
+    if webhook.events.is_empty() {
+        return Err(BadRequest(
+            "A webhook must subscribe to at least one event".to_owned(),
+        ));
+    }
+
+    let mut uow = begin(database).await?;
+    let webhook = webhooks_dao.create_webhook(uow.as_mut(), webhook).await;
+
+    match webhook {
+        Ok(webhook) => {
+            let entry = AuditEntry {
+                actor: created_by,
+                action: AuditAction::WebhookCreated,
+                resource_id: webhook.webhook_uuid.clone(),
+                before: None,
+                after: webhook_snapshot(&webhook),
+                request_id,
+            };
+            audit(uow.as_mut(), audit_dao, entry).await?;
+            commit(uow).await.map(|_| webhook)
+        }
+        Err(err) => {
+            error!("Failed to create webhook: {:?}", err);
+            Err(InternalError(err.to_string()))
+        }
+    }
+}
+
+pub async fn delete_webhook(
+    webhook_uuid: String,
+    admin: String,
+    request_id: Option<String>,
+    database: &(dyn Database + Send + Sync),
+    audit_dao: &(dyn AuditDao + Send + Sync),
+    webhooks_dao: &(dyn WebhooksDao + Send + Sync),
+) -> Result<(), HandlerError> {
+    let mut uow = begin(database).await?;
+    let webhook = webhooks_dao
+        .delete_webhook(uow.as_mut(), webhook_uuid)
+        .await;
+
+    match webhook {
+        Ok(Some(webhook)) => {
+            let entry = AuditEntry {
+                actor: admin,
+                action: AuditAction::WebhookDeleted,
+                resource_id: webhook.webhook_uuid.clone(),
+                before: webhook_snapshot(&webhook),
+                after: None,
+                request_id,
+            };
+            audit(uow.as_mut(), audit_dao, entry).await?;
+            commit(uow).await
+        }
+        // Nothing changed, so there is nothing to record
+        Ok(None) => Ok(()),
+        Err(err) => {
+            error!("Failed to delete webhook: {:?}", err);
+
+            match err {
+                DBError::InvalidUUID(s) => Err(BadRequest(s)),
+                _ => Err(InternalError(err.to_string())),
+            }
+        }
+    }
+}
+
+/// Most records returned by one read of the audit log
+pub const AUDIT_LOG_LIMIT: i64 = 500;
+
+pub async fn read_audit_log(
+    query: AuditQuery,
+    audit_dao: &(dyn AuditDao + Send + Sync),
+) -> Result<Vec<AuditRecord>, HandlerError> {
+    let records = audit_dao.get_audit_log(query, AUDIT_LOG_LIMIT).await;
+
+    match records {
+        Ok(records) => Ok(records),
+        Err(err) => {
+            error!("Failed to read audit log: {:?}", err);
+
+            match err {
+                DBError::InvalidUUID(s) => Err(BadRequest(s)),
+                _ => Err(InternalError(err.to_string())),
+            }
+        }
+    }
+}
+
+/// Days of posts returned by default, and at most, by a read of the statistics
+pub const STATS_DEFAULT_DAYS: i64 = 30;
+pub const STATS_MAX_DAYS: i64 = 366;
+
+pub async fn read_stats(
+    query: StatsQuery,
+    stats_dao: &(dyn StatsDao + Send + Sync),
+) -> Result<Stats, HandlerError> {
+    let days = query.days.unwrap_or(STATS_DEFAULT_DAYS);
+    if !(1..=STATS_MAX_DAYS).contains(&days) {
+        return Err(BadRequest(format!(
+            "The window must be between 1 and {STATS_MAX_DAYS} days, got {days}"
+        )));
+    }
+
+    let stats = stats_dao.get_stats(days).await;
+
+    match stats {
+        Ok(stats) => Ok(stats),
+        Err(err) => {
+            error!("Failed to read statistics: {:?}", err);
+            Err(InternalError(err.to_string()))
+        }
+    }
+}
+
+/// Most recent runs of the maintenance jobs returned by a read
+pub const JOB_RUNS_LIMIT: i64 = 100;
+
+pub async fn read_job_runs(
+    jobs_dao: &(dyn JobsDao + Send + Sync),
+) -> Result<Vec<JobRun>, HandlerError> {
+    let runs = jobs_dao.get_job_runs(JOB_RUNS_LIMIT).await;
+
+    match runs {
+        Ok(runs) => Ok(runs),
+        Err(err) => {
+            error!("Failed to read job runs: {:?}", err);
+            Err(InternalError(err.to_string()))
+        }
+    }
+}
+
+/// Longest slug of a space, slugs are made of lowercase letters, digits and dashes
+pub const MAX_SPACE_SLUG_LEN: usize = 64;
+
+fn is_valid_slug(slug: &str) -> bool {
+    !slug.is_empty()
+        && slug.len() <= MAX_SPACE_SLUG_LEN
+        && !slug.starts_with('-')
+        && slug
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+}
+
+pub async fn create_space(
+    space: Space,
+    admin: String,
+    request_id: Option<String>,
+    database: &(dyn Database + Send + Sync),
+    audit_dao: &(dyn AuditDao + Send + Sync),
+    spaces_dao: &(dyn SpacesDao + Send + Sync),
+) -> Result<SpaceDetail, HandlerError> {
+    if !is_valid_slug(&space.slug) {
+        return Err(BadRequest(format!(
+            "Invalid space slug: {}, up to {MAX_SPACE_SLUG_LEN} lowercase letters, digits and dashes are allowed",
+            space.slug
+        )));
+    }
+    if space.name.trim().is_empty() {
+        return Err(BadRequest("A space must have a name".to_owned()));
+    }
+
+    let mut uow = begin(database).await?;
+    let space = spaces_dao.create_space(uow.as_mut(), space).await;
+
+    match space {
+        Ok(space) => {
+            let entry = AuditEntry {
+                actor: admin,
+                action: AuditAction::SpaceCreated,
+                resource_id: space.space_id.clone(),
+                before: None,
+                after: snapshot(&space),
+                request_id,
+            };
+            audit(uow.as_mut(), audit_dao, entry).await?;
+            commit(uow).await.map(|_| space)
+        }
+        Err(err) => {
+            error!("Failed to create space: {:?}", err);
+
+            match err {
+                DBError::InvalidUUID(s) => Err(BadRequest(s)),
+                _ => Err(InternalError(err.to_string())),
+            }
+        }
+    }
+}
+
+async fn read_space(
+    slug: &str,
+    spaces_dao: &(dyn SpacesDao + Send + Sync),
+) -> Result<Option<SpaceDetail>, HandlerError> {
+    match spaces_dao.get_space(slug.to_owned()).await {
+        Ok(space) => Ok(Some(space)),
+        Err(DBError::InvalidUUID(_)) => Ok(None),
+        Err(err) => {
+            error!("Failed to read space: {:?}", err);
+            Err(InternalError(err.to_string()))
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub async fn set_space_member(
+    slug: String,
+    user_id: String,
+    member: SpaceMember,
+    admin: String,
+    request_id: Option<String>,
+    database: &(dyn Database + Send + Sync),
+    audit_dao: &(dyn AuditDao + Send + Sync),
+    spaces_dao: &(dyn SpacesDao + Send + Sync),
+) -> Result<SpaceMemberDetail, HandlerError> {
+    if user_id.is_empty() || user_id == ANONYMOUS {
+        return Err(BadRequest(format!(
+            "{user_id} cannot be a member of a space"
+        )));
+    }
+    let space = read_space(&slug, spaces_dao)
+        .await?
+        .ok_or_else(|| BadRequest(format!("No such space: {slug}")))?;
+
+    let previous = spaces_dao
+        .get_role(space.space_id.clone(), user_id.clone())
+        .await
+        .map_err(|e| {
+            error!("Failed to read space role: {:?}", e);
+            InternalError(e.to_string())
+        })?;
+
+    let mut uow = begin(database).await?;
+    let member = spaces_dao
+        .set_member(uow.as_mut(), space.space_id, user_id, member.role)
+        .await;
+
+    match member {
+        Ok(member) => {
+            let entry = AuditEntry {
+                actor: admin,
+                action: AuditAction::SpaceMemberSet,
+                resource_id: format!("{}/{}", member.space_id, member.user_id),
+                before: previous.and_then(|role| snapshot(&SpaceMember { role })),
+                after: snapshot(&member),
+                request_id,
+            };
+            audit(uow.as_mut(), audit_dao, entry).await?;
+            commit(uow).await.map(|_| member)
+        }
+        Err(err) => {
+            error!("Failed to set space member: {:?}", err);
+
+            match err {
+                DBError::InvalidUUID(s) => Err(BadRequest(s)),
+                _ => Err(InternalError(err.to_string())),
+            }
+        }
+    }
+}
+
+pub async fn remove_space_member(
+    slug: String,
+    user_id: String,
+    admin: String,
+    request_id: Option<String>,
+    database: &(dyn Database + Send + Sync),
+    audit_dao: &(dyn AuditDao + Send + Sync),
+    spaces_dao: &(dyn SpacesDao + Send + Sync),
+) -> Result<(), HandlerError> {
+    let space = read_space(&slug, spaces_dao)
+        .await?
+        .ok_or_else(|| BadRequest(format!("No such space: {slug}")))?;
+
+    let mut uow = begin(database).await?;
+    let member = spaces_dao
+        .remove_member(uow.as_mut(), space.space_id, user_id)
+        .await;
+
+    match member {
+        Ok(Some(member)) => {
+            let entry = AuditEntry {
+                actor: admin,
+                action: AuditAction::SpaceMemberRemoved,
+                resource_id: format!("{}/{}", member.space_id, member.user_id),
+                before: snapshot(&member),
+                after: None,
+                request_id,
+            };
+            audit(uow.as_mut(), audit_dao, entry).await?;
+            commit(uow).await
+        }
+        // Nothing changed, so there is nothing to record
+        Ok(None) => Ok(()),
+        Err(err) => {
+            error!("Failed to remove space member: {:?}", err);
+
+            match err {
+                DBError::InvalidUUID(s) => Err(BadRequest(s)),
+                _ => Err(InternalError(err.to_string())),
+            }
+        }
+    }
+}
+
+/// Lets a caller into a space if they may act in it with `access`. Open spaces let anyone in
+/// and the admin token opens every space. Unknown spaces are refused like private ones,
+/// so that callers cannot tell whether a space exists.
+pub async fn enter_space(
+    slug: String,
+    user_id: Option<String>,
+    admin: bool,
+    access: SpaceRole,
+    spaces_dao: &(dyn SpacesDao + Send + Sync),
+) -> Result<SpaceDetail, HandlerError> {
+    let space = read_space(&slug, spaces_dao).await?;
+    if let Some(space) = space.as_ref().filter(|space| space.open || admin) {
+        return Ok(space.clone());
+    }
+
+    let Some(user_id) = user_id.filter(|user_id| user_id != ANONYMOUS) else {
+        return Err(Unauthorized(format!(
+            "The caller must identify to enter space {slug}"
+        )));
+    };
+    let Some(space) = space else {
+        return Err(Forbidden(format!(
+            "{user_id} is not a member of space {slug}"
+        )));
+    };
+
+    let role = spaces_dao
+        .get_role(space.space_id.clone(), user_id.clone())
+        .await
+        .map_err(|e| {
+            error!("Failed to read space role: {:?}", e);
+            InternalError(e.to_string())
+        })?;
+
+    match (role, access) {
+        (Some(SpaceRole::Writer), _) | (Some(SpaceRole::Reader), SpaceRole::Reader) => Ok(space),
+        (Some(SpaceRole::Reader), SpaceRole::Writer) => {
+            Err(Forbidden(format!("{user_id} can only read space {slug}")))
+        }
+        (None, _) => Err(Forbidden(format!(
+            "{user_id} is not a member of space {slug}"
+        ))),
+    }
+}
+
+// ***********************************************************
+//                           Tests
+// ***********************************************************
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{
+        models::{BlobError, WebhookDelivery, WebhookEvent},
+        persistance::blob_store::StoredBlob,
+    };
+    use async_trait::async_trait;
+    use sqlx::{types::time::PrimitiveDateTime, PgConnection};
+    use std::{
+        collections::HashMap,
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+        time::{Duration, SystemTime},
+    };
+    use tokio::sync::Mutex;
+
+    /// Counts the units of work committed by handlers
+    struct DatabaseMock {
+        commits: Arc<AtomicUsize>,
+    }
+
+    impl DatabaseMock {
+        pub fn new() -> Self {
+            DatabaseMock {
+                commits: Arc::new(AtomicUsize::new(0)),
+            }
+        }
+        pub fn commits(&self) -> usize {
+            self.commits.load(Ordering::SeqCst)
+        }
+    }
+
+    #[async_trait]
+    impl Database for DatabaseMock {
+        async fn begin(&self) -> Result<Box<dyn UnitOfWork>, DBError> {
+            Ok(Box::new(UnitOfWorkMock {
+                commits: self.commits.clone(),
+            }))
+        }
+    }
+
+    /// Mocked DAOs ignore the unit they are given, so it never needs a connection
+    struct UnitOfWorkMock {
+        commits: Arc<AtomicUsize>,
+    }
+
+    #[async_trait]
+    impl UnitOfWork for UnitOfWorkMock {
+        async fn connection(&mut self) -> Result<&mut PgConnection, DBError> {
+            Err(DBError::Other(
+                "Mocked units of work have no connection".into(),
+            ))
+        }
+        async fn commit(self: Box<Self>) -> Result<(), DBError> {
+            self.commits.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        }
+        fn after_commit(&mut self, _: Box<dyn FnOnce() + Send>) {}
+    }
+
+    /// Keeps the recorded entries, in the order they were recorded
+    struct AuditDaoMock {
+        entries: Mutex<Vec<AuditEntry>>,
+        get_audit_log_response: Mutex<Option<Result<Vec<AuditRecord>, DBError>>>,
+    }
+
+    impl AuditDaoMock {
+        pub fn new() -> Self {
+            AuditDaoMock {
+                entries: Mutex::new(Vec::new()),
+                get_audit_log_response: Mutex::new(None),
+            }
+        }
+        pub fn mock_get_audit_log(&mut self, response: Result<Vec<AuditRecord>, DBError>) {
+            self.get_audit_log_response = Mutex::new(Some(response));
+        }
+        pub async fn actions(&self) -> Vec<AuditAction> {
+            self.entries
+                .lock()
+                .await
+                .iter()
+                .map(|entry| entry.action)
+                .collect()
+        }
+    }
+
+    #[async_trait]
+    impl AuditDao for AuditDaoMock {
+        async fn record(&self, _: &mut dyn UnitOfWork, entry: AuditEntry) -> Result<(), DBError> {
+            self.entries.lock().await.push(entry);
+            Ok(())
+        }
+        async fn get_audit_log(&self, _: AuditQuery, _: i64) -> Result<Vec<AuditRecord>, DBError> {
+            self.get_audit_log_response
+                .lock()
+                .await
+                .take()
+                .expect("get_audit_log_response should not be None.")
+        }
+    }
+
+    struct QuestionsDaoMock {
+        create_question_response: Mutex<Option<Result<QuestionDetail, DBError>>>,
+        get_question_response: Mutex<Option<Result<QuestionDetail, DBError>>>,
+        lock_question_response: Mutex<Option<Result<QuestionDetail, DBError>>>,
+        update_question_response: Mutex<Option<Result<QuestionDetail, DBError>>>,
+        delete_question_response: Mutex<Option<Result<(), DBError>>>,
+        restore_question_response: Mutex<Option<Result<QuestionDetail, DBError>>>,
+        get_questions_response: Mutex<Option<Result<Vec<QuestionDetail>, DBError>>>,
+        get_questions_page_response: Mutex<Option<Result<Vec<QuestionDetail>, DBError>>>,
+        get_questions_needing_attention_response:
+            Mutex<Option<Result<Vec<QuestionDetail>, DBError>>>,
+        get_related_questions_response: Mutex<Option<Result<Vec<SimilarQuestion>, DBError>>>,
+        get_similar_questions_response: Mutex<Option<Result<Vec<SimilarQuestion>, DBError>>>,
+        close_as_duplicate_response: Mutex<Option<Result<QuestionDetail, DBError>>>,
+        get_trending_questions_response: Mutex<Option<Result<Vec<QuestionDetail>, DBError>>>,
+        get_questions_by_uuids_response: Mutex<Option<Result<Vec<QuestionDetail>, DBError>>>,
+        add_views_response: Mutex<Option<Result<(), DBError>>>,
+    }
+
+    impl QuestionsDaoMock {
+        pub fn new() -> Self {
+            QuestionsDaoMock {
+                create_question_response: Mutex::new(None),
+                get_question_response: Mutex::new(None),
+                lock_question_response: Mutex::new(None),
+                update_question_response: Mutex::new(None),
+                delete_question_response: Mutex::new(None),
+                restore_question_response: Mutex::new(None),
+                get_questions_response: Mutex::new(None),
+                get_questions_page_response: Mutex::new(None),
+                get_questions_needing_attention_response: Mutex::new(None),
+                get_related_questions_response: Mutex::new(None),
+                get_similar_questions_response: Mutex::new(None),
+                close_as_duplicate_response: Mutex::new(None),
+                get_trending_questions_response: Mutex::new(None),
+                get_questions_by_uuids_response: Mutex::new(None),
+                add_views_response: Mutex::new(None),
+            }
+        }
+        pub fn mock_create_question(&mut self, response: Result<QuestionDetail, DBError>) {
+            self.create_question_response = Mutex::new(Some(response));
+        }
+        pub fn mock_get_question(&mut self, response: Result<QuestionDetail, DBError>) {
+            self.get_question_response = Mutex::new(Some(response));
+        }
+        pub fn mock_lock_question(&mut self, response: Result<QuestionDetail, DBError>) {
+            self.lock_question_response = Mutex::new(Some(response));
+        }
+        pub fn mock_update_question(&mut self, response: Result<QuestionDetail, DBError>) {
+            self.update_question_response = Mutex::new(Some(response));
+        }
+        pub fn mock_delete_question(&mut self, response: Result<(), DBError>) {
+            self.delete_question_response = Mutex::new(Some(response));
+        }
+        pub fn mock_restore_question(&mut self, response: Result<QuestionDetail, DBError>) {
+            self.restore_question_response = Mutex::new(Some(response));
+        }
+        pub fn mock_get_questions(&mut self, response: Result<Vec<QuestionDetail>, DBError>) {
+            self.get_questions_response = Mutex::new(Some(response));
+        }
+        pub fn mock_get_questions_page(&mut self, response: Result<Vec<QuestionDetail>, DBError>) {
+            self.get_questions_page_response = Mutex::new(Some(response));
+        }
+        pub fn mock_get_questions_needing_attention(
+            &mut self,
+            response: Result<Vec<QuestionDetail>, DBError>,
+        ) {
+            self.get_questions_needing_attention_response = Mutex::new(Some(response));
+        }
+        pub fn mock_get_related_questions(
+            &mut self,
+            response: Result<Vec<SimilarQuestion>, DBError>,
+        ) {
+            self.get_related_questions_response = Mutex::new(Some(response));
+        }
+        pub fn mock_get_similar_questions(
+            &mut self,
+            response: Result<Vec<SimilarQuestion>, DBError>,
+        ) {
+            self.get_similar_questions_response = Mutex::new(Some(response));
+        }
+        pub fn mock_close_as_duplicate(&mut self, response: Result<QuestionDetail, DBError>) {
+            self.close_as_duplicate_response = Mutex::new(Some(response));
+        }
+        pub fn mock_get_trending_questions(
+            &mut self,
+            response: Result<Vec<QuestionDetail>, DBError>,
+        ) {
+            self.get_trending_questions_response = Mutex::new(Some(response));
+        }
+    }
+
+    #[async_trait]
+    impl QuestionsDao for QuestionsDaoMock {
+        async fn create_question(
+            &self,
+            _: &mut dyn UnitOfWork,
+            _: Question,
+            _: String,
+        ) -> Result<QuestionDetail, DBError> {
+            self.create_question_response
+                .lock()
+                .await
+                .take()
+                .expect("create_question_response should not be None.")
+        }
+        async fn get_question(&self, _: String) -> Result<QuestionDetail, DBError> {
+            self.get_question_response
+                .lock()
+                .await
+                .take()
+                .expect("get_question_response should not be None.")
+        }
+        async fn lock_question(
+            &self,
+            _: &mut dyn UnitOfWork,
+            _: String,
+        ) -> Result<QuestionDetail, DBError> {
+            self.lock_question_response
+                .lock()
+                .await
+                .take()
+                .expect("lock_question_response should not be None.")
+        }
+        async fn update_question(
+            &self,
+            _: &mut dyn UnitOfWork,
+            _: String,
+            _: Question,
+        ) -> Result<QuestionDetail, DBError> {
+            self.update_question_response
+                .lock()
+                .await
+                .take()
+                .expect("update_question_response should not be None.")
+        }
+        async fn delete_question(
+            &self,
+            _: &mut dyn UnitOfWork,
+            _: String,
+            _: String,
+        ) -> Result<(), DBError> {
+            self.delete_question_response
+                .lock()
+                .await
+                .take()
+                .expect("delete_question_response should not be None.")
+        }
+        async fn restore_question(
+            &self,
+            _: &mut dyn UnitOfWork,
+            _: String,
+        ) -> Result<QuestionDetail, DBError> {
+            self.restore_question_response
+                .lock()
+                .await
+                .take()
+                .expect("restore_question_response should not be None.")
+        }
+        async fn get_questions(&self) -> Result<Vec<QuestionDetail>, DBError> {
+            self.get_questions_response
+                .lock()
+                .await
+                .take()
+                .expect("get_questions_response should not be None.")
+        }
+        async fn get_questions_page(&self, _: i64, _: i64) -> Result<Vec<QuestionDetail>, DBError> {
+            self.get_questions_page_response
+                .lock()
+                .await
+                .take()
+                .expect("get_questions_page_response should not be None.")
+        }
+        async fn get_questions_by_uuids(
+            &self,
+            _: Vec<String>,
+        ) -> Result<Vec<QuestionDetail>, DBError> {
+            self.get_questions_by_uuids_response
+                .lock()
+                .await
+                .take()
+                .expect("get_questions_by_uuids_response should not be None.")
+        }
+        async fn get_questions_needing_attention(
+            &self,
+            _: i64,
+            _: i64,
+        ) -> Result<Vec<QuestionDetail>, DBError> {
+            self.get_questions_needing_attention_response
+                .lock()
+                .await
+                .take()
+                .expect("get_questions_needing_attention_response should not be None.")
+        }
+        async fn get_related_questions(
+            &self,
+            _: String,
+            _: i64,
+        ) -> Result<Vec<SimilarQuestion>, DBError> {
+            self.get_related_questions_response
+                .lock()
+                .await
+                .take()
+                .expect("get_related_questions_response should not be None.")
+        }
+        async fn get_similar_questions(
+            &self,
+            _: Question,
+            _: i64,
+        ) -> Result<Vec<SimilarQuestion>, DBError> {
+            self.get_similar_questions_response
+                .lock()
+                .await
+                .take()
+                .expect("get_similar_questions_response should not be None.")
+        }
+        async fn close_as_duplicate(
+            &self,
+            _: &mut dyn UnitOfWork,
+            _: String,
+            _: String,
+            _: String,
+        ) -> Result<QuestionDetail, DBError> {
+            self.close_as_duplicate_response
+                .lock()
+                .await
+                .take()
+                .expect("close_as_duplicate_response should not be None.")
+        }
+        async fn add_views(&self, _: HashMap<String, i64>) -> Result<(), DBError> {
+            self.add_views_response
+                .lock()
+                .await
+                .take()
+                .expect("add_views_response should not be None.")
+        }
+        async fn get_trending_questions(&self, _: i64) -> Result<Vec<QuestionDetail>, DBError> {
+            self.get_trending_questions_response
+                .lock()
+                .await
+                .take()
+                .expect("get_trending_questions_response should not be None.")
+        }
+    }
+
+    struct AnswersDaoMock {
+        create_answer_response: Mutex<Option<Result<AnswerDetail, DBError>>>,
+        get_answer_response: Mutex<Option<Result<AnswerDetail, DBError>>>,
+        lock_answer_response: Mutex<Option<Result<AnswerDetail, DBError>>>,
+        update_answer_response: Mutex<Option<Result<AnswerDetail, DBError>>>,
+        delete_answer_response: Mutex<Option<Result<(), DBError>>>,
+        get_answers_response: Mutex<Option<Result<Vec<AnswerDetail>, DBError>>>,
+        get_answers_of_questions_response: Mutex<Option<Result<Vec<AnswerDetail>, DBError>>>,
+    }
+
+    impl AnswersDaoMock {
+        pub fn new() -> Self {
+            AnswersDaoMock {
+                create_answer_response: Mutex::new(None),
+                get_answer_response: Mutex::new(None),
+                lock_answer_response: Mutex::new(None),
+                update_answer_response: Mutex::new(None),
+                delete_answer_response: Mutex::new(None),
+                get_answers_response: Mutex::new(None),
+                get_answers_of_questions_response: Mutex::new(None),
+            }
+        }
+        pub fn mock_create_answer(&mut self, response: Result<AnswerDetail, DBError>) {
+            self.create_answer_response = Mutex::new(Some(response));
+        }
+        pub fn mock_get_answer(&mut self, response: Result<AnswerDetail, DBError>) {
+            self.get_answer_response = Mutex::new(Some(response));
+        }
+        pub fn mock_lock_answer(&mut self, response: Result<AnswerDetail, DBError>) {
+            self.lock_answer_response = Mutex::new(Some(response));
+        }
+        pub fn mock_update_answer(&mut self, response: Result<AnswerDetail, DBError>) {
+            self.update_answer_response = Mutex::new(Some(response));
+        }
+        pub fn mock_delete_answer(&mut self, response: Result<(), DBError>) {
+            self.delete_answer_response = Mutex::new(Some(response));
+        }
+        pub fn mock_get_answers(&mut self, response: Result<Vec<AnswerDetail>, DBError>) {
+            self.get_answers_response = Mutex::new(Some(response));
+        }
+    }
+
+    #[async_trait]
+    impl AnswersDao for AnswersDaoMock {
+        async fn create_answer(
+            &self,
+            _: &mut dyn UnitOfWork,
+            _: Answer,
+            _: String,
+        ) -> Result<AnswerDetail, DBError> {
+            self.create_answer_response
+                .lock()
+                .await
+                .take()
+                .expect("create_answer_response should not be None.")
+        }
+        async fn get_answer(&self, _: String) -> Result<AnswerDetail, DBError> {
+            self.get_answer_response
+                .lock()
+                .await
+                .take()
+                .expect("get_answer_response should not be None.")
+        }
+        async fn lock_answer(
+            &self,
+            _: &mut dyn UnitOfWork,
+            _: String,
+        ) -> Result<AnswerDetail, DBError> {
+            self.lock_answer_response
+                .lock()
+                .await
+                .take()
+                .expect("lock_answer_response should not be None.")
+        }
+        async fn update_answer(
+            &self,
+            _: &mut dyn UnitOfWork,
+            _: String,
+            _: String,
+        ) -> Result<AnswerDetail, DBError> {
+            self.update_answer_response
+                .lock()
+                .await
+                .take()
+                .expect("update_answer_response should not be None.")
+        }
+        async fn delete_answer(
+            &self,
+            _: &mut dyn UnitOfWork,
+            _: String,
+            _: String,
+        ) -> Result<(), DBError> {
+            self.delete_answer_response
+                .lock()
+                .await
+                .take()
+                .expect("delete_answer_response should not be None.")
+        }
+        async fn get_answers(&self, _: String) -> Result<Vec<AnswerDetail>, DBError> {
+            self.get_answers_response
+                .lock()
+                .await
+                .take()
+                .expect("get_answers_response should not be None.")
+        }
+        async fn get_answers_of_questions(
+            &self,
+            _: Vec<String>,
+        ) -> Result<Vec<AnswerDetail>, DBError> {
+            self.get_answers_of_questions_response
+                .lock()
+                .await
+                .take()
+                .expect("get_answers_of_questions_response should not be None.")
+        }
+    }
+
+    /// Keeps follows, bookmarks and inbox items in memory, like the database would
+    struct FollowsDaoMock {
+        questions: Vec<String>,
+        follows: Mutex<Vec<(String, String)>>,
+        bookmarks: Mutex<Vec<(String, String)>>,
+        inbox: Mutex<Vec<(String, InboxItem, bool)>>,
+    }
+
+    impl FollowsDaoMock {
+        /// Only the given questions can be followed or bookmarked
+        pub fn new(questions: &[&str]) -> Self {
+            FollowsDaoMock {
+                questions: questions.iter().map(|q| q.to_string()).collect(),
+                follows: Mutex::new(Vec::new()),
+                bookmarks: Mutex::new(Vec::new()),
+                inbox: Mutex::new(Vec::new()),
+            }
+        }
+        pub async fn add_inbox_item(&self, user_id: &str, item_uuid: &str) {
+            let item = InboxItem {
+                item_uuid: item_uuid.to_owned(),
+                question_uuid: "123".to_owned(),
+                question_title: "test title".to_owned(),
+                answer_uuid: format!("answer {item_uuid}"),
+                answer_author: "author".to_owned(),
+                created_at: "now".to_owned(),
+            };
+            self.inbox
+                .lock()
+                .await
+                .push((user_id.to_owned(), item, false));
+        }
+        fn check_question(&self, question_uuid: &str) -> Result<(), DBError> {
+            if self.questions.iter().any(|q| q == question_uuid) {
+                Ok(())
+            } else {
+                Err(DBError::InvalidUUID(question_uuid.to_owned()))
+            }
+        }
+    }
+
+    #[async_trait]
+    impl FollowsDao for FollowsDaoMock {
+        async fn follow_question(
+            &self,
+            _: &mut dyn UnitOfWork,
+            user_id: String,
+            question_uuid: String,
+        ) -> Result<(), DBError> {
+            self.check_question(&question_uuid)?;
+            let mut follows = self.follows.lock().await;
+            if !follows.contains(&(user_id.clone(), question_uuid.clone())) {
+                follows.push((user_id, question_uuid));
+            }
+            Ok(())
+        }
+        async fn unfollow_question(
+            &self,
+            _: &mut dyn UnitOfWork,
+            user_id: String,
+            question_uuid: String,
+        ) -> Result<(), DBError> {
+            self.follows
+                .lock()
+                .await
+                .retain(|follow| *follow != (user_id.clone(), question_uuid.clone()));
+            Ok(())
+        }
+        async fn bookmark_question(
+            &self,
+            _: &mut dyn UnitOfWork,
+            user_id: String,
+            question_uuid: String,
+        ) -> Result<(), DBError> {
+            self.check_question(&question_uuid)?;
+            let mut bookmarks = self.bookmarks.lock().await;
+            if !bookmarks.contains(&(user_id.clone(), question_uuid.clone())) {
+                bookmarks.push((user_id, question_uuid));
+            }
+            Ok(())
+        }
+        async fn remove_bookmark(
+            &self,
+            _: &mut dyn UnitOfWork,
+            user_id: String,
+            question_uuid: String,
+        ) -> Result<(), DBError> {
+            self.bookmarks
+                .lock()
+                .await
+                .retain(|bookmark| *bookmark != (user_id.clone(), question_uuid.clone()));
+            Ok(())
+        }
+        async fn get_bookmarks(&self, user_id: String) -> Result<Vec<QuestionDetail>, DBError> {
+            Ok(self
+                .bookmarks
+                .lock()
+                .await
+                .iter()
+                .rev()
+                .filter(|(user, _)| *user == user_id)
+                .map(|(_, question_uuid)| QuestionDetail {
+                    question_uuid: question_uuid.clone(),
+                    ..question_detail("user")
+                })
+                .collect())
+        }
+        async fn get_inbox(&self, user_id: String) -> Result<Vec<InboxItem>, DBError> {
+            Ok(self
+                .inbox
+                .lock()
+                .await
+                .iter()
+                .filter(|(user, _, read)| *user == user_id && !read)
+                .map(|(_, item, _)| item.clone())
+                .collect())
+        }
+        async fn mark_inbox_read(
+            &self,
+            _: &mut dyn UnitOfWork,
+            user_id: String,
+            item_uuids: Vec<String>,
+        ) -> Result<u64, DBError> {
+            let mut marked = 0;
+            for (user, item, read) in self.inbox.lock().await.iter_mut() {
+                if *user == user_id && !*read && item_uuids.contains(&item.item_uuid) {
+                    *read = true;
+                    marked += 1;
+                }
+            }
+            Ok(marked)
+        }
+    }
+
+    /// Attachments are kept in memory and attached to question "123"
+    struct AttachmentsDaoMock {
+        attachments: Mutex<Vec<AttachmentDetail>>,
+        purge_attachments_of_deleted_questions_response: Mutex<Option<Result<u64, DBError>>>,
+        get_referenced_digests_response: Mutex<Option<Result<Vec<String>, DBError>>>,
+    }
+
+    impl AttachmentsDaoMock {
+        pub fn new() -> Self {
+            AttachmentsDaoMock {
+                attachments: Mutex::new(Vec::new()),
+                purge_attachments_of_deleted_questions_response: Mutex::new(None),
+                get_referenced_digests_response: Mutex::new(None),
+            }
+        }
+    }
+
+    #[async_trait]
+    impl AttachmentsDao for AttachmentsDaoMock {
+        async fn create_attachment(
+            &self,
+            _: &mut dyn UnitOfWork,
+            attachment: Attachment,
+            uploaded_by: String,
+        ) -> Result<AttachmentDetail, DBError> {
+            let mut attachments = self.attachments.lock().await;
+            let detail = AttachmentDetail {
+                attachment_uuid: format!("attachment-{}", attachments.len()),
+                question_uuid: attachment.question_uuid,
+                file_name: attachment.file_name,
+                content_type: attachment.content_type,
+                size: attachment.size,
+                sha256: attachment.sha256,
+                uploaded_by,
+                created_at: "now".to_owned(),
+            };
+            attachments.push(detail.clone());
+            Ok(detail)
+        }
+        async fn get_attachment(
+            &self,
+            attachment_uuid: String,
+        ) -> Result<AttachmentDetail, DBError> {
+            self.attachments
+                .lock()
+                .await
+                .iter()
+                .find(|a| a.attachment_uuid == attachment_uuid)
+                .cloned()
+                .ok_or_else(|| DBError::InvalidUUID(attachment_uuid))
+        }
+        async fn get_attachments(&self, _: String) -> Result<Vec<AttachmentDetail>, DBError> {
+            Ok(self.attachments.lock().await.clone())
+        }
+        async fn purge_attachments_of_deleted_questions(
+            &self,
+            _: Duration,
+        ) -> Result<u64, DBError> {
+            self.purge_attachments_of_deleted_questions_response
+                .lock()
+                .await
+                .take()
+                .expect("purge_attachments_of_deleted_questions_response should not be None.")
+        }
+        async fn get_referenced_digests(&self, _: Vec<String>) -> Result<Vec<String>, DBError> {
+            self.get_referenced_digests_response
+                .lock()
+                .await
+                .take()
+                .expect("get_referenced_digests_response should not be None.")
+        }
+    }
+
+    /// Blobs are kept in memory under a fake digest
+    struct BlobStoreMock {
+        blobs: Mutex<HashMap<String, Vec<u8>>>,
+        delete_response: Mutex<Option<Result<bool, BlobError>>>,
+        list_response: Mutex<Option<Result<Vec<StoredBlob>, BlobError>>>,
+    }
+
+    impl BlobStoreMock {
+        pub fn new() -> Self {
+            BlobStoreMock {
+                blobs: Mutex::new(HashMap::new()),
+                delete_response: Mutex::new(None),
+                list_response: Mutex::new(None),
+            }
+        }
+    }
+
+    #[async_trait]
+    impl BlobStore for BlobStoreMock {
+        async fn put(&self, content: &[u8]) -> Result<String, BlobError> {
+            let digest = format!("digest-of-{}", String::from_utf8_lossy(content));
+            self.blobs
+                .lock()
+                .await
+                .insert(digest.clone(), content.to_vec());
+            Ok(digest)
+        }
+        async fn get(&self, digest: &str, range: Option<Range<u64>>) -> Result<Vec<u8>, BlobError> {
+            let blobs = self.blobs.lock().await;
+            let content = blobs
+                .get(digest)
+                .ok_or_else(|| BlobError::NotFound(digest.to_owned()))?;
+            Ok(match range {
+                Some(range) => content[range.start as usize..range.end as usize].to_vec(),
+                None => content.clone(),
+            })
+        }
+        async fn delete(&self, _: &str, _: SystemTime) -> Result<bool, BlobError> {
+            self.delete_response
+                .lock()
+                .await
+                .take()
+                .expect("delete_response should not be None.")
+        }
+        async fn list(&self) -> Result<Vec<StoredBlob>, BlobError> {
+            self.list_response
+                .lock()
+                .await
+                .take()
+                .expect("list_response should not be None.")
+        }
+    }
+
+    struct ReputationDaoMock {
+        get_reputation_response: Mutex<Option<Result<i64, DBError>>>,
+        get_user_profile_response: Mutex<Option<Result<UserProfile, DBError>>>,
+        record_answer_deleted_response: Mutex<Option<Result<(), DBError>>>,
+    }
+
+    impl ReputationDaoMock {
+        pub fn new() -> Self {
+            ReputationDaoMock {
+                get_reputation_response: Mutex::new(None),
+                get_user_profile_response: Mutex::new(None),
+                record_answer_deleted_response: Mutex::new(None),
+            }
+        }
+        pub fn mock_get_reputation(&mut self, response: Result<i64, DBError>) {
+            self.get_reputation_response = Mutex::new(Some(response));
+        }
+        pub fn mock_get_user_profile(&mut self, response: Result<UserProfile, DBError>) {
+            self.get_user_profile_response = Mutex::new(Some(response));
+        }
+        pub fn mock_record_answer_deleted(&mut self, response: Result<(), DBError>) {
+            self.record_answer_deleted_response = Mutex::new(Some(response));
+        }
+    }
+
+    #[async_trait]
+    impl ReputationDao for ReputationDaoMock {
+        async fn get_reputation(&self, _: String) -> Result<i64, DBError> {
+            self.get_reputation_response
+                .lock()
+                .await
+                .take()
+                .expect("get_reputation_response should not be None.")
+        }
+        async fn get_user_profile(&self, _: String) -> Result<UserProfile, DBError> {
+            self.get_user_profile_response
+                .lock()
+                .await
+                .take()
+                .expect("get_user_profile_response should not be None.")
+        }
+        async fn record_answer_deleted_by_moderator(
+            &self,
+            _: &mut dyn UnitOfWork,
+            _: String,
+        ) -> Result<(), DBError> {
+            self.record_answer_deleted_response
+                .lock()
+                .await
+                .take()
+                .expect("record_answer_deleted_response should not be None.")
+        }
+    }
+
+    fn question_detail(author: &str) -> QuestionDetail {
+        QuestionDetail {
+            question_uuid: "123".to_owned(),
+            title: "test title".to_owned(),
+            description: "test description".to_owned(),
+            description_html: "<p>test description</p>\n".to_owned(),
+            author: author.to_owned(),
+            duplicate_of: None,
+            view_count: 0,
+            created_at: "now".to_owned(),
+        }
+    }
+
+    fn answer_detail(author: &str) -> AnswerDetail {
+        AnswerDetail {
+            answer_uuid: "456".to_owned(),
+            question_uuid: "123".to_owned(),
+            content: "test content".to_owned(),
+            content_html: "<p>test content</p>\n".to_owned(),
+            author: author.to_owned(),
+            created_at: "now".to_owned(),
+        }
+    }
+
+    struct FlagsDaoMock {
+        create_flag_response: Mutex<Option<Result<FlagDetail, DBError>>>,
+        get_open_flags_response: Mutex<Option<Result<Vec<FlagDetail>, DBError>>>,
+        resolve_flag_response: Mutex<Option<Result<FlagDetail, DBError>>>,
+    }
+
+    impl FlagsDaoMock {
+        pub fn new() -> Self {
+            FlagsDaoMock {
+                create_flag_response: Mutex::new(None),
+                get_open_flags_response: Mutex::new(None),
+                resolve_flag_response: Mutex::new(None),
+            }
+        }
+        pub fn mock_create_flag(&mut self, response: Result<FlagDetail, DBError>) {
+            self.create_flag_response = Mutex::new(Some(response));
+        }
+        pub fn mock_get_open_flags(&mut self, response: Result<Vec<FlagDetail>, DBError>) {
+            self.get_open_flags_response = Mutex::new(Some(response));
+        }
+        pub fn mock_resolve_flag(&mut self, response: Result<FlagDetail, DBError>) {
+            self.resolve_flag_response = Mutex::new(Some(response));
+        }
+    }
+
+    #[async_trait]
+    impl FlagsDao for FlagsDaoMock {
+        async fn create_flag(
+            &self,
+            _: &mut dyn UnitOfWork,
+            _: Flag,
+            _: String,
+        ) -> Result<FlagDetail, DBError> {
+            self.create_flag_response
+                .lock()
+                .await
+                .take()
+                .expect("create_flag_response should not be None.")
+        }
+        async fn get_open_flags(&self) -> Result<Vec<FlagDetail>, DBError> {
+            self.get_open_flags_response
+                .lock()
+                .await
+                .take()
+                .expect("get_open_flags_response should not be None.")
+        }
+        async fn resolve_flag(
+            &self,
+            _: &mut dyn UnitOfWork,
+            _: String,
+            _: FlagResolution,
+            _: String,
+        ) -> Result<FlagDetail, DBError> {
+            self.resolve_flag_response
+                .lock()
+                .await
+                .take()
+                .expect("resolve_flag_response should not be None.")
+        }
+    }
+
+    fn flag_detail(post_kind: PostKind) -> FlagDetail {
+        FlagDetail {
+            flag_uuid: "789".to_owned(),
+            post_kind,
+            post_uuid: "123".to_owned(),
+            reason: "spam".to_owned(),
+            reported_by: "user".to_owned(),
+            status: "open".to_owned(),
+            resolution: None,
+            created_at: "now".to_owned(),
+        }
+    }
+
+    struct WebhooksDaoMock {
+        create_webhook_response: Mutex<Option<Result<WebhookDetail, DBError>>>,
+        delete_webhook_response: Mutex<Option<Result<Option<WebhookDetail>, DBError>>>,
+        get_due_deliveries_response: Mutex<Option<Result<Vec<WebhookDelivery>, DBError>>>,
+        mark_delivered_response: Mutex<Option<Result<(), DBError>>>,
+        reschedule_delivery_response: Mutex<Option<Result<(), DBError>>>,
+        dead_letter_delivery_response: Mutex<Option<Result<(), DBError>>>,
+    }
+
+    impl WebhooksDaoMock {
+        pub fn new() -> Self {
+            WebhooksDaoMock {
+                create_webhook_response: Mutex::new(None),
+                delete_webhook_response: Mutex::new(None),
+                get_due_deliveries_response: Mutex::new(None),
+                mark_delivered_response: Mutex::new(None),
+                reschedule_delivery_response: Mutex::new(None),
+                dead_letter_delivery_response: Mutex::new(None),
+            }
+        }
+        pub fn mock_create_webhook(&mut self, response: Result<WebhookDetail, DBError>) {
+            self.create_webhook_response = Mutex::new(Some(response));
+        }
+        pub fn mock_delete_webhook(&mut self, response: Result<Option<WebhookDetail>, DBError>) {
+            self.delete_webhook_response = Mutex::new(Some(response));
+        }
+    }
+
+    #[async_trait]
+    impl WebhooksDao for WebhooksDaoMock {
+        async fn create_webhook(
+            &self,
+            _: &mut dyn UnitOfWork,
+            _: Webhook,
+        ) -> Result<WebhookDetail, DBError> {
+            self.create_webhook_response
+                .lock()
+                .await
+                .take()
+                .expect("create_webhook_response should not be None.")
+        }
+        async fn delete_webhook(
+            &self,
+            _: &mut dyn UnitOfWork,
+            _: String,
+        ) -> Result<Option<WebhookDetail>, DBError> {
+            self.delete_webhook_response
+                .lock()
+                .await
+                .take()
+                .expect("delete_webhook_response should not be None.")
+        }
+        async fn get_due_deliveries(
+            &self,
+            _: i64,
+            _: Duration,
+        ) -> Result<Vec<WebhookDelivery>, DBError> {
+            self.get_due_deliveries_response
+                .lock()
+                .await
+                .take()
+                .expect("get_due_deliveries_response should not be None.")
+        }
+        async fn mark_delivered(&self, _: String) -> Result<(), DBError> {
+            self.mark_delivered_response
+                .lock()
+                .await
+                .take()
+                .expect("mark_delivered_response should not be None.")
+        }
+        async fn reschedule_delivery(
+            &self,
+            _: String,
+            _: String,
+            _: Duration,
+        ) -> Result<(), DBError> {
+            self.reschedule_delivery_response
+                .lock()
+                .await
+                .take()
+                .expect("reschedule_delivery_response should not be None.")
+        }
+        async fn dead_letter_delivery(&self, _: String, _: String) -> Result<(), DBError> {
+            self.dead_letter_delivery_response
+                .lock()
+                .await
+                .take()
+                .expect("dead_letter_delivery_response should not be None.")
+        }
+    }
+
+    /// Keeps spaces and their members in memory
+    struct SpacesDaoMock {
+        spaces: Mutex<Vec<SpaceDetail>>,
+        members: Mutex<HashMap<(String, String), SpaceRole>>,
+    }
+
+    impl SpacesDaoMock {
+        pub fn new() -> Self {
+            SpacesDaoMock {
+                spaces: Mutex::new(Vec::new()),
+                members: Mutex::new(HashMap::new()),
+            }
+        }
+        pub async fn with_space(self, slug: &str, open: bool) -> Self {
+            let mut spaces = self.spaces.lock().await;
+            let space_id = format!("space-{}", spaces.len());
+            spaces.push(SpaceDetail {
+                space_id,
+                slug: slug.to_owned(),
+                name: slug.to_owned(),
+                open,
+                created_at: "now".to_owned(),
+            });
+            drop(spaces);
+            self
+        }
+        pub async fn with_member(self, slug: &str, user_id: &str, role: SpaceRole) -> Self {
+            let space = self.get_space(slug.to_owned()).await.unwrap();
+            self.members
+                .lock()
+                .await
+                .insert((space.space_id, user_id.to_owned()), role);
+            self
+        }
+    }
+
+    #[async_trait]
+    impl SpacesDao for SpacesDaoMock {
+        async fn create_space(
+            &self,
+            _: &mut dyn UnitOfWork,
+            space: Space,
+        ) -> Result<SpaceDetail, DBError> {
+            let mut spaces = self.spaces.lock().await;
+            if spaces.iter().any(|existing| existing.slug == space.slug) {
+                return Err(DBError::InvalidUUID(space.slug));
+            }
+            let space = SpaceDetail {
+                space_id: format!("space-{}", spaces.len()),
+                slug: space.slug,
+                name: space.name,
+                open: space.open,
+                created_at: "now".to_owned(),
+            };
+            spaces.push(space.clone());
+            Ok(space)
+        }
+        async fn get_space(&self, slug: String) -> Result<SpaceDetail, DBError> {
+            self.spaces
+                .lock()
+                .await
+                .iter()
+                .find(|space| space.slug == slug)
+                .cloned()
+                .ok_or(DBError::InvalidUUID(slug))
+        }
+        async fn get_role(
+            &self,
+            space_id: String,
+            user_id: String,
+        ) -> Result<Option<SpaceRole>, DBError> {
+            Ok(self.members.lock().await.get(&(space_id, user_id)).copied())
+        }
+        async fn set_member(
+            &self,
+            _: &mut dyn UnitOfWork,
+            space_id: String,
+            user_id: String,
+            role: SpaceRole,
+        ) -> Result<SpaceMemberDetail, DBError> {
+            self.members
+                .lock()
+                .await
+                .insert((space_id.clone(), user_id.clone()), role);
+            Ok(SpaceMemberDetail {
+                space_id,
+                user_id,
+                role,
+                created_at: "now".to_owned(),
+            })
+        }
+        async fn remove_member(
+            &self,
+            _: &mut dyn UnitOfWork,
+            space_id: String,
+            user_id: String,
+        ) -> Result<Option<SpaceMemberDetail>, DBError> {
+            let role = self
+                .members
+                .lock()
+                .await
+                .remove(&(space_id.clone(), user_id.clone()));
+            Ok(role.map(|role| SpaceMemberDetail {
+                space_id,
+                user_id,
+                role,
+                created_at: "now".to_owned(),
+            }))
+        }
+    }
+
+    /// Records the windows it is asked for
+    struct StatsDaoMock {
+        days: Mutex<Vec<i64>>,
+        fail: bool,
+    }
+
+    impl StatsDaoMock {
+        pub fn new() -> Self {
+            StatsDaoMock {
+                days: Mutex::new(Vec::new()),
+                fail: false,
+            }
+        }
+        pub fn failing() -> Self {
+            StatsDaoMock {
+                fail: true,
+                ..Self::new()
+            }
+        }
+    }
+
+    #[async_trait]
+    impl StatsDao for StatsDaoMock {
+        async fn get_stats(&self, days: i64) -> Result<Stats, DBError> {
+            if self.fail {
+                return Err(DBError::Other(Box::new(std::io::Error::other("oh no!"))));
+            }
+            self.days.lock().await.push(days);
+            Ok(Stats {
+                questions: 2,
+                answers: 1,
+                unanswered_questions: 1,
+                median_seconds_to_first_answer: Some(60.0),
+                posts_per_day: Vec::new(),
+                refreshed_at: "now".to_owned(),
+            })
+        }
+        async fn refresh_stats(&self) -> Result<(), DBError> {
+            Ok(())
+        }
+    }
+
+    struct JobsDaoMock {
+        fail: bool,
+        claim_run_response: Mutex<Option<Result<bool, DBError>>>,
+        record_run_response: Mutex<Option<Result<(), DBError>>>,
+        close_inactive_questions_response: Mutex<Option<Result<i64, DBError>>>,
+        flag_unanswered_questions_response: Mutex<Option<Result<i64, DBError>>>,
+        archive_questions_response: Mutex<Option<Result<i64, DBError>>>,
+    }
+
+    impl JobsDaoMock {
+        pub fn new() -> Self {
+            JobsDaoMock {
+                fail: false,
+                claim_run_response: Mutex::new(None),
+                record_run_response: Mutex::new(None),
+                close_inactive_questions_response: Mutex::new(None),
+                flag_unanswered_questions_response: Mutex::new(None),
+                archive_questions_response: Mutex::new(None),
+            }
+        }
+        pub fn failing() -> Self {
+            JobsDaoMock {
+                fail: true,
+                ..Self::new()
+            }
+        }
+    }
+
+    #[async_trait]
+    impl JobsDao for JobsDaoMock {
+        async fn claim_run(
+            &self,
+            _: &mut dyn UnitOfWork,
+            _: &str,
+            _: PrimitiveDateTime,
+        ) -> Result<bool, DBError> {
+            self.claim_run_response
+                .lock()
+                .await
+                .take()
+                .expect("claim_run_response should not be None.")
+        }
+        async fn record_run(
+            &self,
+            _: &mut dyn UnitOfWork,
+            _: &str,
+            _: PrimitiveDateTime,
+            _: PrimitiveDateTime,
+            _: Result<i64, String>,
+        ) -> Result<(), DBError> {
+            self.record_run_response
+                .lock()
+                .await
+                .take()
+                .expect("record_run_response should not be None.")
+        }
+        async fn get_job_runs(&self, limit: i64) -> Result<Vec<JobRun>, DBError> {
+            if self.fail {
+                return Err(DBError::Other(Box::new(std::io::Error::other("oh no!"))));
+            }
+            Ok((0..limit.min(2))
+                .map(|i| JobRun {
+                    job: "close_inactive".to_owned(),
+                    scheduled_for: format!("2026-10-{:02} 3:00:00.0", 19 - i),
+                    started_at: "now".to_owned(),
+                    finished_at: "now".to_owned(),
+                    succeeded: true,
+                    affected: i,
+                    error: None,
+                })
+                .collect())
+        }
+        async fn close_inactive_questions(
+            &self,
+            _: &mut dyn UnitOfWork,
+            _: Duration,
+        ) -> Result<i64, DBError> {
+            self.close_inactive_questions_response
+                .lock()
+                .await
+                .take()
+                .expect("close_inactive_questions_response should not be None.")
+        }
+        async fn flag_unanswered_questions(
+            &self,
+            _: &mut dyn UnitOfWork,
+            _: Duration,
+        ) -> Result<i64, DBError> {
+            self.flag_unanswered_questions_response
+                .lock()
+                .await
+                .take()
+                .expect("flag_unanswered_questions_response should not be None.")
+        }
+        async fn archive_questions(
+            &self,
+            _: &mut dyn UnitOfWork,
+            _: Duration,
+        ) -> Result<i64, DBError> {
+            self.archive_questions_response
+                .lock()
+                .await
+                .take()
+                .expect("archive_questions_response should not be None.")
+        }
+    }
+
+    #[tokio::test]
+    async fn create_question_should_return_question() {
+        let question = Question {
+            title: "test title".to_owned(),
+            description: "test description".to_owned(),
+        };
+
+        let question_detail = QuestionDetail {
+            question_uuid: "123".to_owned(),
+            title: question.title.clone(),
+            description: question.description.clone(),
+            description_html: "<p>test description</p>\n".to_owned(),
+            author: "user".to_owned(),
+            duplicate_of: None,
+            view_count: 0,
+            created_at: "now".to_owned(),
+        };
+
+        let mut questions_dao = QuestionsDaoMock::new();
+
+        questions_dao.mock_create_question(Ok(question_detail.clone()));
+
+        let questions_dao: Box<dyn QuestionsDao + Send + Sync> = Box::new(questions_dao);
+
+        let result = create_question(
+            question,
+            "user".to_owned(),
+            None,
+            &DatabaseMock::new(),
+            &AuditDaoMock::new(),
+            questions_dao.as_ref(),
+        )
+        .await;
+
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap(), question_detail);
+    }
+
+    #[tokio::test]
+    async fn create_question_should_return_error() {
+        let question = Question {
+            title: "test title".to_owned(),
+            description: "test description".to_owned(),
+        };
+
+        let mut questions_dao = QuestionsDaoMock::new();
+
+        questions_dao.mock_create_question(Err(DBError::InvalidUUID("test".to_owned())));
+
+        let questions_dao: Box<dyn QuestionsDao + Send + Sync> = Box::new(questions_dao);
+
+        let result = create_question(
+            question,
+            "user".to_owned(),
+            None,
+            &DatabaseMock::new(),
+            &AuditDaoMock::new(),
+            questions_dao.as_ref(),
+        )
+        .await;
+
+        assert!(result.is_err());
+        assert!(
+            std::mem::discriminant(&result.unwrap_err())
+                == std::mem::discriminant(&HandlerError::InternalError("".to_owned()))
+        );
+    }
+
+    #[tokio::test]
+    async fn read_questions_should_return_questions() {
+        let question_detail = QuestionDetail {
+            question_uuid: "123".to_owned(),
+            title: "test title".to_owned(),
+            description: "test description".to_owned(),
+            description_html: "<p>test description</p>\n".to_owned(),
+            author: "user".to_owned(),
+            duplicate_of: None,
+            view_count: 0,
+            created_at: "now".to_owned(),
+        };
+
+        let mut questions_dao = QuestionsDaoMock::new();
+
+        questions_dao.mock_get_questions(Ok(vec![question_detail.clone()]));
+
+        let questions_dao: Box<dyn QuestionsDao + Send + Sync> = Box::new(questions_dao);
+
+        let result = read_questions(questions_dao.as_ref()).await;
+
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap(), vec![question_detail]);
+    }
+
+    #[tokio::test]
+    async fn read_questions_should_return_error() {
+        let mut questions_dao = QuestionsDaoMock::new();
+
+        questions_dao.mock_get_questions(Err(DBError::InvalidUUID("test".to_owned())));
+
+        let questions_dao: Box<dyn QuestionsDao + Send + Sync> = Box::new(questions_dao);
+
+        let result = read_questions(questions_dao.as_ref()).await;
+
+        assert!(result.is_err());
+        assert!(
+            std::mem::discriminant(&result.unwrap_err())
+                == std::mem::discriminant(&HandlerError::InternalError("".to_owned()))
+        );
+    }
+
+    #[tokio::test]
+    async fn delete_question_should_succeed() {
+        let question_id = QuestionId {
+            question_uuid: "123".to_owned(),
+        };
+
+        let mut questions_dao = QuestionsDaoMock::new();
+
+        questions_dao.mock_lock_question(Ok(question_detail("user")));
+        questions_dao.mock_delete_question(Ok(()));
+
+        let questions_dao: Box<dyn QuestionsDao + Send + Sync> = Box::new(questions_dao);
+        // Authors do not need any reputation to delete their own posts
+        let reputation_dao: Box<dyn ReputationDao + Send + Sync> =
+            Box::new(ReputationDaoMock::new());
+
+        let result = delete_question(
+            question_id,
+            "user".to_owned(),
+            false,
+            None,
+            &DatabaseMock::new(),
+            &AuditDaoMock::new(),
+            questions_dao.as_ref(),
+            reputation_dao.as_ref(),
+        )
+        .await;
+
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap(), ());
+    }
+
+    #[tokio::test]
+    async fn delete_question_should_allow_privileged_users() {
+        let question_id = QuestionId {
+            question_uuid: "123".to_owned(),
+        };
+
+        let mut questions_dao = QuestionsDaoMock::new();
+        let mut reputation_dao = ReputationDaoMock::new();
+
+        questions_dao.mock_lock_question(Ok(question_detail("author")));
+        questions_dao.mock_delete_question(Ok(()));
+        reputation_dao.mock_get_reputation(Ok(Privilege::DeleteOthersPosts.min_reputation()));
+
+        let questions_dao: Box<dyn QuestionsDao + Send + Sync> = Box::new(questions_dao);
+        let reputation_dao: Box<dyn ReputationDao + Send + Sync> = Box::new(reputation_dao);
+
+        let result = delete_question(
+            question_id,
+            "user".to_owned(),
+            false,
+            None,
+            &DatabaseMock::new(),
+            &AuditDaoMock::new(),
+            questions_dao.as_ref(),
+            reputation_dao.as_ref(),
+        )
+        .await;
+
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn delete_question_should_return_forbidden_error() {
+        let question_id = QuestionId {
+            question_uuid: "123".to_owned(),
+        };
+
+        let mut questions_dao = QuestionsDaoMock::new();
+        let mut reputation_dao = ReputationDaoMock::new();
+
+        questions_dao.mock_lock_question(Ok(question_detail("author")));
+        reputation_dao.mock_get_reputation(Ok(Privilege::DeleteOthersPosts.min_reputation() - 1));
+
+        let questions_dao: Box<dyn QuestionsDao + Send + Sync> = Box::new(questions_dao);
+        let reputation_dao: Box<dyn ReputationDao + Send + Sync> = Box::new(reputation_dao);
+
+        let result = delete_question(
+            question_id,
+            "user".to_owned(),
+            false,
+            None,
+            &DatabaseMock::new(),
+            &AuditDaoMock::new(),
+            questions_dao.as_ref(),
+            reputation_dao.as_ref(),
+        )
+        .await;
+
+        assert!(result.is_err());
+        assert!(
+            std::mem::discriminant(&result.unwrap_err())
+                == std::mem::discriminant(&HandlerError::Forbidden("".to_owned()))
+        );
+    }
+
+    #[tokio::test]
+    async fn delete_question_should_let_anonymous_users_delete_anonymous_posts_when_open() {
+        let question_id = QuestionId {
+            question_uuid: "123".to_owned(),
+        };
+
+        let mut questions_dao = QuestionsDaoMock::new();
+
+        questions_dao.mock_lock_question(Ok(question_detail(ANONYMOUS)));
+        questions_dao.mock_delete_question(Ok(()));
+
+        let questions_dao: Box<dyn QuestionsDao + Send + Sync> = Box::new(questions_dao);
+        let reputation_dao: Box<dyn ReputationDao + Send + Sync> =
+            Box::new(ReputationDaoMock::new());
+
+        let database = DatabaseMock::new();
+        let result = delete_question(
+            question_id,
+            ANONYMOUS.to_owned(),
+            true,
+            None,
+            &database,
+            &AuditDaoMock::new(),
+            questions_dao.as_ref(),
+            reputation_dao.as_ref(),
+        )
+        .await;
+
+        assert!(result.is_ok());
+        assert_eq!(database.commits(), 1);
+    }
+
+    #[tokio::test]
+    async fn delete_question_should_not_let_anonymous_users_delete_anonymous_posts() {
+        let question_id = QuestionId {
+            question_uuid: "123".to_owned(),
+        };
+
+        let mut questions_dao = QuestionsDaoMock::new();
+
+        questions_dao.mock_lock_question(Ok(question_detail(ANONYMOUS)));
+
+        let questions_dao: Box<dyn QuestionsDao + Send + Sync> = Box::new(questions_dao);
+        let reputation_dao: Box<dyn ReputationDao + Send + Sync> =
+            Box::new(ReputationDaoMock::new());
+
+        let database = DatabaseMock::new();
+        let result = delete_question(
+            question_id,
+            ANONYMOUS.to_owned(),
+            false,
+            None,
+            &database,
+            &AuditDaoMock::new(),
+            questions_dao.as_ref(),
+            reputation_dao.as_ref(),
+        )
+        .await;
+
+        assert!(result.is_err());
+        assert!(
+            std::mem::discriminant(&result.unwrap_err())
+                == std::mem::discriminant(&HandlerError::Forbidden("".to_owned()))
+        );
+        assert_eq!(database.commits(), 0);
+    }
+
+    #[tokio::test]
+    async fn delete_question_should_be_forbidden_for_anonymous_users() {
+        let question_id = QuestionId {
+            question_uuid: "123".to_owned(),
+        };
+
+        let mut questions_dao = QuestionsDaoMock::new();
+
+        questions_dao.mock_lock_question(Ok(question_detail("author")));
+
+        let questions_dao: Box<dyn QuestionsDao + Send + Sync> = Box::new(questions_dao);
+        let reputation_dao: Box<dyn ReputationDao + Send + Sync> =
+            Box::new(ReputationDaoMock::new());
+
+        let result = delete_question(
+            question_id,
+            ANONYMOUS.to_owned(),
+            false,
+            None,
+            &DatabaseMock::new(),
+            &AuditDaoMock::new(),
+            questions_dao.as_ref(),
+            reputation_dao.as_ref(),
+        )
+        .await;
+
+        assert!(result.is_err());
+        assert!(
+            std::mem::discriminant(&result.unwrap_err())
+                == std::mem::discriminant(&HandlerError::Forbidden("".to_owned()))
+        );
+    }
+
+    #[tokio::test]
+    async fn delete_question_should_return_bad_request_error() {
+        let question_id = QuestionId {
+            question_uuid: "123".to_owned(),
+        };
+
+        let mut questions_dao = QuestionsDaoMock::new();
+
+        questions_dao.mock_lock_question(Err(DBError::InvalidUUID("test".to_owned())));
+
+        let questions_dao: Box<dyn QuestionsDao + Send + Sync> = Box::new(questions_dao);
+        let reputation_dao: Box<dyn ReputationDao + Send + Sync> =
+            Box::new(ReputationDaoMock::new());
+
+        let result = delete_question(
+            question_id,
+            "user".to_owned(),
+            false,
+            None,
+            &DatabaseMock::new(),
+            &AuditDaoMock::new(),
+            questions_dao.as_ref(),
+            reputation_dao.as_ref(),
+        )
+        .await;
+
+        assert!(result.is_err());
+        assert!(
+            std::mem::discriminant(&result.unwrap_err())
+                == std::mem::discriminant(&HandlerError::BadRequest("".to_owned()))
+        );
+    }
+
+    #[tokio::test]
+    async fn delete_question_should_return_error() {
+        let question_id = QuestionId {
+            question_uuid: "123".to_owned(),
+        };
+
+        let mut questions_dao = QuestionsDaoMock::new();
+
+        questions_dao.mock_lock_question(Ok(question_detail("user")));
+        questions_dao.mock_delete_question(Err(DBError::InvalidUUID("test".to_owned())));
+
+        let questions_dao: Box<dyn QuestionsDao + Send + Sync> = Box::new(questions_dao);
+        let reputation_dao: Box<dyn ReputationDao + Send + Sync> =
+            Box::new(ReputationDaoMock::new());
+
+        let result = delete_question(
+            question_id,
+            "user".to_owned(),
+            false,
+            None,
+            &DatabaseMock::new(),
+            &AuditDaoMock::new(),
+            questions_dao.as_ref(),
+            reputation_dao.as_ref(),
+        )
+        .await;
+
+        assert!(result.is_err());
+        assert!(
+            std::mem::discriminant(&result.unwrap_err())
+                == std::mem::discriminant(&HandlerError::InternalError("".to_owned()))
+        );
+    }
+
+    #[tokio::test]
+    async fn update_question_should_return_question() {
+        let question_edit = QuestionEdit {
+            question_uuid: "123".to_owned(),
+            title: "test title".to_owned(),
+            description: "test description".to_owned(),
+        };
+
+        let mut questions_dao = QuestionsDaoMock::new();
+
+        let edited = QuestionDetail {
+            title: "new title".to_owned(),
+            ..question_detail("user")
+        };
+        questions_dao.mock_lock_question(Ok(question_detail("user")));
+        questions_dao.mock_update_question(Ok(edited.clone()));
+
+        let questions_dao: Box<dyn QuestionsDao + Send + Sync> = Box::new(questions_dao);
+        let reputation_dao: Box<dyn ReputationDao + Send + Sync> =
+            Box::new(ReputationDaoMock::new());
+
+        let audit_dao = AuditDaoMock::new();
+        let result = update_question(
+            question_edit,
+            "user".to_owned(),
+            false,
+            Some("request".to_owned()),
+            &DatabaseMock::new(),
+            &audit_dao,
+            questions_dao.as_ref(),
+            reputation_dao.as_ref(),
+        )
+        .await;
+
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap(), edited);
+        assert_eq!(
+            *audit_dao.entries.lock().await,
+            vec![AuditEntry {
+                actor: "user".to_owned(),
+                action: AuditAction::QuestionUpdated,
+                resource_id: "123".to_owned(),
+                before: snapshot(&question_detail("user")),
+                after: snapshot(&edited),
+                request_id: Some("request".to_owned()),
+            }]
+        );
+    }
+
+    #[tokio::test]
+    async fn update_question_should_return_forbidden_error() {
+        let question_edit = QuestionEdit {
+            question_uuid: "123".to_owned(),
+            title: "test title".to_owned(),
+            description: "test description".to_owned(),
+        };
+
+        let mut questions_dao = QuestionsDaoMock::new();
+        let mut reputation_dao = ReputationDaoMock::new();
+
+        questions_dao.mock_lock_question(Ok(question_detail("author")));
+        // Enough to delete but not to edit the posts of others
+        reputation_dao.mock_get_reputation(Ok(Privilege::DeleteOthersPosts.min_reputation()));
+
+        let questions_dao: Box<dyn QuestionsDao + Send + Sync> = Box::new(questions_dao);
+        let reputation_dao: Box<dyn ReputationDao + Send + Sync> = Box::new(reputation_dao);
+
+        let result = update_question(
+            question_edit,
+            "user".to_owned(),
+            false,
+            None,
+            &DatabaseMock::new(),
+            &AuditDaoMock::new(),
+            questions_dao.as_ref(),
+            reputation_dao.as_ref(),
+        )
+        .await;
+
+        assert!(result.is_err());
+        assert!(
+            std::mem::discriminant(&result.unwrap_err())
+                == std::mem::discriminant(&HandlerError::Forbidden("".to_owned()))
+        );
+    }
+
+    #[tokio::test]
+    async fn restore_question_should_return_question() {
+        let question_id = QuestionId {
+            question_uuid: "123".to_owned(),
+        };
+
+        let question_detail = QuestionDetail {
+            question_uuid: "123".to_owned(),
+            title: "test title".to_owned(),
+            description: "test description".to_owned(),
+            description_html: "<p>test description</p>\n".to_owned(),
+            author: "user".to_owned(),
+            duplicate_of: None,
+            view_count: 0,
+            created_at: "now".to_owned(),
+        };
+
+        let mut questions_dao = QuestionsDaoMock::new();
+
+        questions_dao.mock_restore_question(Ok(question_detail.clone()));
+
+        let questions_dao: Box<dyn QuestionsDao + Send + Sync> = Box::new(questions_dao);
+
+        let result = restore_question(
+            question_id,
+            "admin".to_owned(),
+            None,
+            &DatabaseMock::new(),
+            &AuditDaoMock::new(),
+            questions_dao.as_ref(),
+        )
+        .await;
+
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap(), question_detail);
+    }
+
+    #[tokio::test]
+    async fn restore_question_should_return_bad_request_error() {
+        let question_id = QuestionId {
+            question_uuid: "123".to_owned(),
+        };
+
+        let mut questions_dao = QuestionsDaoMock::new();
+
+        questions_dao.mock_restore_question(Err(DBError::InvalidUUID("test".to_owned())));
+
+        let questions_dao: Box<dyn QuestionsDao + Send + Sync> = Box::new(questions_dao);
+
+        let result = restore_question(
+            question_id,
+            "admin".to_owned(),
+            None,
+            &DatabaseMock::new(),
+            &AuditDaoMock::new(),
+            questions_dao.as_ref(),
+        )
+        .await;
+
+        assert!(result.is_err());
+        assert!(
+            std::mem::discriminant(&result.unwrap_err())
+                == std::mem::discriminant(&HandlerError::BadRequest("".to_owned()))
+        );
+    }
+
+    #[tokio::test]
+    async fn read_question_should_return_bad_request_error() {
+        let question_id = QuestionId {
+            question_uuid: "123".to_owned(),
+        };
+
+        let mut questions_dao = QuestionsDaoMock::new();
+
+        questions_dao.mock_get_question(Err(DBError::InvalidUUID("test".to_owned())));
+
+        let questions_dao: Box<dyn QuestionsDao + Send + Sync> = Box::new(questions_dao);
+
+        let views = ViewCounter::default();
+
+        let result = read_question(question_id, questions_dao.as_ref(), &views).await;
+
+        assert!(result.is_err());
+        assert!(
+            std::mem::discriminant(&result.unwrap_err())
+                == std::mem::discriminant(&HandlerError::BadRequest("".to_owned()))
+        );
+        assert!(views.take().is_empty());
+    }
+
+    #[tokio::test]
+    async fn read_question_should_count_a_view() {
+        let question_id = QuestionId {
+            question_uuid: "123".to_owned(),
+        };
+
+        let mut questions_dao = QuestionsDaoMock::new();
+
+        questions_dao.mock_get_question(Ok(question_detail("user")));
+
+        let questions_dao: Box<dyn QuestionsDao + Send + Sync> = Box::new(questions_dao);
+
+        let views = ViewCounter::default();
+
+        let result = read_question(question_id, questions_dao.as_ref(), &views).await;
+
+        assert!(result.is_ok());
+        assert_eq!(views.take(), HashMap::from([("123".to_owned(), 1)]));
+    }
+
+    #[tokio::test]
+    async fn read_question_should_not_count_views_of_duplicates() {
+        let question_id = QuestionId {
+            question_uuid: "123".to_owned(),
+        };
+
+        let mut questions_dao = QuestionsDaoMock::new();
+
+        questions_dao.mock_get_question(Ok(QuestionDetail {
+            duplicate_of: Some("456".to_owned()),
+            ..question_detail("user")
+        }));
+
+        let questions_dao: Box<dyn QuestionsDao + Send + Sync> = Box::new(questions_dao);
+
+        let views = ViewCounter::default();
+
+        let result = read_question(question_id, questions_dao.as_ref(), &views).await;
+
+        assert!(result.is_ok());
+        assert!(views.take().is_empty());
+    }
+
+    #[tokio::test]
+    async fn read_trending_questions_should_return_questions() {
+        let mut questions_dao = QuestionsDaoMock::new();
+
+        questions_dao.mock_get_trending_questions(Ok(vec![question_detail("user")]));
+
+        let questions_dao: Box<dyn QuestionsDao + Send + Sync> = Box::new(questions_dao);
+
+        let result = read_trending_questions(questions_dao.as_ref()).await;
+
+        assert_eq!(result.unwrap(), vec![question_detail("user")]);
+    }
+
+    #[tokio::test]
+    async fn read_trending_questions_should_return_internal_error() {
+        let mut questions_dao = QuestionsDaoMock::new();
+
+        questions_dao.mock_get_trending_questions(Err(DBError::Other(Box::new(
+            std::io::Error::other("oh no!"),
+        ))));
+
+        let questions_dao: Box<dyn QuestionsDao + Send + Sync> = Box::new(questions_dao);
+
+        let result = read_trending_questions(questions_dao.as_ref()).await;
+
+        assert!(
+            std::mem::discriminant(&result.unwrap_err())
+                == std::mem::discriminant(&HandlerError::InternalError("".to_owned()))
+        );
+    }
+
+    fn similar_question() -> SimilarQuestion {
+        SimilarQuestion {
+            question_uuid: "456".to_owned(),
+            title: "test title".to_owned(),
+            similarity: 0.5,
+        }
+    }
+
+    #[tokio::test]
+    async fn read_related_questions_should_return_questions() {
+        let question_id = QuestionId {
+            question_uuid: "123".to_owned(),
+        };
+
+        let mut questions_dao = QuestionsDaoMock::new();
+
+        questions_dao.mock_get_related_questions(Ok(vec![similar_question()]));
+
+        let questions_dao: Box<dyn QuestionsDao + Send + Sync> = Box::new(questions_dao);
+
+        let result = read_related_questions(question_id, questions_dao.as_ref()).await;
+
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap(), vec![similar_question()]);
+    }
+
+    #[tokio::test]
+    async fn read_related_questions_should_return_bad_request_error() {
+        let question_id = QuestionId {
+            question_uuid: "123".to_owned(),
+        };
+
+        let mut questions_dao = QuestionsDaoMock::new();
+
+        questions_dao.mock_get_related_questions(Err(DBError::InvalidUUID("test".to_owned())));
+
+        let questions_dao: Box<dyn QuestionsDao + Send + Sync> = Box::new(questions_dao);
+
+        let result = read_related_questions(question_id, questions_dao.as_ref()).await;
+
+        assert!(result.is_err());
+        assert!(
+            std::mem::discriminant(&result.unwrap_err())
+                == std::mem::discriminant(&HandlerError::BadRequest("".to_owned()))
+        );
+    }
+
+    #[tokio::test]
+    async fn read_similar_questions_should_return_questions() {
+        let question = Question {
+            title: "test title".to_owned(),
+            description: "test description".to_owned(),
+        };
+
+        let mut questions_dao = QuestionsDaoMock::new();
+
+        questions_dao.mock_get_similar_questions(Ok(vec![similar_question()]));
+
+        let questions_dao: Box<dyn QuestionsDao + Send + Sync> = Box::new(questions_dao);
+
+        let result = read_similar_questions(question, questions_dao.as_ref()).await;
+
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap(), vec![similar_question()]);
+    }
+
+    #[tokio::test]
+    async fn close_as_duplicate_should_return_question() {
+        let question_id = QuestionId {
+            question_uuid: "123".to_owned(),
+        };
+        let duplicate_of = DuplicateOf {
+            duplicate_of: "456".to_owned(),
+        };
+
+        let closed_question = QuestionDetail {
+            duplicate_of: Some("456".to_owned()),
+            view_count: 0,
+            ..question_detail("user")
+        };
+
+        let mut questions_dao = QuestionsDaoMock::new();
+
+        questions_dao.mock_lock_question(Ok(question_detail("user")));
+        questions_dao.mock_close_as_duplicate(Ok(closed_question.clone()));
+
+        let questions_dao: Box<dyn QuestionsDao + Send + Sync> = Box::new(questions_dao);
+        let reputation_dao: Box<dyn ReputationDao + Send + Sync> =
+            Box::new(ReputationDaoMock::new());
+
+        let result = close_as_duplicate(
+            question_id,
+            duplicate_of,
+            "user".to_owned(),
+            false,
+            None,
+            &DatabaseMock::new(),
+            &AuditDaoMock::new(),
+            questions_dao.as_ref(),
+            reputation_dao.as_ref(),
+        )
+        .await;
+
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap(), closed_question);
+    }
+
+    #[tokio::test]
+    async fn close_as_duplicate_should_return_forbidden_error() {
+        let question_id = QuestionId {
+            question_uuid: "123".to_owned(),
+        };
+        let duplicate_of = DuplicateOf {
+            duplicate_of: "456".to_owned(),
+        };
+
+        let mut questions_dao = QuestionsDaoMock::new();
+        let mut reputation_dao = ReputationDaoMock::new();
+
+        questions_dao.mock_lock_question(Ok(question_detail("author")));
+        reputation_dao.mock_get_reputation(Ok(0));
+
+        let questions_dao: Box<dyn QuestionsDao + Send + Sync> = Box::new(questions_dao);
+        let reputation_dao: Box<dyn ReputationDao + Send + Sync> = Box::new(reputation_dao);
+
+        let result = close_as_duplicate(
+            question_id,
+            duplicate_of,
+            "user".to_owned(),
+            false,
+            None,
+            &DatabaseMock::new(),
+            &AuditDaoMock::new(),
+            questions_dao.as_ref(),
+            reputation_dao.as_ref(),
+        )
+        .await;
+
+        assert!(result.is_err());
+        assert!(
+            std::mem::discriminant(&result.unwrap_err())
+                == std::mem::discriminant(&HandlerError::Forbidden("".to_owned()))
+        );
+    }
+
+    #[tokio::test]
+    async fn create_answer_should_return_answer() {
+        let answer = Answer {
+            question_uuid: "123".to_owned(),
+            content: "test content".to_owned(),
+        };
+
+        let answer_detail = AnswerDetail {
+            answer_uuid: "456".to_owned(),
+            question_uuid: answer.question_uuid.clone(),
+            content: answer.content.clone(),
+            content_html: "<p>test content</p>\n".to_owned(),
+            author: "user".to_owned(),
+            created_at: "now".to_owned(),
+        };
+
+        let mut answers_dao = AnswersDaoMock::new();
+
+        answers_dao.mock_create_answer(Ok(answer_detail.clone()));
+
+        let answers_dao: Box<dyn AnswersDao + Send + Sync> = Box::new(answers_dao);
+
+        let result = create_answer(
+            answer,
+            "user".to_owned(),
+            None,
+            &DatabaseMock::new(),
+            &AuditDaoMock::new(),
+            answers_dao.as_ref(),
+        )
+        .await;
+
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap(), answer_detail);
+    }
+
+    #[tokio::test]
+    async fn create_answer_should_return_bad_request_error() {
+        let answer = Answer {
+            question_uuid: "123".to_owned(),
+            content: "test content".to_owned(),
+        };
+
+        let mut answers_dao = AnswersDaoMock::new();
+
+        answers_dao.mock_create_answer(Err(DBError::InvalidUUID("test".to_owned())));
+
+        let answers_dao: Box<dyn AnswersDao + Send + Sync> = Box::new(answers_dao);
+
+        let result = create_answer(
+            answer,
+            "user".to_owned(),
+            None,
+            &DatabaseMock::new(),
+            &AuditDaoMock::new(),
+            answers_dao.as_ref(),
+        )
+        .await;
+
+        assert!(result.is_err());
+        assert!(
+            std::mem::discriminant(&result.unwrap_err())
+                == std::mem::discriminant(&HandlerError::BadRequest("".to_owned()))
+        );
+    }
+
+    #[tokio::test]
+    async fn create_answer_should_return_internal_error() {
+        let answer = Answer {
+            question_uuid: "123".to_owned(),
+            content: "test content".to_owned(),
+        };
+
+        let mut answers_dao = AnswersDaoMock::new();
+
+        answers_dao.mock_create_answer(Err(DBError::Other(Box::new(std::io::Error::other(
+            "oh no!",
+        )))));
+
+        let answers_dao: Box<dyn AnswersDao + Send + Sync> = Box::new(answers_dao);
+
+        let result = create_answer(
+            answer,
+            "user".to_owned(),
+            None,
+            &DatabaseMock::new(),
+            &AuditDaoMock::new(),
+            answers_dao.as_ref(),
+        )
+        .await;
+
+        assert!(result.is_err());
+        assert!(
+            std::mem::discriminant(&result.unwrap_err())
+                == std::mem::discriminant(&HandlerError::InternalError("".to_owned()))
+        );
+    }
+
+    #[tokio::test]
+    async fn read_answers_should_return_answers() {
+        let answer_detail = AnswerDetail {
+            answer_uuid: "456".to_owned(),
+            question_uuid: "123".to_owned(),
+            content: "test content".to_owned(),
+            content_html: "<p>test content</p>\n".to_owned(),
+            author: "user".to_owned(),
+            created_at: "now".to_owned(),
+        };
+
+        let question_id = QuestionId {
+            question_uuid: "123".to_owned(),
+        };
+
+        let mut answers_dao = AnswersDaoMock::new();
+
+        answers_dao.mock_get_answers(Ok(vec![answer_detail.clone()]));
+
+        let answers_dao: Box<dyn AnswersDao + Send + Sync> = Box::new(answers_dao);
+
+        let result = read_answers(question_id, answers_dao.as_ref()).await;
+
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap(), vec![answer_detail]);
+    }
+
+    #[tokio::test]
+    async fn read_answers_should_return_error() {
+        let question_id = QuestionId {
+            question_uuid: "123".to_owned(),
+        };
+
+        let mut answers_dao = AnswersDaoMock::new();
+
+        answers_dao.mock_get_answers(Err(DBError::InvalidUUID("test".to_owned())));
+
+        let answers_dao: Box<dyn AnswersDao + Send + Sync> = Box::new(answers_dao);
+
+        let result = read_answers(question_id, answers_dao.as_ref()).await;
+
+        assert!(result.is_err());
+        assert!(
+            std::mem::discriminant(&result.unwrap_err())
+                == std::mem::discriminant(&HandlerError::InternalError("".to_owned()))
+        );
+    }
+
+    #[tokio::test]
+    async fn update_answer_should_return_answer() {
+        let answer_edit = AnswerEdit {
+            answer_uuid: "456".to_owned(),
+            content: "test content".to_owned(),
+        };
+
+        let mut answers_dao = AnswersDaoMock::new();
+        let mut reputation_dao = ReputationDaoMock::new();
+
+        answers_dao.mock_lock_answer(Ok(answer_detail("author")));
+        answers_dao.mock_update_answer(Ok(answer_detail("author")));
+        reputation_dao.mock_get_reputation(Ok(Privilege::EditOthersPosts.min_reputation()));
+
+        let answers_dao: Box<dyn AnswersDao + Send + Sync> = Box::new(answers_dao);
+        let reputation_dao: Box<dyn ReputationDao + Send + Sync> = Box::new(reputation_dao);
+
+        let result = update_answer(
+            answer_edit,
+            "user".to_owned(),
+            false,
+            None,
+            &DatabaseMock::new(),
+            &AuditDaoMock::new(),
+            answers_dao.as_ref(),
+            reputation_dao.as_ref(),
+        )
+        .await;
+
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap(), answer_detail("author"));
+    }
+
+    #[tokio::test]
+    async fn update_answer_should_return_bad_request_error() {
+        let answer_edit = AnswerEdit {
+            answer_uuid: "456".to_owned(),
+            content: "test content".to_owned(),
+        };
+
+        let mut answers_dao = AnswersDaoMock::new();
+
+        answers_dao.mock_lock_answer(Err(DBError::InvalidUUID("test".to_owned())));
+
+        let answers_dao: Box<dyn AnswersDao + Send + Sync> = Box::new(answers_dao);
+        let reputation_dao: Box<dyn ReputationDao + Send + Sync> =
+            Box::new(ReputationDaoMock::new());
+
+        let result = update_answer(
+            answer_edit,
+            "user".to_owned(),
+            false,
+            None,
+            &DatabaseMock::new(),
+            &AuditDaoMock::new(),
+            answers_dao.as_ref(),
+            reputation_dao.as_ref(),
+        )
+        .await;
+
+        assert!(result.is_err());
+        assert!(
+            std::mem::discriminant(&result.unwrap_err())
+                == std::mem::discriminant(&HandlerError::BadRequest("".to_owned()))
+        );
+    }
+
+    #[tokio::test]
+    async fn delete_answer_should_succeed() {
+        let answer_id = AnswerId {
+            answer_uuid: "123".to_owned(),
+        };
+
+        let mut answers_dao = AnswersDaoMock::new();
+
+        answers_dao.mock_lock_answer(Ok(answer_detail("user")));
+        answers_dao.mock_delete_answer(Ok(()));
+
+        let answers_dao: Box<dyn AnswersDao + Send + Sync> = Box::new(answers_dao);
+        let reputation_dao: Box<dyn ReputationDao + Send + Sync> =
+            Box::new(ReputationDaoMock::new());
+
+        let result = delete_answer(
+            answer_id,
+            "user".to_owned(),
+            false,
+            None,
+            &DatabaseMock::new(),
+            &AuditDaoMock::new(),
+            answers_dao.as_ref(),
+            reputation_dao.as_ref(),
+        )
+        .await;
+
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap(), ());
+    }
+
+    #[tokio::test]
+    async fn delete_answer_should_return_forbidden_error() {
+        let answer_id = AnswerId {
+            answer_uuid: "123".to_owned(),
+        };
+
+        let mut answers_dao = AnswersDaoMock::new();
+        let mut reputation_dao = ReputationDaoMock::new();
+
+        answers_dao.mock_lock_answer(Ok(answer_detail("author")));
+        reputation_dao.mock_get_reputation(Ok(0));
+
+        let answers_dao: Box<dyn AnswersDao + Send + Sync> = Box::new(answers_dao);
+        let reputation_dao: Box<dyn ReputationDao + Send + Sync> = Box::new(reputation_dao);
+
+        let result = delete_answer(
+            answer_id,
+            "user".to_owned(),
+            false,
+            None,
+            &DatabaseMock::new(),
+            &AuditDaoMock::new(),
+            answers_dao.as_ref(),
+            reputation_dao.as_ref(),
+        )
+        .await;
+
+        assert!(result.is_err());
+        assert!(
+            std::mem::discriminant(&result.unwrap_err())
+                == std::mem::discriminant(&HandlerError::Forbidden("".to_owned()))
+        );
+    }
+
+    #[tokio::test]
+    async fn delete_answer_should_return_error() {
+        let answer_id = AnswerId {
+            answer_uuid: "123".to_owned(),
+        };
+
+        let mut answers_dao = AnswersDaoMock::new();
+
+        answers_dao.mock_lock_answer(Ok(answer_detail("user")));
+        answers_dao.mock_delete_answer(Err(DBError::InvalidUUID("test".to_owned())));
+
+        let answers_dao: Box<dyn AnswersDao + Send + Sync> = Box::new(answers_dao);
+        let reputation_dao: Box<dyn ReputationDao + Send + Sync> =
+            Box::new(ReputationDaoMock::new());
+
+        let result = delete_answer(
+            answer_id,
+            "user".to_owned(),
+            false,
+            None,
+            &DatabaseMock::new(),
+            &AuditDaoMock::new(),
+            answers_dao.as_ref(),
+            reputation_dao.as_ref(),
+        )
+        .await;
+
+        assert!(result.is_err());
+        assert!(
+            std::mem::discriminant(&result.unwrap_err())
+                == std::mem::discriminant(&HandlerError::InternalError("".to_owned()))
+        );
+    }
+
+    fn upload(content_type: &str, content: &[u8]) -> Upload {
+        Upload {
+            file_name: "C:\\Users\\me\\screenshot.png".to_owned(),
+            content_type: content_type.to_owned(),
+            content: content.to_vec(),
+        }
+    }
+
+    #[tokio::test]
+    async fn create_attachment_should_store_content_of_the_author() {
+        let question_id = QuestionId {
+            question_uuid: "123".to_owned(),
+        };
+
+        let mut questions_dao = QuestionsDaoMock::new();
+        questions_dao.mock_get_question(Ok(question_detail("author")));
+        let attachments_dao = AttachmentsDaoMock::new();
+        let blob_store = BlobStoreMock::new();
+
+        let result = create_attachment(
+            question_id,
+            upload("Image/PNG; charset=binary", b"png"),
+            "author".to_owned(),
+            false,
+            None,
+            &DatabaseMock::new(),
+            &AuditDaoMock::new(),
+            &questions_dao,
+            &attachments_dao,
+            &ReputationDaoMock::new(),
+            &blob_store,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(result.question_uuid, "123");
+        assert_eq!(result.file_name, "screenshot.png");
+        assert_eq!(result.content_type, "image/png");
+        assert_eq!(result.size, 3);
+
+        let attachment = read_attachment(
+            QuestionId {
+                question_uuid: "123".to_owned(),
+            },
+            result.attachment_uuid.clone(),
+            &attachments_dao,
+        )
+        .await
+        .unwrap();
+        let content = read_attachment_content(&attachment, Some(1..3), &blob_store)
+            .await
+            .unwrap();
+
+        assert_eq!(attachment, result);
+        assert_eq!(content, b"ng");
+    }
+
+    #[tokio::test]
+    async fn create_attachment_should_reject_invalid_uploads() {
+        let too_large = vec![0; MAX_ATTACHMENT_SIZE + 1];
+        let uploads = [
+            upload("image/png", b""),
+            upload("image/png", &too_large),
+            upload("text/html", b"<script></script>"),
+        ];
+
+        for upload in uploads {
+            let question_id = QuestionId {
+                question_uuid: "123".to_owned(),
+            };
+
+            let result = create_attachment(
+                question_id,
+                upload,
+                "author".to_owned(),
+                false,
+                None,
+                &DatabaseMock::new(),
+                &AuditDaoMock::new(),
+                &QuestionsDaoMock::new(),
+                &AttachmentsDaoMock::new(),
+                &ReputationDaoMock::new(),
+                &BlobStoreMock::new(),
+            )
+            .await;
+
+            assert!(result.is_err());
+            assert!(
+                std::mem::discriminant(&result.unwrap_err())
+                    == std::mem::discriminant(&HandlerError::BadRequest("".to_owned()))
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn create_attachment_should_return_forbidden_error() {
+        let question_id = QuestionId {
+            question_uuid: "123".to_owned(),
+        };
+
+        let mut questions_dao = QuestionsDaoMock::new();
+        let mut reputation_dao = ReputationDaoMock::new();
+
+        questions_dao.mock_get_question(Ok(question_detail("author")));
+        reputation_dao.mock_get_reputation(Ok(Privilege::EditOthersPosts.min_reputation() - 1));
+
+        let blob_store = BlobStoreMock::new();
+
+        let result = create_attachment(
+            question_id,
+            upload("image/png", b"png"),
+            "user".to_owned(),
+            false,
+            None,
+            &DatabaseMock::new(),
+            &AuditDaoMock::new(),
+            &questions_dao,
+            &AttachmentsDaoMock::new(),
+            &reputation_dao,
+            &blob_store,
+        )
+        .await;
+
+        assert!(result.is_err());
+        assert!(
+            std::mem::discriminant(&result.unwrap_err())
+                == std::mem::discriminant(&HandlerError::Forbidden("".to_owned()))
+        );
+        assert!(blob_store.blobs.lock().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn read_attachment_should_reject_attachment_of_other_question() {
+        let attachments_dao = AttachmentsDaoMock::new();
+        let mut uow = DatabaseMock::new().begin().await.unwrap();
+        let attachment = attachments_dao
+            .create_attachment(
+                uow.as_mut(),
+                Attachment {
+                    question_uuid: "123".to_owned(),
+                    file_name: "a.txt".to_owned(),
+                    content_type: "text/plain".to_owned(),
+                    size: 1,
+                    sha256: "digest".to_owned(),
+                },
+                "author".to_owned(),
+            )
+            .await
+            .unwrap();
+
+        let question_id = QuestionId {
+            question_uuid: "456".to_owned(),
+        };
+        let result =
+            read_attachment(question_id, attachment.attachment_uuid, &attachments_dao).await;
+
+        assert!(result.is_err());
+        assert!(
+            std::mem::discriminant(&result.unwrap_err())
+                == std::mem::discriminant(&HandlerError::BadRequest("".to_owned()))
+        );
+    }
+
+    #[tokio::test]
+    async fn follow_question_should_be_idempotent() {
+        let follows_dao = FollowsDaoMock::new(&["123"]);
+
+        for _ in 0..2 {
+            let question_id = QuestionId {
+                question_uuid: "123".to_owned(),
+            };
+            let result = follow_question(
+                question_id,
+                "user".to_owned(),
+                None,
+                &DatabaseMock::new(),
+                &AuditDaoMock::new(),
+                &follows_dao,
+            )
+            .await;
+            assert!(result.is_ok());
+        }
+
+        assert_eq!(
+            *follows_dao.follows.lock().await,
+            vec![("user".to_owned(), "123".to_owned())]
+        );
+
+        let question_id = QuestionId {
+            question_uuid: "123".to_owned(),
+        };
+        let result = unfollow_question(
+            question_id,
+            "user".to_owned(),
+            None,
+            &DatabaseMock::new(),
+            &AuditDaoMock::new(),
+            &follows_dao,
+        )
+        .await;
+
+        assert!(result.is_ok());
+        assert!(follows_dao.follows.lock().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn follow_question_should_return_bad_request_error() {
+        let question_id = QuestionId {
+            question_uuid: "456".to_owned(),
+        };
+
+        let follows_dao = FollowsDaoMock::new(&["123"]);
+
+        let result = follow_question(
+            question_id,
+            "user".to_owned(),
+            None,
+            &DatabaseMock::new(),
+            &AuditDaoMock::new(),
+            &follows_dao,
+        )
+        .await;
+
+        assert!(result.is_err());
+        assert!(
+            std::mem::discriminant(&result.unwrap_err())
+                == std::mem::discriminant(&HandlerError::BadRequest("".to_owned()))
+        );
+    }
+
+    #[tokio::test]
+    async fn read_bookmarks_should_only_return_bookmarks_of_the_user() {
+        let follows_dao = FollowsDaoMock::new(&["123", "456"]);
+
+        for (user, question_uuid) in [("user", "123"), ("user", "456"), ("other", "123")] {
+            let question_id = QuestionId {
+                question_uuid: question_uuid.to_owned(),
+            };
+            bookmark_question(
+                question_id,
+                user.to_owned(),
+                None,
+                &DatabaseMock::new(),
+                &AuditDaoMock::new(),
+                &follows_dao,
+            )
+            .await
+            .unwrap();
+        }
+        let question_id = QuestionId {
+            question_uuid: "123".to_owned(),
+        };
+        remove_bookmark(
+            question_id,
+            "user".to_owned(),
+            None,
+            &DatabaseMock::new(),
+            &AuditDaoMock::new(),
+            &follows_dao,
+        )
+        .await
+        .unwrap();
+
+        let result = read_bookmarks("user".to_owned(), &follows_dao).await;
+
+        let uuids: Vec<String> = result
+            .unwrap()
+            .into_iter()
+            .map(|q| q.question_uuid)
+            .collect();
+        assert_eq!(uuids, vec!["456".to_owned()]);
+    }
+
+    #[tokio::test]
+    async fn mark_inbox_read_should_hide_read_items_of_the_caller_only() {
+        let follows_dao = FollowsDaoMock::new(&["123"]);
+        follows_dao.add_inbox_item("user", "1").await;
+        follows_dao.add_inbox_item("user", "2").await;
+        follows_dao.add_inbox_item("other", "3").await;
+
+        let database = DatabaseMock::new();
+        let audit_dao = AuditDaoMock::new();
+
+        let mark_read = MarkRead {
+            item_uuids: vec!["1".to_owned(), "3".to_owned()],
+        };
+        let result = mark_inbox_read(
+            mark_read,
+            "user".to_owned(),
+            None,
+            &database,
+            &audit_dao,
+            &follows_dao,
+        )
+        .await;
+        assert!(result.is_ok());
+        assert_eq!(database.commits(), 1);
+        assert_eq!(audit_dao.actions().await, vec![AuditAction::InboxRead]);
+
+        let inbox = read_inbox("user".to_owned(), &follows_dao).await.unwrap();
+        let uuids: Vec<&str> = inbox.iter().map(|item| item.item_uuid.as_str()).collect();
+        assert_eq!(uuids, vec!["2"]);
+
+        let inbox = read_inbox("other".to_owned(), &follows_dao).await.unwrap();
+        assert_eq!(inbox.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn create_flag_should_return_flag() {
+        let flag = Flag {
+            post_kind: PostKind::Question,
+            post_uuid: "123".to_owned(),
+            reason: "spam".to_owned(),
+        };
+
+        let mut flags_dao = FlagsDaoMock::new();
+
+        flags_dao.mock_create_flag(Ok(flag_detail(PostKind::Question)));
+
+        let flags_dao: Box<dyn FlagsDao + Send + Sync> = Box::new(flags_dao);
+
+        let result = create_flag(
+            flag,
+            "user".to_owned(),
+            None,
+            &DatabaseMock::new(),
+            &AuditDaoMock::new(),
+            flags_dao.as_ref(),
+        )
+        .await;
+
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap(), flag_detail(PostKind::Question));
+    }
+
+    #[tokio::test]
+    async fn create_flag_should_reject_empty_reason() {
+        let flag = Flag {
+            post_kind: PostKind::Question,
+            post_uuid: "123".to_owned(),
+            reason: "  ".to_owned(),
+        };
+
+        let flags_dao: Box<dyn FlagsDao + Send + Sync> = Box::new(FlagsDaoMock::new());
+
+        let result = create_flag(
+            flag,
+            "user".to_owned(),
+            None,
+            &DatabaseMock::new(),
+            &AuditDaoMock::new(),
+            flags_dao.as_ref(),
+        )
+        .await;
+
+        assert!(result.is_err());
+        assert!(
+            std::mem::discriminant(&result.unwrap_err())
+                == std::mem::discriminant(&HandlerError::BadRequest("".to_owned()))
+        );
+    }
+
+    #[tokio::test]
+    async fn create_flag_should_return_bad_request_error() {
+        let flag = Flag {
+            post_kind: PostKind::Answer,
+            post_uuid: "123".to_owned(),
+            reason: "spam".to_owned(),
+        };
+
+        let mut flags_dao = FlagsDaoMock::new();
+
+        flags_dao.mock_create_flag(Err(DBError::InvalidUUID("test".to_owned())));
+
+        let flags_dao: Box<dyn FlagsDao + Send + Sync> = Box::new(flags_dao);
+
+        let result = create_flag(
+            flag,
+            "user".to_owned(),
+            None,
+            &DatabaseMock::new(),
+            &AuditDaoMock::new(),
+            flags_dao.as_ref(),
+        )
+        .await;
+
+        assert!(result.is_err());
+        assert!(
+            std::mem::discriminant(&result.unwrap_err())
+                == std::mem::discriminant(&HandlerError::BadRequest("".to_owned()))
+        );
+    }
+
+    #[tokio::test]
+    async fn read_flags_should_return_flags() {
+        let mut flags_dao = FlagsDaoMock::new();
+
+        flags_dao.mock_get_open_flags(Ok(vec![flag_detail(PostKind::Answer)]));
+
+        let flags_dao: Box<dyn FlagsDao + Send + Sync> = Box::new(flags_dao);
+
+        let result = read_flags(flags_dao.as_ref()).await;
+
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap(), vec![flag_detail(PostKind::Answer)]);
+    }
+
+    #[tokio::test]
+    async fn resolve_flag_should_only_close_flag_when_dismissed() {
+        let mut flags_dao = FlagsDaoMock::new();
+
+        flags_dao.mock_resolve_flag(Ok(flag_detail(PostKind::Question)));
+
+        let flags_dao: Box<dyn FlagsDao + Send + Sync> = Box::new(flags_dao);
+        // Deleting through the mocks would panic as no response is set
+        let questions_dao: Box<dyn QuestionsDao + Send + Sync> = Box::new(QuestionsDaoMock::new());
+        let answers_dao: Box<dyn AnswersDao + Send + Sync> = Box::new(AnswersDaoMock::new());
+        let reputation_dao: Box<dyn ReputationDao + Send + Sync> =
+            Box::new(ReputationDaoMock::new());
+
+        let result = resolve_flag(
+            "789".to_owned(),
+            FlagResolution::Dismiss,
+            "admin".to_owned(),
+            None,
+            &DatabaseMock::new(),
+            &AuditDaoMock::new(),
+            flags_dao.as_ref(),
+            questions_dao.as_ref(),
+            answers_dao.as_ref(),
+            reputation_dao.as_ref(),
+        )
+        .await;
+
+        assert!(result.is_ok());
+    }
 
-        let mut questions_dao = QuestionsDaoMock::new();
+    #[tokio::test]
+    async fn resolve_flag_should_delete_flagged_answer() {
+        let mut flags_dao = FlagsDaoMock::new();
+        let mut answers_dao = AnswersDaoMock::new();
+        let mut reputation_dao = ReputationDaoMock::new();
 
-        questions_dao.mock_create_question(Ok(question_detail.clone()));
+        flags_dao.mock_resolve_flag(Ok(flag_detail(PostKind::Answer)));
+        answers_dao.mock_get_answer(Ok(answer_detail("author")));
+        answers_dao.mock_delete_answer(Ok(()));
+        // The author of the answer is penalised
+        reputation_dao.mock_record_answer_deleted(Ok(()));
 
-        let questions_dao: Box<dyn QuestionsDao + Send + Sync> = Box::new(questions_dao);
+        let flags_dao: Box<dyn FlagsDao + Send + Sync> = Box::new(flags_dao);
+        let questions_dao: Box<dyn QuestionsDao + Send + Sync> = Box::new(QuestionsDaoMock::new());
+        let answers_dao: Box<dyn AnswersDao + Send + Sync> = Box::new(answers_dao);
+        let reputation_dao: Box<dyn ReputationDao + Send + Sync> = Box::new(reputation_dao);
 
-        let result = create_question(question, questions_dao.as_ref()).await;
+        let database = DatabaseMock::new();
+        let audit_dao = AuditDaoMock::new();
+        let result = resolve_flag(
+            "789".to_owned(),
+            FlagResolution::DeletePost,
+            "admin".to_owned(),
+            Some("request".to_owned()),
+            &database,
+            &audit_dao,
+            flags_dao.as_ref(),
+            questions_dao.as_ref(),
+            answers_dao.as_ref(),
+            reputation_dao.as_ref(),
+        )
+        .await;
 
         assert!(result.is_ok());
-        assert_eq!(result.unwrap(), question_detail);
+        // The flag, the deletion, the penalty and their audit entries are committed together
+        assert_eq!(database.commits(), 1);
+        assert_eq!(
+            audit_dao.actions().await,
+            vec![AuditAction::AnswerDeleted, AuditAction::FlagResolved]
+        );
+        let deletion = audit_dao.entries.lock().await[0].clone();
+        assert_eq!(deletion.actor, "admin");
+        assert_eq!(deletion.resource_id, "123");
+        assert_eq!(deletion.before, snapshot(&answer_detail("author")));
+        assert_eq!(deletion.after, None);
+        assert_eq!(deletion.request_id.as_deref(), Some("request"));
     }
 
     #[tokio::test]
-    async fn create_question_should_return_error() {
-        let question = Question {
-            title: "test title".to_owned(),
-            description: "test description".to_owned(),
-        };
-
+    async fn resolve_flag_should_return_error_if_post_deletion_fails() {
+        let mut flags_dao = FlagsDaoMock::new();
         let mut questions_dao = QuestionsDaoMock::new();
 
-        questions_dao.mock_create_question(Err(DBError::InvalidUUID("test".to_owned())));
+        flags_dao.mock_resolve_flag(Ok(flag_detail(PostKind::Question)));
+        questions_dao.mock_get_question(Ok(question_detail("author")));
+        questions_dao.mock_delete_question(Err(DBError::InvalidUUID("test".to_owned())));
 
+        let flags_dao: Box<dyn FlagsDao + Send + Sync> = Box::new(flags_dao);
         let questions_dao: Box<dyn QuestionsDao + Send + Sync> = Box::new(questions_dao);
+        let answers_dao: Box<dyn AnswersDao + Send + Sync> = Box::new(AnswersDaoMock::new());
+        let reputation_dao: Box<dyn ReputationDao + Send + Sync> =
+            Box::new(ReputationDaoMock::new());
 
-        let result = create_question(question, questions_dao.as_ref()).await;
+        let database = DatabaseMock::new();
+        let result = resolve_flag(
+            "789".to_owned(),
+            FlagResolution::DeletePost,
+            "admin".to_owned(),
+            None,
+            &database,
+            &AuditDaoMock::new(),
+            flags_dao.as_ref(),
+            questions_dao.as_ref(),
+            answers_dao.as_ref(),
+            reputation_dao.as_ref(),
+        )
+        .await;
 
         assert!(result.is_err());
         assert!(
             std::mem::discriminant(&result.unwrap_err())
                 == std::mem::discriminant(&HandlerError::InternalError("".to_owned()))
         );
+        // The flag is left open as the unit of work is rolled back
+        assert_eq!(database.commits(), 0);
     }
 
     #[tokio::test]
-    async fn read_questions_should_return_questions() {
-        let question_detail = QuestionDetail {
-            question_uuid: "123".to_owned(),
-            title: "test title".to_owned(),
-            description: "test description".to_owned(),
-            created_at: "now".to_owned(),
+    async fn read_user_should_return_profile() {
+        let profile = UserProfile {
+            user_id: "user".to_owned(),
+            reputation: 15,
+            question_count: 1,
+            answer_count: 1,
         };
 
-        let mut questions_dao = QuestionsDaoMock::new();
+        let mut reputation_dao = ReputationDaoMock::new();
 
-        questions_dao.mock_get_questions(Ok(vec![question_detail.clone()]));
+        reputation_dao.mock_get_user_profile(Ok(profile.clone()));
 
-        let questions_dao: Box<dyn QuestionsDao + Send + Sync> = Box::new(questions_dao);
+        let reputation_dao: Box<dyn ReputationDao + Send + Sync> = Box::new(reputation_dao);
 
-        let result = read_questions(questions_dao.as_ref()).await;
+        let result = read_user("user".to_owned(), reputation_dao.as_ref()).await;
 
         assert!(result.is_ok());
-        assert_eq!(result.unwrap(), vec![question_detail]);
+        assert_eq!(result.unwrap(), profile);
     }
 
     #[tokio::test]
-    async fn read_questions_should_return_error() {
-        let mut questions_dao = QuestionsDaoMock::new();
+    async fn read_user_should_return_error() {
+        let mut reputation_dao = ReputationDaoMock::new();
 
-        questions_dao.mock_get_questions(Err(DBError::InvalidUUID("test".to_owned())));
+        reputation_dao.mock_get_user_profile(Err(DBError::Other(Box::new(std::io::Error::other(
+            "oh no!",
+        )))));
 
-        let questions_dao: Box<dyn QuestionsDao + Send + Sync> = Box::new(questions_dao);
+        let reputation_dao: Box<dyn ReputationDao + Send + Sync> = Box::new(reputation_dao);
 
-        let result = read_questions(questions_dao.as_ref()).await;
+        let result = read_user("user".to_owned(), reputation_dao.as_ref()).await;
 
         assert!(result.is_err());
         assert!(
@@ -302,84 +4557,231 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn delete_question_should_succeed() {
-        let question_id = QuestionId {
-            question_uuid: "123".to_owned(),
+    async fn create_webhook_should_return_webhook() {
+        let webhook = Webhook {
+            url: "https://example.com/hook".to_owned(),
+            events: vec![WebhookEvent::QuestionCreated],
         };
 
-        let mut questions_dao = QuestionsDaoMock::new();
+        let webhook_detail = WebhookDetail {
+            webhook_uuid: "123".to_owned(),
+            url: webhook.url.clone(),
+            events: vec!["question.created".to_owned()],
+            secret: "secret".to_owned(),
+            created_at: "now".to_owned(),
+        };
 
-        questions_dao.mock_delete_question(Ok(()));
+        let mut webhooks_dao = WebhooksDaoMock::new();
 
-        let questions_dao: Box<dyn QuestionsDao + Send + Sync> = Box::new(questions_dao);
+        webhooks_dao.mock_create_webhook(Ok(webhook_detail.clone()));
+
+        let webhooks_dao: Box<dyn WebhooksDao + Send + Sync> = Box::new(webhooks_dao);
 
-        let result = delete_question(question_id, questions_dao.as_ref()).await;
+        let audit_dao = AuditDaoMock::new();
+        let result = create_webhook(
+            webhook,
+            false,
+            "user".to_owned(),
+            None,
+            &DatabaseMock::new(),
+            &audit_dao,
+            webhooks_dao.as_ref(),
+        )
+        .await;
 
         assert!(result.is_ok());
-        assert_eq!(result.unwrap(), ());
+        assert_eq!(result.unwrap(), webhook_detail);
+        // The secret is kept out of the audit log
+        let after = audit_dao.entries.lock().await[0].after.clone().unwrap();
+        assert_eq!(after["webhook_uuid"], "123");
+        assert!(after.get("secret").is_none());
     }
 
     #[tokio::test]
-    async fn delete_question_should_return_error() {
-        let question_id = QuestionId {
-            question_uuid: "123".to_owned(),
+    async fn create_webhook_should_reject_invalid_url() {
+        let webhook = Webhook {
+            url: "ftp://example.com/hook".to_owned(),
+            events: vec![WebhookEvent::QuestionCreated],
         };
 
-        let mut questions_dao = QuestionsDaoMock::new();
+        let webhooks_dao: Box<dyn WebhooksDao + Send + Sync> = Box::new(WebhooksDaoMock::new());
 
-        questions_dao.mock_delete_question(Err(DBError::InvalidUUID("test".to_owned())));
+        let result = create_webhook(
+            webhook,
+            false,
+            "user".to_owned(),
+            None,
+            &DatabaseMock::new(),
+            &AuditDaoMock::new(),
+            webhooks_dao.as_ref(),
+        )
+        .await;
 
-        let questions_dao: Box<dyn QuestionsDao + Send + Sync> = Box::new(questions_dao);
+        assert!(result.is_err());
+        assert!(
+            std::mem::discriminant(&result.unwrap_err())
+                == std::mem::discriminant(&HandlerError::BadRequest("".to_owned()))
+        );
+    }
+
+    #[tokio::test]
+    async fn create_webhook_should_reject_empty_event_filter() {
+        let webhook = Webhook {
+            url: "https://example.com/hook".to_owned(),
+            events: vec![],
+        };
 
-        let result = delete_question(question_id, questions_dao.as_ref()).await;
+        let webhooks_dao: Box<dyn WebhooksDao + Send + Sync> = Box::new(WebhooksDaoMock::new());
+
+        let result = create_webhook(
+            webhook,
+            false,
+            "user".to_owned(),
+            None,
+            &DatabaseMock::new(),
+            &AuditDaoMock::new(),
+            webhooks_dao.as_ref(),
+        )
+        .await;
 
         assert!(result.is_err());
         assert!(
             std::mem::discriminant(&result.unwrap_err())
-                == std::mem::discriminant(&HandlerError::InternalError("".to_owned()))
+                == std::mem::discriminant(&HandlerError::BadRequest("".to_owned()))
         );
     }
 
     #[tokio::test]
-    async fn create_answer_should_return_answer() {
-        let answer = Answer {
-            question_uuid: "123".to_owned(),
-            content: "test content".to_owned(),
+    async fn create_webhook_should_return_error() {
+        let webhook = Webhook {
+            url: "https://example.com/hook".to_owned(),
+            events: vec![WebhookEvent::AnswerCreated],
         };
 
-        let answer_detail = AnswerDetail {
-            answer_uuid: "456".to_owned(),
-            question_uuid: answer.question_uuid.clone(),
-            content: answer.content.clone(),
-            created_at: "now".to_owned(),
+        let mut webhooks_dao = WebhooksDaoMock::new();
+
+        webhooks_dao.mock_create_webhook(Err(DBError::InvalidUUID("test".to_owned())));
+
+        let webhooks_dao: Box<dyn WebhooksDao + Send + Sync> = Box::new(webhooks_dao);
+
+        let result = create_webhook(
+            webhook,
+            false,
+            "user".to_owned(),
+            None,
+            &DatabaseMock::new(),
+            &AuditDaoMock::new(),
+            webhooks_dao.as_ref(),
+        )
+        .await;
+
+        assert!(result.is_err());
+        assert!(
+            std::mem::discriminant(&result.unwrap_err())
+                == std::mem::discriminant(&HandlerError::InternalError("".to_owned()))
+        );
+    }
+
+    #[tokio::test]
+    async fn create_webhook_should_reject_private_targets_unless_allowed() {
+        let url = "http://169.254.169.254/latest/meta-data";
+        let webhook = || Webhook {
+            url: url.to_owned(),
+            events: vec![WebhookEvent::QuestionCreated],
         };
 
-        let mut answers_dao = AnswersDaoMock::new();
+        let webhooks_dao: Box<dyn WebhooksDao + Send + Sync> = Box::new(WebhooksDaoMock::new());
 
-        answers_dao.mock_create_answer(Ok(answer_detail.clone()));
+        let result = create_webhook(
+            webhook(),
+            false,
+            "admin".to_owned(),
+            None,
+            &DatabaseMock::new(),
+            &AuditDaoMock::new(),
+            webhooks_dao.as_ref(),
+        )
+        .await;
 
-        let answers_dao: Box<dyn AnswersDao + Send + Sync> = Box::new(answers_dao);
+        assert!(result.is_err());
+        assert!(
+            std::mem::discriminant(&result.unwrap_err())
+                == std::mem::discriminant(&HandlerError::BadRequest("".to_owned()))
+        );
+
+        let mut webhooks_dao = WebhooksDaoMock::new();
+        webhooks_dao.mock_create_webhook(Ok(WebhookDetail {
+            webhook_uuid: "123".to_owned(),
+            url: url.to_owned(),
+            events: vec!["question.created".to_owned()],
+            secret: "secret".to_owned(),
+            created_at: "now".to_owned(),
+        }));
 
-        let result = create_answer(answer, answers_dao.as_ref()).await;
+        let result = create_webhook(
+            webhook(),
+            true,
+            "admin".to_owned(),
+            None,
+            &DatabaseMock::new(),
+            &AuditDaoMock::new(),
+            &webhooks_dao,
+        )
+        .await;
 
         assert!(result.is_ok());
-        assert_eq!(result.unwrap(), answer_detail);
     }
 
     #[tokio::test]
-    async fn create_answer_should_return_bad_request_error() {
-        let answer = Answer {
-            question_uuid: "123".to_owned(),
-            content: "test content".to_owned(),
+    async fn delete_webhook_should_only_record_deletions() {
+        let webhook_detail = WebhookDetail {
+            webhook_uuid: "123".to_owned(),
+            url: "https://example.com/hook".to_owned(),
+            events: vec!["question.created".to_owned()],
+            secret: "secret".to_owned(),
+            created_at: "now".to_owned(),
         };
+        let audit_dao = AuditDaoMock::new();
 
-        let mut answers_dao = AnswersDaoMock::new();
+        for response in [Some(webhook_detail), None] {
+            let mut webhooks_dao = WebhooksDaoMock::new();
+            webhooks_dao.mock_delete_webhook(Ok(response));
 
-        answers_dao.mock_create_answer(Err(DBError::InvalidUUID("test".to_owned())));
+            let result = delete_webhook(
+                "123".to_owned(),
+                "admin".to_owned(),
+                None,
+                &DatabaseMock::new(),
+                &audit_dao,
+                &webhooks_dao,
+            )
+            .await;
 
-        let answers_dao: Box<dyn AnswersDao + Send + Sync> = Box::new(answers_dao);
+            assert!(result.is_ok());
+        }
+
+        assert_eq!(audit_dao.actions().await, vec![AuditAction::WebhookDeleted]);
+        // The secret is kept out of the audit log
+        let before = audit_dao.entries.lock().await[0].before.clone().unwrap();
+        assert_eq!(before["webhook_uuid"], "123");
+        assert!(before.get("secret").is_none());
+    }
 
-        let result = create_answer(answer, answers_dao.as_ref()).await;
+    #[tokio::test]
+    async fn delete_webhook_should_return_error() {
+        let mut webhooks_dao = WebhooksDaoMock::new();
+
+        webhooks_dao.mock_delete_webhook(Err(DBError::InvalidUUID("test".to_owned())));
+
+        let result = delete_webhook(
+            "abc".to_owned(),
+            "admin".to_owned(),
+            None,
+            &DatabaseMock::new(),
+            &AuditDaoMock::new(),
+            &webhooks_dao,
+        )
+        .await;
 
         assert!(result.is_err());
         assert!(
@@ -389,70 +4791,329 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn create_answer_should_return_internal_error() {
-        let answer = Answer {
-            question_uuid: "123".to_owned(),
-            content: "test content".to_owned(),
+    async fn read_audit_log_should_return_records() {
+        let record = AuditRecord {
+            audit_uuid: "123".to_owned(),
+            actor: "user".to_owned(),
+            action: "question.deleted".to_owned(),
+            resource_id: "456".to_owned(),
+            before: None,
+            after: None,
+            request_id: None,
+            created_at: "now".to_owned(),
         };
 
-        let mut answers_dao = AnswersDaoMock::new();
+        let mut audit_dao = AuditDaoMock::new();
+        audit_dao.mock_get_audit_log(Ok(vec![record.clone()]));
 
-        answers_dao.mock_create_answer(Err(DBError::Other(Box::new(std::io::Error::new(
-            std::io::ErrorKind::Other,
-            "oh no!",
-        )))));
+        let result = read_audit_log(AuditQuery::default(), &audit_dao).await;
 
-        let answers_dao: Box<dyn AnswersDao + Send + Sync> = Box::new(answers_dao);
+        assert_eq!(result.unwrap(), vec![record]);
+    }
+
+    #[tokio::test]
+    async fn read_audit_log_should_return_bad_request_error() {
+        let mut audit_dao = AuditDaoMock::new();
+        audit_dao.mock_get_audit_log(Err(DBError::InvalidUUID("test".to_owned())));
 
-        let result = create_answer(answer, answers_dao.as_ref()).await;
+        let query = AuditQuery {
+            since: Some("yesterday".to_owned()),
+            ..AuditQuery::default()
+        };
+        let result = read_audit_log(query, &audit_dao).await;
 
         assert!(result.is_err());
         assert!(
             std::mem::discriminant(&result.unwrap_err())
-                == std::mem::discriminant(&HandlerError::InternalError("".to_owned()))
+                == std::mem::discriminant(&HandlerError::BadRequest("".to_owned()))
         );
     }
 
     #[tokio::test]
-    async fn read_answers_should_return_answers() {
-        let answer_detail = AnswerDetail {
-            answer_uuid: "456".to_owned(),
-            question_uuid: "123".to_owned(),
-            content: "test content".to_owned(),
-            created_at: "now".to_owned(),
-        };
+    async fn create_space_should_reject_invalid_slug() {
+        for slug in ["", "Team", "team space", "-team", &"a".repeat(65)] {
+            let result = create_space(
+                Space {
+                    slug: slug.to_owned(),
+                    name: "Team".to_owned(),
+                    open: false,
+                },
+                "admin".to_owned(),
+                None,
+                &DatabaseMock::new(),
+                &AuditDaoMock::new(),
+                &SpacesDaoMock::new(),
+            )
+            .await;
 
-        let question_id = QuestionId {
-            question_uuid: "123".to_owned(),
-        };
+            assert!(
+                std::mem::discriminant(&result.unwrap_err())
+                    == std::mem::discriminant(&HandlerError::BadRequest("".to_owned())),
+                "{slug} was accepted"
+            );
+        }
+    }
 
-        let mut answers_dao = AnswersDaoMock::new();
+    #[tokio::test]
+    async fn create_space_should_return_bad_request_error_for_taken_slug() {
+        let spaces_dao = SpacesDaoMock::new().with_space("team", false).await;
+        let audit_dao = AuditDaoMock::new();
 
-        answers_dao.mock_get_answers(Ok(vec![answer_detail.clone()]));
+        let result = create_space(
+            Space {
+                slug: "team".to_owned(),
+                name: "Team".to_owned(),
+                open: false,
+            },
+            "admin".to_owned(),
+            None,
+            &DatabaseMock::new(),
+            &audit_dao,
+            &spaces_dao,
+        )
+        .await;
 
-        let answers_dao: Box<dyn AnswersDao + Send + Sync> = Box::new(answers_dao);
+        assert!(
+            std::mem::discriminant(&result.unwrap_err())
+                == std::mem::discriminant(&HandlerError::BadRequest("".to_owned()))
+        );
+        assert!(audit_dao.actions().await.is_empty());
+    }
 
-        let result = read_answers(question_id, answers_dao.as_ref()).await;
+    #[tokio::test]
+    async fn set_space_member_should_record_previous_role() {
+        let spaces_dao = SpacesDaoMock::new()
+            .with_space("team", false)
+            .await
+            .with_member("team", "toto", SpaceRole::Reader)
+            .await;
+        let database = DatabaseMock::new();
+        let audit_dao = AuditDaoMock::new();
 
-        assert!(result.is_ok());
-        assert_eq!(result.unwrap(), vec![answer_detail]);
+        let result = set_space_member(
+            "team".to_owned(),
+            "toto".to_owned(),
+            SpaceMember {
+                role: SpaceRole::Writer,
+            },
+            "admin".to_owned(),
+            Some("request".to_owned()),
+            &database,
+            &audit_dao,
+            &spaces_dao,
+        )
+        .await;
+
+        assert_eq!(result.unwrap().role, SpaceRole::Writer);
+        assert_eq!(database.commits(), 1);
+        let entries = audit_dao.entries.lock().await;
+        assert_eq!(entries[0].action, AuditAction::SpaceMemberSet);
+        assert_eq!(entries[0].resource_id, "space-0/toto");
+        assert_eq!(entries[0].before.as_ref().unwrap()["role"], "reader");
+        assert_eq!(entries[0].after.as_ref().unwrap()["role"], "writer");
     }
 
     #[tokio::test]
-    async fn read_answers_should_return_error() {
-        let question_id = QuestionId {
-            question_uuid: "123".to_owned(),
+    async fn set_space_member_should_return_bad_request_error() {
+        let spaces_dao = SpacesDaoMock::new().with_space("team", false).await;
+
+        for (slug, user_id) in [("other", "toto"), ("team", ANONYMOUS), ("team", "")] {
+            let result = set_space_member(
+                slug.to_owned(),
+                user_id.to_owned(),
+                SpaceMember {
+                    role: SpaceRole::Reader,
+                },
+                "admin".to_owned(),
+                None,
+                &DatabaseMock::new(),
+                &AuditDaoMock::new(),
+                &spaces_dao,
+            )
+            .await;
+
+            assert!(
+                std::mem::discriminant(&result.unwrap_err())
+                    == std::mem::discriminant(&HandlerError::BadRequest("".to_owned()))
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn remove_space_member_should_only_record_removals() {
+        let spaces_dao = SpacesDaoMock::new()
+            .with_space("team", false)
+            .await
+            .with_member("team", "toto", SpaceRole::Writer)
+            .await;
+        let audit_dao = AuditDaoMock::new();
+
+        for _ in 0..2 {
+            let result = remove_space_member(
+                "team".to_owned(),
+                "toto".to_owned(),
+                "admin".to_owned(),
+                None,
+                &DatabaseMock::new(),
+                &audit_dao,
+                &spaces_dao,
+            )
+            .await;
+
+            assert!(result.is_ok());
+        }
+
+        assert_eq!(
+            audit_dao.actions().await,
+            vec![AuditAction::SpaceMemberRemoved]
+        );
+        assert!(spaces_dao.members.lock().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn enter_space_should_check_the_role_of_members() {
+        let spaces_dao = SpacesDaoMock::new()
+            .with_space("team", false)
+            .await
+            .with_member("team", "reader", SpaceRole::Reader)
+            .await
+            .with_member("team", "writer", SpaceRole::Writer)
+            .await;
+
+        let enter = |user_id: &str, access| {
+            enter_space(
+                "team".to_owned(),
+                Some(user_id.to_owned()),
+                false,
+                access,
+                &spaces_dao,
+            )
         };
 
-        let mut answers_dao = AnswersDaoMock::new();
+        assert_eq!(
+            enter("reader", SpaceRole::Reader).await.unwrap().slug,
+            "team"
+        );
+        assert!(enter("writer", SpaceRole::Reader).await.is_ok());
+        assert!(enter("writer", SpaceRole::Writer).await.is_ok());
+        for (user_id, access) in [
+            ("reader", SpaceRole::Writer),
+            ("stranger", SpaceRole::Reader),
+            ("stranger", SpaceRole::Writer),
+        ] {
+            assert!(
+                std::mem::discriminant(&enter(user_id, access).await.unwrap_err())
+                    == std::mem::discriminant(&HandlerError::Forbidden("".to_owned())),
+                "{user_id} was let in with {access:?}"
+            );
+        }
+    }
 
-        answers_dao.mock_get_answers(Err(DBError::InvalidUUID("test".to_owned())));
+    #[tokio::test]
+    async fn enter_space_should_let_anyone_in_open_spaces_and_admins_everywhere() {
+        let spaces_dao = SpacesDaoMock::new()
+            .with_space("open", true)
+            .await
+            .with_space("team", false)
+            .await;
 
-        let answers_dao: Box<dyn AnswersDao + Send + Sync> = Box::new(answers_dao);
+        let result = enter_space(
+            "open".to_owned(),
+            None,
+            false,
+            SpaceRole::Writer,
+            &spaces_dao,
+        )
+        .await;
+        assert!(result.is_ok());
 
-        let result = read_answers(question_id, answers_dao.as_ref()).await;
+        let result = enter_space(
+            "team".to_owned(),
+            None,
+            true,
+            SpaceRole::Writer,
+            &spaces_dao,
+        )
+        .await;
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn enter_space_should_refuse_unknown_spaces_like_private_ones() {
+        let spaces_dao = SpacesDaoMock::new().with_space("team", false).await;
+
+        for slug in ["team", "unknown"] {
+            let result = enter_space(
+                slug.to_owned(),
+                Some(ANONYMOUS.to_owned()),
+                false,
+                SpaceRole::Reader,
+                &spaces_dao,
+            )
+            .await;
+            assert!(
+                std::mem::discriminant(&result.unwrap_err())
+                    == std::mem::discriminant(&HandlerError::Unauthorized("".to_owned()))
+            );
 
+            let result = enter_space(
+                slug.to_owned(),
+                Some("toto".to_owned()),
+                false,
+                SpaceRole::Reader,
+                &spaces_dao,
+            )
+            .await;
+            assert_eq!(
+                result.unwrap_err(),
+                HandlerError::Forbidden(format!("toto is not a member of space {slug}"))
+            );
+        }
+
+        // Not even the admin token reveals spaces that do not exist
+        let result = enter_space(
+            "unknown".to_owned(),
+            None,
+            true,
+            SpaceRole::Reader,
+            &spaces_dao,
+        )
+        .await;
         assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn read_stats_should_default_the_window() {
+        let stats_dao = StatsDaoMock::new();
+
+        let result = read_stats(StatsQuery::default(), &stats_dao).await;
+        assert_eq!(result.unwrap().questions, 2);
+
+        let result = read_stats(StatsQuery { days: Some(7) }, &stats_dao).await;
+        assert!(result.is_ok());
+
+        assert_eq!(*stats_dao.days.lock().await, vec![STATS_DEFAULT_DAYS, 7]);
+    }
+
+    #[tokio::test]
+    async fn read_stats_should_reject_windows_out_of_bounds() {
+        let stats_dao = StatsDaoMock::new();
+
+        for days in [0, -1, STATS_MAX_DAYS + 1] {
+            let result = read_stats(StatsQuery { days: Some(days) }, &stats_dao).await;
+            assert!(
+                std::mem::discriminant(&result.unwrap_err())
+                    == std::mem::discriminant(&HandlerError::BadRequest("".to_owned())),
+                "{days} days were accepted"
+            );
+        }
+        assert!(stats_dao.days.lock().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn read_stats_should_return_error_if_the_dao_fails() {
+        let result = read_stats(StatsQuery::default(), &StatsDaoMock::failing()).await;
+
         assert!(
             std::mem::discriminant(&result.unwrap_err())
                 == std::mem::discriminant(&HandlerError::InternalError("".to_owned()))
@@ -460,41 +5121,141 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn delete_answer_should_succeed() {
-        let answer_id = AnswerId {
-            answer_uuid: "123".to_owned(),
+    async fn read_questions_page_should_tell_whether_there_is_a_next_page() {
+        let question = |i: usize| QuestionDetail {
+            question_uuid: i.to_string(),
+            title: "test title".to_owned(),
+            description: "test description".to_owned(),
+            description_html: "<p>test description</p>\n".to_owned(),
+            author: "user".to_owned(),
+            duplicate_of: None,
+            view_count: 0,
+            created_at: "now".to_owned(),
         };
 
-        let mut answers_dao = AnswersDaoMock::new();
-
-        answers_dao.mock_delete_answer(Ok(()));
+        let mut questions_dao = QuestionsDaoMock::new();
+        questions_dao.mock_get_questions_page(Ok((0..3).map(question).collect()));
+        let query = PageQuery {
+            offset: Some(4),
+            limit: Some(2),
+        };
+        let page = read_questions_page(query, &questions_dao).await.unwrap();
+        assert_eq!(page.items, vec![question(0), question(1)]);
+        assert_eq!(page.next_offset, Some(6));
 
-        let answers_dao: Box<dyn AnswersDao + Send + Sync> = Box::new(answers_dao);
+        questions_dao.mock_get_questions_page(Ok(vec![question(0)]));
+        let page = read_questions_page(PageQuery::default(), &questions_dao)
+            .await
+            .unwrap();
+        assert_eq!(page.next_offset, None);
+    }
 
-        let result = delete_answer(answer_id, answers_dao.as_ref()).await;
+    #[tokio::test]
+    async fn read_questions_page_should_reject_bounds_out_of_range() {
+        for (offset, limit) in [(-1, 1), (0, 0), (0, MAX_PAGE_SIZE + 1)] {
+            let query = PageQuery {
+                offset: Some(offset),
+                limit: Some(limit),
+            };
+            let result = read_questions_page(query, &QuestionsDaoMock::new()).await;
 
-        assert!(result.is_ok());
-        assert_eq!(result.unwrap(), ());
+            assert!(
+                std::mem::discriminant(&result.unwrap_err())
+                    == std::mem::discriminant(&HandlerError::BadRequest("".to_owned())),
+                "offset {offset} and limit {limit} were accepted"
+            );
+        }
     }
 
     #[tokio::test]
-    async fn delete_answer_should_return_error() {
-        let answer_id = AnswerId {
-            answer_uuid: "123".to_owned(),
+    async fn read_needs_attention_should_page_the_listed_questions() {
+        let question = |i: usize| QuestionDetail {
+            question_uuid: i.to_string(),
+            title: "test title".to_owned(),
+            description: "test description".to_owned(),
+            description_html: "<p>test description</p>\n".to_owned(),
+            author: "user".to_owned(),
+            duplicate_of: None,
+            view_count: 0,
+            created_at: "now".to_owned(),
         };
 
-        let mut answers_dao = AnswersDaoMock::new();
+        let mut questions_dao = QuestionsDaoMock::new();
+        questions_dao.mock_get_questions_needing_attention(Ok((0..2).map(question).collect()));
+        let query = PageQuery {
+            offset: None,
+            limit: Some(1),
+        };
+        let page = read_needs_attention(query, &questions_dao).await.unwrap();
+        assert_eq!(page.items, vec![question(0)]);
+        assert_eq!(page.next_offset, Some(1));
 
-        answers_dao.mock_delete_answer(Err(DBError::InvalidUUID("test".to_owned())));
+        questions_dao.mock_get_questions_needing_attention(Err(DBError::Other(Box::new(
+            std::io::Error::other("oh no!"),
+        ))));
+        let result = read_needs_attention(PageQuery::default(), &questions_dao).await;
+        assert!(
+            std::mem::discriminant(&result.unwrap_err())
+                == std::mem::discriminant(&HandlerError::InternalError("".to_owned()))
+        );
+    }
 
-        let answers_dao: Box<dyn AnswersDao + Send + Sync> = Box::new(answers_dao);
+    #[tokio::test]
+    async fn read_job_runs_should_return_recent_runs() {
+        let runs = read_job_runs(&JobsDaoMock::new()).await.unwrap();
 
-        let result = delete_answer(answer_id, answers_dao.as_ref()).await;
+        assert_eq!(runs.len(), 2);
+        assert_eq!(runs[0].scheduled_for, "2026-10-19 3:00:00.0");
+    }
+
+    #[tokio::test]
+    async fn read_job_runs_should_fail_with_internal_error() {
+        let result = read_job_runs(&JobsDaoMock::failing()).await;
 
-        assert!(result.is_err());
         assert!(
             std::mem::discriminant(&result.unwrap_err())
                 == std::mem::discriminant(&HandlerError::InternalError("".to_owned()))
         );
     }
+
+    #[tokio::test]
+    async fn read_answers_page_should_page_the_answers() {
+        let answers = ["a", "b", "c"].map(|uuid| AnswerDetail {
+            answer_uuid: uuid.to_owned(),
+            ..answer_detail("author")
+        });
+        let question_id = || QuestionId {
+            question_uuid: "123".to_owned(),
+        };
+        let mut answers_dao = AnswersDaoMock::new();
+
+        answers_dao.mock_get_answers(Ok(answers.to_vec()));
+        let query = PageQuery {
+            offset: Some(1),
+            limit: Some(1),
+        };
+        let page = read_answers_page(question_id(), query, &answers_dao)
+            .await
+            .unwrap();
+        assert_eq!(page.items, vec![answers[1].clone()]);
+        assert_eq!(page.next_offset, Some(2));
+
+        answers_dao.mock_get_answers(Ok(answers.to_vec()));
+        let query = PageQuery {
+            offset: Some(2),
+            limit: None,
+        };
+        let page = read_answers_page(question_id(), query, &answers_dao)
+            .await
+            .unwrap();
+        assert_eq!(page.items, vec![answers[2].clone()]);
+        assert_eq!(page.next_offset, None);
+
+        answers_dao.mock_get_answers(Err(DBError::InvalidUUID("test".to_owned())));
+        let result = read_answers_page(question_id(), PageQuery::default(), &answers_dao).await;
+        assert!(
+            std::mem::discriminant(&result.unwrap_err())
+                == std::mem::discriminant(&HandlerError::BadRequest("".to_owned()))
+        );
+    }
 }
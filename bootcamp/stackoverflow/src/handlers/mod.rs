@@ -1,6 +1,25 @@
-use crate::{models::*, AppState};
-use axum::{extract::State, http::StatusCode, response::IntoResponse, Json};
+use crate::{
+    attachments::{self, RangeNotSatisfiable, MAX_ATTACHMENT_SIZE},
+    markdown,
+    models::*,
+    AppState,
+};
+use axum::{
+    extract::{Multipart, OriginalUri, Path, Query, Request, State},
+    http::{header, HeaderMap, HeaderValue, StatusCode, Uri},
+    middleware::Next,
+    response::{IntoResponse, Redirect, Response},
+    Json,
+};
+use serde::Deserialize;
+pub mod extractors;
 pub mod handlers_inner;
+pub mod v2;
+
+use extractors::{
+    Admin, Caller, Identity, RequestId, SpaceReader, SpaceWriter, MAX_REQUEST_ID_LEN,
+    REQUEST_ID_HEADER,
+};
 
 impl IntoResponse for handlers_inner::HandlerError {
     fn into_response(self) -> axum::response::Response {
@@ -8,6 +27,12 @@ impl IntoResponse for handlers_inner::HandlerError {
             handlers_inner::HandlerError::BadRequest(msg) => {
                 (StatusCode::BAD_REQUEST, msg).into_response()
             }
+            handlers_inner::HandlerError::Unauthorized(msg) => {
+                (StatusCode::UNAUTHORIZED, msg).into_response()
+            }
+            handlers_inner::HandlerError::Forbidden(msg) => {
+                (StatusCode::FORBIDDEN, msg).into_response()
+            }
             handlers_inner::HandlerError::InternalError(msg) => {
                 (StatusCode::INTERNAL_SERVER_ERROR, msg).into_response()
             }
@@ -15,44 +40,374 @@ impl IntoResponse for handlers_inner::HandlerError {
     }
 }
 
+/// Keeps the request id given by the caller or a proxy, or assigns one,
+/// and sends it back so that it can be quoted when asking about a request
+pub async fn request_id(mut request: Request, next: Next) -> Response {
+    let given = request
+        .headers()
+        .get(REQUEST_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|id| !id.is_empty() && id.len() <= MAX_REQUEST_ID_LEN);
+    if !given {
+        let id = HeaderValue::from_str(&uuid::Uuid::new_v4().to_string())
+            .expect("A UUID is a valid header value");
+        request.headers_mut().insert(REQUEST_ID_HEADER, id);
+    }
+
+    let id = request.headers().get(REQUEST_ID_HEADER).cloned();
+    let mut response = next.run(request).await;
+    if let Some(id) = id {
+        response.headers_mut().insert(REQUEST_ID_HEADER, id);
+    }
+    response
+}
+
+/// Versions of the API, served under their prefix or at the root when asked for
+/// with an `Accept` header like `application/vnd.stackoverflow.v2+json`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ApiVersion {
+    V1,
+    V2,
+}
+
+impl ApiVersion {
+    pub const ALL: [ApiVersion; 2] = [ApiVersion::V1, ApiVersion::V2];
+
+    pub fn prefix(&self) -> &'static str {
+        match self {
+            ApiVersion::V1 => "/v1",
+            ApiVersion::V2 => "/v2",
+        }
+    }
+
+    /// Version asked for by the first versioned media type of an `Accept` header,
+    /// `Err` with that media type if the version does not exist
+    fn from_accept(accept: &str) -> Result<Option<ApiVersion>, String> {
+        let requested = accept
+            .split(',')
+            .map(|media_type| media_type.split(';').next().unwrap_or_default().trim())
+            .find_map(|media_type| {
+                media_type
+                    .strip_prefix("application/vnd.stackoverflow.v")
+                    .and_then(|rest| rest.strip_suffix("+json"))
+                    .map(|version| (media_type, version))
+            });
+
+        match requested {
+            None => Ok(None),
+            Some((_, "1")) => Ok(Some(ApiVersion::V1)),
+            Some((_, "2")) => Ok(Some(ApiVersion::V2)),
+            Some((media_type, _)) => Err(media_type.to_owned()),
+        }
+    }
+}
+
+/// Routes requests without a version prefix to the version asked for in the `Accept`
+/// header, v1 when none is, so that consumers predating versions keep today's responses
+pub async fn negotiate_version(mut request: Request, next: Next) -> Response {
+    let path = request.uri().path();
+    let versioned = ApiVersion::ALL.iter().any(|version| {
+        path.strip_prefix(version.prefix())
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+    });
+    if versioned {
+        return next.run(request).await;
+    }
+
+    let accept = request
+        .headers()
+        .get(header::ACCEPT)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default();
+    let version = match ApiVersion::from_accept(accept) {
+        Ok(version) => version.unwrap_or(ApiVersion::V1),
+        Err(media_type) => {
+            return (
+                StatusCode::NOT_ACCEPTABLE,
+                format!("{media_type} is not a version of this API"),
+            )
+                .into_response();
+        }
+    };
+
+    let path_and_query = request
+        .uri()
+        .path_and_query()
+        .map_or(path, |path_and_query| path_and_query.as_str());
+    let mut parts = request.uri().clone().into_parts();
+    parts.path_and_query = Some(
+        format!("{}{}", version.prefix(), path_and_query)
+            .parse()
+            .expect("A prefixed path is a valid path"),
+    );
+    *request.uri_mut() = Uri::from_parts(parts).expect("Only the path was changed");
+
+    let mut response = next.run(request).await;
+    response
+        .headers_mut()
+        .append(header::VARY, HeaderValue::from_static("accept"));
+    response
+}
+
+/// Redirects the read of a question closed as a duplicate to the original question,
+/// under the same prefix as the request
+fn duplicate_redirect(uri: &Uri, duplicate_of: &str) -> Response {
+    let prefix = uri.path().rsplit_once('/').map_or("", |(prefix, _)| prefix);
+    Redirect::temporary(&format!("{prefix}/{duplicate_of}")).into_response()
+}
+
+// Path parameters are read by name, so that the `slug` of routes nested
+// under `/spaces/{slug}` is ignored by handlers that do not need it
+
+#[derive(Deserialize)]
+pub struct QuestionPath {
+    question_uuid: String,
+}
+
+#[derive(Deserialize)]
+pub struct AttachmentPath {
+    question_uuid: String,
+    attachment_uuid: String,
+}
+
+#[derive(Deserialize)]
+pub struct FlagPath {
+    flag_uuid: String,
+}
+
+#[derive(Deserialize)]
+pub struct WebhookPath {
+    webhook_uuid: String,
+}
+
+#[derive(Deserialize)]
+pub struct UserPath {
+    user_id: String,
+}
+
 // ---- CRUD for Questions ----
 pub async fn create_question(
-    State(AppState { questions_dao, .. }): State<AppState>,
+    SpaceWriter(AppState {
+        database,
+        audit_dao,
+        questions_dao,
+        ..
+    }): SpaceWriter,
+    Caller(caller): Caller,
+    RequestId(request_id): RequestId,
     Json(question): Json<Question>,
 ) -> Result<impl IntoResponse, impl IntoResponse> {
-    handlers_inner::create_question(question, questions_dao.as_ref())
-        .await
-        .map(Json)
+    handlers_inner::create_question(
+        question,
+        caller,
+        request_id,
+        database.as_ref(),
+        audit_dao.as_ref(),
+        questions_dao.as_ref(),
+    )
+    .await
+    .map(Json)
 }
 
 pub async fn read_questions(
-    State(AppState { questions_dao, .. }): State<AppState>,
+    SpaceReader(AppState { questions_dao, .. }): SpaceReader,
 ) -> Result<impl IntoResponse, impl IntoResponse> {
     handlers_inner::read_questions(questions_dao.as_ref())
         .await
         .map(Json)
 }
 
+pub async fn update_question(
+    SpaceWriter(AppState {
+        database,
+        audit_dao,
+        questions_dao,
+        reputation_dao,
+        config,
+        ..
+    }): SpaceWriter,
+    Caller(caller): Caller,
+    RequestId(request_id): RequestId,
+    Json(question_edit): Json<QuestionEdit>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    handlers_inner::update_question(
+        question_edit,
+        caller,
+        config.anonymous_posts_open(),
+        request_id,
+        database.as_ref(),
+        audit_dao.as_ref(),
+        questions_dao.as_ref(),
+        reputation_dao.as_ref(),
+    )
+    .await
+    .map(Json)
+}
+
 pub async fn delete_question(
-    State(AppState { questions_dao, .. }): State<AppState>,
+    SpaceWriter(AppState {
+        database,
+        audit_dao,
+        questions_dao,
+        reputation_dao,
+        config,
+        ..
+    }): SpaceWriter,
+    Caller(caller): Caller,
+    RequestId(request_id): RequestId,
     Json(question_uuid): Json<QuestionId>,
 ) -> Result<(), impl IntoResponse> {
-    handlers_inner::delete_question(question_uuid, questions_dao.as_ref()).await
+    handlers_inner::delete_question(
+        question_uuid,
+        caller,
+        config.anonymous_posts_open(),
+        request_id,
+        database.as_ref(),
+        audit_dao.as_ref(),
+        questions_dao.as_ref(),
+        reputation_dao.as_ref(),
+    )
+    .await
+}
+
+pub async fn restore_question(
+    Admin(admin): Admin,
+    SpaceWriter(AppState {
+        database,
+        audit_dao,
+        questions_dao,
+        ..
+    }): SpaceWriter,
+    RequestId(request_id): RequestId,
+    Path(QuestionId { question_uuid }): Path<QuestionId>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    handlers_inner::restore_question(
+        QuestionId { question_uuid },
+        admin,
+        request_id,
+        database.as_ref(),
+        audit_dao.as_ref(),
+        questions_dao.as_ref(),
+    )
+    .await
+    .map(Json)
+}
+
+/// Questions closed as a duplicate redirect to the question they duplicate, in the same space
+pub async fn read_question(
+    SpaceReader(AppState {
+        questions_dao,
+        views,
+        ..
+    }): SpaceReader,
+    OriginalUri(uri): OriginalUri,
+    Path(QuestionPath { question_uuid, .. }): Path<QuestionPath>,
+) -> Result<Response, handlers_inner::HandlerError> {
+    let question = handlers_inner::read_question(
+        QuestionId { question_uuid },
+        questions_dao.as_ref(),
+        views.as_ref(),
+    )
+    .await?;
+
+    Ok(match &question.duplicate_of {
+        Some(duplicate_of) => duplicate_redirect(&uri, duplicate_of),
+        None => Json(question).into_response(),
+    })
+}
+
+pub async fn read_trending_questions(
+    SpaceReader(AppState { questions_dao, .. }): SpaceReader,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    handlers_inner::read_trending_questions(questions_dao.as_ref())
+        .await
+        .map(Json)
+}
+
+/// Open questions left unanswered for a while, as listed by the last maintenance run
+pub async fn read_needs_attention(
+    SpaceReader(AppState { questions_dao, .. }): SpaceReader,
+    Query(query): Query<PageQuery>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    handlers_inner::read_needs_attention(query, questions_dao.as_ref())
+        .await
+        .map(Json)
+}
+
+pub async fn read_related_questions(
+    SpaceReader(AppState { questions_dao, .. }): SpaceReader,
+    Path(QuestionId { question_uuid }): Path<QuestionId>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    handlers_inner::read_related_questions(QuestionId { question_uuid }, questions_dao.as_ref())
+        .await
+        .map(Json)
+}
+
+pub async fn read_similar_questions(
+    SpaceReader(AppState { questions_dao, .. }): SpaceReader,
+    Json(question): Json<Question>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    handlers_inner::read_similar_questions(question, questions_dao.as_ref())
+        .await
+        .map(Json)
+}
+
+pub async fn close_as_duplicate(
+    SpaceWriter(AppState {
+        database,
+        audit_dao,
+        questions_dao,
+        reputation_dao,
+        config,
+        ..
+    }): SpaceWriter,
+    Caller(caller): Caller,
+    RequestId(request_id): RequestId,
+    Path(QuestionId { question_uuid }): Path<QuestionId>,
+    Json(duplicate_of): Json<DuplicateOf>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    handlers_inner::close_as_duplicate(
+        QuestionId { question_uuid },
+        duplicate_of,
+        caller,
+        config.anonymous_posts_open(),
+        request_id,
+        database.as_ref(),
+        audit_dao.as_ref(),
+        questions_dao.as_ref(),
+        reputation_dao.as_ref(),
+    )
+    .await
+    .map(Json)
 }
 
 // ---- CRUD for Answers ----
 
 pub async fn create_answer(
-    State(AppState { answers_dao, .. }): State<AppState>,
+    SpaceWriter(AppState {
+        database,
+        audit_dao,
+        answers_dao,
+        ..
+    }): SpaceWriter,
+    Caller(caller): Caller,
+    RequestId(request_id): RequestId,
     Json(answer): Json<Answer>,
 ) -> Result<impl IntoResponse, impl IntoResponse> {
-    handlers_inner::create_answer(answer, answers_dao.as_ref())
-        .await
-        .map(Json)
+    handlers_inner::create_answer(
+        answer,
+        caller,
+        request_id,
+        database.as_ref(),
+        audit_dao.as_ref(),
+        answers_dao.as_ref(),
+    )
+    .await
+    .map(Json)
 }
 
 pub async fn read_answers(
-    State(AppState { answers_dao, .. }): State<AppState>,
+    SpaceReader(AppState { answers_dao, .. }): SpaceReader,
     Json(question_uuid): Json<QuestionId>,
 ) -> Result<impl IntoResponse, impl IntoResponse> {
     handlers_inner::read_answers(question_uuid, answers_dao.as_ref())
@@ -60,9 +415,602 @@ pub async fn read_answers(
         .map(Json)
 }
 
+pub async fn update_answer(
+    SpaceWriter(AppState {
+        database,
+        audit_dao,
+        answers_dao,
+        reputation_dao,
+        config,
+        ..
+    }): SpaceWriter,
+    Caller(caller): Caller,
+    RequestId(request_id): RequestId,
+    Json(answer_edit): Json<AnswerEdit>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    handlers_inner::update_answer(
+        answer_edit,
+        caller,
+        config.anonymous_posts_open(),
+        request_id,
+        database.as_ref(),
+        audit_dao.as_ref(),
+        answers_dao.as_ref(),
+        reputation_dao.as_ref(),
+    )
+    .await
+    .map(Json)
+}
+
 pub async fn delete_answer(
-    State(AppState { answers_dao, .. }): State<AppState>,
+    SpaceWriter(AppState {
+        database,
+        audit_dao,
+        answers_dao,
+        reputation_dao,
+        config,
+        ..
+    }): SpaceWriter,
+    Caller(caller): Caller,
+    RequestId(request_id): RequestId,
     Json(answer_uuid): Json<AnswerId>,
 ) -> Result<(), impl IntoResponse> {
-    handlers_inner::delete_answer(answer_uuid, answers_dao.as_ref()).await
+    handlers_inner::delete_answer(
+        answer_uuid,
+        caller,
+        config.anonymous_posts_open(),
+        request_id,
+        database.as_ref(),
+        audit_dao.as_ref(),
+        answers_dao.as_ref(),
+        reputation_dao.as_ref(),
+    )
+    .await
+}
+
+pub async fn highlight_css() -> impl IntoResponse {
+    (
+        [(header::CONTENT_TYPE, "text/css")],
+        markdown::highlight_css(),
+    )
+}
+
+// ---- Attachments ----
+
+/// Reads the `file` field of a multipart body, giving up as soon as it exceeds the size limit
+async fn read_upload(multipart: &mut Multipart) -> Result<Upload, HandlerError> {
+    let bad_request =
+        |e: axum::extract::multipart::MultipartError| HandlerError::BadRequest(e.body_text());
+
+    while let Some(mut field) = multipart.next_field().await.map_err(bad_request)? {
+        if field.name() != Some("file") {
+            continue;
+        }
+
+        let file_name = field.file_name().unwrap_or_default().to_owned();
+        let content_type = field
+            .content_type()
+            .unwrap_or("application/octet-stream")
+            .to_owned();
+        let mut content = Vec::new();
+        while let Some(chunk) = field.chunk().await.map_err(bad_request)? {
+            if content.len() + chunk.len() > MAX_ATTACHMENT_SIZE {
+                return Err(HandlerError::BadRequest(format!(
+                    "Attachments are limited to {MAX_ATTACHMENT_SIZE} bytes"
+                )));
+            }
+            content.extend_from_slice(&chunk);
+        }
+
+        return Ok(Upload {
+            file_name,
+            content_type,
+            content,
+        });
+    }
+
+    Err(HandlerError::BadRequest(
+        "A file field is required".to_owned(),
+    ))
+}
+
+pub async fn create_attachment(
+    SpaceWriter(AppState {
+        database,
+        audit_dao,
+        questions_dao,
+        attachments_dao,
+        reputation_dao,
+        blob_store,
+        config,
+        ..
+    }): SpaceWriter,
+    Caller(caller): Caller,
+    RequestId(request_id): RequestId,
+    Path(QuestionId { question_uuid }): Path<QuestionId>,
+    mut multipart: Multipart,
+) -> Result<impl IntoResponse, HandlerError> {
+    let upload = read_upload(&mut multipart).await?;
+
+    handlers_inner::create_attachment(
+        QuestionId { question_uuid },
+        upload,
+        caller,
+        config.anonymous_posts_open(),
+        request_id,
+        database.as_ref(),
+        audit_dao.as_ref(),
+        questions_dao.as_ref(),
+        attachments_dao.as_ref(),
+        reputation_dao.as_ref(),
+        blob_store.as_ref(),
+    )
+    .await
+    .map(Json)
+}
+
+pub async fn read_attachments(
+    SpaceReader(AppState {
+        attachments_dao, ..
+    }): SpaceReader,
+    Path(QuestionId { question_uuid }): Path<QuestionId>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    handlers_inner::read_attachments(QuestionId { question_uuid }, attachments_dao.as_ref())
+        .await
+        .map(Json)
+}
+
+/// File names end up in a quoted header parameter, anything that could break out of it is replaced
+fn content_disposition(attachment: &AttachmentDetail) -> String {
+    let disposition = if attachment.content_type.starts_with("image/") {
+        "inline"
+    } else {
+        "attachment"
+    };
+    let file_name: String = attachment
+        .file_name
+        .chars()
+        .map(|c| match c {
+            ' '..='~' if c != '"' && c != '\\' => c,
+            _ => '_',
+        })
+        .collect();
+
+    format!("{disposition}; filename=\"{file_name}\"")
+}
+
+pub async fn download_attachment(
+    SpaceReader(AppState {
+        attachments_dao,
+        blob_store,
+        ..
+    }): SpaceReader,
+    Path(AttachmentPath {
+        question_uuid,
+        attachment_uuid,
+    }): Path<AttachmentPath>,
+    headers: HeaderMap,
+) -> Result<Response, HandlerError> {
+    let attachment = handlers_inner::read_attachment(
+        QuestionId { question_uuid },
+        attachment_uuid,
+        attachments_dao.as_ref(),
+    )
+    .await?;
+
+    let size = attachment.size as u64;
+    let range = headers
+        .get(header::RANGE)
+        .and_then(|value| value.to_str().ok());
+    let range = match attachments::parse_range(range, size) {
+        Ok(range) => range,
+        Err(RangeNotSatisfiable) => {
+            return Ok((
+                StatusCode::RANGE_NOT_SATISFIABLE,
+                [(header::CONTENT_RANGE, format!("bytes */{size}"))],
+            )
+                .into_response());
+        }
+    };
+
+    let content =
+        handlers_inner::read_attachment_content(&attachment, range.clone(), blob_store.as_ref())
+            .await?;
+
+    let headers = [
+        (header::CONTENT_TYPE, attachment.content_type.clone()),
+        (
+            header::CONTENT_DISPOSITION,
+            content_disposition(&attachment),
+        ),
+        (header::X_CONTENT_TYPE_OPTIONS, "nosniff".to_owned()),
+        (header::ACCEPT_RANGES, "bytes".to_owned()),
+        (header::ETAG, format!("\"{}\"", attachment.sha256)),
+    ];
+    Ok(match range {
+        Some(range) => (
+            StatusCode::PARTIAL_CONTENT,
+            headers,
+            [(
+                header::CONTENT_RANGE,
+                format!("bytes {}-{}/{size}", range.start, range.end - 1),
+            )],
+            content,
+        )
+            .into_response(),
+        None => (headers, content).into_response(),
+    })
+}
+
+// ---- Users ----
+
+pub async fn read_user(
+    SpaceReader(AppState { reputation_dao, .. }): SpaceReader,
+    Path(UserPath { user_id }): Path<UserPath>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    handlers_inner::read_user(user_id, reputation_dao.as_ref())
+        .await
+        .map(Json)
+}
+
+// ---- Follows and bookmarks ----
+
+pub async fn follow_question(
+    SpaceReader(AppState {
+        database,
+        audit_dao,
+        follows_dao,
+        ..
+    }): SpaceReader,
+    Identity(user_id): Identity,
+    RequestId(request_id): RequestId,
+    Path(QuestionId { question_uuid }): Path<QuestionId>,
+) -> Result<(), impl IntoResponse> {
+    handlers_inner::follow_question(
+        QuestionId { question_uuid },
+        user_id,
+        request_id,
+        database.as_ref(),
+        audit_dao.as_ref(),
+        follows_dao.as_ref(),
+    )
+    .await
+}
+
+pub async fn unfollow_question(
+    SpaceReader(AppState {
+        database,
+        audit_dao,
+        follows_dao,
+        ..
+    }): SpaceReader,
+    Identity(user_id): Identity,
+    RequestId(request_id): RequestId,
+    Path(QuestionId { question_uuid }): Path<QuestionId>,
+) -> Result<(), impl IntoResponse> {
+    handlers_inner::unfollow_question(
+        QuestionId { question_uuid },
+        user_id,
+        request_id,
+        database.as_ref(),
+        audit_dao.as_ref(),
+        follows_dao.as_ref(),
+    )
+    .await
+}
+
+pub async fn bookmark_question(
+    SpaceReader(AppState {
+        database,
+        audit_dao,
+        follows_dao,
+        ..
+    }): SpaceReader,
+    Identity(user_id): Identity,
+    RequestId(request_id): RequestId,
+    Path(QuestionId { question_uuid }): Path<QuestionId>,
+) -> Result<(), impl IntoResponse> {
+    handlers_inner::bookmark_question(
+        QuestionId { question_uuid },
+        user_id,
+        request_id,
+        database.as_ref(),
+        audit_dao.as_ref(),
+        follows_dao.as_ref(),
+    )
+    .await
+}
+
+pub async fn remove_bookmark(
+    SpaceReader(AppState {
+        database,
+        audit_dao,
+        follows_dao,
+        ..
+    }): SpaceReader,
+    Identity(user_id): Identity,
+    RequestId(request_id): RequestId,
+    Path(QuestionId { question_uuid }): Path<QuestionId>,
+) -> Result<(), impl IntoResponse> {
+    handlers_inner::remove_bookmark(
+        QuestionId { question_uuid },
+        user_id,
+        request_id,
+        database.as_ref(),
+        audit_dao.as_ref(),
+        follows_dao.as_ref(),
+    )
+    .await
+}
+
+pub async fn read_bookmarks(
+    SpaceReader(AppState { follows_dao, .. }): SpaceReader,
+    Identity(user_id): Identity,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    handlers_inner::read_bookmarks(user_id, follows_dao.as_ref())
+        .await
+        .map(Json)
+}
+
+pub async fn read_inbox(
+    SpaceReader(AppState { follows_dao, .. }): SpaceReader,
+    Identity(user_id): Identity,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    handlers_inner::read_inbox(user_id, follows_dao.as_ref())
+        .await
+        .map(Json)
+}
+
+pub async fn mark_inbox_read(
+    SpaceReader(AppState {
+        database,
+        audit_dao,
+        follows_dao,
+        ..
+    }): SpaceReader,
+    Identity(user_id): Identity,
+    RequestId(request_id): RequestId,
+    Json(mark_read): Json<MarkRead>,
+) -> Result<(), impl IntoResponse> {
+    handlers_inner::mark_inbox_read(
+        mark_read,
+        user_id,
+        request_id,
+        database.as_ref(),
+        audit_dao.as_ref(),
+        follows_dao.as_ref(),
+    )
+    .await
+}
+
+// ---- Moderation ----
+
+pub async fn create_flag(
+    SpaceWriter(AppState {
+        database,
+        audit_dao,
+        flags_dao,
+        ..
+    }): SpaceWriter,
+    Caller(caller): Caller,
+    RequestId(request_id): RequestId,
+    Json(flag): Json<Flag>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    handlers_inner::create_flag(
+        flag,
+        caller,
+        request_id,
+        database.as_ref(),
+        audit_dao.as_ref(),
+        flags_dao.as_ref(),
+    )
+    .await
+    .map(Json)
+}
+
+pub async fn read_flags(
+    _: Admin,
+    SpaceReader(AppState { flags_dao, .. }): SpaceReader,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    handlers_inner::read_flags(flags_dao.as_ref())
+        .await
+        .map(Json)
+}
+
+pub async fn resolve_flag(
+    Admin(admin): Admin,
+    SpaceWriter(AppState {
+        database,
+        audit_dao,
+        flags_dao,
+        questions_dao,
+        answers_dao,
+        reputation_dao,
+        ..
+    }): SpaceWriter,
+    RequestId(request_id): RequestId,
+    Path(FlagPath { flag_uuid }): Path<FlagPath>,
+    Json(ResolveFlag { resolution }): Json<ResolveFlag>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    handlers_inner::resolve_flag(
+        flag_uuid,
+        resolution,
+        admin,
+        request_id,
+        database.as_ref(),
+        audit_dao.as_ref(),
+        flags_dao.as_ref(),
+        questions_dao.as_ref(),
+        answers_dao.as_ref(),
+        reputation_dao.as_ref(),
+    )
+    .await
+    .map(Json)
+}
+
+// ---- Webhooks ----
+
+pub async fn create_webhook(
+    Admin(admin): Admin,
+    SpaceWriter(AppState {
+        database,
+        audit_dao,
+        webhooks_dao,
+        config,
+        ..
+    }): SpaceWriter,
+    RequestId(request_id): RequestId,
+    Json(webhook): Json<Webhook>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    handlers_inner::create_webhook(
+        webhook,
+        config.webhook_private_targets,
+        admin,
+        request_id,
+        database.as_ref(),
+        audit_dao.as_ref(),
+        webhooks_dao.as_ref(),
+    )
+    .await
+    .map(Json)
+}
+
+pub async fn delete_webhook(
+    Admin(admin): Admin,
+    SpaceWriter(AppState {
+        database,
+        audit_dao,
+        webhooks_dao,
+        ..
+    }): SpaceWriter,
+    RequestId(request_id): RequestId,
+    Path(WebhookPath { webhook_uuid }): Path<WebhookPath>,
+) -> Result<(), impl IntoResponse> {
+    handlers_inner::delete_webhook(
+        webhook_uuid,
+        admin,
+        request_id,
+        database.as_ref(),
+        audit_dao.as_ref(),
+        webhooks_dao.as_ref(),
+    )
+    .await
+}
+
+// ---- Statistics ----
+
+pub async fn read_stats(
+    _: Admin,
+    SpaceReader(AppState { stats_dao, .. }): SpaceReader,
+    Query(query): Query<StatsQuery>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    handlers_inner::read_stats(query, stats_dao.as_ref())
+        .await
+        .map(Json)
+}
+
+/// Counters of the cache of this instance, which is shared by every space
+pub async fn read_cache_metrics(
+    _: Admin,
+    State(AppState { read_cache, .. }): State<AppState>,
+) -> impl IntoResponse {
+    Json(read_cache.metrics())
+}
+
+// ---- Maintenance ----
+
+/// Runs of the maintenance jobs of every instance, most recent first
+pub async fn read_job_runs(
+    _: Admin,
+    State(AppState { jobs_dao, .. }): State<AppState>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    handlers_inner::read_job_runs(jobs_dao.as_ref())
+        .await
+        .map(Json)
+}
+
+// ---- Spaces ----
+
+pub async fn create_space(
+    Admin(admin): Admin,
+    State(AppState {
+        database,
+        audit_dao,
+        spaces_dao,
+        ..
+    }): State<AppState>,
+    RequestId(request_id): RequestId,
+    Json(space): Json<Space>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    handlers_inner::create_space(
+        space,
+        admin,
+        request_id,
+        database.as_ref(),
+        audit_dao.as_ref(),
+        spaces_dao.as_ref(),
+    )
+    .await
+    .map(Json)
+}
+
+pub async fn set_space_member(
+    Admin(admin): Admin,
+    State(AppState {
+        database,
+        audit_dao,
+        spaces_dao,
+        ..
+    }): State<AppState>,
+    RequestId(request_id): RequestId,
+    Path((slug, user_id)): Path<(String, String)>,
+    Json(member): Json<SpaceMember>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    handlers_inner::set_space_member(
+        slug,
+        user_id,
+        member,
+        admin,
+        request_id,
+        database.as_ref(),
+        audit_dao.as_ref(),
+        spaces_dao.as_ref(),
+    )
+    .await
+    .map(Json)
+}
+
+pub async fn remove_space_member(
+    Admin(admin): Admin,
+    State(AppState {
+        database,
+        audit_dao,
+        spaces_dao,
+        ..
+    }): State<AppState>,
+    RequestId(request_id): RequestId,
+    Path((slug, user_id)): Path<(String, String)>,
+) -> Result<(), impl IntoResponse> {
+    handlers_inner::remove_space_member(
+        slug,
+        user_id,
+        admin,
+        request_id,
+        database.as_ref(),
+        audit_dao.as_ref(),
+        spaces_dao.as_ref(),
+    )
+    .await
+}
+
+// ---- Audit log ----
+
+pub async fn read_audit_log(
+    _: Admin,
+    State(AppState { audit_dao, .. }): State<AppState>,
+    Query(query): Query<AuditQuery>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    handlers_inner::read_audit_log(query, audit_dao.as_ref())
+        .await
+        .map(Json)
 }
@@ -0,0 +1,64 @@
+//! Handlers whose responses changed shape in v2, the other routes are shared with v1
+
+use axum::{
+    extract::{OriginalUri, Path, Query},
+    response::{IntoResponse, Response},
+    Json,
+};
+
+use super::{duplicate_redirect, extractors::SpaceReader, handlers_inner, QuestionPath};
+use crate::{
+    models::{AnswerV2, PageQuery, QuestionId, QuestionV2},
+    AppState,
+};
+
+pub async fn read_questions(
+    SpaceReader(AppState { questions_dao, .. }): SpaceReader,
+    Query(query): Query<PageQuery>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    handlers_inner::read_questions_page(query, questions_dao.as_ref())
+        .await
+        .map(|page| Json(page.map(QuestionV2::from)))
+}
+
+pub async fn read_needs_attention(
+    SpaceReader(AppState { questions_dao, .. }): SpaceReader,
+    Query(query): Query<PageQuery>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    handlers_inner::read_needs_attention(query, questions_dao.as_ref())
+        .await
+        .map(|page| Json(page.map(QuestionV2::from)))
+}
+
+pub async fn read_question(
+    SpaceReader(AppState {
+        questions_dao,
+        views,
+        ..
+    }): SpaceReader,
+    OriginalUri(uri): OriginalUri,
+    Path(QuestionPath { question_uuid, .. }): Path<QuestionPath>,
+) -> Result<Response, handlers_inner::HandlerError> {
+    let question = handlers_inner::read_question(
+        QuestionId { question_uuid },
+        questions_dao.as_ref(),
+        views.as_ref(),
+    )
+    .await?;
+
+    Ok(match &question.duplicate_of {
+        Some(duplicate_of) => duplicate_redirect(&uri, duplicate_of),
+        None => Json(QuestionV2::from(question)).into_response(),
+    })
+}
+
+/// Answers are listed under their question rather than read with a body
+pub async fn read_answers(
+    SpaceReader(AppState { answers_dao, .. }): SpaceReader,
+    Path(QuestionPath { question_uuid, .. }): Path<QuestionPath>,
+    Query(query): Query<PageQuery>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    handlers_inner::read_answers_page(QuestionId { question_uuid }, query, answers_dao.as_ref())
+        .await
+        .map(|page| Json(page.map(AnswerV2::from)))
+}
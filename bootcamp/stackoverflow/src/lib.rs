@@ -1,65 +1,366 @@
+#[cfg(feature = "server")]
+mod attachments;
+#[cfg(feature = "client")]
+pub mod client;
+#[cfg(feature = "server")]
+pub mod config;
+#[cfg(feature = "server")]
+mod graphql;
+#[cfg(feature = "server")]
 mod handlers;
-mod models;
+#[cfg(feature = "server")]
+mod maintenance;
+#[cfg(feature = "server")]
+mod markdown;
+#[cfg(all(test, feature = "server"))]
+mod model_tests;
+pub mod models;
+#[cfg(feature = "server")]
 mod persistance;
+#[cfg(feature = "server")]
+mod stats;
+#[cfg(feature = "server")]
+mod views;
+#[cfg(feature = "server")]
+mod webhooks;
 
-use handlers::*;
+#[cfg(feature = "server")]
+use config::Config;
+#[cfg(feature = "server")]
+use handlers::{
+    extractors::{BearerIdentity, HeaderIdentity, IdentityProvider},
+    *,
+};
+#[cfg(feature = "server")]
+use markdown::RenderCache;
+#[cfg(feature = "server")]
 use persistance::{
     answers_dao::{AnswersDao, AnswersDaoImpl},
+    attachments_dao::{AttachmentsDao, AttachmentsDaoImpl},
+    audit_dao::{AuditDao, AuditDaoImpl},
+    blob_store::{BlobStore, LocalBlobStore},
+    flags_dao::{FlagsDao, FlagsDaoImpl},
+    follows_dao::{FollowsDao, FollowsDaoImpl},
+    jobs_dao::{JobsDao, JobsDaoImpl},
     questions_dao::{QuestionsDao, QuestionsDaoImpl},
+    read_cache::{CachedAnswersDao, CachedQuestionsDao, ReadCache},
+    reputation_dao::{ReputationDao, ReputationDaoImpl},
+    spaces_dao::{SpacesDao, SpacesDaoImpl, DEFAULT_SPACE_ID},
+    stats_dao::{StatsDao, StatsDaoImpl},
+    unit_of_work::{Database, DatabaseImpl},
+    webhooks_dao::{WebhooksDao, WebhooksDaoImpl},
 };
-use sqlx::{pool::Pool, Postgres};
+#[cfg(feature = "server")]
+use sqlx::{pool::Pool, types::Uuid, Postgres};
+#[cfg(feature = "server")]
 use std::sync::Arc;
+#[cfg(feature = "server")]
+use views::ViewCounter;
 
+#[cfg(feature = "server")]
 use axum::{
-    routing::{delete, get, post},
+    extract::DefaultBodyLimit,
+    middleware,
+    routing::{delete, get, post, put},
     Router,
 };
 
+#[cfg(feature = "server")]
 #[derive(Clone)]
 pub struct AppState {
+    /// Pool the DAOs of a space are created on
+    pub db: Pool<Postgres>,
+    pub database: Arc<dyn Database + Send + Sync>,
+    pub audit_dao: Arc<dyn AuditDao + Send + Sync>,
     pub questions_dao: Arc<dyn QuestionsDao + Send + Sync>,
     pub answers_dao: Arc<dyn AnswersDao + Send + Sync>,
+    pub webhooks_dao: Arc<dyn WebhooksDao + Send + Sync>,
+    pub flags_dao: Arc<dyn FlagsDao + Send + Sync>,
+    pub reputation_dao: Arc<dyn ReputationDao + Send + Sync>,
+    pub follows_dao: Arc<dyn FollowsDao + Send + Sync>,
+    pub attachments_dao: Arc<dyn AttachmentsDao + Send + Sync>,
+    pub spaces_dao: Arc<dyn SpacesDao + Send + Sync>,
+    pub stats_dao: Arc<dyn StatsDao + Send + Sync>,
+    /// Maintenance of the questions of every space
+    pub jobs_dao: Arc<dyn JobsDao + Send + Sync>,
+    /// Reads of the questions and answers DAOs of every space
+    pub read_cache: Arc<ReadCache>,
+    /// Markdown of the posts of every space rendered to HTML
+    pub render_cache: Arc<RenderCache>,
+    pub blob_store: Arc<dyn BlobStore + Send + Sync>,
+    pub views: Arc<ViewCounter>,
+    pub identity: Arc<dyn IdentityProvider>,
+    pub config: Arc<Config>,
+}
+
+#[cfg(feature = "server")]
+impl AppState {
+    /// Same state with the DAOs that hold content scoped to another space
+    pub fn in_space(&self, space_id: Uuid) -> AppState {
+        AppState {
+            questions_dao: Arc::new(CachedQuestionsDao::new(
+                Arc::new(
+                    QuestionsDaoImpl::in_space(self.db.clone(), space_id)
+                        .with_render_cache(self.render_cache.clone()),
+                ),
+                self.read_cache.clone(),
+                space_id,
+            )),
+            answers_dao: Arc::new(CachedAnswersDao::new(
+                Arc::new(
+                    AnswersDaoImpl::in_space(self.db.clone(), space_id)
+                        .with_render_cache(self.render_cache.clone()),
+                ),
+                self.read_cache.clone(),
+                space_id,
+            )),
+            webhooks_dao: Arc::new(WebhooksDaoImpl::in_space(self.db.clone(), space_id)),
+            flags_dao: Arc::new(FlagsDaoImpl::in_space(self.db.clone(), space_id)),
+            follows_dao: Arc::new(
+                FollowsDaoImpl::in_space(self.db.clone(), space_id)
+                    .with_render_cache(self.render_cache.clone()),
+            ),
+            attachments_dao: Arc::new(AttachmentsDaoImpl::in_space(self.db.clone(), space_id)),
+            stats_dao: Arc::new(StatsDaoImpl::in_space(self.db.clone(), space_id)),
+            reputation_dao: Arc::new(ReputationDaoImpl::in_space(self.db.clone(), space_id)),
+            ..self.clone()
+        }
+    }
 }
 
-pub async fn run(pool: Pool<Postgres>) {
+#[cfg(feature = "server")]
+pub async fn run(pool: Pool<Postgres>, config: Config) {
     let listener = tokio::net::TcpListener::bind("127.0.0.1:8000")
         .await
         .unwrap();
 
-    let app = app(pool);
-    axum::serve(listener, app).await.unwrap();
+    // Deliver webhook events written to the outbox in the background
+    tokio::spawn(webhooks::run_delivery_worker(
+        Arc::new(WebhooksDaoImpl::new(pool.clone())),
+        webhooks::DeliveryConfig {
+            allow_private_targets: config.webhook_private_targets,
+            ..webhooks::DeliveryConfig::default()
+        },
+    ));
+
+    let state = app_state(pool.clone(), config);
+
+    // Forget the reads invalidated by the writes of other instances
+    if state.config.cache_notify {
+        tokio::spawn(persistance::read_cache::run_invalidation_listener(
+            pool,
+            state.read_cache.clone(),
+        ));
+    }
+
+    // Write the views of questions in batches rather than on every read
+    tokio::spawn(views::run_view_flusher(
+        state.views.clone(),
+        state.questions_dao.clone(),
+        views::FLUSH_INTERVAL,
+    ));
+
+    // Delete the blobs of attachments that are gone
+    tokio::spawn(attachments::run_blob_sweeper(
+        state.attachments_dao.clone(),
+        state.blob_store.clone(),
+        attachments::SweepConfig::default(),
+    ));
+
+    // Keep the statistics read by `/stats` up to date
+    tokio::spawn(stats::run_stats_refresher(
+        state.stats_dao.clone(),
+        stats::REFRESH_INTERVAL,
+    ));
+
+    // Close, flag and archive questions at the times of the configured schedules
+    tokio::spawn(maintenance::run_scheduler(
+        state.database.clone(),
+        state.jobs_dao.clone(),
+        state.read_cache.clone(),
+        state.config.maintenance.clone(),
+    ));
+
+    axum::serve(listener, app(state)).await.unwrap();
 }
 
-fn app(pool: Pool<Postgres>) -> Router {
-    let questions_dao = Arc::new(QuestionsDaoImpl::new(pool.clone()));
-    let answers_dao = Arc::new(AnswersDaoImpl::new(pool));
-    let state = AppState {
+#[cfg(feature = "server")]
+pub fn app_state(pool: Pool<Postgres>, config: Config) -> AppState {
+    let database = Arc::new(DatabaseImpl::new(pool.clone()));
+    let audit_dao = Arc::new(AuditDaoImpl::new(pool.clone()));
+    let read_cache = Arc::new(ReadCache::new(
+        config.cache_capacity,
+        config.cache_ttl,
+        config.cache_notify,
+    ));
+    let render_cache = Arc::new(RenderCache::default());
+    let questions_dao = Arc::new(CachedQuestionsDao::new(
+        Arc::new(QuestionsDaoImpl::new(pool.clone()).with_render_cache(render_cache.clone())),
+        read_cache.clone(),
+        DEFAULT_SPACE_ID,
+    ));
+    let answers_dao = Arc::new(CachedAnswersDao::new(
+        Arc::new(AnswersDaoImpl::new(pool.clone()).with_render_cache(render_cache.clone())),
+        read_cache.clone(),
+        DEFAULT_SPACE_ID,
+    ));
+    let webhooks_dao = Arc::new(WebhooksDaoImpl::new(pool.clone()));
+    let flags_dao = Arc::new(FlagsDaoImpl::new(pool.clone()));
+    let reputation_dao = Arc::new(ReputationDaoImpl::new(pool.clone()));
+    let follows_dao =
+        Arc::new(FollowsDaoImpl::new(pool.clone()).with_render_cache(render_cache.clone()));
+    let attachments_dao = Arc::new(AttachmentsDaoImpl::new(pool.clone()));
+    let spaces_dao = Arc::new(SpacesDaoImpl::new(pool.clone()));
+    let stats_dao = Arc::new(StatsDaoImpl::new(pool.clone()));
+    let jobs_dao = Arc::new(JobsDaoImpl::new(pool.clone()));
+    let blob_store = Arc::new(LocalBlobStore::new(config.blob_dir.clone()));
+    let identity: Arc<dyn IdentityProvider> = if config.user_tokens.is_empty() {
+        Arc::new(HeaderIdentity)
+    } else {
+        Arc::new(BearerIdentity::new(config.user_tokens.clone()))
+    };
+    AppState {
+        db: pool,
+        database,
+        audit_dao,
         questions_dao,
         answers_dao,
+        webhooks_dao,
+        flags_dao,
+        reputation_dao,
+        follows_dao,
+        attachments_dao,
+        spaces_dao,
+        stats_dao,
+        jobs_dao,
+        read_cache,
+        render_cache,
+        blob_store,
+        views: Arc::new(ViewCounter::default()),
+        identity,
+        config: Arc::new(config),
+    }
+}
+
+/// Routes of the content of a space, served for the default space at the root
+/// and for every space under `/spaces/{slug}`
+#[cfg(feature = "server")]
+fn space_routes(version: ApiVersion) -> Router<AppState> {
+    let router = match version {
+        ApiVersion::V1 => Router::new()
+            .route("/questions", get(read_questions))
+            .route("/questions/{question_uuid}", get(read_question))
+            .route("/questions/needs-attention", get(read_needs_attention))
+            .route("/answers", get(read_answers)),
+        ApiVersion::V2 => Router::new()
+            .route("/questions", get(v2::read_questions))
+            .route("/questions/{question_uuid}", get(v2::read_question))
+            .route("/questions/needs-attention", get(v2::read_needs_attention))
+            .route("/questions/{question_uuid}/answers", get(v2::read_answers)),
     };
 
-    Router::new()
+    router
         .route("/question", post(create_question))
-        .route("/questions", get(read_questions))
+        .route("/question", put(update_question))
         .route("/question", delete(delete_question))
+        .route("/questions/trending", get(read_trending_questions))
+        .route("/questions/{question_uuid}/restore", post(restore_question))
+        .route(
+            "/questions/{question_uuid}/related",
+            get(read_related_questions),
+        )
+        .route(
+            "/questions/{question_uuid}/duplicate",
+            post(close_as_duplicate),
+        )
+        .route("/questions/similar", post(read_similar_questions))
         .route("/answer", post(create_answer))
-        .route("/answers", get(read_answers))
+        .route("/answer", put(update_answer))
         .route("/answer", delete(delete_answer))
-        .with_state(state)
+        .route(
+            "/questions/{question_uuid}/attachments",
+            post(create_attachment)
+                .get(read_attachments)
+                // Leaves room for the multipart framing around the largest attachment
+                .layer(DefaultBodyLimit::max(
+                    attachments::MAX_ATTACHMENT_SIZE + 64 * 1024,
+                )),
+        )
+        .route(
+            "/questions/{question_uuid}/attachments/{attachment_uuid}",
+            get(download_attachment),
+        )
+        .route(
+            "/questions/{question_uuid}/follow",
+            post(follow_question).delete(unfollow_question),
+        )
+        .route(
+            "/questions/{question_uuid}/bookmark",
+            post(bookmark_question).delete(remove_bookmark),
+        )
+        .route("/me/bookmarks", get(read_bookmarks))
+        .route("/me/inbox", get(read_inbox))
+        .route("/me/inbox/read", post(mark_inbox_read))
+        .route("/webhooks", post(create_webhook))
+        .route("/webhooks/{webhook_uuid}", delete(delete_webhook))
+        .route("/flags", post(create_flag))
+        .route("/moderation/flags", get(read_flags))
+        .route("/moderation/flags/{flag_uuid}/resolve", post(resolve_flag))
+        .route("/stats", get(read_stats))
+        .route("/users/{user_id}", get(read_user))
 }
 
-#[cfg(test)]
+/// Router of the whole API, also driven in process by the tests and benchmarks
+#[cfg(feature = "server")]
+pub fn app(state: AppState) -> Router {
+    let versions = ApiVersion::ALL
+        .into_iter()
+        .fold(Router::new(), |router, version| {
+            router.nest(version.prefix(), api(version))
+        })
+        .with_state(state);
+
+    // Paths are only rewritten before routing by middleware wrapping the whole router
+    Router::new()
+        .fallback_service(versions)
+        .layer(middleware::from_fn(negotiate_version))
+        .layer(middleware::from_fn(request_id))
+}
+
+/// Routes of one version of the API
+#[cfg(feature = "server")]
+fn api(version: ApiVersion) -> Router<AppState> {
+    Router::new()
+        .merge(space_routes(version))
+        .nest("/spaces/{slug}", space_routes(version))
+        .route("/spaces", post(create_space))
+        .route(
+            "/spaces/{slug}/members/{user_id}",
+            put(set_space_member).delete(remove_space_member),
+        )
+        .route("/highlight.css", get(highlight_css))
+        .route("/graphql", get(graphql::graphiql).post(graphql::graphql))
+        .route("/audit", get(read_audit_log))
+        .route("/stats/cache", get(read_cache_metrics))
+        .route("/jobs", get(read_job_runs))
+}
+
+#[cfg(all(test, feature = "server"))]
 mod tests {
     use super::*;
-    use crate::models::*;
+    use crate::{config::MaintenanceConfig, models::*};
     use axum::http::StatusCode;
-    use axum_test::TestServer;
+    use axum_test::{
+        multipart::{MultipartForm, Part},
+        TestServer,
+    };
+    use serde_json::{json, Value};
     use sqlx::PgPool;
 
     /// An e2e test of our app
     #[sqlx::test]
     async fn e2e(pool: PgPool) -> sqlx::Result<()> {
-        let app = app(pool.clone());
+        let app = app(app_state(pool.clone(), Config::default()));
         let server = TestServer::new(app).unwrap();
 
         let test_question = Question {
@@ -72,6 +373,10 @@ mod tests {
         let created_question = create_question_req.await.json::<QuestionDetail>();
         assert_eq!(created_question.title, test_question.title);
         assert_eq!(created_question.description, test_question.description);
+        assert_eq!(
+            created_question.description_html,
+            "<p>Toto description</p>\n"
+        );
         let qid = QuestionId {
             question_uuid: created_question.question_uuid.clone(),
         };
@@ -135,6 +440,1349 @@ mod tests {
         Ok(())
     }
 
+    /// Deleted questions can be flagged, moderated and restored by an admin
+    #[sqlx::test]
+    async fn moderation(pool: PgPool) -> sqlx::Result<()> {
+        let config = Config {
+            admin_token: Some("admin-token".to_owned()),
+            admin_tokens: [("alice-token".to_owned(), "alice".to_owned())].into(),
+            ..Config::default()
+        };
+        let server = TestServer::new(app(app_state(pool.clone(), config))).unwrap();
+
+        let created_question = server
+            .post("/question")
+            .json(&Question {
+                title: "Toto title".to_string(),
+                description: "Toto description".to_string(),
+            })
+            .await
+            .json::<QuestionDetail>();
+
+        // Report the question
+        let flag = server
+            .post("/flags")
+            .add_header("X-User-Id", "toto")
+            .json(&Flag {
+                post_kind: PostKind::Question,
+                post_uuid: created_question.question_uuid.clone(),
+                reason: "Off-topic".to_string(),
+            })
+            .await
+            .json::<FlagDetail>();
+        assert_eq!(flag.reported_by, "toto");
+
+        // The moderation queue is restricted to admins
+        server
+            .get("/moderation/flags")
+            .expect_failure()
+            .await
+            .assert_status(StatusCode::UNAUTHORIZED);
+        let flags = server
+            .get("/moderation/flags")
+            .authorization_bearer("admin-token")
+            .await
+            .json::<Vec<FlagDetail>>();
+        assert_eq!(flags, vec![flag.clone()]);
+
+        // Resolving the flag deletes the question, both in the name of the admin
+        server
+            .post(&format!("/moderation/flags/{}/resolve", flag.flag_uuid))
+            .authorization_bearer("alice-token")
+            .json(&ResolveFlag {
+                resolution: FlagResolution::DeletePost,
+            })
+            .expect_success()
+            .await;
+        let questions_in_db = server.get("/questions").await.json::<Vec<QuestionDetail>>();
+        assert!(questions_in_db.is_empty());
+        let resolved_by: String =
+            sqlx::query_scalar("SELECT resolved_by FROM flags WHERE flag_uuid = $1::uuid")
+                .bind(&flag.flag_uuid)
+                .fetch_one(&pool)
+                .await?;
+        assert_eq!(resolved_by, "alice");
+        let deleted_by: String =
+            sqlx::query_scalar("SELECT deleted_by FROM questions WHERE question_uuid = $1::uuid")
+                .bind(&created_question.question_uuid)
+                .fetch_one(&pool)
+                .await?;
+        assert_eq!(deleted_by, "alice");
+
+        // Restore the question
+        let restore_path = format!("/questions/{}/restore", created_question.question_uuid);
+        server
+            .post(&restore_path)
+            .authorization_bearer("wrong-token")
+            .expect_failure()
+            .await
+            .assert_status(StatusCode::UNAUTHORIZED);
+        let restored_question = server
+            .post(&restore_path)
+            .authorization_bearer("admin-token")
+            .await
+            .json::<QuestionDetail>();
+        assert_eq!(restored_question, created_question);
+        let questions_in_db = server.get("/questions").await.json::<Vec<QuestionDetail>>();
+        assert_eq!(questions_in_db, vec![created_question]);
+
+        Ok(())
+    }
+
+    /// Reads are counted in memory and only show up once flushed, trending questions
+    /// are ranked by their views
+    #[sqlx::test]
+    async fn views(pool: PgPool) -> sqlx::Result<()> {
+        let state = app_state(pool.clone(), Config::default());
+        let server = TestServer::new(app(state.clone())).unwrap();
+
+        let mut questions = Vec::new();
+        for title in ["Rarely read", "Often read"] {
+            let question = server
+                .post("/question")
+                .json(&Question {
+                    title: title.to_string(),
+                    description: "Toto description".to_string(),
+                })
+                .await
+                .json::<QuestionDetail>();
+            questions.push(question);
+        }
+        let path = |question: &QuestionDetail| format!("/questions/{}", question.question_uuid);
+
+        server.get(&path(&questions[0])).expect_success().await;
+        for _ in 0..3 {
+            server.get(&path(&questions[1])).expect_success().await;
+        }
+
+        // Nothing is written until the counter is flushed
+        let question = server
+            .get(&path(&questions[1]))
+            .await
+            .json::<QuestionDetail>();
+        assert_eq!(question.view_count, 0);
+        let trending = server
+            .get("/questions/trending")
+            .await
+            .json::<Vec<QuestionDetail>>();
+        assert!(trending.is_empty());
+
+        let flushed = views::flush(&state.views, state.questions_dao.as_ref())
+            .await
+            .unwrap();
+        assert_eq!(flushed, 2);
+
+        let question = server
+            .get(&path(&questions[1]))
+            .await
+            .json::<QuestionDetail>();
+        assert_eq!(question.view_count, 4);
+        let trending = server
+            .get("/questions/trending")
+            .await
+            .json::<Vec<QuestionDetail>>();
+        let titles: Vec<&str> = trending.iter().map(|q| q.title.as_str()).collect();
+        assert_eq!(titles, vec!["Often read", "Rarely read"]);
+
+        Ok(())
+    }
+
+    /// Followers of a question are told about its new answers, callers identify with a bearer
+    /// token when tokens are configured
+    #[sqlx::test]
+    async fn follows(pool: PgPool) -> sqlx::Result<()> {
+        let config = Config {
+            user_tokens: [("toto-token", "toto"), ("titi-token", "titi")]
+                .into_iter()
+                .map(|(token, user)| (token.to_owned(), user.to_owned()))
+                .collect(),
+            ..Config::default()
+        };
+        let server = TestServer::new(app(app_state(pool.clone(), config))).unwrap();
+
+        let question = server
+            .post("/question")
+            .authorization_bearer("toto-token")
+            .json(&Question {
+                title: "Toto title".to_string(),
+                description: "Toto description".to_string(),
+            })
+            .await
+            .json::<QuestionDetail>();
+        let follow_path = format!("/questions/{}/follow", question.question_uuid);
+
+        // The header is not trusted once tokens are configured
+        server
+            .post(&follow_path)
+            .add_header("X-User-Id", "toto")
+            .expect_failure()
+            .await
+            .assert_status(StatusCode::UNAUTHORIZED);
+        server
+            .post(&follow_path)
+            .authorization_bearer("toto-token")
+            .expect_success()
+            .await;
+
+        let answer = server
+            .post("/answer")
+            .authorization_bearer("titi-token")
+            .json(&Answer {
+                question_uuid: question.question_uuid.clone(),
+                content: "Answer content".to_string(),
+            })
+            .await
+            .json::<AnswerDetail>();
+
+        let inbox = server
+            .get("/me/inbox")
+            .authorization_bearer("toto-token")
+            .await
+            .json::<Vec<InboxItem>>();
+        assert_eq!(inbox.len(), 1);
+        assert_eq!(inbox[0].answer_uuid, answer.answer_uuid);
+        assert_eq!(inbox[0].question_title, "Toto title");
+        let titi_inbox = server
+            .get("/me/inbox")
+            .authorization_bearer("titi-token")
+            .await
+            .json::<Vec<InboxItem>>();
+        assert!(titi_inbox.is_empty());
+
+        server
+            .post("/me/inbox/read")
+            .authorization_bearer("toto-token")
+            .json(&MarkRead {
+                item_uuids: vec![inbox[0].item_uuid.clone()],
+            })
+            .expect_success()
+            .await;
+        let inbox = server
+            .get("/me/inbox")
+            .authorization_bearer("toto-token")
+            .await
+            .json::<Vec<InboxItem>>();
+        assert!(inbox.is_empty());
+
+        // Bookmarks
+        let bookmark_path = format!("/questions/{}/bookmark", question.question_uuid);
+        server
+            .post(&bookmark_path)
+            .authorization_bearer("titi-token")
+            .expect_success()
+            .await;
+        let bookmarks = server
+            .get("/me/bookmarks")
+            .authorization_bearer("titi-token")
+            .await
+            .json::<Vec<QuestionDetail>>();
+        assert_eq!(bookmarks, vec![question]);
+        server
+            .delete(&bookmark_path)
+            .authorization_bearer("titi-token")
+            .expect_success()
+            .await;
+        let bookmarks = server
+            .get("/me/bookmarks")
+            .authorization_bearer("titi-token")
+            .await
+            .json::<Vec<QuestionDetail>>();
+        assert!(bookmarks.is_empty());
+
+        Ok(())
+    }
+
+    /// Once tokens are configured, the `X-User-Id` header does not make anyone the author of
+    /// a post
+    #[sqlx::test]
+    async fn spoofed_caller(pool: PgPool) -> sqlx::Result<()> {
+        let config = Config {
+            user_tokens: [("toto-token".to_owned(), "toto".to_owned())]
+                .into_iter()
+                .collect(),
+            ..Config::default()
+        };
+        let server = TestServer::new(app(app_state(pool.clone(), config))).unwrap();
+
+        let question = server
+            .post("/question")
+            .authorization_bearer("toto-token")
+            .json(&Question {
+                title: "Toto title".to_string(),
+                description: "Toto description".to_string(),
+            })
+            .await
+            .json::<QuestionDetail>();
+        assert_eq!(question.author, "toto");
+
+        // Posts made with the header only are anonymous
+        let answer = server
+            .post("/answer")
+            .add_header("X-User-Id", "toto")
+            .json(&Answer {
+                question_uuid: question.question_uuid.clone(),
+                content: "Answer content".to_string(),
+            })
+            .await
+            .json::<AnswerDetail>();
+        assert_eq!(answer.author, ANONYMOUS);
+
+        // The header does not let anyone edit or delete the posts of toto
+        server
+            .put("/question")
+            .add_header("X-User-Id", "toto")
+            .json(&QuestionEdit {
+                question_uuid: question.question_uuid.clone(),
+                title: "Spoofed title".to_string(),
+                description: "Spoofed description".to_string(),
+            })
+            .expect_failure()
+            .await
+            .assert_status(StatusCode::FORBIDDEN);
+        server
+            .delete("/question")
+            .add_header("X-User-Id", "toto")
+            .json(&QuestionId {
+                question_uuid: question.question_uuid.clone(),
+            })
+            .expect_failure()
+            .await
+            .assert_status(StatusCode::FORBIDDEN);
+        let questions_in_db = server.get("/questions").await.json::<Vec<QuestionDetail>>();
+        assert_eq!(questions_in_db, vec![question.clone()]);
+
+        server
+            .delete("/question")
+            .authorization_bearer("toto-token")
+            .json(&QuestionId {
+                question_uuid: question.question_uuid.clone(),
+            })
+            .expect_success()
+            .await;
+
+        Ok(())
+    }
+
+    /// Files attached to a question are stored once and served back with ranges
+    #[sqlx::test]
+    async fn attachments(pool: PgPool) -> sqlx::Result<()> {
+        let blob_dir = tempfile::tempdir().unwrap();
+        let config = Config {
+            blob_dir: blob_dir.path().to_owned(),
+            ..Config::default()
+        };
+        let server = TestServer::new(app(app_state(pool.clone(), config))).unwrap();
+
+        let question = server
+            .post("/question")
+            .add_header("X-User-Id", "toto")
+            .json(&Question {
+                title: "Toto title".to_string(),
+                description: "Toto description".to_string(),
+            })
+            .await
+            .json::<QuestionDetail>();
+        let attachments_path = format!("/questions/{}/attachments", question.question_uuid);
+        let form = |mime_type: &str| {
+            MultipartForm::new().add_part(
+                "file",
+                Part::bytes(b"0123456789".as_slice())
+                    .file_name("digits.txt")
+                    .mime_type(mime_type),
+            )
+        };
+
+        let attachment = server
+            .post(&attachments_path)
+            .add_header("X-User-Id", "toto")
+            .multipart(form("text/plain"))
+            .await
+            .json::<AttachmentDetail>();
+        assert_eq!(attachment.file_name, "digits.txt");
+        assert_eq!(attachment.size, 10);
+        let copy = server
+            .post(&attachments_path)
+            .add_header("X-User-Id", "toto")
+            .multipart(form("text/plain"))
+            .await
+            .json::<AttachmentDetail>();
+        assert_eq!(copy.sha256, attachment.sha256);
+
+        // Only the author can attach files, and only files of allowed types
+        server
+            .post(&attachments_path)
+            .add_header("X-User-Id", "titi")
+            .multipart(form("text/plain"))
+            .expect_failure()
+            .await
+            .assert_status(StatusCode::FORBIDDEN);
+        server
+            .post(&attachments_path)
+            .add_header("X-User-Id", "toto")
+            .multipart(form("text/html"))
+            .expect_failure()
+            .await
+            .assert_status(StatusCode::BAD_REQUEST);
+
+        let attachments = server
+            .get(&attachments_path)
+            .await
+            .json::<Vec<AttachmentDetail>>();
+        assert_eq!(attachments, vec![attachment.clone(), copy]);
+
+        let download_path = format!("{}/{}", attachments_path, attachment.attachment_uuid);
+        let download = server.get(&download_path).await;
+        download.assert_status_ok();
+        download.assert_header("Content-Type", "text/plain");
+        download.assert_header("X-Content-Type-Options", "nosniff");
+        assert_eq!(download.as_bytes().as_ref(), b"0123456789");
+
+        let partial = server
+            .get(&download_path)
+            .add_header("Range", "bytes=2-4")
+            .await;
+        partial.assert_status(StatusCode::PARTIAL_CONTENT);
+        partial.assert_header("Content-Range", "bytes 2-4/10");
+        assert_eq!(partial.as_bytes().as_ref(), b"234");
+
+        server
+            .get(&download_path)
+            .add_header("Range", "bytes=10-")
+            .expect_failure()
+            .await
+            .assert_status(StatusCode::RANGE_NOT_SATISFIABLE);
+
+        Ok(())
+    }
+
+    /// Mutations are recorded with the request that made them, only admins read the log
+    #[sqlx::test]
+    async fn audit(pool: PgPool) -> sqlx::Result<()> {
+        let config = Config {
+            admin_token: Some("admin-token".to_owned()),
+            admin_tokens: [("alice-token".to_owned(), "alice".to_owned())].into(),
+            ..Config::default()
+        };
+        let server = TestServer::new(app(app_state(pool.clone(), config))).unwrap();
+
+        let response = server
+            .post("/question")
+            .add_header("X-User-Id", "toto")
+            .json(&Question {
+                title: "Toto title".to_string(),
+                description: "Toto description".to_string(),
+            })
+            .await;
+        // Requests without an id are given one
+        let create_request_id = response.header("X-Request-Id").to_str().unwrap().to_owned();
+        assert!(!create_request_id.is_empty());
+        let question = response.json::<QuestionDetail>();
+
+        server
+            .delete("/question")
+            .add_header("X-User-Id", "toto")
+            .add_header("X-Request-Id", "delete-request")
+            .json(&QuestionId {
+                question_uuid: question.question_uuid.clone(),
+            })
+            .expect_success()
+            .await
+            .assert_header("X-Request-Id", "delete-request");
+
+        // Named admins are recorded under their own name
+        server
+            .post(&format!("/questions/{}/restore", question.question_uuid))
+            .authorization_bearer("alice-token")
+            .expect_success()
+            .await;
+
+        let audit_path = format!("/audit?resource_id={}", question.question_uuid);
+        server
+            .get(&audit_path)
+            .expect_failure()
+            .await
+            .assert_status(StatusCode::UNAUTHORIZED);
+
+        let records = server
+            .get(&audit_path)
+            .authorization_bearer("admin-token")
+            .await
+            .json::<Vec<AuditRecord>>();
+        assert_eq!(records.len(), 3);
+        assert_eq!(records[0].action, "question.restored");
+        assert_eq!(records[0].actor, "alice");
+        assert_eq!(records[1].action, "question.deleted");
+        assert_eq!(records[1].actor, "toto");
+        assert_eq!(records[1].before.as_ref().unwrap()["title"], "Toto title");
+        assert_eq!(records[1].after, None);
+        assert_eq!(records[1].request_id.as_deref(), Some("delete-request"));
+        assert_eq!(records[2].action, "question.created");
+        assert_eq!(records[2].request_id, Some(create_request_id));
+
+        let records = server
+            .get("/audit?actor=titi")
+            .authorization_bearer("admin-token")
+            .await
+            .json::<Vec<AuditRecord>>();
+        assert!(records.is_empty());
+        server
+            .get("/audit?since=someday")
+            .authorization_bearer("admin-token")
+            .expect_failure()
+            .await
+            .assert_status(StatusCode::BAD_REQUEST);
+
+        Ok(())
+    }
+
+    /// Posts stay within their space, and private spaces are only read by their members and
+    /// written by their writers
+    #[sqlx::test]
+    async fn spaces(pool: PgPool) -> sqlx::Result<()> {
+        let config = Config {
+            admin_token: Some("admin-token".to_owned()),
+            ..Config::default()
+        };
+        let server = TestServer::new(app(app_state(pool.clone(), config))).unwrap();
+
+        let team = Space {
+            slug: "team".to_owned(),
+            name: "Team".to_owned(),
+            open: false,
+        };
+        server
+            .post("/spaces")
+            .json(&team)
+            .expect_failure()
+            .await
+            .assert_status(StatusCode::UNAUTHORIZED);
+        server
+            .post("/spaces")
+            .authorization_bearer("admin-token")
+            .json(&team)
+            .await
+            .assert_status_ok();
+        for (user_id, role) in [("toto", SpaceRole::Writer), ("titi", SpaceRole::Reader)] {
+            server
+                .put(&format!("/spaces/team/members/{user_id}"))
+                .authorization_bearer("admin-token")
+                .json(&SpaceMember { role })
+                .await
+                .assert_status_ok();
+        }
+
+        let question = Question {
+            title: "Team title".to_string(),
+            description: "Team description".to_string(),
+        };
+        let original = server
+            .post("/spaces/team/question")
+            .add_header("X-User-Id", "toto")
+            .json(&question)
+            .await
+            .json::<QuestionDetail>();
+        let duplicate = server
+            .post("/spaces/team/question")
+            .add_header("X-User-Id", "toto")
+            .json(&question)
+            .await
+            .json::<QuestionDetail>();
+
+        // Readers read but do not write, strangers do neither
+        server
+            .post("/spaces/team/question")
+            .add_header("X-User-Id", "titi")
+            .json(&question)
+            .expect_failure()
+            .await
+            .assert_status(StatusCode::FORBIDDEN);
+        let questions = server
+            .get("/spaces/team/questions")
+            .add_header("X-User-Id", "titi")
+            .await
+            .json::<Vec<QuestionDetail>>();
+        assert_eq!(questions.len(), 2);
+        server
+            .get("/spaces/team/questions")
+            .add_header("X-User-Id", "tutu")
+            .expect_failure()
+            .await
+            .assert_status(StatusCode::FORBIDDEN);
+        server
+            .get("/spaces/team/questions")
+            .expect_failure()
+            .await
+            .assert_status(StatusCode::UNAUTHORIZED);
+        server
+            .get("/spaces/unknown/questions")
+            .add_header("X-User-Id", "toto")
+            .expect_failure()
+            .await
+            .assert_status(StatusCode::FORBIDDEN);
+
+        // Nothing of the space shows outside of it, the default space is open to all
+        let questions = server.get("/questions").await.json::<Vec<QuestionDetail>>();
+        assert!(questions.is_empty());
+        let questions = server
+            .get("/spaces/default/questions")
+            .await
+            .json::<Vec<QuestionDetail>>();
+        assert!(questions.is_empty());
+        server
+            .get(&format!("/questions/{}", original.question_uuid))
+            .expect_failure()
+            .await
+            .assert_status(StatusCode::BAD_REQUEST);
+
+        // Reputation only counts in the space where it was earned
+        let profile = server.get("/users/toto").await.json::<UserProfile>();
+        assert_eq!((profile.reputation, profile.question_count), (0, 0));
+        let profile = server
+            .get("/spaces/team/users/toto")
+            .add_header("X-User-Id", "titi")
+            .await
+            .json::<UserProfile>();
+        assert_eq!((profile.reputation, profile.question_count), (10, 2));
+        server
+            .get("/spaces/team/users/toto")
+            .add_header("X-User-Id", "tutu")
+            .expect_failure()
+            .await
+            .assert_status(StatusCode::FORBIDDEN);
+
+        // Readers keep their own bookmarks
+        server
+            .post(&format!(
+                "/spaces/team/questions/{}/bookmark",
+                original.question_uuid
+            ))
+            .add_header("X-User-Id", "titi")
+            .await
+            .assert_status_ok();
+        let bookmarks = server
+            .get("/spaces/team/me/bookmarks")
+            .add_header("X-User-Id", "titi")
+            .await
+            .json::<Vec<QuestionDetail>>();
+        assert_eq!(bookmarks, vec![original.clone()]);
+
+        // Duplicates redirect within the space
+        server
+            .post(&format!(
+                "/spaces/team/questions/{}/duplicate",
+                duplicate.question_uuid
+            ))
+            .add_header("X-User-Id", "toto")
+            .json(&DuplicateOf {
+                duplicate_of: original.question_uuid.clone(),
+            })
+            .await
+            .assert_status_ok();
+        let response = server
+            .get(&format!(
+                "/spaces/team/questions/{}",
+                duplicate.question_uuid
+            ))
+            .add_header("X-User-Id", "titi")
+            .await;
+        response.assert_status(StatusCode::TEMPORARY_REDIRECT);
+        response.assert_header(
+            "location",
+            format!("/spaces/team/questions/{}", original.question_uuid),
+        );
+
+        server
+            .delete("/spaces/team/members/titi")
+            .authorization_bearer("admin-token")
+            .await
+            .assert_status_ok();
+        server
+            .get("/spaces/team/questions")
+            .add_header("X-User-Id", "titi")
+            .expect_failure()
+            .await
+            .assert_status(StatusCode::FORBIDDEN);
+
+        Ok(())
+    }
+
+    /// Posting earns reputation, which is required to delete the posts of others
+    #[sqlx::test]
+    async fn reputation(pool: PgPool) -> sqlx::Result<()> {
+        let server = TestServer::new(app(app_state(pool.clone(), Config::default()))).unwrap();
+
+        let created_question = server
+            .post("/question")
+            .add_header("X-User-Id", "toto")
+            .json(&Question {
+                title: "Toto title".to_string(),
+                description: "Toto description".to_string(),
+            })
+            .await
+            .json::<QuestionDetail>();
+        assert_eq!(created_question.author, "toto");
+        server
+            .post("/answer")
+            .add_header("X-User-Id", "toto")
+            .json(&Answer {
+                question_uuid: created_question.question_uuid.clone(),
+                content: "Answer content".to_string(),
+            })
+            .expect_success()
+            .await;
+
+        let profile = server.get("/users/toto").await.json::<UserProfile>();
+        assert_eq!(
+            profile,
+            UserProfile {
+                user_id: "toto".to_owned(),
+                reputation: 15,
+                question_count: 1,
+                answer_count: 1,
+            }
+        );
+
+        // Neither anonymous nor low reputation users can delete the question
+        let qid = QuestionId {
+            question_uuid: created_question.question_uuid.clone(),
+        };
+        server
+            .delete("/question")
+            .json(&qid)
+            .expect_failure()
+            .await
+            .assert_status(StatusCode::FORBIDDEN);
+        server
+            .delete("/question")
+            .add_header("X-User-Id", "titi")
+            .json(&qid)
+            .expect_failure()
+            .await
+            .assert_status(StatusCode::FORBIDDEN);
+
+        // Until they earn enough reputation
+        sqlx::query(
+            "INSERT INTO reputation_events ( user_id, reason, points ) VALUES ( 'titi', 'bonus', 100 )",
+        )
+        .execute(&pool)
+        .await?;
+        server
+            .delete("/question")
+            .add_header("X-User-Id", "titi")
+            .json(&qid)
+            .expect_success()
+            .await;
+
+        let profile = server.get("/users/toto").await.json::<UserProfile>();
+        assert_eq!(profile.question_count, 0);
+
+        Ok(())
+    }
+
+    /// Markdown is rendered to sanitised HTML, and re-rendered when edited
+    #[sqlx::test]
+    async fn markdown(pool: PgPool) -> sqlx::Result<()> {
+        let server = TestServer::new(app(app_state(pool.clone(), Config::default()))).unwrap();
+
+        let created_question = server
+            .post("/question")
+            .add_header("X-User-Id", "toto")
+            .json(&Question {
+                title: "Toto title".to_string(),
+                description: "```rust\nfn main() {}\n```\n<script>alert(1)</script>".to_string(),
+            })
+            .await
+            .json::<QuestionDetail>();
+        assert!(created_question
+            .description_html
+            .contains("<code class=\"language-rust\">"));
+        assert!(!created_question.description_html.contains("<script"));
+
+        // Only the author can edit a question
+        let question_edit = QuestionEdit {
+            question_uuid: created_question.question_uuid.clone(),
+            title: "Toto title".to_string(),
+            description: "*edited*".to_string(),
+        };
+        server
+            .put("/question")
+            .add_header("X-User-Id", "titi")
+            .json(&question_edit)
+            .expect_failure()
+            .await
+            .assert_status(StatusCode::FORBIDDEN);
+        let edited_question = server
+            .put("/question")
+            .add_header("X-User-Id", "toto")
+            .json(&question_edit)
+            .await
+            .json::<QuestionDetail>();
+        assert_eq!(edited_question.description_html, "<p><em>edited</em></p>\n");
+
+        let questions_in_db = server.get("/questions").await.json::<Vec<QuestionDetail>>();
+        assert_eq!(questions_in_db, vec![edited_question]);
+
+        server
+            .get("/highlight.css")
+            .await
+            .assert_header("content-type", "text/css");
+
+        Ok(())
+    }
+
+    /// Similar questions are suggested, and reading a duplicate redirects to the original
+    #[sqlx::test]
+    async fn duplicates(pool: PgPool) -> sqlx::Result<()> {
+        let server = TestServer::new(app(app_state(pool.clone(), Config::default()))).unwrap();
+
+        let original = server
+            .post("/question")
+            .add_header("X-User-Id", "toto")
+            .json(&Question {
+                title: "How to sort a vector in Rust".to_string(),
+                description: "I have a Vec<i32>".to_string(),
+            })
+            .await
+            .json::<QuestionDetail>();
+
+        // Warn about the original before asking again
+        let draft = Question {
+            title: "How to sort a vector in Rust?".to_string(),
+            description: "I have a Vec<u8>".to_string(),
+        };
+        let similar = server
+            .post("/questions/similar")
+            .json(&draft)
+            .await
+            .json::<Vec<SimilarQuestion>>();
+        assert_eq!(similar.len(), 1);
+        assert_eq!(similar[0].question_uuid, original.question_uuid);
+
+        let duplicate = server
+            .post("/question")
+            .add_header("X-User-Id", "titi")
+            .json(&draft)
+            .await
+            .json::<QuestionDetail>();
+        let related = server
+            .get(&format!("/questions/{}/related", duplicate.question_uuid))
+            .await
+            .json::<Vec<SimilarQuestion>>();
+        assert_eq!(related[0].question_uuid, original.question_uuid);
+
+        // Only the author or privileged users can close a question
+        let duplicate_path = format!("/questions/{}/duplicate", duplicate.question_uuid);
+        let duplicate_of = DuplicateOf {
+            duplicate_of: original.question_uuid.clone(),
+        };
+        server
+            .post(&duplicate_path)
+            .add_header("X-User-Id", "toto")
+            .json(&duplicate_of)
+            .expect_failure()
+            .await
+            .assert_status(StatusCode::FORBIDDEN);
+        let closed = server
+            .post(&duplicate_path)
+            .add_header("X-User-Id", "titi")
+            .json(&duplicate_of)
+            .await
+            .json::<QuestionDetail>();
+        assert_eq!(closed.duplicate_of, Some(original.question_uuid.clone()));
+
+        // Reads are redirected to the original
+        let response = server
+            .get(&format!("/questions/{}", duplicate.question_uuid))
+            .await;
+        response.assert_status(StatusCode::TEMPORARY_REDIRECT);
+        response.assert_header("location", format!("/questions/{}", original.question_uuid));
+        let read = server
+            .get(&format!("/questions/{}", original.question_uuid))
+            .await
+            .json::<QuestionDetail>();
+        assert_eq!(read, original);
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn graphql(pool: PgPool) -> sqlx::Result<()> {
+        let server = TestServer::new(app(app_state(pool.clone(), Config::default()))).unwrap();
+        let graphql = |user_id: &str, query: String| {
+            server
+                .post("/graphql")
+                .add_header("X-User-Id", user_id)
+                .json(&json!({ "query": query }))
+        };
+
+        let mut question_uuids = Vec::new();
+        for title in ["First", "Second", "Third"] {
+            let created = graphql(
+                "toto",
+                format!(
+                    r#"mutation {{ createQuestion(title: "{title}", description: "*{title}*") {{ questionUuid }} }}"#
+                ),
+            )
+            .await
+            .json::<Value>();
+            question_uuids.push(created["data"]["createQuestion"]["questionUuid"].clone());
+        }
+        for (question_uuid, content) in [(&question_uuids[0], "A"), (&question_uuids[0], "B")] {
+            graphql(
+                "titi",
+                format!(
+                    r#"mutation {{ createAnswer(questionUuid: {question_uuid}, content: "{content}") {{ answerUuid }} }}"#
+                ),
+            )
+            .await;
+        }
+
+        // Questions and their answers in a single round trip
+        let page_query = |after: &str| {
+            format!(
+                r#"{{ questions(first: 2{after}) {{
+                    pageInfo {{ hasNextPage endCursor }}
+                    edges {{ node {{
+                        title descriptionHtml
+                        answers {{ edges {{ node {{ content author question {{ title }} }} }} }}
+                    }} }}
+                }} }}"#
+            )
+        };
+        let first_page = graphql("toto", page_query("")).await.json::<Value>();
+        let questions = &first_page["data"]["questions"];
+        assert_eq!(questions["pageInfo"]["hasNextPage"], true);
+        assert_eq!(questions["edges"].as_array().unwrap().len(), 2);
+        let first = &questions["edges"][0]["node"];
+        assert_eq!(first["title"], "First");
+        assert_eq!(first["descriptionHtml"], "<p><em>First</em></p>\n");
+        let answers = &first["answers"]["edges"];
+        assert_eq!(answers[0]["node"]["content"], "A");
+        assert_eq!(answers[1]["node"]["content"], "B");
+        assert_eq!(answers[1]["node"]["author"], "titi");
+        assert_eq!(answers[1]["node"]["question"]["title"], "First");
+        assert_eq!(questions["edges"][1]["node"]["answers"]["edges"], json!([]));
+
+        let end_cursor = questions["pageInfo"]["endCursor"].as_str().unwrap();
+        let last_page = graphql("toto", page_query(&format!(r#", after: "{end_cursor}""#)))
+            .await
+            .json::<Value>();
+        let questions = &last_page["data"]["questions"];
+        assert_eq!(questions["pageInfo"]["hasNextPage"], false);
+        assert_eq!(questions["edges"][0]["node"]["title"], "Third");
+
+        // Mutations are subject to the same permissions as the REST API
+        let delete = |user_id: &str| {
+            graphql(
+                user_id,
+                format!(
+                    "mutation {{ deleteQuestion(questionUuid: {}) }}",
+                    question_uuids[2]
+                ),
+            )
+        };
+        let forbidden = delete("titi").await.json::<Value>();
+        assert_eq!(forbidden["errors"][0]["extensions"]["code"], "FORBIDDEN");
+        let deleted = delete("toto").await.json::<Value>();
+        assert_eq!(deleted["data"]["deleteQuestion"], true);
+
+        Ok(())
+    }
+
+    /// Statistics are for admins, and only account for the posts of the space they are read in
+    #[sqlx::test]
+    async fn stats(pool: PgPool) -> sqlx::Result<()> {
+        let config = Config {
+            admin_token: Some("admin-token".to_owned()),
+            ..Config::default()
+        };
+        let server = TestServer::new(app(app_state(pool.clone(), config))).unwrap();
+
+        let question = server
+            .post("/question")
+            .json(&Question {
+                title: "Stats title".to_string(),
+                description: "Stats description".to_string(),
+            })
+            .await
+            .json::<QuestionDetail>();
+        server
+            .post("/answer")
+            .json(&Answer {
+                question_uuid: question.question_uuid,
+                content: "Stats answer".to_string(),
+            })
+            .await
+            .assert_status_ok();
+        server
+            .post("/spaces")
+            .authorization_bearer("admin-token")
+            .json(&Space {
+                slug: "team".to_owned(),
+                name: "Team".to_owned(),
+                open: true,
+            })
+            .await
+            .assert_status_ok();
+
+        server
+            .get("/stats")
+            .expect_failure()
+            .await
+            .assert_status(StatusCode::UNAUTHORIZED);
+        server
+            .get("/stats")
+            .authorization_bearer("admin-token")
+            .add_query_param("days", 0)
+            .expect_failure()
+            .await
+            .assert_status(StatusCode::BAD_REQUEST);
+
+        // The background refresher is not running in tests
+        StatsDaoImpl::new(pool.clone())
+            .refresh_stats()
+            .await
+            .unwrap();
+
+        let stats = server
+            .get("/stats")
+            .authorization_bearer("admin-token")
+            .add_query_param("days", 3)
+            .await
+            .json::<Stats>();
+        assert_eq!((stats.questions, stats.answers), (1, 1));
+        assert_eq!(stats.unanswered_questions, 0);
+        assert!(stats.median_seconds_to_first_answer.is_some());
+        assert_eq!(stats.posts_per_day.len(), 3);
+        assert_eq!(stats.posts_per_day[2].questions, 1);
+
+        let stats = server
+            .get("/spaces/team/stats")
+            .authorization_bearer("admin-token")
+            .await
+            .json::<Stats>();
+        assert_eq!(stats.questions, 0);
+        assert_eq!(
+            stats.posts_per_day.len() as i64,
+            handlers::handlers_inner::STATS_DEFAULT_DAYS
+        );
+
+        server
+            .get("/stats/cache")
+            .expect_failure()
+            .await
+            .assert_status(StatusCode::UNAUTHORIZED);
+        server.get("/questions").await.assert_status_ok();
+        server.get("/questions").await.assert_status_ok();
+        let metrics = server
+            .get("/stats/cache")
+            .authorization_bearer("admin-token")
+            .await
+            .json::<CacheMetrics>();
+        assert!(metrics.hits >= 1, "{metrics:?}");
+
+        Ok(())
+    }
+
+    /// Jobs run once per scheduled time, and their runs are for admins to read
+    #[sqlx::test]
+    async fn maintenance(pool: PgPool) -> sqlx::Result<()> {
+        let config = Config {
+            admin_token: Some("admin-token".to_owned()),
+            ..Config::default()
+        };
+        let state = app_state(pool.clone(), config);
+        let server = TestServer::new(app(state.clone())).unwrap();
+
+        let question = server
+            .post("/question")
+            .json(&Question {
+                title: "Maintenance title".to_string(),
+                description: "Maintenance description".to_string(),
+            })
+            .await
+            .json::<QuestionDetail>();
+        sqlx::query(
+            "UPDATE questions SET created_at = created_at - INTERVAL '3 days' WHERE question_uuid = $1::UUID",
+        )
+        .bind(&question.question_uuid)
+        .execute(&pool)
+        .await?;
+
+        // The scheduler is not running in tests
+        let run = |job, scheduled_for: &str| {
+            let state = state.clone();
+            let scheduled_for = scheduled_for.parse().unwrap();
+            async move {
+                maintenance::run_job(
+                    state.database.as_ref(),
+                    state.jobs_dao.as_ref(),
+                    &state.read_cache,
+                    &MaintenanceConfig {
+                        inactive_after: std::time::Duration::from_secs(2 * 24 * 60 * 60),
+                        ..MaintenanceConfig::default()
+                    },
+                    job,
+                    scheduled_for,
+                )
+                .await
+                .unwrap()
+            }
+        };
+        assert_eq!(
+            run(maintenance::Job::FlagUnanswered, "2026-10-19T03:00:00Z").await,
+            Some(1)
+        );
+        assert_eq!(
+            run(maintenance::Job::FlagUnanswered, "2026-10-19T03:00:00Z").await,
+            None
+        );
+
+        let page = server
+            .get("/questions/needs-attention")
+            .await
+            .json::<Page<QuestionDetail>>();
+        assert_eq!(page.items.len(), 1);
+        assert_eq!(page.items[0].question_uuid, question.question_uuid);
+        let page = server
+            .get("/v2/questions/needs-attention")
+            .await
+            .json::<Page<QuestionV2>>();
+        assert_eq!(page.items.len(), 1);
+
+        assert_eq!(
+            run(maintenance::Job::CloseInactive, "2026-10-19T03:00:00Z").await,
+            Some(1)
+        );
+        server
+            .post("/answer")
+            .json(&Answer {
+                question_uuid: question.question_uuid.clone(),
+                content: "Too late".to_string(),
+            })
+            .expect_failure()
+            .await
+            .assert_status(StatusCode::BAD_REQUEST);
+        let page = server
+            .get("/questions/needs-attention")
+            .await
+            .json::<Page<QuestionDetail>>();
+        assert!(page.items.is_empty());
+
+        server
+            .get("/jobs")
+            .expect_failure()
+            .await
+            .assert_status(StatusCode::UNAUTHORIZED);
+        let runs = server
+            .get("/jobs")
+            .authorization_bearer("admin-token")
+            .await
+            .json::<Vec<JobRun>>();
+        let mut jobs = runs.iter().map(|run| run.job.as_str()).collect::<Vec<_>>();
+        jobs.sort();
+        assert_eq!(jobs, vec!["close_inactive", "flag_unanswered"]);
+        assert!(runs.iter().all(|run| run.succeeded && run.affected == 1));
+
+        Ok(())
+    }
+
+    /// Pins the exact v1 responses to `/questions` and `/answers`, which consumers
+    /// predating versions rely on, whether v1 is asked for by path, header or not at all
+    #[sqlx::test]
+    async fn v1_contract(pool: PgPool) -> sqlx::Result<()> {
+        let server = TestServer::new(app(app_state(pool.clone(), Config::default()))).unwrap();
+
+        let question = server
+            .post("/question")
+            .add_header("X-User-Id", "toto")
+            .json(&Question {
+                title: "Contract title".to_string(),
+                description: "Contract *description*".to_string(),
+            })
+            .await
+            .json::<Value>();
+        let question_uuid = question["question_uuid"].as_str().unwrap().to_owned();
+        let expected_question = json!({
+            "question_uuid": question_uuid,
+            "title": "Contract title",
+            "description": "Contract *description*",
+            "description_html": "<p>Contract <em>description</em></p>\n",
+            "author": "toto",
+            "duplicate_of": null,
+            "view_count": 0,
+            "created_at": question["created_at"],
+        });
+        assert_eq!(question, expected_question);
+
+        let answer = server
+            .post("/answer")
+            .add_header("X-User-Id", "titi")
+            .json(&Answer {
+                question_uuid: question_uuid.clone(),
+                content: "Contract answer".to_string(),
+            })
+            .await
+            .json::<Value>();
+        let expected_answer = json!({
+            "answer_uuid": answer["answer_uuid"],
+            "question_uuid": question_uuid,
+            "content": "Contract answer",
+            "content_html": "<p>Contract answer</p>\n",
+            "author": "titi",
+            "created_at": answer["created_at"],
+        });
+        assert_eq!(answer, expected_answer);
+
+        for (prefix, accept) in [
+            ("", None),
+            ("/v1", None),
+            ("", Some("application/vnd.stackoverflow.v1+json")),
+            // An explicit prefix wins over the header
+            ("/v1", Some("application/vnd.stackoverflow.v2+json")),
+        ] {
+            let get = |path: &str| {
+                let request = server.get(&format!("{prefix}{path}"));
+                match accept {
+                    Some(accept) => request.add_header("Accept", accept),
+                    None => request,
+                }
+            };
+
+            let questions = get("/questions").await.json::<Value>();
+            assert_eq!(questions, json!([expected_question]), "{prefix} {accept:?}");
+            let read = get(&format!("/questions/{question_uuid}"))
+                .await
+                .json::<Value>();
+            assert_eq!(read, expected_question, "{prefix} {accept:?}");
+            let answers = get("/answers")
+                .json(&QuestionId {
+                    question_uuid: question_uuid.clone(),
+                })
+                .await
+                .json::<Value>();
+            assert_eq!(answers, json!([expected_answer]), "{prefix} {accept:?}");
+        }
+
+        // Reads of duplicates are redirected under the prefix they were made with
+        let duplicate = server
+            .post("/question")
+            .add_header("X-User-Id", "toto")
+            .json(&Question {
+                title: "Duplicate title".to_string(),
+                description: "Duplicate description".to_string(),
+            })
+            .await
+            .json::<QuestionDetail>();
+        server
+            .post(&format!("/questions/{}/duplicate", duplicate.question_uuid))
+            .add_header("X-User-Id", "toto")
+            .json(&DuplicateOf {
+                duplicate_of: question_uuid.clone(),
+            })
+            .await
+            .assert_status_ok();
+        for prefix in ["", "/v1", "/v2"] {
+            let response = server
+                .get(&format!("{prefix}/questions/{}", duplicate.question_uuid))
+                .await;
+            response.assert_status(StatusCode::TEMPORARY_REDIRECT);
+            assert_eq!(
+                response.header("location"),
+                format!("{prefix}/questions/{question_uuid}")
+            );
+        }
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn v2(pool: PgPool) -> sqlx::Result<()> {
+        let server = TestServer::new(app(app_state(pool.clone(), Config::default()))).unwrap();
+
+        let mut questions = Vec::new();
+        for title in ["First", "Second", "Third"] {
+            let question = server
+                .post("/question")
+                .json(&Question {
+                    title: title.to_string(),
+                    description: "V2 description".to_string(),
+                })
+                .await
+                .json::<QuestionDetail>();
+            questions.push(question);
+        }
+        let answer = server
+            .post("/answer")
+            .json(&Answer {
+                question_uuid: questions[0].question_uuid.clone(),
+                content: "V2 answer".to_string(),
+            })
+            .await
+            .json::<AnswerDetail>();
+
+        let page = server
+            .get("/v2/questions")
+            .add_query_param("limit", 2)
+            .await
+            .json::<Page<QuestionV2>>();
+        assert_eq!(
+            page,
+            Page {
+                items: questions[..2]
+                    .iter()
+                    .cloned()
+                    .map(QuestionV2::from)
+                    .collect(),
+                next_offset: Some(2),
+            }
+        );
+        let last_page = server
+            .get("/questions")
+            .add_header("Accept", "application/vnd.stackoverflow.v2+json")
+            .add_query_param("offset", 2)
+            .await;
+        assert_eq!(last_page.header("vary"), "accept");
+        let last_page = last_page.json::<Page<QuestionV2>>();
+        assert_eq!(last_page.items[0].title, "Third");
+        assert_eq!(last_page.next_offset, None);
+
+        let read = server
+            .get(&format!("/v2/questions/{}", questions[0].question_uuid))
+            .await
+            .json::<Value>();
+        assert_eq!(read["id"], questions[0].question_uuid.as_str());
+        assert!(read.get("question_uuid").is_none());
+
+        let answers = server
+            .get(&format!(
+                "/v2/questions/{}/answers",
+                questions[0].question_uuid
+            ))
+            .await
+            .json::<Page<AnswerV2>>();
+        assert_eq!(answers.items, vec![AnswerV2::from(answer)]);
+        server
+            .get("/v2/questions/malformed/answers")
+            .expect_failure()
+            .await
+            .assert_status(StatusCode::BAD_REQUEST);
+        server
+            .get("/v2/answers")
+            .expect_failure()
+            .await
+            .assert_status(StatusCode::NOT_FOUND);
+
+        server
+            .get("/questions")
+            .add_header("Accept", "application/vnd.stackoverflow.v3+json")
+            .expect_failure()
+            .await
+            .assert_status(StatusCode::NOT_ACCEPTABLE);
+
+        Ok(())
+    }
+
     /// Code for debugging
     #[allow(dead_code)]
     async fn print_db_state(pool: &PgPool) {
@@ -151,6 +1799,10 @@ mod tests {
                 question_uuid: rec.question_uuid.to_string(),
                 title: rec.title.to_string(),
                 description: rec.description.to_string(),
+                description_html: markdown::render(&rec.description),
+                author: rec.author.to_string(),
+                duplicate_of: rec.duplicate_of.map(|uuid| uuid.to_string()),
+                view_count: rec.view_count,
                 created_at: rec.created_at.to_string(),
             })
             .collect();
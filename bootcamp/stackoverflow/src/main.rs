@@ -1,6 +1,7 @@
 use dotenvy::dotenv;
 use log::*;
 use sqlx::postgres::PgPoolOptions;
+use stackoverflow::config::Config;
 
 #[tokio::main]
 async fn main() {
@@ -15,5 +16,5 @@ async fn main() {
 
     info!("Connected to Postgres db");
 
-    stackoverflow::run(pool).await;
+    stackoverflow::run(pool, Config::from_env()).await;
 }
@@ -0,0 +1,208 @@
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use cron::Schedule;
+use log::{error, info};
+use sqlx::types::time::{OffsetDateTime, PrimitiveDateTime};
+
+use crate::{
+    config::MaintenanceConfig,
+    models::DBError,
+    persistance::{jobs_dao::JobsDao, read_cache::ReadCache, unit_of_work::Database},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Job {
+    CloseInactive,
+    FlagUnanswered,
+    Archive,
+}
+
+impl Job {
+    /// Name the runs of the job are recorded under
+    pub fn name(&self) -> &'static str {
+        match self {
+            Job::CloseInactive => "close_inactive",
+            Job::FlagUnanswered => "flag_unanswered",
+            Job::Archive => "archive",
+        }
+    }
+}
+
+/// Jobs which have a schedule
+fn scheduled_jobs(config: &MaintenanceConfig) -> Vec<(Job, Schedule)> {
+    [
+        (Job::CloseInactive, &config.close_inactive),
+        (Job::FlagUnanswered, &config.flag_unanswered),
+        (Job::Archive, &config.archive),
+    ]
+    .into_iter()
+    .filter_map(|(job, schedule)| Some((job, schedule.clone()?)))
+    .collect()
+}
+
+/// Background task running the jobs at the times of their schedules until the runtime shuts
+/// down. Every instance runs it, each scheduled time of a job is run by the first instance
+/// to claim it. Times missed while a job was running are skipped rather than caught up with.
+pub async fn run_scheduler(
+    database: Arc<dyn Database + Send + Sync>,
+    jobs_dao: Arc<dyn JobsDao + Send + Sync>,
+    read_cache: Arc<ReadCache>,
+    config: MaintenanceConfig,
+) {
+    let mut upcoming = scheduled_jobs(&config)
+        .into_iter()
+        .filter_map(|(job, schedule)| {
+            let due = schedule.after(&Utc::now()).next()?;
+            Some((job, schedule, due))
+        })
+        .collect::<Vec<_>>();
+
+    while let Some(next) = (0..upcoming.len()).min_by_key(|&i| upcoming[i].2) {
+        let (job, schedule, due) = upcoming[next].clone();
+        let wait = (due - Utc::now()).to_std().unwrap_or_default();
+        tokio::time::sleep(wait).await;
+
+        match run_job(
+            database.as_ref(),
+            jobs_dao.as_ref(),
+            &read_cache,
+            &config,
+            job,
+            due,
+        )
+        .await
+        {
+            Ok(Some(affected)) => info!("Ran the {} job on {} questions", job.name(), affected),
+            Ok(None) => info!("Left the {} job to another instance", job.name()),
+            Err(e) => error!("Failed to run the {} job: {:?}", job.name(), e),
+        }
+
+        match schedule.after(&due.max(Utc::now())).next() {
+            Some(due) => upcoming[next].2 = due,
+            None => {
+                upcoming.remove(next);
+            }
+        }
+    }
+}
+
+/// Runs `job` for its time `scheduled_for` unless another instance is running it or ran it,
+/// returns the number of questions affected or `None` when the run was left to another instance.
+/// Failed runs are recorded too, and not retried.
+pub async fn run_job(
+    database: &(dyn Database + Send + Sync),
+    jobs_dao: &(dyn JobsDao + Send + Sync),
+    read_cache: &Arc<ReadCache>,
+    config: &MaintenanceConfig,
+    job: Job,
+    scheduled_for: DateTime<Utc>,
+) -> Result<Option<i64>, DBError> {
+    let scheduled_for = utc_timestamp(scheduled_for);
+    let started_at = utc_timestamp(Utc::now());
+
+    let mut uow = database.begin().await?;
+    if !jobs_dao
+        .claim_run(uow.as_mut(), job.name(), scheduled_for)
+        .await?
+    {
+        // Ended at once, a dropped unit may hold the lock until its connection is used again
+        uow.commit().await?;
+        return Ok(None);
+    }
+
+    let affected = match job {
+        Job::CloseInactive => {
+            jobs_dao
+                .close_inactive_questions(uow.as_mut(), config.inactive_after)
+                .await
+        }
+        Job::FlagUnanswered => {
+            jobs_dao
+                .flag_unanswered_questions(uow.as_mut(), config.unanswered_after)
+                .await
+        }
+        Job::Archive => {
+            jobs_dao
+                .archive_questions(uow.as_mut(), config.archive_after)
+                .await
+        }
+    };
+
+    match affected {
+        Ok(affected) => {
+            if affected > 0 {
+                read_cache.invalidate_all_on_commit(uow.as_mut()).await?;
+            }
+            jobs_dao
+                .record_run(
+                    uow.as_mut(),
+                    job.name(),
+                    scheduled_for,
+                    started_at,
+                    Ok(affected),
+                )
+                .await?;
+            uow.commit().await?;
+
+            Ok(Some(affected))
+        }
+        Err(err) => {
+            // Rolls back whatever the job did, the failure is recorded on its own
+            drop(uow);
+            let mut uow = database.begin().await?;
+            jobs_dao
+                .record_run(
+                    uow.as_mut(),
+                    job.name(),
+                    scheduled_for,
+                    started_at,
+                    Err(err.to_string()),
+                )
+                .await?;
+            uow.commit().await?;
+
+            Err(err)
+        }
+    }
+}
+
+/// Runs are recorded in UTC whatever the time zone of the database
+fn utc_timestamp(time: DateTime<Utc>) -> PrimitiveDateTime {
+    let time = OffsetDateTime::from_unix_timestamp(time.timestamp())
+        .expect("Cron schedules stay within the supported years");
+    PrimitiveDateTime::new(time.date(), time.time())
+}
+
+// ***********************************************************
+//                           Tests
+// ***********************************************************
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    #[test]
+    fn scheduled_jobs_should_leave_out_jobs_without_a_schedule() {
+        let config = MaintenanceConfig {
+            archive: Some(Schedule::from_str("0 0 4 * * *").unwrap()),
+            flag_unanswered: None,
+            ..MaintenanceConfig::default()
+        };
+
+        let jobs = scheduled_jobs(&config)
+            .into_iter()
+            .map(|(job, _)| job)
+            .collect::<Vec<_>>();
+
+        assert_eq!(jobs, vec![Job::CloseInactive, Job::Archive]);
+    }
+
+    #[test]
+    fn utc_timestamp_should_keep_the_time_to_the_second() {
+        let time = "2026-10-19T03:00:00Z".parse::<DateTime<Utc>>().unwrap();
+
+        assert_eq!(format!("{:?}", utc_timestamp(time)), "2026-10-19 3:00:00.0");
+    }
+}
@@ -0,0 +1,270 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex, OnceLock},
+};
+
+use pulldown_cmark::{html, CodeBlockKind, CowStr, Event, Options, Parser, Tag, TagEnd};
+use sha2::{Digest, Sha256};
+use syntect::{
+    highlighting::ThemeSet,
+    html::{css_for_theme_with_class_style, ClassStyle, ClassedHTMLGenerator},
+    parsing::SyntaxSet,
+    util::LinesWithEndings,
+};
+use tokio::task::JoinError;
+
+/// Highlighted tokens are emitted as `<span class="hl-...">`, see `highlight_css`
+const CLASS_STYLE: ClassStyle = ClassStyle::SpacedPrefixed { prefix: "hl-" };
+const THEME: &str = "InspiredGitHub";
+const DEFAULT_CACHE_CAPACITY: usize = 10_000;
+
+fn syntax_set() -> &'static SyntaxSet {
+    static SYNTAX_SET: OnceLock<SyntaxSet> = OnceLock::new();
+    SYNTAX_SET.get_or_init(SyntaxSet::load_defaults_newlines)
+}
+
+fn sanitizer() -> &'static ammonia::Builder<'static> {
+    static SANITIZER: OnceLock<ammonia::Builder<'static>> = OnceLock::new();
+    SANITIZER.get_or_init(|| {
+        let mut builder = ammonia::Builder::default();
+        builder
+            .add_tag_attributes("span", &["class"])
+            .add_tag_attributes("code", &["class"]);
+        builder
+    })
+}
+
+/// Renders Markdown to HTML which is safe to embed in a page.
+/// Fenced code blocks are syntax highlighted when their language is known.
+pub fn render(source: &str) -> String {
+    let mut events = Vec::new();
+    let mut code_block: Option<(String, String)> = None;
+
+    for event in Parser::new_ext(
+        source,
+        Options::ENABLE_TABLES | Options::ENABLE_STRIKETHROUGH,
+    ) {
+        match (event, &mut code_block) {
+            (Event::Start(Tag::CodeBlock(kind)), None) => {
+                let lang = match kind {
+                    CodeBlockKind::Fenced(info) => {
+                        info.split_whitespace().next().unwrap_or("").to_owned()
+                    }
+                    CodeBlockKind::Indented => String::new(),
+                };
+                code_block = Some((lang, String::new()));
+            }
+            (Event::Text(text), Some((_, code))) => code.push_str(&text),
+            (Event::End(TagEnd::CodeBlock), Some(_)) => {
+                let (lang, code) = code_block.take().expect("Inside a code block");
+                events.push(Event::Html(CowStr::from(highlight(&lang, &code))));
+            }
+            (event, _) => events.push(event),
+        }
+    }
+
+    let mut unsafe_html = String::new();
+    html::push_html(&mut unsafe_html, events.into_iter());
+
+    // Raw HTML is allowed in Markdown, the sanitizer strips scripts, handlers, etc.
+    sanitizer().clean(&unsafe_html).to_string()
+}
+
+fn highlight(lang: &str, code: &str) -> String {
+    let syntax_set = syntax_set();
+    let syntax = syntax_set
+        .find_syntax_by_token(lang)
+        .unwrap_or_else(|| syntax_set.find_syntax_plain_text());
+
+    let mut generator = ClassedHTMLGenerator::new_with_class_style(syntax, syntax_set, CLASS_STYLE);
+    for line in LinesWithEndings::from(code) {
+        if generator
+            .parse_html_for_line_which_includes_newline(line)
+            .is_err()
+        {
+            // Fall back to the escaped source rather than failing the whole post
+            return format!("<pre><code>{}</code></pre>\n", ammonia::clean_text(code));
+        }
+    }
+
+    let class = if lang.is_empty() {
+        String::new()
+    } else {
+        format!(" class=\"language-{}\"", ammonia::clean_text(lang))
+    };
+    format!("<pre><code{class}>{}</code></pre>\n", generator.finalize())
+}
+
+/// Stylesheet matching the classes of highlighted code blocks
+pub fn highlight_css() -> String {
+    let themes = ThemeSet::load_defaults();
+    css_for_theme_with_class_style(&themes.themes[THEME], CLASS_STYLE)
+        .expect("Default theme is valid")
+}
+
+struct CachedRender {
+    source_digest: [u8; 32],
+    html: String,
+}
+
+/// Rendered HTML of posts, keyed by post UUID.
+/// Entries remember a digest of the source they were rendered from so that an edited post
+/// is never served stale HTML, even if the edit happened on another instance.
+pub struct RenderCache {
+    capacity: usize,
+    entries: Mutex<HashMap<String, CachedRender>>,
+}
+
+impl RenderCache {
+    pub fn new(capacity: usize) -> Self {
+        RenderCache {
+            capacity,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// HTML of `source`, which is rendered on the blocking pool when it is not cached
+    pub async fn render(self: &Arc<Self>, key: &str, source: &str) -> Result<String, JoinError> {
+        if let Some(html) = self.cached(key, &digest(source)) {
+            return Ok(html);
+        }
+
+        let cache = self.clone();
+        let (key, source) = (key.to_owned(), source.to_owned());
+        tokio::task::spawn_blocking(move || cache.get_or_render(&key, &source)).await
+    }
+
+    pub fn get_or_render(&self, key: &str, source: &str) -> String {
+        let source_digest = digest(source);
+        if let Some(html) = self.cached(key, &source_digest) {
+            return html;
+        }
+
+        // Render without holding the lock, rendering large code blocks is slow
+        let html = render(source);
+
+        let mut entries = self.entries.lock().unwrap();
+        if entries.len() >= self.capacity && !entries.contains_key(key) {
+            let evicted = entries.keys().next().cloned();
+            if let Some(evicted) = evicted {
+                entries.remove(&evicted);
+            }
+        }
+        entries.insert(
+            key.to_owned(),
+            CachedRender {
+                source_digest,
+                html: html.clone(),
+            },
+        );
+
+        html
+    }
+
+    fn cached(&self, key: &str, source_digest: &[u8; 32]) -> Option<String> {
+        self.entries.lock().unwrap().get(key).and_then(|cached| {
+            (cached.source_digest == *source_digest).then(|| cached.html.clone())
+        })
+    }
+
+    #[cfg(test)]
+    pub fn len(&self) -> usize {
+        self.entries.lock().unwrap().len()
+    }
+
+    #[cfg(test)]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl Default for RenderCache {
+    fn default() -> Self {
+        RenderCache::new(DEFAULT_CACHE_CAPACITY)
+    }
+}
+
+fn digest(source: &str) -> [u8; 32] {
+    Sha256::digest(source.as_bytes()).into()
+}
+
+// ***********************************************************
+//                           Tests
+// ***********************************************************
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn render_should_convert_markdown() {
+        let html = render("# Title\n\nSome *emphasis* and `code`.");
+
+        assert!(html.contains("<h1>Title</h1>"));
+        assert!(html.contains("<em>emphasis</em>"));
+        assert!(html.contains("<code>code</code>"));
+    }
+
+    #[test]
+    fn render_should_strip_scripts() {
+        let html = render(
+            "<script>alert(1)</script>\n\n<img src=x onerror=\"alert(1)\">\n\n[link](javascript:alert(1))",
+        );
+
+        assert!(!html.contains("<script"));
+        assert!(!html.contains("onerror"));
+        assert!(!html.contains("javascript:"));
+    }
+
+    #[test]
+    fn render_should_highlight_fenced_code() {
+        let html = render("```rust\nfn main() {}\n```");
+
+        assert!(html.contains("<pre><code class=\"language-rust\">"));
+        assert!(html.contains("<span class=\"hl-"));
+        assert!(html.contains("main"));
+    }
+
+    #[test]
+    fn render_should_escape_code_in_unknown_languages() {
+        let html = render("```notalanguage\n<script>alert(1)</script>\n```");
+
+        assert!(!html.contains("<script"));
+        assert!(html.contains("&lt;script&gt;"));
+    }
+
+    #[test]
+    fn highlight_css_should_style_prefixed_classes() {
+        assert!(highlight_css().contains(".hl-"));
+    }
+
+    #[test]
+    fn render_cache_should_rerender_edited_sources() {
+        let cache = RenderCache::new(10);
+
+        assert!(cache.is_empty());
+        assert_eq!(cache.get_or_render("123", "*a*"), render("*a*"));
+        assert_eq!(cache.get_or_render("123", "*b*"), render("*b*"));
+        assert_eq!(cache.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn render_cache_should_render_misses_off_the_runtime() {
+        let cache = Arc::new(RenderCache::new(10));
+
+        assert_eq!(cache.render("123", "*a*").await.unwrap(), render("*a*"));
+        assert_eq!(cache.len(), 1);
+        assert_eq!(cache.render("123", "*a*").await.unwrap(), render("*a*"));
+    }
+
+    #[test]
+    fn render_cache_should_be_bounded() {
+        let cache = RenderCache::new(2);
+
+        cache.get_or_render("1", "a");
+        cache.get_or_render("2", "b");
+        cache.get_or_render("3", "c");
+
+        assert_eq!(cache.len(), 2);
+    }
+}
@@ -0,0 +1,368 @@
+//! Model-based test of the API. Random sequences of operations on questions and answers are run
+//! against `app()` and against a model of what the API should do, each response is checked
+//! against the model. Failing sequences are shrunk before being reported.
+
+use axum::http::StatusCode;
+use axum_test::TestServer;
+use proptest::{
+    prelude::*,
+    sample::Index,
+    strategy::ValueTree,
+    test_runner::{Config as ProptestConfig, TestRunner},
+};
+use sqlx::PgPool;
+
+use crate::{app, app_state, config::Config, models::*};
+
+/// Every operation is made by the same user, who may delete what they posted
+const USER: &str = "model";
+const ADMIN_TOKEN: &str = "admin-token";
+/// Sequences run per test, and runs spent shrinking a failing one at most
+const CASES: u32 = 32;
+const MAX_SHRINK_RUNS: u32 = 256;
+
+/// Question or answer an operation is about
+#[derive(Debug, Clone)]
+enum Pick {
+    /// One of those created so far, deleted ones included
+    Created(Index),
+    /// A well formed UUID which was never created
+    Unknown,
+    Malformed,
+}
+
+#[derive(Debug, Clone)]
+enum Op {
+    CreateQuestion { title: String, description: String },
+    ListQuestions,
+    DeleteQuestion(Pick),
+    CreateAnswer { question: Pick, content: String },
+    ListAnswers(Pick),
+    DeleteAnswer(Pick),
+}
+
+fn pick() -> impl Strategy<Value = Pick> {
+    prop_oneof![
+        8 => any::<Index>().prop_map(Pick::Created),
+        1 => Just(Pick::Unknown),
+        1 => Just(Pick::Malformed),
+    ]
+}
+
+/// Texts the columns hold, control characters like NUL aside
+fn text() -> impl Strategy<Value = String> {
+    "\\PC{0,64}"
+}
+
+fn op() -> impl Strategy<Value = Op> {
+    prop_oneof![
+        3 => (text(), text())
+            .prop_map(|(title, description)| Op::CreateQuestion { title, description }),
+        2 => Just(Op::ListQuestions),
+        1 => pick().prop_map(Op::DeleteQuestion),
+        3 => (pick(), text()).prop_map(|(question, content)| Op::CreateAnswer { question, content }),
+        2 => pick().prop_map(Op::ListAnswers),
+        1 => pick().prop_map(Op::DeleteAnswer),
+    ]
+}
+
+fn ops() -> impl Strategy<Value = Vec<Op>> {
+    prop::collection::vec(op(), 1..30)
+}
+
+#[derive(Debug, Clone, PartialEq, PartialOrd, Eq, Ord)]
+struct ModelQuestion {
+    question_uuid: String,
+    title: String,
+    description: String,
+    deleted: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, PartialOrd, Eq, Ord)]
+struct ModelAnswer {
+    answer_uuid: String,
+    question_uuid: String,
+    content: String,
+    deleted: bool,
+}
+
+/// What the API should hold, in the order it was created
+#[derive(Default)]
+struct Model {
+    questions: Vec<ModelQuestion>,
+    answers: Vec<ModelAnswer>,
+}
+
+impl Model {
+    fn question(&self, pick: &Pick) -> String {
+        let uuids = self.questions.iter().map(|q| &q.question_uuid);
+        resolve(pick, uuids.collect())
+    }
+
+    fn answer(&self, pick: &Pick) -> String {
+        let uuids = self.answers.iter().map(|a| &a.answer_uuid);
+        resolve(pick, uuids.collect())
+    }
+
+    fn live_question(&self, question_uuid: &str) -> Option<&ModelQuestion> {
+        self.questions
+            .iter()
+            .find(|q| q.question_uuid == question_uuid && !q.deleted)
+    }
+}
+
+fn resolve(pick: &Pick, uuids: Vec<&String>) -> String {
+    match pick {
+        Pick::Created(index) if !uuids.is_empty() => uuids[index.index(uuids.len())].clone(),
+        Pick::Created(_) | Pick::Unknown => "5f0c6a39-0000-4000-8000-000000000000".to_owned(),
+        Pick::Malformed => "not-a-uuid".to_owned(),
+    }
+}
+
+fn expect_status(actual: StatusCode, expected: StatusCode) -> Result<(), String> {
+    if actual != expected {
+        return Err(format!("Expected {expected} but got {actual}"));
+    }
+    Ok(())
+}
+
+/// Runs `op` against the API under `prefix`, checks the response and updates the model
+async fn step(server: &TestServer, prefix: &str, model: &mut Model, op: &Op) -> Result<(), String> {
+    match op {
+        Op::CreateQuestion { title, description } => {
+            let response = server
+                .post(&format!("{prefix}/question"))
+                .add_header("X-User-Id", USER)
+                .json(&Question {
+                    title: title.clone(),
+                    description: description.clone(),
+                })
+                .await;
+            expect_status(response.status_code(), StatusCode::OK)?;
+
+            let question = response.json::<QuestionDetail>();
+            if (
+                &question.title,
+                &question.description,
+                question.author.as_str(),
+            ) != (title, description, USER)
+            {
+                return Err(format!("Created {:?}", question));
+            }
+            model.questions.push(ModelQuestion {
+                question_uuid: question.question_uuid,
+                title: title.clone(),
+                description: description.clone(),
+                deleted: false,
+            });
+        }
+        Op::ListQuestions => {
+            let response = server.get(&format!("{prefix}/questions")).await;
+            expect_status(response.status_code(), StatusCode::OK)?;
+
+            let mut listed = response
+                .json::<Vec<QuestionDetail>>()
+                .into_iter()
+                .map(|q| ModelQuestion {
+                    question_uuid: q.question_uuid,
+                    title: q.title,
+                    description: q.description,
+                    deleted: false,
+                })
+                .collect::<Vec<_>>();
+            let mut expected = model
+                .questions
+                .iter()
+                .filter(|q| !q.deleted)
+                .cloned()
+                .collect::<Vec<_>>();
+            listed.sort();
+            expected.sort();
+            if listed != expected {
+                return Err(format!("Expected {:?} but listed {:?}", expected, listed));
+            }
+        }
+        Op::DeleteQuestion(pick) => {
+            let question_uuid = model.question(pick);
+            let response = server
+                .delete(&format!("{prefix}/question"))
+                .add_header("X-User-Id", USER)
+                .json(&QuestionId {
+                    question_uuid: question_uuid.clone(),
+                })
+                .await;
+
+            match model
+                .questions
+                .iter_mut()
+                .find(|q| q.question_uuid == question_uuid && !q.deleted)
+            {
+                Some(question) => {
+                    expect_status(response.status_code(), StatusCode::OK)?;
+                    question.deleted = true;
+                }
+                None => expect_status(response.status_code(), StatusCode::BAD_REQUEST)?,
+            }
+        }
+        Op::CreateAnswer { question, content } => {
+            let question_uuid = model.question(question);
+            let response = server
+                .post(&format!("{prefix}/answer"))
+                .add_header("X-User-Id", USER)
+                .json(&Answer {
+                    question_uuid: question_uuid.clone(),
+                    content: content.clone(),
+                })
+                .await;
+
+            if model.live_question(&question_uuid).is_none() {
+                return expect_status(response.status_code(), StatusCode::BAD_REQUEST);
+            }
+            expect_status(response.status_code(), StatusCode::OK)?;
+            let answer = response.json::<AnswerDetail>();
+            if (&answer.question_uuid, &answer.content) != (&question_uuid, content) {
+                return Err(format!("Created {:?}", answer));
+            }
+            model.answers.push(ModelAnswer {
+                answer_uuid: answer.answer_uuid,
+                question_uuid,
+                content: content.clone(),
+                deleted: false,
+            });
+        }
+        Op::ListAnswers(pick) => {
+            let question_uuid = model.question(pick);
+            let response = server
+                .get(&format!("{prefix}/answers"))
+                .json(&QuestionId {
+                    question_uuid: question_uuid.clone(),
+                })
+                .await;
+
+            // v1 reports malformed UUIDs on this route as internal errors, as its handler
+            // tests pin it
+            if matches!(pick, Pick::Malformed) {
+                return expect_status(response.status_code(), StatusCode::INTERNAL_SERVER_ERROR);
+            }
+            expect_status(response.status_code(), StatusCode::OK)?;
+
+            let mut listed = response
+                .json::<Vec<AnswerDetail>>()
+                .into_iter()
+                .map(|a| ModelAnswer {
+                    answer_uuid: a.answer_uuid,
+                    question_uuid: a.question_uuid,
+                    content: a.content,
+                    deleted: false,
+                })
+                .collect::<Vec<_>>();
+            // The answers of deleted questions are hidden with them
+            let mut expected = match model.live_question(&question_uuid) {
+                Some(_) => model
+                    .answers
+                    .iter()
+                    .filter(|a| a.question_uuid == question_uuid && !a.deleted)
+                    .cloned()
+                    .collect::<Vec<_>>(),
+                None => Vec::new(),
+            };
+            listed.sort();
+            expected.sort();
+            if listed != expected {
+                return Err(format!("Expected {:?} but listed {:?}", expected, listed));
+            }
+        }
+        Op::DeleteAnswer(pick) => {
+            let answer_uuid = model.answer(pick);
+            let response = server
+                .delete(&format!("{prefix}/answer"))
+                .add_header("X-User-Id", USER)
+                .json(&AnswerId {
+                    answer_uuid: answer_uuid.clone(),
+                })
+                .await;
+
+            // Answers of deleted questions may still be deleted
+            match model
+                .answers
+                .iter_mut()
+                .find(|a| a.answer_uuid == answer_uuid && !a.deleted)
+            {
+                Some(answer) => {
+                    expect_status(response.status_code(), StatusCode::OK)?;
+                    answer.deleted = true;
+                }
+                None => expect_status(response.status_code(), StatusCode::BAD_REQUEST)?,
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Runs a sequence in a space of its own, so that sequences do not see each other's posts
+async fn check(server: &TestServer, run: &mut u32, ops: &[Op]) -> Result<(), String> {
+    *run += 1;
+    let slug = format!("model-{run}");
+    server
+        .post("/spaces")
+        .authorization_bearer(ADMIN_TOKEN)
+        .json(&Space {
+            slug: slug.clone(),
+            name: "Model".to_owned(),
+            open: true,
+        })
+        .await
+        .assert_status_ok();
+
+    let prefix = format!("/spaces/{slug}");
+    let mut model = Model::default();
+    for (i, op) in ops.iter().enumerate() {
+        step(server, &prefix, &mut model, op)
+            .await
+            .map_err(|e| format!("Operation {i} {:?}: {e}", op))?;
+    }
+
+    Ok(())
+}
+
+/// Proptest drives synchronous tests, the value trees are walked by hand so that the sequences
+/// run on the runtime of the test and its database
+#[sqlx::test]
+async fn app_should_agree_with_the_model(pool: PgPool) -> Result<(), String> {
+    let config = Config {
+        admin_token: Some(ADMIN_TOKEN.to_owned()),
+        ..Config::default()
+    };
+    let server = TestServer::new(app(app_state(pool, config))).unwrap();
+    let mut runner = TestRunner::new(ProptestConfig::with_cases(CASES));
+    let mut run = 0;
+
+    for _ in 0..CASES {
+        let mut tree = ops().new_tree(&mut runner).map_err(|e| e.to_string())?;
+        let Err(error) = check(&server, &mut run, &tree.current()).await else {
+            continue;
+        };
+
+        let mut failure = (tree.current(), error);
+        let mut shrink_runs = 0;
+        let mut simplified = tree.simplify();
+        while simplified && shrink_runs < MAX_SHRINK_RUNS {
+            shrink_runs += 1;
+            simplified = match check(&server, &mut run, &tree.current()).await {
+                Err(error) => {
+                    failure = (tree.current(), error);
+                    tree.simplify()
+                }
+                Ok(()) => tree.complicate(),
+            };
+        }
+
+        return Err(format!(
+            "{}\nMinimal failing sequence: {:#?}",
+            failure.1, failure.0
+        ));
+    }
+
+    Ok(())
+}
@@ -1,11 +1,14 @@
 use serde::{Deserialize, Serialize};
+#[cfg(feature = "server")]
 use sqlx::types::{time::PrimitiveDateTime, Uuid};
+#[cfg(feature = "server")]
 use sqlx::{postgres::PgRow, FromRow, Row};
 use thiserror::Error;
 
 #[derive(Serialize, Deserialize)]
 pub struct Question {
     pub title: String,
+    /// Markdown source
     pub description: String,
 }
 
@@ -14,20 +17,36 @@ pub struct QuestionDetail {
     pub question_uuid: String,
     pub title: String,
     pub description: String,
+    /// Sanitised HTML rendering of `description`
+    pub description_html: String,
+    pub author: String,
+    /// Set when the question was closed as a duplicate of another one
+    pub duplicate_of: Option<String>,
+    /// Views counted so far, recent views may not be included yet
+    pub view_count: i64,
     pub created_at: String,
 }
 
+#[cfg(feature = "server")]
 impl FromRow<'_, PgRow> for QuestionDetail {
     fn from_row(row: &PgRow) -> sqlx::Result<Self> {
         let uuid: Uuid = row.try_get("question_uuid")?;
         let title: String = row.try_get("title")?;
         let description: String = row.try_get("description")?;
+        let author: String = row.try_get("author")?;
+        let duplicate_of: Option<Uuid> = row.try_get("duplicate_of")?;
+        let view_count: i64 = row.try_get("view_count")?;
         let created_at: PrimitiveDateTime = row.try_get("created_at")?;
         let created_at = format!("{:?}", created_at);
         Ok(QuestionDetail {
             question_uuid: uuid.to_string(),
             title,
             description,
+            // Rendered by the DAOs, decoding a row must stay cheap
+            description_html: String::new(),
+            author,
+            duplicate_of: duplicate_of.map(|uuid| uuid.to_string()),
+            view_count,
             created_at,
         })
     }
@@ -38,11 +57,48 @@ pub struct QuestionId {
     pub question_uuid: String,
 }
 
+#[derive(Serialize, Deserialize)]
+pub struct DuplicateOf {
+    pub duplicate_of: String,
+}
+
+/// A question ranked by its trigram similarity to another question or draft
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SimilarQuestion {
+    pub question_uuid: String,
+    pub title: String,
+    /// Between 0 and 1, higher is more similar
+    pub similarity: f32,
+}
+
+#[cfg(feature = "server")]
+impl FromRow<'_, PgRow> for SimilarQuestion {
+    fn from_row(row: &PgRow) -> sqlx::Result<Self> {
+        let uuid: Uuid = row.try_get("question_uuid")?;
+        let title: String = row.try_get("title")?;
+        let similarity: f32 = row.try_get("similarity")?;
+        Ok(SimilarQuestion {
+            question_uuid: uuid.to_string(),
+            title,
+            similarity,
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct QuestionEdit {
+    pub question_uuid: String,
+    pub title: String,
+    /// Markdown source
+    pub description: String,
+}
+
 // ----------
 
 #[derive(Serialize, Deserialize)]
 pub struct Answer {
     pub question_uuid: String,
+    /// Markdown source
     pub content: String,
 }
 
@@ -51,20 +107,28 @@ pub struct AnswerDetail {
     pub answer_uuid: String,
     pub question_uuid: String,
     pub content: String,
+    /// Sanitised HTML rendering of `content`
+    pub content_html: String,
+    pub author: String,
     pub created_at: String,
 }
 
+#[cfg(feature = "server")]
 impl FromRow<'_, PgRow> for AnswerDetail {
     fn from_row(row: &PgRow) -> sqlx::Result<Self> {
         let quid: Uuid = row.try_get("question_uuid")?;
         let auid: Uuid = row.try_get("answer_uuid")?;
         let content: String = row.try_get("content")?;
+        let author: String = row.try_get("author")?;
         let created_at: PrimitiveDateTime = row.try_get("created_at")?;
         let created_at = format!("{:?}", created_at);
         Ok(AnswerDetail {
             question_uuid: quid.to_string(),
             answer_uuid: auid.to_string(),
             content,
+            // Rendered by the DAOs as well
+            content_html: String::new(),
+            author,
             created_at,
         })
     }
@@ -75,8 +139,765 @@ pub struct AnswerId {
     pub answer_uuid: String,
 }
 
+#[derive(Serialize, Deserialize)]
+pub struct AnswerEdit {
+    pub answer_uuid: String,
+    /// Markdown source
+    pub content: String,
+}
+
+// ----------
+
+/// Identity of callers that did not say who they are, they never earn reputation
+pub const ANONYMOUS: &str = "anonymous";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReputationReason {
+    QuestionAsked,
+    AnswerPosted,
+    AnswerDeletedByModerator,
+}
+
+impl ReputationReason {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ReputationReason::QuestionAsked => "question_asked",
+            ReputationReason::AnswerPosted => "answer_posted",
+            ReputationReason::AnswerDeletedByModerator => "answer_deleted_by_moderator",
+        }
+    }
+
+    pub fn points(&self) -> i32 {
+        match self {
+            ReputationReason::QuestionAsked => 5,
+            ReputationReason::AnswerPosted => 10,
+            ReputationReason::AnswerDeletedByModerator => -15,
+        }
+    }
+}
+
+/// Actions that require a minimum reputation
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Privilege {
+    DeleteOthersPosts,
+    EditOthersPosts,
+    CloseOthersQuestions,
+}
+
+impl Privilege {
+    pub fn min_reputation(&self) -> i64 {
+        match self {
+            Privilege::DeleteOthersPosts => 100,
+            Privilege::EditOthersPosts => 200,
+            Privilege::CloseOthersQuestions => 150,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct UserProfile {
+    pub user_id: String,
+    pub reputation: i64,
+    pub question_count: i64,
+    pub answer_count: i64,
+}
+
+// ----------
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PostKind {
+    Question,
+    Answer,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct Flag {
+    pub post_kind: PostKind,
+    pub post_uuid: String,
+    pub reason: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct FlagDetail {
+    pub flag_uuid: String,
+    pub post_kind: PostKind,
+    pub post_uuid: String,
+    pub reason: String,
+    pub reported_by: String,
+    pub status: String,
+    pub resolution: Option<String>,
+    pub created_at: String,
+}
+
+#[cfg(feature = "server")]
+impl FromRow<'_, PgRow> for FlagDetail {
+    fn from_row(row: &PgRow) -> sqlx::Result<Self> {
+        let uuid: Uuid = row.try_get("flag_uuid")?;
+        let quid: Option<Uuid> = row.try_get("question_uuid")?;
+        let auid: Option<Uuid> = row.try_get("answer_uuid")?;
+        let (post_kind, post_uuid) = match (quid, auid) {
+            (Some(quid), _) => (PostKind::Question, quid),
+            (None, Some(auid)) => (PostKind::Answer, auid),
+            (None, None) => return Err(sqlx::Error::RowNotFound),
+        };
+        let reason: String = row.try_get("reason")?;
+        let reported_by: String = row.try_get("reported_by")?;
+        let status: String = row.try_get("status")?;
+        let resolution: Option<String> = row.try_get("resolution")?;
+        let created_at: PrimitiveDateTime = row.try_get("created_at")?;
+        let created_at = format!("{:?}", created_at);
+        Ok(FlagDetail {
+            flag_uuid: uuid.to_string(),
+            post_kind,
+            post_uuid: post_uuid.to_string(),
+            reason,
+            reported_by,
+            status,
+            resolution,
+            created_at,
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum FlagResolution {
+    /// Keep the post and close the flag
+    Dismiss,
+    /// Soft delete the flagged post and close the flag
+    DeletePost,
+}
+
+impl FlagResolution {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FlagResolution::Dismiss => "dismiss",
+            FlagResolution::DeletePost => "delete_post",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ResolveFlag {
+    pub resolution: FlagResolution,
+}
+
+// ----------
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WebhookEvent {
+    #[serde(rename = "question.created")]
+    QuestionCreated,
+    #[serde(rename = "answer.created")]
+    AnswerCreated,
+}
+
+impl WebhookEvent {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WebhookEvent::QuestionCreated => "question.created",
+            WebhookEvent::AnswerCreated => "answer.created",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct Webhook {
+    pub url: String,
+    pub events: Vec<WebhookEvent>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct WebhookDetail {
+    pub webhook_uuid: String,
+    pub url: String,
+    pub events: Vec<String>,
+    /// Shared secret used to sign deliveries, only ever returned on creation
+    pub secret: String,
+    pub created_at: String,
+}
+
+#[cfg(feature = "server")]
+impl FromRow<'_, PgRow> for WebhookDetail {
+    fn from_row(row: &PgRow) -> sqlx::Result<Self> {
+        let uuid: Uuid = row.try_get("webhook_uuid")?;
+        let url: String = row.try_get("url")?;
+        let events: Vec<String> = row.try_get("events")?;
+        let secret: String = row.try_get("secret")?;
+        let created_at: PrimitiveDateTime = row.try_get("created_at")?;
+        let created_at = format!("{:?}", created_at);
+        Ok(WebhookDetail {
+            webhook_uuid: uuid.to_string(),
+            url,
+            events,
+            secret,
+            created_at,
+        })
+    }
+}
+
+/// A pending delivery of an event to a webhook, read from the outbox
+#[derive(Debug, Clone, PartialEq)]
+pub struct WebhookDelivery {
+    pub delivery_uuid: String,
+    pub url: String,
+    pub secret: String,
+    pub event: String,
+    pub payload: String,
+    pub attempts: i32,
+}
+
+#[cfg(feature = "server")]
+impl FromRow<'_, PgRow> for WebhookDelivery {
+    fn from_row(row: &PgRow) -> sqlx::Result<Self> {
+        let uuid: Uuid = row.try_get("delivery_uuid")?;
+        let url: String = row.try_get("url")?;
+        let secret: String = row.try_get("secret")?;
+        let event: String = row.try_get("event")?;
+        let payload: serde_json::Value = row.try_get("payload")?;
+        let attempts: i32 = row.try_get("attempts")?;
+        Ok(WebhookDelivery {
+            delivery_uuid: uuid.to_string(),
+            url,
+            secret,
+            event,
+            payload: payload.to_string(),
+            attempts,
+        })
+    }
+}
+
+// ----------
+
+/// File received for a question, before it is validated
+pub struct Upload {
+    pub file_name: String,
+    pub content_type: String,
+    pub content: Vec<u8>,
+}
+
+/// File attached to a question, as validated before it is stored
+#[derive(Debug, Clone)]
+pub struct Attachment {
+    pub question_uuid: String,
+    pub file_name: String,
+    pub content_type: String,
+    pub size: i64,
+    /// Hex digest of the content, which is also its key in the blob store
+    pub sha256: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct AttachmentDetail {
+    pub attachment_uuid: String,
+    pub question_uuid: String,
+    pub file_name: String,
+    pub content_type: String,
+    pub size: i64,
+    pub sha256: String,
+    pub uploaded_by: String,
+    pub created_at: String,
+}
+
+#[cfg(feature = "server")]
+impl FromRow<'_, PgRow> for AttachmentDetail {
+    fn from_row(row: &PgRow) -> sqlx::Result<Self> {
+        let attachment_uuid: Uuid = row.try_get("attachment_uuid")?;
+        let question_uuid: Uuid = row.try_get("question_uuid")?;
+        let file_name: String = row.try_get("file_name")?;
+        let content_type: String = row.try_get("content_type")?;
+        let size: i64 = row.try_get("size")?;
+        let sha256: String = row.try_get("sha256")?;
+        let uploaded_by: String = row.try_get("uploaded_by")?;
+        let created_at: PrimitiveDateTime = row.try_get("created_at")?;
+        let created_at = format!("{:?}", created_at);
+        Ok(AttachmentDetail {
+            attachment_uuid: attachment_uuid.to_string(),
+            question_uuid: question_uuid.to_string(),
+            file_name,
+            content_type,
+            size,
+            sha256,
+            uploaded_by,
+            created_at,
+        })
+    }
+}
+
+// ----------
+
+/// A new answer on a question the user follows
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct InboxItem {
+    pub item_uuid: String,
+    pub question_uuid: String,
+    pub question_title: String,
+    pub answer_uuid: String,
+    pub answer_author: String,
+    pub created_at: String,
+}
+
+#[cfg(feature = "server")]
+impl FromRow<'_, PgRow> for InboxItem {
+    fn from_row(row: &PgRow) -> sqlx::Result<Self> {
+        let item_uuid: Uuid = row.try_get("item_uuid")?;
+        let question_uuid: Uuid = row.try_get("question_uuid")?;
+        let question_title: String = row.try_get("question_title")?;
+        let answer_uuid: Uuid = row.try_get("answer_uuid")?;
+        let answer_author: String = row.try_get("answer_author")?;
+        let created_at: PrimitiveDateTime = row.try_get("created_at")?;
+        let created_at = format!("{:?}", created_at);
+        Ok(InboxItem {
+            item_uuid: item_uuid.to_string(),
+            question_uuid: question_uuid.to_string(),
+            question_title,
+            answer_uuid: answer_uuid.to_string(),
+            answer_author,
+            created_at,
+        })
+    }
+}
+
+/// Inbox items to mark as read, items of other users are ignored
+#[derive(Serialize, Deserialize)]
+pub struct MarkRead {
+    pub item_uuids: Vec<String>,
+}
+
+// ----------
+
+/// Isolated set of questions, with their answers, attachments and flags
+#[derive(Serialize, Deserialize)]
+pub struct Space {
+    pub slug: String,
+    pub name: String,
+    /// Open spaces can be read and written by anyone, members or not
+    #[serde(default)]
+    pub open: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SpaceDetail {
+    pub space_id: String,
+    pub slug: String,
+    pub name: String,
+    pub open: bool,
+    pub created_at: String,
+}
+
+#[cfg(feature = "server")]
+impl FromRow<'_, PgRow> for SpaceDetail {
+    fn from_row(row: &PgRow) -> sqlx::Result<Self> {
+        let space_id: Uuid = row.try_get("space_id")?;
+        let slug: String = row.try_get("slug")?;
+        let name: String = row.try_get("name")?;
+        let open: bool = row.try_get("open")?;
+        let created_at: PrimitiveDateTime = row.try_get("created_at")?;
+        let created_at = format!("{:?}", created_at);
+        Ok(SpaceDetail {
+            space_id: space_id.to_string(),
+            slug,
+            name,
+            open,
+            created_at,
+        })
+    }
+}
+
+/// What a member may do in a space
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SpaceRole {
+    /// Read questions and answers, follow and bookmark them
+    Reader,
+    /// Also ask, answer, edit, attach and flag
+    Writer,
+}
+
+impl SpaceRole {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SpaceRole::Reader => "reader",
+            SpaceRole::Writer => "writer",
+        }
+    }
+
+    pub fn parse(role: &str) -> Option<Self> {
+        match role {
+            "reader" => Some(SpaceRole::Reader),
+            "writer" => Some(SpaceRole::Writer),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SpaceMember {
+    pub role: SpaceRole,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SpaceMemberDetail {
+    pub space_id: String,
+    pub user_id: String,
+    pub role: SpaceRole,
+    pub created_at: String,
+}
+
+#[cfg(feature = "server")]
+impl FromRow<'_, PgRow> for SpaceMemberDetail {
+    fn from_row(row: &PgRow) -> sqlx::Result<Self> {
+        let space_id: Uuid = row.try_get("space_id")?;
+        let user_id: String = row.try_get("user_id")?;
+        let role: String = row.try_get("role")?;
+        let role = SpaceRole::parse(&role).ok_or_else(|| sqlx::Error::ColumnDecode {
+            index: "role".to_owned(),
+            source: format!("unknown space role {role}").into(),
+        })?;
+        let created_at: PrimitiveDateTime = row.try_get("created_at")?;
+        let created_at = format!("{:?}", created_at);
+        Ok(SpaceMemberDetail {
+            space_id: space_id.to_string(),
+            user_id,
+            role,
+            created_at,
+        })
+    }
+}
+
 // ----------
 
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AuditAction {
+    #[serde(rename = "question.created")]
+    QuestionCreated,
+    #[serde(rename = "question.updated")]
+    QuestionUpdated,
+    #[serde(rename = "question.deleted")]
+    QuestionDeleted,
+    #[serde(rename = "question.restored")]
+    QuestionRestored,
+    #[serde(rename = "question.closed_as_duplicate")]
+    QuestionClosedAsDuplicate,
+    #[serde(rename = "answer.created")]
+    AnswerCreated,
+    #[serde(rename = "answer.updated")]
+    AnswerUpdated,
+    #[serde(rename = "answer.deleted")]
+    AnswerDeleted,
+    #[serde(rename = "flag.created")]
+    FlagCreated,
+    #[serde(rename = "flag.resolved")]
+    FlagResolved,
+    #[serde(rename = "attachment.created")]
+    AttachmentCreated,
+    #[serde(rename = "webhook.created")]
+    WebhookCreated,
+    #[serde(rename = "webhook.deleted")]
+    WebhookDeleted,
+    #[serde(rename = "question.followed")]
+    QuestionFollowed,
+    #[serde(rename = "question.unfollowed")]
+    QuestionUnfollowed,
+    #[serde(rename = "question.bookmarked")]
+    QuestionBookmarked,
+    #[serde(rename = "question.bookmark_removed")]
+    QuestionBookmarkRemoved,
+    #[serde(rename = "inbox.read")]
+    InboxRead,
+    #[serde(rename = "space.created")]
+    SpaceCreated,
+    #[serde(rename = "space.member_set")]
+    SpaceMemberSet,
+    #[serde(rename = "space.member_removed")]
+    SpaceMemberRemoved,
+}
+
+impl AuditAction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AuditAction::QuestionCreated => "question.created",
+            AuditAction::QuestionUpdated => "question.updated",
+            AuditAction::QuestionDeleted => "question.deleted",
+            AuditAction::QuestionRestored => "question.restored",
+            AuditAction::QuestionClosedAsDuplicate => "question.closed_as_duplicate",
+            AuditAction::AnswerCreated => "answer.created",
+            AuditAction::AnswerUpdated => "answer.updated",
+            AuditAction::AnswerDeleted => "answer.deleted",
+            AuditAction::FlagCreated => "flag.created",
+            AuditAction::FlagResolved => "flag.resolved",
+            AuditAction::AttachmentCreated => "attachment.created",
+            AuditAction::WebhookCreated => "webhook.created",
+            AuditAction::WebhookDeleted => "webhook.deleted",
+            AuditAction::QuestionFollowed => "question.followed",
+            AuditAction::QuestionUnfollowed => "question.unfollowed",
+            AuditAction::QuestionBookmarked => "question.bookmarked",
+            AuditAction::QuestionBookmarkRemoved => "question.bookmark_removed",
+            AuditAction::InboxRead => "inbox.read",
+            AuditAction::SpaceCreated => "space.created",
+            AuditAction::SpaceMemberSet => "space.member_set",
+            AuditAction::SpaceMemberRemoved => "space.member_removed",
+        }
+    }
+}
+
+/// A mutation to record, `before` and `after` are snapshots of the resource
+/// and are absent when it did not exist before or does not exist after
+#[derive(Debug, Clone, PartialEq)]
+pub struct AuditEntry {
+    pub actor: String,
+    pub action: AuditAction,
+    pub resource_id: String,
+    pub before: Option<serde_json::Value>,
+    pub after: Option<serde_json::Value>,
+    pub request_id: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct AuditRecord {
+    pub audit_uuid: String,
+    pub actor: String,
+    pub action: String,
+    pub resource_id: String,
+    pub before: Option<serde_json::Value>,
+    pub after: Option<serde_json::Value>,
+    pub request_id: Option<String>,
+    pub created_at: String,
+}
+
+#[cfg(feature = "server")]
+impl FromRow<'_, PgRow> for AuditRecord {
+    fn from_row(row: &PgRow) -> sqlx::Result<Self> {
+        let audit_uuid: Uuid = row.try_get("audit_uuid")?;
+        let actor: String = row.try_get("actor")?;
+        let action: String = row.try_get("action")?;
+        let resource_id: String = row.try_get("resource_id")?;
+        let before: Option<serde_json::Value> = row.try_get("before")?;
+        let after: Option<serde_json::Value> = row.try_get("after")?;
+        let request_id: Option<String> = row.try_get("request_id")?;
+        let created_at: PrimitiveDateTime = row.try_get("created_at")?;
+        let created_at = format!("{:?}", created_at);
+        Ok(AuditRecord {
+            audit_uuid: audit_uuid.to_string(),
+            actor,
+            action,
+            resource_id,
+            before,
+            after,
+            request_id,
+            created_at,
+        })
+    }
+}
+
+/// Filters of the audit log, `since` and `until` are timestamps like `2026-10-18 09:00:00`
+/// bounding `created_at`, `until` being excluded
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct AuditQuery {
+    pub actor: Option<String>,
+    pub resource_id: Option<String>,
+    pub since: Option<String>,
+    pub until: Option<String>,
+}
+
+// ----------
+
+/// Figures of a space, as of the last refresh of the statistics. Deleted posts are left out.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Stats {
+    pub questions: i64,
+    pub answers: i64,
+    /// Questions without any answer, questions closed as duplicates excepted
+    pub unanswered_questions: i64,
+    /// Median time between a question and its first answer, among answered questions
+    pub median_seconds_to_first_answer: Option<f64>,
+    /// One entry per day of the window, oldest first, days without posts included
+    pub posts_per_day: Vec<DailyPosts>,
+    pub refreshed_at: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct DailyPosts {
+    /// Formatted like `2026-10-18`
+    pub day: String,
+    pub questions: i64,
+    pub answers: i64,
+}
+
+#[cfg(feature = "server")]
+impl FromRow<'_, PgRow> for DailyPosts {
+    fn from_row(row: &PgRow) -> sqlx::Result<Self> {
+        let day: String = row.try_get("day")?;
+        let questions: i64 = row.try_get("questions")?;
+        let answers: i64 = row.try_get("answers")?;
+        Ok(DailyPosts {
+            day,
+            questions,
+            answers,
+        })
+    }
+}
+
+/// Window of the posts per day, the last `days` days up to today
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct StatsQuery {
+    pub days: Option<i64>,
+}
+
+/// Run of a maintenance job, times are in UTC
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct JobRun {
+    pub job: String,
+    /// Time the run was due according to the schedule of the job
+    pub scheduled_for: String,
+    pub started_at: String,
+    pub finished_at: String,
+    pub succeeded: bool,
+    /// Questions closed, flagged or archived by the run
+    pub affected: i64,
+    pub error: Option<String>,
+}
+
+#[cfg(feature = "server")]
+impl FromRow<'_, PgRow> for JobRun {
+    fn from_row(row: &PgRow) -> sqlx::Result<Self> {
+        let job: String = row.try_get("job")?;
+        let scheduled_for: PrimitiveDateTime = row.try_get("scheduled_for")?;
+        let started_at: PrimitiveDateTime = row.try_get("started_at")?;
+        let finished_at: PrimitiveDateTime = row.try_get("finished_at")?;
+        let succeeded: bool = row.try_get("succeeded")?;
+        let affected: i64 = row.try_get("affected")?;
+        let error: Option<String> = row.try_get("error")?;
+        Ok(JobRun {
+            job,
+            scheduled_for: format!("{:?}", scheduled_for),
+            started_at: format!("{:?}", started_at),
+            finished_at: format!("{:?}", finished_at),
+            succeeded,
+            affected,
+            error,
+        })
+    }
+}
+
+/// Counters of the read cache of the instance that answered, since it started
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct CacheMetrics {
+    pub hits: u64,
+    pub misses: u64,
+    /// Times the reads of a space, or all of them, were forgotten after a write
+    pub invalidations: u64,
+    /// Reads currently cached
+    pub entries: usize,
+}
+
+// ---- API v2 ----
+//
+// v2 names ids by what they identify and wraps listings in pages, v1 keeps the shapes above
+
+/// UUID of a question
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
+#[serde(transparent)]
+pub struct QuestionUuid(pub String);
+
+/// UUID of an answer
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
+#[serde(transparent)]
+pub struct AnswerUuid(pub String);
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct QuestionV2 {
+    pub id: QuestionUuid,
+    pub title: String,
+    pub description: String,
+    /// Sanitised HTML rendering of `description`
+    pub description_html: String,
+    pub author: String,
+    /// Set when the question was closed as a duplicate of another one
+    pub duplicate_of: Option<QuestionUuid>,
+    /// Views counted so far, recent views may not be included yet
+    pub view_count: i64,
+    pub created_at: String,
+}
+
+impl From<QuestionDetail> for QuestionV2 {
+    fn from(question: QuestionDetail) -> Self {
+        QuestionV2 {
+            id: QuestionUuid(question.question_uuid),
+            title: question.title,
+            description: question.description,
+            description_html: question.description_html,
+            author: question.author,
+            duplicate_of: question.duplicate_of.map(QuestionUuid),
+            view_count: question.view_count,
+            created_at: question.created_at,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct AnswerV2 {
+    pub id: AnswerUuid,
+    pub question_id: QuestionUuid,
+    pub content: String,
+    /// Sanitised HTML rendering of `content`
+    pub content_html: String,
+    pub author: String,
+    pub created_at: String,
+}
+
+impl From<AnswerDetail> for AnswerV2 {
+    fn from(answer: AnswerDetail) -> Self {
+        AnswerV2 {
+            id: AnswerUuid(answer.answer_uuid),
+            question_id: QuestionUuid(answer.question_uuid),
+            content: answer.content,
+            content_html: answer.content_html,
+            author: answer.author,
+            created_at: answer.created_at,
+        }
+    }
+}
+
+/// Part of a listing, oldest first
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    /// Offset of the next page, `None` on the last page
+    pub next_offset: Option<i64>,
+}
+
+impl<T> Page<T> {
+    pub fn map<U>(self, f: impl FnMut(T) -> U) -> Page<U> {
+        Page {
+            items: self.items.into_iter().map(f).collect(),
+            next_offset: self.next_offset,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct PageQuery {
+    pub offset: Option<i64>,
+    pub limit: Option<i64>,
+}
+
+// ----------
+
+/// Errors returned by the API, the client maps error responses back to them
+#[derive(Error, Debug, Clone, PartialEq, Deserialize)]
+pub enum HandlerError {
+    #[error("Bad request: {0}")]
+    BadRequest(String),
+    #[error("Unauthorized: {0}")]
+    Unauthorized(String),
+    #[error("Forbidden: {0}")]
+    Forbidden(String),
+    #[error("Internal error: {0}")]
+    InternalError(String),
+}
+
 #[derive(Error, Debug)]
 pub enum DBError {
     #[error("Invalid UUID provided: {0}")]
@@ -85,8 +906,19 @@ pub enum DBError {
     Other(#[from] Box<dyn std::error::Error + Send + Sync>),
 }
 
+#[derive(Error, Debug)]
+pub enum BlobError {
+    #[error("Blob not found: {0}")]
+    NotFound(String),
+    #[error("Blob store error: {0}")]
+    Io(#[from] std::io::Error),
+}
+
 // source: https://www.postgresql.org/docs/current/errcodes-appendix.html
 #[allow(dead_code)]
 pub mod postgres_error_codes {
     pub const FOREIGN_KEY_VIOLATION: &str = "23503";
+    pub const UNIQUE_VIOLATION: &str = "23505";
+    pub const INVALID_DATETIME_FORMAT: &str = "22007";
+    pub const DATETIME_FIELD_OVERFLOW: &str = "22008";
 }
@@ -1,28 +1,114 @@
+use std::sync::Arc;
+
 use async_trait::async_trait;
-use sqlx::{types::Uuid, PgPool};
+use sqlx::{types::Uuid, Connection, PgPool};
 
-use crate::models::{Answer, AnswerDetail, DBError, QuestionDetail};
+use crate::markdown::RenderCache;
+use crate::models::{
+    Answer, AnswerDetail, DBError, QuestionDetail, ReputationReason, WebhookEvent,
+};
+use crate::persistance::{
+    follows_dao::notify_followers, reputation_dao::record_reputation, spaces_dao::DEFAULT_SPACE_ID,
+    unit_of_work::UnitOfWork, webhooks_dao::enqueue_event,
+};
 
 #[async_trait]
 pub trait AnswersDao {
-    async fn create_answer(&self, answer: Answer) -> Result<AnswerDetail, DBError>;
-    async fn delete_answer(&self, answer_uuid: String) -> Result<(), DBError>;
+    async fn create_answer(
+        &self,
+        uow: &mut dyn UnitOfWork,
+        answer: Answer,
+        author: String,
+    ) -> Result<AnswerDetail, DBError>;
+    async fn get_answer(&self, answer_uuid: String) -> Result<AnswerDetail, DBError>;
+    /// Reads an answer and locks it until `uow` ends, so that checks made on it still hold
+    async fn lock_answer(
+        &self,
+        uow: &mut dyn UnitOfWork,
+        answer_uuid: String,
+    ) -> Result<AnswerDetail, DBError>;
+    /// Replaces the content of an answer that was not deleted
+    async fn update_answer(
+        &self,
+        uow: &mut dyn UnitOfWork,
+        answer_uuid: String,
+        content: String,
+    ) -> Result<AnswerDetail, DBError>;
+    /// Soft deletes an answer
+    async fn delete_answer(
+        &self,
+        uow: &mut dyn UnitOfWork,
+        answer_uuid: String,
+        deleted_by: String,
+    ) -> Result<(), DBError>;
     async fn get_answers(&self, question_uuid: String) -> Result<Vec<AnswerDetail>, DBError>;
+    /// Answers of all the given questions in a single query, oldest first
+    async fn get_answers_of_questions(
+        &self,
+        question_uuids: Vec<String>,
+    ) -> Result<Vec<AnswerDetail>, DBError>;
 }
 
+/// Reads and writes the answers to the questions of a single space
 pub struct AnswersDaoImpl {
     db: PgPool,
+    space_id: Uuid,
+    render_cache: Arc<RenderCache>,
 }
 
 impl AnswersDaoImpl {
     pub fn new(db: PgPool) -> Self {
-        AnswersDaoImpl { db }
+        Self::in_space(db, DEFAULT_SPACE_ID)
+    }
+
+    pub fn in_space(db: PgPool, space_id: Uuid) -> Self {
+        AnswersDaoImpl {
+            db,
+            space_id,
+            render_cache: Arc::default(),
+        }
+    }
+
+    /// Shares the rendered contents with the other DAOs of the application
+    pub fn with_render_cache(self, render_cache: Arc<RenderCache>) -> Self {
+        AnswersDaoImpl {
+            render_cache,
+            ..self
+        }
+    }
+}
+
+/// Fills in the HTML of an answer read from the database
+async fn render_answer(
+    render_cache: &Arc<RenderCache>,
+    mut answer: AnswerDetail,
+) -> Result<AnswerDetail, DBError> {
+    answer.content_html = render_cache
+        .render(&answer.answer_uuid, &answer.content)
+        .await
+        .map_err(|e| DBError::Other(Box::new(e)))?;
+    Ok(answer)
+}
+
+async fn render_answers(
+    render_cache: &Arc<RenderCache>,
+    answers: Vec<AnswerDetail>,
+) -> Result<Vec<AnswerDetail>, DBError> {
+    let mut rendered = Vec::with_capacity(answers.len());
+    for answer in answers {
+        rendered.push(render_answer(render_cache, answer).await?);
     }
+    Ok(rendered)
 }
 
 #[async_trait]
 impl AnswersDao for AnswersDaoImpl {
-    async fn create_answer(&self, answer: Answer) -> Result<AnswerDetail, DBError> {
+    async fn create_answer(
+        &self,
+        uow: &mut dyn UnitOfWork,
+        answer: Answer,
+        author: String,
+    ) -> Result<AnswerDetail, DBError> {
         // Use the `sqlx::types::Uuid::parse_str` method to parse the `question_uuid` field
         // in `Answer` into a `Uuid` type.
         // parse_str docs: https://docs.rs/sqlx/latest/sqlx/types/struct.Uuid.html#method.parse_str
@@ -32,13 +118,26 @@ impl AnswersDao for AnswersDaoImpl {
         let uuid = Uuid::parse_str(&answer.question_uuid)
             .map_err(|e| DBError::InvalidUUID(e.to_string()))?;
 
+        let mut tx = uow
+            .connection()
+            .await?
+            .begin()
+            .await
+            .map_err(|e| DBError::Other(Box::new(e)))?;
+
+        // Questions closed for being inactive may not be answered. The question is locked until
+        // the unit of work ends, so that it cannot be deleted or closed between this check and
+        // the insert. A concurrent delete makes this wait for it and then find no question.
         let _ = sqlx::query_as::<_, QuestionDetail>(
             r"
-        SELECT * FROM questions WHERE question_uuid = $1
+        SELECT * FROM questions
+        WHERE question_uuid = $1 AND space_id = $2 AND deleted_at IS NULL AND closed_at IS NULL
+        FOR SHARE
         ",
         )
         .bind(uuid)
-        .fetch_one(&self.db)
+        .bind(self.space_id)
+        .fetch_one(&mut *tx)
         .await
         .map_err(|e| match e {
             sqlx::Error::RowNotFound => DBError::InvalidUUID(e.to_string()),
@@ -48,29 +147,138 @@ impl AnswersDao for AnswersDaoImpl {
         // Make a database query to insert a new answer.
         // Here is the SQL query:
         // ```
-        // INSERT INTO answers ( question_uuid, content )
-        // VALUES ( $1, $2 )
+        // INSERT INTO answers ( question_uuid, content, author )
+        // VALUES ( $1, $2, $3 )
         // RETURNING *
         // ```
         // If executing the query results in an error, check to see if
         // the error code matches `postgres_error_codes::FOREIGN_KEY_VIOLATION`.
         // If so early return the `DBError::InvalidUUID` error. Otherwise early return
         // the `DBError::Other` error.
-        sqlx::query_as::<_, AnswerDetail>(
+        let answer = sqlx::query_as::<_, AnswerDetail>(
             r"
-        INSERT INTO answers ( question_uuid, content )
-        VALUES ( $1, $2 )
+        INSERT INTO answers ( question_uuid, content, author )
+        VALUES ( $1, $2, $3 )
         RETURNING *
         ",
         )
         .bind(uuid)
         .bind(answer.content)
+        .bind(&author)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(|e| DBError::Other(Box::new(e)))?;
+        let answer = render_answer(&self.render_cache, answer).await?;
+        let answer_uuid = Uuid::parse_str(&answer.answer_uuid)
+            .map_err(|e| DBError::InvalidUUID(e.to_string()))?;
+
+        enqueue_event(&mut tx, self.space_id, WebhookEvent::AnswerCreated, &answer).await?;
+        notify_followers(&mut tx, uuid, answer_uuid, &author).await?;
+        record_reputation(
+            &mut tx,
+            self.space_id,
+            &author,
+            ReputationReason::AnswerPosted,
+            answer_uuid,
+        )
+        .await?;
+
+        tx.commit().await.map_err(|e| DBError::Other(Box::new(e)))?;
+
+        Ok(answer)
+    }
+
+    async fn get_answer(&self, answer_uuid: String) -> Result<AnswerDetail, DBError> {
+        let uuid =
+            Uuid::parse_str(&answer_uuid).map_err(|e| DBError::InvalidUUID(e.to_string()))?;
+
+        let answer = sqlx::query_as::<_, AnswerDetail>(
+            r"
+        SELECT answers.* FROM answers
+        JOIN questions ON questions.question_uuid = answers.question_uuid
+        WHERE answers.answer_uuid = $1
+            AND answers.deleted_at IS NULL
+            AND questions.space_id = $2
+        ",
+        )
+        .bind(uuid)
+        .bind(self.space_id)
         .fetch_one(&self.db)
         .await
-        .map_err(|e| DBError::Other(Box::new(e)))
+        .map_err(|e| match e {
+            sqlx::Error::RowNotFound => DBError::InvalidUUID(e.to_string()),
+            _ => DBError::Other(Box::new(e)),
+        })?;
+
+        render_answer(&self.render_cache, answer).await
     }
 
-    async fn delete_answer(&self, answer_uuid: String) -> Result<(), DBError> {
+    async fn lock_answer(
+        &self,
+        uow: &mut dyn UnitOfWork,
+        answer_uuid: String,
+    ) -> Result<AnswerDetail, DBError> {
+        let uuid =
+            Uuid::parse_str(&answer_uuid).map_err(|e| DBError::InvalidUUID(e.to_string()))?;
+
+        let answer = sqlx::query_as::<_, AnswerDetail>(
+            r"
+        SELECT answers.* FROM answers
+        JOIN questions ON questions.question_uuid = answers.question_uuid
+        WHERE answers.answer_uuid = $1
+            AND answers.deleted_at IS NULL
+            AND questions.space_id = $2
+        FOR UPDATE OF answers
+        ",
+        )
+        .bind(uuid)
+        .bind(self.space_id)
+        .fetch_one(uow.connection().await?)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::RowNotFound => DBError::InvalidUUID(e.to_string()),
+            _ => DBError::Other(Box::new(e)),
+        })?;
+
+        render_answer(&self.render_cache, answer).await
+    }
+
+    async fn update_answer(
+        &self,
+        uow: &mut dyn UnitOfWork,
+        answer_uuid: String,
+        content: String,
+    ) -> Result<AnswerDetail, DBError> {
+        let uuid =
+            Uuid::parse_str(&answer_uuid).map_err(|e| DBError::InvalidUUID(e.to_string()))?;
+
+        let answer = sqlx::query_as::<_, AnswerDetail>(
+            r"
+        UPDATE answers SET content = $2
+        WHERE answer_uuid = $1 AND deleted_at IS NULL
+            AND question_uuid IN ( SELECT question_uuid FROM questions WHERE space_id = $3 )
+        RETURNING *
+        ",
+        )
+        .bind(uuid)
+        .bind(content)
+        .bind(self.space_id)
+        .fetch_one(uow.connection().await?)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::RowNotFound => DBError::InvalidUUID(e.to_string()),
+            _ => DBError::Other(Box::new(e)),
+        })?;
+
+        render_answer(&self.render_cache, answer).await
+    }
+
+    async fn delete_answer(
+        &self,
+        uow: &mut dyn UnitOfWork,
+        answer_uuid: String,
+        deleted_by: String,
+    ) -> Result<(), DBError> {
         // Use the `sqlx::types::Uuid::parse_str` method to parse `answer_uuid` into a `Uuid` type.
         // parse_str docs: https://docs.rs/sqlx/latest/sqlx/types/struct.Uuid.html#method.parse_str
         //
@@ -79,21 +287,21 @@ impl AnswersDao for AnswersDaoImpl {
         let uuid =
             Uuid::parse_str(&answer_uuid).map_err(|e| DBError::InvalidUUID(e.to_string()))?;
 
-        // Make a database query to delete an answer given the answer uuid.
-        // Here is the SQL query:
-        // ```
-        // DELETE FROM answers WHERE answer_uuid = $1
-        // ```
+        // Make a database query to soft delete an answer given the answer uuid.
         // If executing the query results in an error, map that error
         // to a `DBError::Other` error and early return from this function.
 
         sqlx::query(
             r"
-        DELETE FROM answers WHERE answer_uuid = $1
+        UPDATE answers SET deleted_at = CURRENT_TIMESTAMP, deleted_by = $2
+        WHERE answer_uuid = $1 AND deleted_at IS NULL
+            AND question_uuid IN ( SELECT question_uuid FROM questions WHERE space_id = $3 )
         ",
         )
         .bind(uuid)
-        .execute(&self.db)
+        .bind(deleted_by)
+        .bind(self.space_id)
+        .execute(uow.connection().await?)
         .await
         .map_err(|e| DBError::Other(Box::new(e)))?;
         Ok(())
@@ -109,20 +317,55 @@ impl AnswersDao for AnswersDaoImpl {
             Uuid::parse_str(&question_uuid).map_err(|e| DBError::InvalidUUID(e.to_string()))?;
 
         // Make a database query to get all answers associated with a question uuid.
-        // Here is the SQL query:
-        // ```
-        // SELECT * FROM answers WHERE question_uuid = $1
-        // ```
+        // Deleted answers, and answers of a deleted question, are left out.
         // If executing the query results in an error, map that error
         // to a `DBError::Other` error and early return from this function.
-        sqlx::query_as::<_, AnswerDetail>(
+        let answers = sqlx::query_as::<_, AnswerDetail>(
             r"
-        SELECT * FROM answers WHERE question_uuid = $1
+        SELECT answers.* FROM answers
+        JOIN questions ON questions.question_uuid = answers.question_uuid
+        WHERE answers.question_uuid = $1
+            AND answers.deleted_at IS NULL
+            AND questions.deleted_at IS NULL
+            AND questions.space_id = $2
         ",
         )
         .bind(uuid)
+        .bind(self.space_id)
         .fetch_all(&self.db)
         .await
-        .map_err(|e| DBError::Other(Box::new(e)))
+        .map_err(|e| DBError::Other(Box::new(e)))?;
+
+        render_answers(&self.render_cache, answers).await
+    }
+
+    async fn get_answers_of_questions(
+        &self,
+        question_uuids: Vec<String>,
+    ) -> Result<Vec<AnswerDetail>, DBError> {
+        let uuids = question_uuids
+            .iter()
+            .map(|uuid| Uuid::parse_str(uuid))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| DBError::InvalidUUID(e.to_string()))?;
+
+        let answers = sqlx::query_as::<_, AnswerDetail>(
+            r"
+        SELECT answers.* FROM answers
+        JOIN questions ON questions.question_uuid = answers.question_uuid
+        WHERE answers.question_uuid = ANY($1)
+            AND answers.deleted_at IS NULL
+            AND questions.deleted_at IS NULL
+            AND questions.space_id = $2
+        ORDER BY answers.created_at, answers.answer_uuid
+        ",
+        )
+        .bind(uuids)
+        .bind(self.space_id)
+        .fetch_all(&self.db)
+        .await
+        .map_err(|e| DBError::Other(Box::new(e)))?;
+
+        render_answers(&self.render_cache, answers).await
     }
 }
@@ -0,0 +1,159 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+use sqlx::{types::Uuid, PgPool};
+
+use crate::models::{Attachment, AttachmentDetail, DBError};
+use crate::persistance::{spaces_dao::DEFAULT_SPACE_ID, unit_of_work::UnitOfWork};
+
+#[async_trait]
+pub trait AttachmentsDao {
+    /// Records a content already put in the blob store, the question must not be deleted
+    async fn create_attachment(
+        &self,
+        uow: &mut dyn UnitOfWork,
+        attachment: Attachment,
+        uploaded_by: String,
+    ) -> Result<AttachmentDetail, DBError>;
+    /// Attachment of a question that was not deleted
+    async fn get_attachment(&self, attachment_uuid: String) -> Result<AttachmentDetail, DBError>;
+    /// Attachments of a question that was not deleted, oldest first
+    async fn get_attachments(
+        &self,
+        question_uuid: String,
+    ) -> Result<Vec<AttachmentDetail>, DBError>;
+    /// Forgets the attachments of questions deleted more than `retention` ago in any space,
+    /// returns how many were forgotten
+    async fn purge_attachments_of_deleted_questions(
+        &self,
+        retention: Duration,
+    ) -> Result<u64, DBError>;
+    /// Digests among `digests` that some attachment of any space still refers to
+    async fn get_referenced_digests(&self, digests: Vec<String>) -> Result<Vec<String>, DBError>;
+}
+
+/// Attachments of the questions of a single space. The blob store is shared by all spaces,
+/// so the sweeping methods cover every space.
+pub struct AttachmentsDaoImpl {
+    db: PgPool,
+    space_id: Uuid,
+}
+
+impl AttachmentsDaoImpl {
+    pub fn new(db: PgPool) -> Self {
+        Self::in_space(db, DEFAULT_SPACE_ID)
+    }
+
+    pub fn in_space(db: PgPool, space_id: Uuid) -> Self {
+        AttachmentsDaoImpl { db, space_id }
+    }
+}
+
+#[async_trait]
+impl AttachmentsDao for AttachmentsDaoImpl {
+    async fn create_attachment(
+        &self,
+        uow: &mut dyn UnitOfWork,
+        attachment: Attachment,
+        uploaded_by: String,
+    ) -> Result<AttachmentDetail, DBError> {
+        let uuid = Uuid::parse_str(&attachment.question_uuid)
+            .map_err(|e| DBError::InvalidUUID(e.to_string()))?;
+
+        sqlx::query_as::<_, AttachmentDetail>(
+            r"
+        INSERT INTO attachments ( question_uuid, file_name, content_type, size, sha256, uploaded_by )
+        SELECT question_uuid, $2, $3, $4, $5, $6 FROM questions
+        WHERE question_uuid = $1 AND space_id = $7 AND deleted_at IS NULL
+        RETURNING *
+        ",
+        )
+        .bind(uuid)
+        .bind(attachment.file_name)
+        .bind(attachment.content_type)
+        .bind(attachment.size)
+        .bind(attachment.sha256)
+        .bind(uploaded_by)
+        .bind(self.space_id)
+        .fetch_one(uow.connection().await?)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::RowNotFound => DBError::InvalidUUID(e.to_string()),
+            _ => DBError::Other(Box::new(e)),
+        })
+    }
+
+    async fn get_attachment(&self, attachment_uuid: String) -> Result<AttachmentDetail, DBError> {
+        let uuid =
+            Uuid::parse_str(&attachment_uuid).map_err(|e| DBError::InvalidUUID(e.to_string()))?;
+
+        sqlx::query_as::<_, AttachmentDetail>(
+            r"
+        SELECT a.* FROM attachments a
+        JOIN questions q ON q.question_uuid = a.question_uuid
+        WHERE a.attachment_uuid = $1 AND q.space_id = $2 AND q.deleted_at IS NULL
+        ",
+        )
+        .bind(uuid)
+        .bind(self.space_id)
+        .fetch_one(&self.db)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::RowNotFound => DBError::InvalidUUID(e.to_string()),
+            _ => DBError::Other(Box::new(e)),
+        })
+    }
+
+    async fn get_attachments(
+        &self,
+        question_uuid: String,
+    ) -> Result<Vec<AttachmentDetail>, DBError> {
+        let uuid =
+            Uuid::parse_str(&question_uuid).map_err(|e| DBError::InvalidUUID(e.to_string()))?;
+
+        sqlx::query_as::<_, AttachmentDetail>(
+            r"
+        SELECT a.* FROM attachments a
+        JOIN questions q ON q.question_uuid = a.question_uuid
+        WHERE a.question_uuid = $1 AND q.space_id = $2 AND q.deleted_at IS NULL
+        ORDER BY a.created_at, a.attachment_uuid
+        ",
+        )
+        .bind(uuid)
+        .bind(self.space_id)
+        .fetch_all(&self.db)
+        .await
+        .map_err(|e| DBError::Other(Box::new(e)))
+    }
+
+    async fn purge_attachments_of_deleted_questions(
+        &self,
+        retention: Duration,
+    ) -> Result<u64, DBError> {
+        let result = sqlx::query(
+            r"
+        DELETE FROM attachments a USING questions q
+        WHERE q.question_uuid = a.question_uuid
+            AND q.deleted_at <= CURRENT_TIMESTAMP - make_interval(secs => $1)
+        ",
+        )
+        .bind(retention.as_secs_f64())
+        .execute(&self.db)
+        .await
+        .map_err(|e| DBError::Other(Box::new(e)))?;
+
+        Ok(result.rows_affected())
+    }
+
+    async fn get_referenced_digests(&self, digests: Vec<String>) -> Result<Vec<String>, DBError> {
+        sqlx::query_scalar::<_, String>(
+            r"
+        SELECT DISTINCT sha256::text FROM attachments WHERE sha256 = ANY($1)
+        ",
+        )
+        .bind(digests)
+        .fetch_all(&self.db)
+        .await
+        .map_err(|e| DBError::Other(Box::new(e)))
+    }
+}
@@ -0,0 +1,90 @@
+use async_trait::async_trait;
+use sqlx::{types::Json, PgPool};
+
+use crate::models::{postgres_error_codes, AuditEntry, AuditQuery, AuditRecord, DBError};
+use crate::persistance::unit_of_work::UnitOfWork;
+
+/// Append-only log of mutations, the table rejects updates and deletes
+#[async_trait]
+pub trait AuditDao {
+    /// Appends a record in the unit of work of the mutation it describes,
+    /// so that a mutation is never committed without its record
+    async fn record(&self, uow: &mut dyn UnitOfWork, entry: AuditEntry) -> Result<(), DBError>;
+    /// Records matching `query`, most recent first
+    async fn get_audit_log(
+        &self,
+        query: AuditQuery,
+        limit: i64,
+    ) -> Result<Vec<AuditRecord>, DBError>;
+}
+
+pub struct AuditDaoImpl {
+    db: PgPool,
+}
+
+impl AuditDaoImpl {
+    pub fn new(db: PgPool) -> Self {
+        AuditDaoImpl { db }
+    }
+}
+
+#[async_trait]
+impl AuditDao for AuditDaoImpl {
+    async fn record(&self, uow: &mut dyn UnitOfWork, entry: AuditEntry) -> Result<(), DBError> {
+        sqlx::query(
+            r"
+        INSERT INTO audit_log ( actor, action, resource_id, before, after, request_id )
+        VALUES ( $1, $2, $3, $4, $5, $6 )
+        ",
+        )
+        .bind(entry.actor)
+        .bind(entry.action.as_str())
+        .bind(entry.resource_id)
+        .bind(entry.before.map(Json))
+        .bind(entry.after.map(Json))
+        .bind(entry.request_id)
+        .execute(uow.connection().await?)
+        .await
+        .map_err(|e| DBError::Other(Box::new(e)))?;
+
+        Ok(())
+    }
+
+    async fn get_audit_log(
+        &self,
+        query: AuditQuery,
+        limit: i64,
+    ) -> Result<Vec<AuditRecord>, DBError> {
+        // Bounds are sent as text so that Postgres parses them, malformed ones are the caller's fault
+        sqlx::query_as::<_, AuditRecord>(
+            r"
+        SELECT * FROM audit_log
+        WHERE ( $1::text IS NULL OR actor = $1 )
+            AND ( $2::text IS NULL OR resource_id = $2 )
+            AND ( $3::text IS NULL OR created_at >= $3::text::timestamp )
+            AND ( $4::text IS NULL OR created_at < $4::text::timestamp )
+        ORDER BY created_at DESC, audit_uuid
+        LIMIT $5
+        ",
+        )
+        .bind(query.actor)
+        .bind(query.resource_id)
+        .bind(query.since)
+        .bind(query.until)
+        .bind(limit)
+        .fetch_all(&self.db)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::Database(ref db_err)
+                if matches!(
+                    db_err.code().as_deref(),
+                    Some(postgres_error_codes::INVALID_DATETIME_FORMAT)
+                        | Some(postgres_error_codes::DATETIME_FIELD_OVERFLOW)
+                ) =>
+            {
+                DBError::InvalidUUID(e.to_string())
+            }
+            _ => DBError::Other(Box::new(e)),
+        })
+    }
+}
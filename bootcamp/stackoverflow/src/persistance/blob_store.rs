@@ -0,0 +1,261 @@
+use std::{
+    io::{ErrorKind, SeekFrom},
+    ops::Range,
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicU64, Ordering},
+    time::SystemTime,
+};
+
+use async_trait::async_trait;
+use sha2::{Digest, Sha256};
+use tokio::{
+    fs,
+    io::{AsyncReadExt, AsyncSeekExt},
+};
+
+use crate::models::BlobError;
+
+/// A blob and the last time it was stored
+#[derive(Debug, Clone, PartialEq)]
+pub struct StoredBlob {
+    pub digest: String,
+    pub modified: SystemTime,
+}
+
+/// Content-addressed storage of attachment contents
+#[async_trait]
+pub trait BlobStore {
+    /// Stores `content` under its hex SHA-256 digest and returns the digest.
+    /// Storing a content that is already there keeps a single copy and refreshes its `modified` time.
+    async fn put(&self, content: &[u8]) -> Result<String, BlobError>;
+    /// Bytes `range` of a blob, the whole blob when no range is given
+    async fn get(&self, digest: &str, range: Option<Range<u64>>) -> Result<Vec<u8>, BlobError>;
+    /// Deletes a blob unless it was stored after `cutoff`, and returns whether it was deleted.
+    /// Deleting a missing blob is not an error
+    async fn delete(&self, digest: &str, cutoff: SystemTime) -> Result<bool, BlobError>;
+    async fn list(&self) -> Result<Vec<StoredBlob>, BlobError>;
+}
+
+/// Blobs are files named after their digest, in directories named after its first two characters
+pub struct LocalBlobStore {
+    root: PathBuf,
+}
+
+impl LocalBlobStore {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        LocalBlobStore { root: root.into() }
+    }
+
+    /// Digests come from requests through the database, anything that is not one
+    /// must not be turned into a path
+    fn path(&self, digest: &str) -> Result<PathBuf, BlobError> {
+        if !is_digest(digest) {
+            return Err(BlobError::NotFound(digest.to_owned()));
+        }
+        Ok(self.root.join(&digest[..2]).join(digest))
+    }
+}
+
+/// Path a blob is written or deleted aside at, which `list` skips
+fn aside(path: &Path) -> PathBuf {
+    static TMP_FILES: AtomicU64 = AtomicU64::new(0);
+    path.with_extension(format!(
+        "{}.{}.tmp",
+        std::process::id(),
+        TMP_FILES.fetch_add(1, Ordering::Relaxed)
+    ))
+}
+
+fn is_digest(name: &str) -> bool {
+    name.len() == 64
+        && name
+            .bytes()
+            .all(|b| b.is_ascii_digit() || (b'a'..=b'f').contains(&b))
+}
+
+#[async_trait]
+impl BlobStore for LocalBlobStore {
+    async fn put(&self, content: &[u8]) -> Result<String, BlobError> {
+        let digest = hex::encode(Sha256::digest(content));
+        let path = self.path(&digest)?;
+
+        match fs::OpenOptions::new().append(true).open(&path).await {
+            Ok(file) => {
+                file.into_std().await.set_modified(SystemTime::now())?;
+                return Ok(digest);
+            }
+            Err(e) if e.kind() == ErrorKind::NotFound => {}
+            Err(e) => return Err(e.into()),
+        }
+
+        // Written aside and renamed so that a blob is never seen half written
+        let tmp = aside(&path);
+        fs::create_dir_all(&self.root.join(&digest[..2])).await?;
+        fs::write(&tmp, content).await?;
+        if let Err(e) = fs::rename(&tmp, &path).await {
+            let _ = fs::remove_file(&tmp).await;
+            return Err(e.into());
+        }
+
+        Ok(digest)
+    }
+
+    async fn get(&self, digest: &str, range: Option<Range<u64>>) -> Result<Vec<u8>, BlobError> {
+        let mut file = fs::File::open(self.path(digest)?)
+            .await
+            .map_err(|e| match e.kind() {
+                ErrorKind::NotFound => BlobError::NotFound(digest.to_owned()),
+                _ => BlobError::Io(e),
+            })?;
+
+        let mut content = Vec::new();
+        match range {
+            Some(range) => {
+                file.seek(SeekFrom::Start(range.start)).await?;
+                content.resize((range.end - range.start) as usize, 0);
+                file.read_exact(&mut content).await?;
+            }
+            None => {
+                file.read_to_end(&mut content).await?;
+            }
+        }
+
+        Ok(content)
+    }
+
+    async fn delete(&self, digest: &str, cutoff: SystemTime) -> Result<bool, BlobError> {
+        let path = self.path(digest)?;
+
+        // Moved aside before its time is checked, so that storing it again meanwhile either
+        // refreshes the time checked or writes a new copy
+        let tmp = aside(&path);
+        match fs::rename(&path, &tmp).await {
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(false),
+            result => result?,
+        }
+        let modified = match fs::metadata(&tmp).await.and_then(|m| m.modified()) {
+            Ok(modified) => modified,
+            Err(e) => {
+                let _ = fs::rename(&tmp, &path).await;
+                return Err(e.into());
+            }
+        };
+        if modified > cutoff {
+            fs::rename(&tmp, &path).await?;
+            return Ok(false);
+        }
+        fs::remove_file(&tmp).await?;
+
+        Ok(true)
+    }
+
+    async fn list(&self) -> Result<Vec<StoredBlob>, BlobError> {
+        let mut blobs = Vec::new();
+        let mut dirs = match fs::read_dir(&self.root).await {
+            Ok(dirs) => dirs,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(blobs),
+            Err(e) => return Err(e.into()),
+        };
+
+        while let Some(dir) = dirs.next_entry().await? {
+            if !dir.file_type().await?.is_dir() {
+                continue;
+            }
+            let mut files = fs::read_dir(dir.path()).await?;
+            while let Some(file) = files.next_entry().await? {
+                // Skips files being written
+                let Some(digest) = file
+                    .file_name()
+                    .to_str()
+                    .filter(|n| is_digest(n))
+                    .map(str::to_owned)
+                else {
+                    continue;
+                };
+                let modified = file.metadata().await?.modified()?;
+                blobs.push(StoredBlob { digest, modified });
+            }
+        }
+
+        Ok(blobs)
+    }
+}
+
+// ***********************************************************
+//                           Tests
+// ***********************************************************
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn put_should_store_identical_contents_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = LocalBlobStore::new(dir.path());
+
+        let first = store.put(b"hello").await.unwrap();
+        let second = store.put(b"hello").await.unwrap();
+        let other = store.put(b"world").await.unwrap();
+
+        assert_eq!(
+            first,
+            "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824"
+        );
+        assert_eq!(first, second);
+        let mut digests: Vec<String> = store
+            .list()
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|blob| blob.digest)
+            .collect();
+        digests.sort();
+        let mut expected = vec![first, other];
+        expected.sort();
+        assert_eq!(digests, expected);
+    }
+
+    #[tokio::test]
+    async fn get_should_read_ranges() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = LocalBlobStore::new(dir.path());
+        let digest = store.put(b"0123456789").await.unwrap();
+
+        assert_eq!(store.get(&digest, None).await.unwrap(), b"0123456789");
+        assert_eq!(store.get(&digest, Some(2..5)).await.unwrap(), b"234");
+    }
+
+    #[tokio::test]
+    async fn deleted_and_malformed_digests_should_not_be_found() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = LocalBlobStore::new(dir.path());
+        let digest = store.put(b"hello").await.unwrap();
+
+        assert!(store.delete(&digest, SystemTime::now()).await.unwrap());
+        assert!(!store.delete(&digest, SystemTime::now()).await.unwrap());
+
+        assert!(matches!(
+            store.get(&digest, None).await,
+            Err(BlobError::NotFound(_))
+        ));
+        assert!(matches!(
+            store.get("../../etc/passwd", None).await,
+            Err(BlobError::NotFound(_))
+        ));
+        assert!(store.list().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn delete_should_keep_blobs_stored_after_the_cutoff() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = LocalBlobStore::new(dir.path());
+        let cutoff = SystemTime::now() - std::time::Duration::from_secs(60);
+        let digest = store.put(b"hello").await.unwrap();
+
+        assert!(!store.delete(&digest, cutoff).await.unwrap());
+
+        assert_eq!(store.get(&digest, None).await.unwrap(), b"hello");
+        assert_eq!(store.list().await.unwrap().len(), 1);
+    }
+}
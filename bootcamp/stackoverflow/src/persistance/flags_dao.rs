@@ -0,0 +1,137 @@
+use async_trait::async_trait;
+use sqlx::{types::Uuid, PgPool};
+
+use crate::models::{postgres_error_codes, DBError, Flag, FlagDetail, FlagResolution, PostKind};
+use crate::persistance::{spaces_dao::DEFAULT_SPACE_ID, unit_of_work::UnitOfWork};
+
+#[async_trait]
+pub trait FlagsDao {
+    async fn create_flag(
+        &self,
+        uow: &mut dyn UnitOfWork,
+        flag: Flag,
+        reported_by: String,
+    ) -> Result<FlagDetail, DBError>;
+    /// Moderation queue, oldest flags first
+    async fn get_open_flags(&self) -> Result<Vec<FlagDetail>, DBError>;
+    async fn resolve_flag(
+        &self,
+        uow: &mut dyn UnitOfWork,
+        flag_uuid: String,
+        resolution: FlagResolution,
+        resolved_by: String,
+    ) -> Result<FlagDetail, DBError>;
+}
+
+/// Flags are on a question, or an answer to a question, of the space bound as `$1`
+const IN_SPACE: &str = r"
+    ( f.question_uuid IN ( SELECT question_uuid FROM questions WHERE space_id = $1 )
+        OR f.answer_uuid IN (
+            SELECT a.answer_uuid FROM answers a
+            JOIN questions q ON q.question_uuid = a.question_uuid
+            WHERE q.space_id = $1
+        ) )
+";
+
+/// Flags on the posts of a single space, each space has its own moderation queue
+pub struct FlagsDaoImpl {
+    db: PgPool,
+    space_id: Uuid,
+}
+
+impl FlagsDaoImpl {
+    pub fn new(db: PgPool) -> Self {
+        Self::in_space(db, DEFAULT_SPACE_ID)
+    }
+
+    pub fn in_space(db: PgPool, space_id: Uuid) -> Self {
+        FlagsDaoImpl { db, space_id }
+    }
+}
+
+#[async_trait]
+impl FlagsDao for FlagsDaoImpl {
+    async fn create_flag(
+        &self,
+        uow: &mut dyn UnitOfWork,
+        flag: Flag,
+        reported_by: String,
+    ) -> Result<FlagDetail, DBError> {
+        let uuid =
+            Uuid::parse_str(&flag.post_uuid).map_err(|e| DBError::InvalidUUID(e.to_string()))?;
+        let (question_uuid, answer_uuid) = match flag.post_kind {
+            PostKind::Question => (Some(uuid), None),
+            PostKind::Answer => (None, Some(uuid)),
+        };
+
+        // Nothing is inserted for a post of another space, as if it did not exist
+        sqlx::query_as::<_, FlagDetail>(&format!(
+            r"
+        INSERT INTO flags ( question_uuid, answer_uuid, reason, reported_by )
+        SELECT f.question_uuid, f.answer_uuid, $4, $5
+        FROM ( SELECT $2::uuid AS question_uuid, $3::uuid AS answer_uuid ) f
+        WHERE {IN_SPACE}
+        RETURNING *
+        "
+        ))
+        .bind(self.space_id)
+        .bind(question_uuid)
+        .bind(answer_uuid)
+        .bind(flag.reason)
+        .bind(reported_by)
+        .fetch_one(uow.connection().await?)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::RowNotFound => DBError::InvalidUUID(e.to_string()),
+            sqlx::Error::Database(ref db_err)
+                if db_err.code().as_deref()
+                    == Some(postgres_error_codes::FOREIGN_KEY_VIOLATION) =>
+            {
+                DBError::InvalidUUID(e.to_string())
+            }
+            _ => DBError::Other(Box::new(e)),
+        })
+    }
+
+    async fn get_open_flags(&self) -> Result<Vec<FlagDetail>, DBError> {
+        sqlx::query_as::<_, FlagDetail>(&format!(
+            r"
+        SELECT * FROM flags f WHERE f.status = 'open' AND {IN_SPACE} ORDER BY f.created_at
+        "
+        ))
+        .bind(self.space_id)
+        .fetch_all(&self.db)
+        .await
+        .map_err(|e| DBError::Other(Box::new(e)))
+    }
+
+    async fn resolve_flag(
+        &self,
+        uow: &mut dyn UnitOfWork,
+        flag_uuid: String,
+        resolution: FlagResolution,
+        resolved_by: String,
+    ) -> Result<FlagDetail, DBError> {
+        let uuid = Uuid::parse_str(&flag_uuid).map_err(|e| DBError::InvalidUUID(e.to_string()))?;
+
+        sqlx::query_as::<_, FlagDetail>(&format!(
+            r"
+        UPDATE flags f
+        SET status = 'resolved', resolution = $3, resolved_by = $4,
+            resolved_at = CURRENT_TIMESTAMP
+        WHERE f.flag_uuid = $2 AND f.status = 'open' AND {IN_SPACE}
+        RETURNING *
+        "
+        ))
+        .bind(self.space_id)
+        .bind(uuid)
+        .bind(resolution.as_str())
+        .bind(resolved_by)
+        .fetch_one(uow.connection().await?)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::RowNotFound => DBError::InvalidUUID(e.to_string()),
+            _ => DBError::Other(Box::new(e)),
+        })
+    }
+}
@@ -0,0 +1,294 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use sqlx::{types::Uuid, PgConnection, PgPool};
+
+use crate::markdown::RenderCache;
+use crate::models::{DBError, InboxItem, QuestionDetail};
+use crate::persistance::{
+    questions_dao::render_questions, spaces_dao::DEFAULT_SPACE_ID, unit_of_work::UnitOfWork,
+};
+
+#[async_trait]
+pub trait FollowsDao {
+    /// Following a question twice is not an error
+    async fn follow_question(
+        &self,
+        uow: &mut dyn UnitOfWork,
+        user_id: String,
+        question_uuid: String,
+    ) -> Result<(), DBError>;
+    async fn unfollow_question(
+        &self,
+        uow: &mut dyn UnitOfWork,
+        user_id: String,
+        question_uuid: String,
+    ) -> Result<(), DBError>;
+    /// Bookmarking a question twice is not an error
+    async fn bookmark_question(
+        &self,
+        uow: &mut dyn UnitOfWork,
+        user_id: String,
+        question_uuid: String,
+    ) -> Result<(), DBError>;
+    async fn remove_bookmark(
+        &self,
+        uow: &mut dyn UnitOfWork,
+        user_id: String,
+        question_uuid: String,
+    ) -> Result<(), DBError>;
+    /// Bookmarked questions that were not deleted, most recently bookmarked first
+    async fn get_bookmarks(&self, user_id: String) -> Result<Vec<QuestionDetail>, DBError>;
+    /// Unread answers on followed questions, oldest first
+    async fn get_inbox(&self, user_id: String) -> Result<Vec<InboxItem>, DBError>;
+    /// Returns how many unread items were marked
+    async fn mark_inbox_read(
+        &self,
+        uow: &mut dyn UnitOfWork,
+        user_id: String,
+        item_uuids: Vec<String>,
+    ) -> Result<u64, DBError>;
+}
+
+/// Follows, bookmarks and inbox items on the questions of a single space
+pub struct FollowsDaoImpl {
+    db: PgPool,
+    space_id: Uuid,
+    render_cache: Arc<RenderCache>,
+}
+
+impl FollowsDaoImpl {
+    pub fn new(db: PgPool) -> Self {
+        Self::in_space(db, DEFAULT_SPACE_ID)
+    }
+
+    pub fn in_space(db: PgPool, space_id: Uuid) -> Self {
+        FollowsDaoImpl {
+            db,
+            space_id,
+            render_cache: Arc::default(),
+        }
+    }
+
+    /// Shares the rendered descriptions of bookmarked questions with the questions DAO
+    pub fn with_render_cache(self, render_cache: Arc<RenderCache>) -> Self {
+        FollowsDaoImpl {
+            render_cache,
+            ..self
+        }
+    }
+}
+
+/// Adds the answer to the inbox of every follower of its question but its author.
+/// Takes a connection so that it can be called inside the transaction creating the answer.
+pub async fn notify_followers(
+    conn: &mut PgConnection,
+    question_uuid: Uuid,
+    answer_uuid: Uuid,
+    author: &str,
+) -> Result<(), DBError> {
+    sqlx::query(
+        r"
+        INSERT INTO inbox_items ( user_id, answer_uuid )
+        SELECT user_id, $2 FROM follows WHERE question_uuid = $1 AND user_id <> $3
+        ",
+    )
+    .bind(question_uuid)
+    .bind(answer_uuid)
+    .bind(author)
+    .execute(conn)
+    .await
+    .map_err(|e| DBError::Other(Box::new(e)))?;
+
+    Ok(())
+}
+
+#[async_trait]
+impl FollowsDao for FollowsDaoImpl {
+    async fn follow_question(
+        &self,
+        uow: &mut dyn UnitOfWork,
+        user_id: String,
+        question_uuid: String,
+    ) -> Result<(), DBError> {
+        let uuid =
+            Uuid::parse_str(&question_uuid).map_err(|e| DBError::InvalidUUID(e.to_string()))?;
+
+        // The no-op update returns the existing row, so that no row means no such question
+        sqlx::query(
+            r"
+        INSERT INTO follows ( user_id, question_uuid )
+        SELECT $1, question_uuid FROM questions
+        WHERE question_uuid = $2 AND space_id = $3 AND deleted_at IS NULL
+        ON CONFLICT ( user_id, question_uuid ) DO UPDATE SET user_id = EXCLUDED.user_id
+        RETURNING question_uuid
+        ",
+        )
+        .bind(user_id)
+        .bind(uuid)
+        .bind(self.space_id)
+        .fetch_one(uow.connection().await?)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::RowNotFound => DBError::InvalidUUID(e.to_string()),
+            _ => DBError::Other(Box::new(e)),
+        })?;
+
+        Ok(())
+    }
+
+    async fn unfollow_question(
+        &self,
+        uow: &mut dyn UnitOfWork,
+        user_id: String,
+        question_uuid: String,
+    ) -> Result<(), DBError> {
+        let uuid =
+            Uuid::parse_str(&question_uuid).map_err(|e| DBError::InvalidUUID(e.to_string()))?;
+
+        sqlx::query(
+            r"
+        DELETE FROM follows
+        WHERE user_id = $1 AND question_uuid = $2
+            AND question_uuid IN ( SELECT question_uuid FROM questions WHERE space_id = $3 )
+        ",
+        )
+        .bind(user_id)
+        .bind(uuid)
+        .bind(self.space_id)
+        .execute(uow.connection().await?)
+        .await
+        .map_err(|e| DBError::Other(Box::new(e)))?;
+
+        Ok(())
+    }
+
+    async fn bookmark_question(
+        &self,
+        uow: &mut dyn UnitOfWork,
+        user_id: String,
+        question_uuid: String,
+    ) -> Result<(), DBError> {
+        let uuid =
+            Uuid::parse_str(&question_uuid).map_err(|e| DBError::InvalidUUID(e.to_string()))?;
+
+        sqlx::query(
+            r"
+        INSERT INTO bookmarks ( user_id, question_uuid )
+        SELECT $1, question_uuid FROM questions
+        WHERE question_uuid = $2 AND space_id = $3 AND deleted_at IS NULL
+        ON CONFLICT ( user_id, question_uuid ) DO UPDATE SET user_id = EXCLUDED.user_id
+        RETURNING question_uuid
+        ",
+        )
+        .bind(user_id)
+        .bind(uuid)
+        .bind(self.space_id)
+        .fetch_one(uow.connection().await?)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::RowNotFound => DBError::InvalidUUID(e.to_string()),
+            _ => DBError::Other(Box::new(e)),
+        })?;
+
+        Ok(())
+    }
+
+    async fn remove_bookmark(
+        &self,
+        uow: &mut dyn UnitOfWork,
+        user_id: String,
+        question_uuid: String,
+    ) -> Result<(), DBError> {
+        let uuid =
+            Uuid::parse_str(&question_uuid).map_err(|e| DBError::InvalidUUID(e.to_string()))?;
+
+        sqlx::query(
+            r"
+        DELETE FROM bookmarks
+        WHERE user_id = $1 AND question_uuid = $2
+            AND question_uuid IN ( SELECT question_uuid FROM questions WHERE space_id = $3 )
+        ",
+        )
+        .bind(user_id)
+        .bind(uuid)
+        .bind(self.space_id)
+        .execute(uow.connection().await?)
+        .await
+        .map_err(|e| DBError::Other(Box::new(e)))?;
+
+        Ok(())
+    }
+
+    async fn get_bookmarks(&self, user_id: String) -> Result<Vec<QuestionDetail>, DBError> {
+        let questions = sqlx::query_as::<_, QuestionDetail>(
+            r"
+        SELECT q.* FROM questions q
+        JOIN bookmarks b ON b.question_uuid = q.question_uuid
+        WHERE b.user_id = $1 AND q.space_id = $2 AND q.deleted_at IS NULL
+        ORDER BY b.created_at DESC, q.question_uuid
+        ",
+        )
+        .bind(user_id)
+        .bind(self.space_id)
+        .fetch_all(&self.db)
+        .await
+        .map_err(|e| DBError::Other(Box::new(e)))?;
+
+        render_questions(&self.render_cache, questions).await
+    }
+
+    async fn get_inbox(&self, user_id: String) -> Result<Vec<InboxItem>, DBError> {
+        // Answers and questions deleted since they were posted drop out of the inbox
+        sqlx::query_as::<_, InboxItem>(
+            r"
+        SELECT i.item_uuid, q.question_uuid, q.title AS question_title,
+            a.answer_uuid, a.author AS answer_author, i.created_at
+        FROM inbox_items i
+        JOIN answers a ON a.answer_uuid = i.answer_uuid
+        JOIN questions q ON q.question_uuid = a.question_uuid
+        WHERE i.user_id = $1 AND i.read_at IS NULL AND q.space_id = $2
+            AND a.deleted_at IS NULL AND q.deleted_at IS NULL
+        ORDER BY i.created_at, i.item_uuid
+        ",
+        )
+        .bind(user_id)
+        .bind(self.space_id)
+        .fetch_all(&self.db)
+        .await
+        .map_err(|e| DBError::Other(Box::new(e)))
+    }
+
+    async fn mark_inbox_read(
+        &self,
+        uow: &mut dyn UnitOfWork,
+        user_id: String,
+        item_uuids: Vec<String>,
+    ) -> Result<u64, DBError> {
+        let uuids = item_uuids
+            .iter()
+            .map(|uuid| Uuid::parse_str(uuid))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| DBError::InvalidUUID(e.to_string()))?;
+
+        let result = sqlx::query(
+            r"
+        UPDATE inbox_items SET read_at = CURRENT_TIMESTAMP
+        WHERE user_id = $1 AND item_uuid = ANY($2) AND read_at IS NULL
+            AND answer_uuid IN (
+                SELECT a.answer_uuid FROM answers a
+                JOIN questions q ON q.question_uuid = a.question_uuid
+                WHERE q.space_id = $3
+            )
+        ",
+        )
+        .bind(user_id)
+        .bind(uuids)
+        .bind(self.space_id)
+        .execute(uow.connection().await?)
+        .await
+        .map_err(|e| DBError::Other(Box::new(e)))?;
+
+        Ok(result.rows_affected())
+    }
+}
@@ -0,0 +1,265 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+use sqlx::{types::time::PrimitiveDateTime, PgPool};
+
+use crate::models::{DBError, JobRun};
+use crate::persistance::unit_of_work::UnitOfWork;
+
+/// Recorded as the closer of the questions closed for being inactive
+pub const MAINTENANCE_ACTOR: &str = "maintenance";
+
+/// Maintenance of the questions of every space, and the record of its runs.
+/// Jobs run in the unit of work of their claim, so that a failed run leaves nothing behind.
+#[async_trait]
+pub trait JobsDao {
+    /// Claims the run of `job` due at `scheduled_for` until the unit of work ends.
+    /// Returns false when another instance holds the claim or the run was already recorded.
+    async fn claim_run(
+        &self,
+        uow: &mut dyn UnitOfWork,
+        job: &str,
+        scheduled_for: PrimitiveDateTime,
+    ) -> Result<bool, DBError>;
+    /// Records how a run went, `outcome` being the number of questions affected or the error
+    async fn record_run(
+        &self,
+        uow: &mut dyn UnitOfWork,
+        job: &str,
+        scheduled_for: PrimitiveDateTime,
+        started_at: PrimitiveDateTime,
+        outcome: Result<i64, String>,
+    ) -> Result<(), DBError>;
+    /// Most recent runs first
+    async fn get_job_runs(&self, limit: i64) -> Result<Vec<JobRun>, DBError>;
+    /// Closes the open questions without a new answer for `inactive_for`,
+    /// returns the number of questions closed
+    async fn close_inactive_questions(
+        &self,
+        uow: &mut dyn UnitOfWork,
+        inactive_for: Duration,
+    ) -> Result<i64, DBError>;
+    /// Adds the open questions left unanswered for `unanswered_for` to the needs attention list
+    /// and drops those which are not anymore, returns the number of questions added
+    async fn flag_unanswered_questions(
+        &self,
+        uow: &mut dyn UnitOfWork,
+        unanswered_for: Duration,
+    ) -> Result<i64, DBError>;
+    /// Moves the questions asked more than `retained_for` ago, and their answers, to the archive.
+    /// Questions still pointed at by a duplicate which is kept are kept too. Their attachments,
+    /// flags and follows are deleted rather than archived.
+    /// Returns the number of questions archived.
+    async fn archive_questions(
+        &self,
+        uow: &mut dyn UnitOfWork,
+        retained_for: Duration,
+    ) -> Result<i64, DBError>;
+}
+
+pub struct JobsDaoImpl {
+    db: PgPool,
+}
+
+impl JobsDaoImpl {
+    pub fn new(db: PgPool) -> Self {
+        JobsDaoImpl { db }
+    }
+}
+
+#[async_trait]
+impl JobsDao for JobsDaoImpl {
+    async fn claim_run(
+        &self,
+        uow: &mut dyn UnitOfWork,
+        job: &str,
+        scheduled_for: PrimitiveDateTime,
+    ) -> Result<bool, DBError> {
+        // Instances running the same job at the same time skip it rather than wait, the one
+        // holding the lock may still fail and is left to record it
+        let locked = sqlx::query_scalar::<_, bool>(
+            "SELECT pg_try_advisory_xact_lock(hashtextextended('job_runs/' || $1, 0))",
+        )
+        .bind(job)
+        .fetch_one(uow.connection().await?)
+        .await
+        .map_err(|e| DBError::Other(Box::new(e)))?;
+        if !locked {
+            return Ok(false);
+        }
+
+        // Instances whose clocks are late reach the scheduled time after the run committed
+        let ran = sqlx::query_scalar::<_, bool>(
+            r"
+        SELECT EXISTS (
+            SELECT 1 FROM job_runs WHERE job = $1 AND scheduled_for = $2
+        )
+        ",
+        )
+        .bind(job)
+        .bind(scheduled_for)
+        .fetch_one(uow.connection().await?)
+        .await
+        .map_err(|e| DBError::Other(Box::new(e)))?;
+
+        Ok(!ran)
+    }
+
+    async fn record_run(
+        &self,
+        uow: &mut dyn UnitOfWork,
+        job: &str,
+        scheduled_for: PrimitiveDateTime,
+        started_at: PrimitiveDateTime,
+        outcome: Result<i64, String>,
+    ) -> Result<(), DBError> {
+        let (succeeded, affected, error) = match outcome {
+            Ok(affected) => (true, affected, None),
+            Err(error) => (false, 0, Some(error)),
+        };
+
+        sqlx::query(
+            r"
+        INSERT INTO job_runs ( job, scheduled_for, started_at, finished_at, succeeded, affected, error )
+        VALUES ( $1, $2, $3, timezone('UTC', clock_timestamp()), $4, $5, $6 )
+        ON CONFLICT ( job, scheduled_for ) DO NOTHING
+        ",
+        )
+        .bind(job)
+        .bind(scheduled_for)
+        .bind(started_at)
+        .bind(succeeded)
+        .bind(affected)
+        .bind(error)
+        .execute(uow.connection().await?)
+        .await
+        .map_err(|e| DBError::Other(Box::new(e)))?;
+
+        Ok(())
+    }
+
+    async fn get_job_runs(&self, limit: i64) -> Result<Vec<JobRun>, DBError> {
+        sqlx::query_as::<_, JobRun>(
+            r"
+        SELECT * FROM job_runs
+        ORDER BY started_at DESC, run_id DESC
+        LIMIT $1
+        ",
+        )
+        .bind(limit)
+        .fetch_all(&self.db)
+        .await
+        .map_err(|e| DBError::Other(Box::new(e)))
+    }
+
+    async fn close_inactive_questions(
+        &self,
+        uow: &mut dyn UnitOfWork,
+        inactive_for: Duration,
+    ) -> Result<i64, DBError> {
+        // A question is active as long as it gets answers
+        let closed = sqlx::query(
+            r"
+        UPDATE questions q SET closed_at = LOCALTIMESTAMP, closed_by = $2
+        WHERE q.deleted_at IS NULL AND q.duplicate_of IS NULL AND q.closed_at IS NULL
+            AND GREATEST(
+                q.created_at,
+                (
+                    SELECT MAX(a.created_at) FROM answers a
+                    WHERE a.question_uuid = q.question_uuid AND a.deleted_at IS NULL
+                )
+            ) < LOCALTIMESTAMP - make_interval(secs => $1)
+        ",
+        )
+        .bind(inactive_for.as_secs_f64())
+        .bind(MAINTENANCE_ACTOR)
+        .execute(uow.connection().await?)
+        .await
+        .map_err(|e| DBError::Other(Box::new(e)))?;
+
+        Ok(closed.rows_affected() as i64)
+    }
+
+    async fn flag_unanswered_questions(
+        &self,
+        uow: &mut dyn UnitOfWork,
+        unanswered_for: Duration,
+    ) -> Result<i64, DBError> {
+        sqlx::query(
+            r"
+        DELETE FROM needs_attention n
+        USING questions q
+        WHERE q.question_uuid = n.question_uuid
+            AND (
+                q.deleted_at IS NOT NULL OR q.duplicate_of IS NOT NULL OR q.closed_at IS NOT NULL
+                OR EXISTS (
+                    SELECT 1 FROM answers a
+                    WHERE a.question_uuid = q.question_uuid AND a.deleted_at IS NULL
+                )
+            )
+        ",
+        )
+        .execute(uow.connection().await?)
+        .await
+        .map_err(|e| DBError::Other(Box::new(e)))?;
+
+        let flagged = sqlx::query(
+            r"
+        INSERT INTO needs_attention ( question_uuid )
+        SELECT q.question_uuid FROM questions q
+        WHERE q.deleted_at IS NULL AND q.duplicate_of IS NULL AND q.closed_at IS NULL
+            AND q.created_at < LOCALTIMESTAMP - make_interval(secs => $1)
+            AND NOT EXISTS (
+                SELECT 1 FROM answers a
+                WHERE a.question_uuid = q.question_uuid AND a.deleted_at IS NULL
+            )
+        ON CONFLICT DO NOTHING
+        ",
+        )
+        .bind(unanswered_for.as_secs_f64())
+        .execute(uow.connection().await?)
+        .await
+        .map_err(|e| DBError::Other(Box::new(e)))?;
+
+        Ok(flagged.rows_affected() as i64)
+    }
+
+    async fn archive_questions(
+        &self,
+        uow: &mut dyn UnitOfWork,
+        retained_for: Duration,
+    ) -> Result<i64, DBError> {
+        // The statement sees the answers as they were before the questions were deleted,
+        // the deletes cascading to them
+        let archived = sqlx::query(
+            r"
+        WITH archived AS (
+            DELETE FROM questions q
+            WHERE q.created_at < LOCALTIMESTAMP - make_interval(secs => $1)
+                AND NOT EXISTS (
+                    SELECT 1 FROM questions d
+                    WHERE d.duplicate_of = q.question_uuid
+                        AND d.created_at >= LOCALTIMESTAMP - make_interval(secs => $1)
+                )
+            RETURNING q.*
+        )
+        INSERT INTO questions_archive ( question_uuid, space_id, question, answers )
+        SELECT q.question_uuid, q.space_id, to_jsonb(q),
+            COALESCE(
+                (
+                    SELECT jsonb_agg(to_jsonb(a) ORDER BY a.created_at) FROM answers a
+                    WHERE a.question_uuid = q.question_uuid
+                ),
+                '[]'
+            )
+        FROM archived q
+        ",
+        )
+        .bind(retained_for.as_secs_f64())
+        .execute(uow.connection().await?)
+        .await
+        .map_err(|e| DBError::Other(Box::new(e)))?;
+
+        Ok(archived.rows_affected() as i64)
+    }
+}
@@ -1,5 +1,17 @@
 pub mod answers_dao;
+pub mod attachments_dao;
+pub mod audit_dao;
+pub mod blob_store;
+pub mod flags_dao;
+pub mod follows_dao;
+pub mod jobs_dao;
 pub mod questions_dao;
+pub mod read_cache;
+pub mod reputation_dao;
+pub mod spaces_dao;
+pub mod stats_dao;
+pub mod unit_of_work;
+pub mod webhooks_dao;
 
 #[cfg(test)]
 mod tests;
@@ -1,54 +1,310 @@
+use std::{collections::HashMap, sync::Arc};
+
 use async_trait::async_trait;
-use sqlx::{types::Uuid, PgPool};
+use sqlx::{types::Uuid, Connection, PgPool};
 
-use crate::models::{DBError, Question, QuestionDetail};
+use crate::markdown::RenderCache;
+use crate::models::{
+    DBError, Question, QuestionDetail, ReputationReason, SimilarQuestion, WebhookEvent,
+};
+use crate::persistance::{
+    reputation_dao::record_reputation, spaces_dao::DEFAULT_SPACE_ID, unit_of_work::UnitOfWork,
+    webhooks_dao::enqueue_event,
+};
 
 #[async_trait]
 pub trait QuestionsDao {
-    async fn create_question(&self, question: Question) -> Result<QuestionDetail, DBError>;
-    async fn delete_question(&self, question_uuid: String) -> Result<(), DBError>;
+    async fn create_question(
+        &self,
+        uow: &mut dyn UnitOfWork,
+        question: Question,
+        author: String,
+    ) -> Result<QuestionDetail, DBError>;
+    async fn get_question(&self, question_uuid: String) -> Result<QuestionDetail, DBError>;
+    /// Reads a question and locks it until `uow` ends, so that checks made on it still hold
+    async fn lock_question(
+        &self,
+        uow: &mut dyn UnitOfWork,
+        question_uuid: String,
+    ) -> Result<QuestionDetail, DBError>;
+    /// Replaces the title and description of a question that was not deleted
+    async fn update_question(
+        &self,
+        uow: &mut dyn UnitOfWork,
+        question_uuid: String,
+        question: Question,
+    ) -> Result<QuestionDetail, DBError>;
+    /// Soft deletes a question, it stays in the database until restored
+    async fn delete_question(
+        &self,
+        uow: &mut dyn UnitOfWork,
+        question_uuid: String,
+        deleted_by: String,
+    ) -> Result<(), DBError>;
+    async fn restore_question(
+        &self,
+        uow: &mut dyn UnitOfWork,
+        question_uuid: String,
+    ) -> Result<QuestionDetail, DBError>;
     async fn get_questions(&self) -> Result<Vec<QuestionDetail>, DBError>;
+    /// Questions that were not deleted, oldest first, skipping the first `offset` ones
+    async fn get_questions_page(
+        &self,
+        offset: i64,
+        limit: i64,
+    ) -> Result<Vec<QuestionDetail>, DBError>;
+    /// Questions that were not deleted among `question_uuids`, in no particular order
+    async fn get_questions_by_uuids(
+        &self,
+        question_uuids: Vec<String>,
+    ) -> Result<Vec<QuestionDetail>, DBError>;
+    /// Open questions on the needs attention list which are still unanswered,
+    /// longest listed first, skipping the first `offset` ones
+    async fn get_questions_needing_attention(
+        &self,
+        offset: i64,
+        limit: i64,
+    ) -> Result<Vec<QuestionDetail>, DBError>;
+    /// Open questions similar to an existing question, most similar first
+    async fn get_related_questions(
+        &self,
+        question_uuid: String,
+        limit: i64,
+    ) -> Result<Vec<SimilarQuestion>, DBError>;
+    /// Open questions similar to a question that was not asked yet, most similar first
+    async fn get_similar_questions(
+        &self,
+        question: Question,
+        limit: i64,
+    ) -> Result<Vec<SimilarQuestion>, DBError>;
+    /// Closes a question as a duplicate of another one. If that other question is itself
+    /// a duplicate, the question is pointed at the original instead.
+    async fn close_as_duplicate(
+        &self,
+        uow: &mut dyn UnitOfWork,
+        question_uuid: String,
+        duplicate_of: String,
+        closed_by: String,
+    ) -> Result<QuestionDetail, DBError>;
+    /// Adds views counted since the last flush, keyed by question UUID.
+    /// Views are counted by reads that were already scoped, so they are added in any space.
+    async fn add_views(&self, views: HashMap<String, i64>) -> Result<(), DBError>;
+    /// Open questions with the most recent views and answers, highest score first
+    async fn get_trending_questions(&self, limit: i64) -> Result<Vec<QuestionDetail>, DBError>;
 }
 
+/// Title matches weigh more than description matches
+const SIMILARITY: &str = "0.7 * similarity(q.title, $1) + 0.3 * similarity(q.description, $2)";
+
+/// Views and answers count half as much in the trending score every `TRENDING_HALF_LIFE_HOURS`
+const TRENDING_HALF_LIFE_HOURS: f64 = 24.0;
+/// Score of an answer relative to a single view
+const TRENDING_ANSWER_WEIGHT: f64 = 10.0;
+
+/// Reads and writes the questions of a single space, questions of other spaces
+/// are as good as missing
 pub struct QuestionsDaoImpl {
     db: PgPool,
+    space_id: Uuid,
+    render_cache: Arc<RenderCache>,
 }
 
 impl QuestionsDaoImpl {
     pub fn new(db: PgPool) -> Self {
-        QuestionsDaoImpl { db }
+        Self::in_space(db, DEFAULT_SPACE_ID)
+    }
+
+    pub fn in_space(db: PgPool, space_id: Uuid) -> Self {
+        QuestionsDaoImpl {
+            db,
+            space_id,
+            render_cache: Arc::default(),
+        }
+    }
+
+    /// Shares the rendered descriptions with the other DAOs of the application
+    pub fn with_render_cache(self, render_cache: Arc<RenderCache>) -> Self {
+        QuestionsDaoImpl {
+            render_cache,
+            ..self
+        }
     }
 }
 
+/// Fills in the HTML of a question read from the database
+async fn render_question(
+    render_cache: &Arc<RenderCache>,
+    mut question: QuestionDetail,
+) -> Result<QuestionDetail, DBError> {
+    question.description_html = render_cache
+        .render(&question.question_uuid, &question.description)
+        .await
+        .map_err(|e| DBError::Other(Box::new(e)))?;
+    Ok(question)
+}
+
+pub async fn render_questions(
+    render_cache: &Arc<RenderCache>,
+    questions: Vec<QuestionDetail>,
+) -> Result<Vec<QuestionDetail>, DBError> {
+    let mut rendered = Vec::with_capacity(questions.len());
+    for question in questions {
+        rendered.push(render_question(render_cache, question).await?);
+    }
+    Ok(rendered)
+}
+
 #[async_trait]
 impl QuestionsDao for QuestionsDaoImpl {
-    async fn create_question(&self, question: Question) -> Result<QuestionDetail, DBError> {
+    async fn create_question(
+        &self,
+        uow: &mut dyn UnitOfWork,
+        question: Question,
+        author: String,
+    ) -> Result<QuestionDetail, DBError> {
         // Make a database query to insert a new question.
         // Here is the SQL query:
         // ```
-        // INSERT INTO questions ( title, description )
-        // VALUES ( $1, $2 )
+        // INSERT INTO questions ( title, description, author )
+        // VALUES ( $1, $2, $3 )
         // RETURNING *
         // ```
         // If executing the query results in an error, map that error to
         // the`DBError::Other` error and early return from this function.
+        //
+        // The webhook outbox and reputation ledger are written in the same transaction
+        // so that they only ever reflect a question that was actually committed.
+        let mut tx = uow
+            .connection()
+            .await?
+            .begin()
+            .await
+            .map_err(|e| DBError::Other(Box::new(e)))?;
+
         let mut record = sqlx::query_as::<_, QuestionDetail>(
             r"
-        INSERT INTO questions ( title, description )
-        VALUES ( $1, $2 )
+        INSERT INTO questions ( title, description, author, space_id )
+        VALUES ( $1, $2, $3, $4 )
         RETURNING *
 ",
         )
         .bind(&question.title)
         .bind(&question.description)
-        .fetch_all(&self.db)
+        .bind(&author)
+        .bind(self.space_id)
+        .fetch_all(&mut *tx)
         .await
         .map_err(|e| DBError::Other(Box::new(e)))?;
+        let question = render_question(&self.render_cache, record.pop().unwrap()).await?;
+        let uuid = Uuid::parse_str(&question.question_uuid)
+            .map_err(|e| DBError::InvalidUUID(e.to_string()))?;
+
+        enqueue_event(
+            &mut tx,
+            self.space_id,
+            WebhookEvent::QuestionCreated,
+            &question,
+        )
+        .await?;
+        record_reputation(
+            &mut tx,
+            self.space_id,
+            &author,
+            ReputationReason::QuestionAsked,
+            uuid,
+        )
+        .await?;
+
+        tx.commit().await.map_err(|e| DBError::Other(Box::new(e)))?;
+
+        render_question(&self.render_cache, question).await
+    }
+
+    async fn get_question(&self, question_uuid: String) -> Result<QuestionDetail, DBError> {
+        let uuid =
+            Uuid::parse_str(&question_uuid).map_err(|e| DBError::InvalidUUID(e.to_string()))?;
+
+        let question = sqlx::query_as::<_, QuestionDetail>(
+            r"
+        SELECT * FROM questions
+        WHERE question_uuid = $1 AND space_id = $2 AND deleted_at IS NULL
+        ",
+        )
+        .bind(uuid)
+        .bind(self.space_id)
+        .fetch_one(&self.db)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::RowNotFound => DBError::InvalidUUID(e.to_string()),
+            _ => DBError::Other(Box::new(e)),
+        })?;
+
+        render_question(&self.render_cache, question).await
+    }
+
+    async fn lock_question(
+        &self,
+        uow: &mut dyn UnitOfWork,
+        question_uuid: String,
+    ) -> Result<QuestionDetail, DBError> {
+        let uuid =
+            Uuid::parse_str(&question_uuid).map_err(|e| DBError::InvalidUUID(e.to_string()))?;
 
-        Ok(record.pop().unwrap())
+        let question = sqlx::query_as::<_, QuestionDetail>(
+            r"
+        SELECT * FROM questions
+        WHERE question_uuid = $1 AND space_id = $2 AND deleted_at IS NULL
+        FOR UPDATE
+        ",
+        )
+        .bind(uuid)
+        .bind(self.space_id)
+        .fetch_one(uow.connection().await?)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::RowNotFound => DBError::InvalidUUID(e.to_string()),
+            _ => DBError::Other(Box::new(e)),
+        })?;
+
+        render_question(&self.render_cache, question).await
     }
 
-    async fn delete_question(&self, question_uuid: String) -> Result<(), DBError> {
+    async fn update_question(
+        &self,
+        uow: &mut dyn UnitOfWork,
+        question_uuid: String,
+        question: Question,
+    ) -> Result<QuestionDetail, DBError> {
+        let uuid =
+            Uuid::parse_str(&question_uuid).map_err(|e| DBError::InvalidUUID(e.to_string()))?;
+
+        let question = sqlx::query_as::<_, QuestionDetail>(
+            r"
+        UPDATE questions SET title = $2, description = $3
+        WHERE question_uuid = $1 AND space_id = $4 AND deleted_at IS NULL
+        RETURNING *
+        ",
+        )
+        .bind(uuid)
+        .bind(question.title)
+        .bind(question.description)
+        .bind(self.space_id)
+        .fetch_one(uow.connection().await?)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::RowNotFound => DBError::InvalidUUID(e.to_string()),
+            _ => DBError::Other(Box::new(e)),
+        })?;
+
+        render_question(&self.render_cache, question).await
+    }
+
+    async fn delete_question(
+        &self,
+        uow: &mut dyn UnitOfWork,
+        question_uuid: String,
+        deleted_by: String,
+    ) -> Result<(), DBError> {
         // Use the `sqlx::types::Uuid::parse_str` method to parse `question_uuid` into a `Uuid` type.
         // parse_str docs: https://docs.rs/sqlx/latest/sqlx/types/struct.Uuid.html#method.parse_str
         //
@@ -57,35 +313,69 @@ impl QuestionsDao for QuestionsDaoImpl {
         let uuid =
             Uuid::parse_str(&question_uuid).map_err(|e| DBError::InvalidUUID(e.to_string()))?;
 
-        // Make a database query to delete a question given the question uuid.
-        // Here is the SQL query:
-        // ```
-        // DELETE FROM questions WHERE question_uuid = $1
-        // ```
+        // Make a database query to soft delete a question given the question uuid.
+        // Answers are kept as is and hidden through their question.
         // If executing the query results in an error, map that error
         // to a `DBError::Other` error and early return from this function.
 
-        sqlx::query(r"DELETE FROM questions WHERE question_uuid = $1")
-            .bind(uuid)
-            .execute(&self.db)
-            .await
-            .map_err(|e| DBError::Other(Box::new(e)))?;
+        sqlx::query(
+            r"
+        UPDATE questions SET deleted_at = CURRENT_TIMESTAMP, deleted_by = $2
+        WHERE question_uuid = $1 AND space_id = $3 AND deleted_at IS NULL
+        ",
+        )
+        .bind(uuid)
+        .bind(deleted_by)
+        .bind(self.space_id)
+        .execute(uow.connection().await?)
+        .await
+        .map_err(|e| DBError::Other(Box::new(e)))?;
 
         Ok(())
     }
 
+    async fn restore_question(
+        &self,
+        uow: &mut dyn UnitOfWork,
+        question_uuid: String,
+    ) -> Result<QuestionDetail, DBError> {
+        let uuid =
+            Uuid::parse_str(&question_uuid).map_err(|e| DBError::InvalidUUID(e.to_string()))?;
+
+        let question = sqlx::query_as::<_, QuestionDetail>(
+            r"
+        UPDATE questions SET deleted_at = NULL, deleted_by = NULL
+        WHERE question_uuid = $1 AND space_id = $2
+        RETURNING *
+        ",
+        )
+        .bind(uuid)
+        .bind(self.space_id)
+        .fetch_one(uow.connection().await?)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::RowNotFound => DBError::InvalidUUID(e.to_string()),
+            _ => DBError::Other(Box::new(e)),
+        })?;
+
+        render_question(&self.render_cache, question).await
+    }
+
     async fn get_questions(&self) -> Result<Vec<QuestionDetail>, DBError> {
-        // Make a database query to get all questions.
+        // Make a database query to get all questions that were not deleted.
         // Here is the SQL query:
         // ```
-        // SELECT * FROM questions
+        // SELECT * FROM questions WHERE deleted_at IS NULL
         // ```
         // If executing the query results in an error, map that error
         // to a `DBError::Other` error and early return from this function.
-        let records = sqlx::query!(r"SELECT * FROM questions")
-            .fetch_all(&self.db)
-            .await
-            .map_err(|e| DBError::Other(Box::new(e)))?;
+        let records = sqlx::query!(
+            r"SELECT * FROM questions WHERE space_id = $1 AND deleted_at IS NULL",
+            self.space_id
+        )
+        .fetch_all(&self.db)
+        .await
+        .map_err(|e| DBError::Other(Box::new(e)))?;
 
         // Iterate over `records` and map each record to a `QuestionDetail` type
         let questions = records
@@ -94,10 +384,282 @@ impl QuestionsDao for QuestionsDaoImpl {
                 question_uuid: rec.question_uuid.to_string(),
                 title: rec.title.to_string(),
                 description: rec.description.to_string(),
+                description_html: String::new(),
+                author: rec.author.to_string(),
+                duplicate_of: rec.duplicate_of.map(|uuid| uuid.to_string()),
+                view_count: rec.view_count,
                 created_at: rec.created_at.to_string(),
             })
             .collect();
 
-        Ok(questions)
+        render_questions(&self.render_cache, questions).await
+    }
+
+    async fn get_questions_page(
+        &self,
+        offset: i64,
+        limit: i64,
+    ) -> Result<Vec<QuestionDetail>, DBError> {
+        let questions = sqlx::query_as::<_, QuestionDetail>(
+            r"
+        SELECT * FROM questions
+        WHERE space_id = $3 AND deleted_at IS NULL
+        ORDER BY created_at, question_uuid
+        OFFSET $1 LIMIT $2
+        ",
+        )
+        .bind(offset)
+        .bind(limit)
+        .bind(self.space_id)
+        .fetch_all(&self.db)
+        .await
+        .map_err(|e| DBError::Other(Box::new(e)))?;
+
+        render_questions(&self.render_cache, questions).await
+    }
+
+    async fn get_questions_by_uuids(
+        &self,
+        question_uuids: Vec<String>,
+    ) -> Result<Vec<QuestionDetail>, DBError> {
+        let uuids = question_uuids
+            .iter()
+            .map(|uuid| Uuid::parse_str(uuid))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| DBError::InvalidUUID(e.to_string()))?;
+
+        let questions = sqlx::query_as::<_, QuestionDetail>(
+            r"
+        SELECT * FROM questions
+        WHERE question_uuid = ANY($1) AND space_id = $2 AND deleted_at IS NULL
+        ",
+        )
+        .bind(uuids)
+        .bind(self.space_id)
+        .fetch_all(&self.db)
+        .await
+        .map_err(|e| DBError::Other(Box::new(e)))?;
+
+        render_questions(&self.render_cache, questions).await
+    }
+
+    async fn get_questions_needing_attention(
+        &self,
+        offset: i64,
+        limit: i64,
+    ) -> Result<Vec<QuestionDetail>, DBError> {
+        // Answers posted since the last run of the job take the question off the list at once
+        let questions = sqlx::query_as::<_, QuestionDetail>(
+            r"
+        SELECT q.* FROM needs_attention n
+        JOIN questions q ON q.question_uuid = n.question_uuid
+        WHERE q.space_id = $3
+            AND q.deleted_at IS NULL AND q.duplicate_of IS NULL AND q.closed_at IS NULL
+            AND NOT EXISTS (
+                SELECT 1 FROM answers a
+                WHERE a.question_uuid = q.question_uuid AND a.deleted_at IS NULL
+            )
+        ORDER BY n.added_at, q.question_uuid
+        OFFSET $1 LIMIT $2
+        ",
+        )
+        .bind(offset)
+        .bind(limit)
+        .bind(self.space_id)
+        .fetch_all(&self.db)
+        .await
+        .map_err(|e| DBError::Other(Box::new(e)))?;
+
+        render_questions(&self.render_cache, questions).await
+    }
+
+    async fn get_related_questions(
+        &self,
+        question_uuid: String,
+        limit: i64,
+    ) -> Result<Vec<SimilarQuestion>, DBError> {
+        let question = self.get_question(question_uuid).await?;
+        let uuid = Uuid::parse_str(&question.question_uuid)
+            .map_err(|e| DBError::InvalidUUID(e.to_string()))?;
+
+        sqlx::query_as::<_, SimilarQuestion>(&format!(
+            r"
+        SELECT q.question_uuid, q.title, ({SIMILARITY})::REAL AS similarity
+        FROM questions q
+        WHERE q.space_id = $5
+            AND q.deleted_at IS NULL AND q.duplicate_of IS NULL AND q.closed_at IS NULL
+            AND q.question_uuid <> $3
+            AND (q.title % $1 OR q.description % $2)
+        ORDER BY similarity DESC
+        LIMIT $4
+        "
+        ))
+        .bind(question.title)
+        .bind(question.description)
+        .bind(uuid)
+        .bind(limit)
+        .bind(self.space_id)
+        .fetch_all(&self.db)
+        .await
+        .map_err(|e| DBError::Other(Box::new(e)))
+    }
+
+    async fn get_similar_questions(
+        &self,
+        question: Question,
+        limit: i64,
+    ) -> Result<Vec<SimilarQuestion>, DBError> {
+        sqlx::query_as::<_, SimilarQuestion>(&format!(
+            r"
+        SELECT q.question_uuid, q.title, ({SIMILARITY})::REAL AS similarity
+        FROM questions q
+        WHERE q.space_id = $4
+            AND q.deleted_at IS NULL AND q.duplicate_of IS NULL AND q.closed_at IS NULL
+            AND (q.title % $1 OR q.description % $2)
+        ORDER BY similarity DESC
+        LIMIT $3
+        "
+        ))
+        .bind(question.title)
+        .bind(question.description)
+        .bind(limit)
+        .bind(self.space_id)
+        .fetch_all(&self.db)
+        .await
+        .map_err(|e| DBError::Other(Box::new(e)))
+    }
+
+    async fn close_as_duplicate(
+        &self,
+        uow: &mut dyn UnitOfWork,
+        question_uuid: String,
+        duplicate_of: String,
+        closed_by: String,
+    ) -> Result<QuestionDetail, DBError> {
+        let uuid =
+            Uuid::parse_str(&question_uuid).map_err(|e| DBError::InvalidUUID(e.to_string()))?;
+        let duplicate_of =
+            Uuid::parse_str(&duplicate_of).map_err(|e| DBError::InvalidUUID(e.to_string()))?;
+
+        // Nothing is updated if either question is missing or in another space, or if the question
+        // would end up being a duplicate of itself
+        let question = sqlx::query_as::<_, QuestionDetail>(
+            r"
+        WITH original AS (
+            SELECT COALESCE(duplicate_of, question_uuid) AS question_uuid FROM questions
+            WHERE question_uuid = $2 AND space_id = $4 AND deleted_at IS NULL
+        )
+        UPDATE questions q SET duplicate_of = original.question_uuid, closed_by = $3
+        FROM original
+        WHERE q.question_uuid = $1 AND q.space_id = $4 AND q.deleted_at IS NULL
+            AND q.question_uuid <> original.question_uuid
+        RETURNING q.*
+        ",
+        )
+        .bind(uuid)
+        .bind(duplicate_of)
+        .bind(closed_by)
+        .bind(self.space_id)
+        .fetch_one(uow.connection().await?)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::RowNotFound => DBError::InvalidUUID(e.to_string()),
+            _ => DBError::Other(Box::new(e)),
+        })?;
+
+        let original = question
+            .duplicate_of
+            .as_deref()
+            .map(Uuid::parse_str)
+            .transpose()
+            .map_err(|e| DBError::InvalidUUID(e.to_string()))?;
+
+        // Duplicates of the question now point to its original, so that duplicates never
+        // chain and cannot form a cycle
+        sqlx::query(
+            r"
+        UPDATE questions SET duplicate_of = $2
+        WHERE duplicate_of = $1 AND space_id = $3
+        ",
+        )
+        .bind(uuid)
+        .bind(original)
+        .bind(self.space_id)
+        .execute(uow.connection().await?)
+        .await
+        .map_err(|e| DBError::Other(Box::new(e)))?;
+
+        Ok(question)
+    }
+
+    async fn add_views(&self, views: HashMap<String, i64>) -> Result<(), DBError> {
+        let mut uuids = Vec::with_capacity(views.len());
+        let mut counts = Vec::with_capacity(views.len());
+        for (question_uuid, count) in views {
+            uuids.push(
+                Uuid::parse_str(&question_uuid).map_err(|e| DBError::InvalidUUID(e.to_string()))?,
+            );
+            counts.push(count);
+        }
+
+        // Both the total and the hourly bucket are written by the same statement
+        sqlx::query(
+            r"
+        WITH views AS (
+            SELECT * FROM UNNEST($1::uuid[], $2::bigint[]) AS v(question_uuid, views)
+        ), counted AS (
+            UPDATE questions q SET view_count = q.view_count + views.views
+            FROM views
+            WHERE q.question_uuid = views.question_uuid
+            RETURNING q.question_uuid, views.views
+        )
+        INSERT INTO question_views ( question_uuid, hour, views )
+        SELECT question_uuid, date_trunc('hour', CURRENT_TIMESTAMP), views FROM counted
+        ON CONFLICT ( question_uuid, hour )
+        DO UPDATE SET views = question_views.views + EXCLUDED.views
+        ",
+        )
+        .bind(uuids)
+        .bind(counts)
+        .execute(&self.db)
+        .await
+        .map_err(|e| DBError::Other(Box::new(e)))?;
+
+        Ok(())
+    }
+
+    async fn get_trending_questions(&self, limit: i64) -> Result<Vec<QuestionDetail>, DBError> {
+        // Activity older than a week weighs less than 1% of fresh activity and is ignored
+        let questions = sqlx::query_as::<_, QuestionDetail>(
+            r"
+        SELECT q.* FROM questions q
+        JOIN (
+            SELECT question_uuid,
+                SUM(weight * power(0.5, EXTRACT(EPOCH FROM CURRENT_TIMESTAMP - at)::float8 / 3600 / $1))
+                    AS score
+            FROM (
+                SELECT question_uuid, views::float8 AS weight, hour AS at FROM question_views
+                UNION ALL
+                SELECT question_uuid, $2 AS weight, created_at AS at FROM answers
+                WHERE deleted_at IS NULL
+            ) activity
+            WHERE at > CURRENT_TIMESTAMP - INTERVAL '7 days'
+            GROUP BY question_uuid
+        ) trending ON trending.question_uuid = q.question_uuid
+        WHERE q.space_id = $4
+            AND q.deleted_at IS NULL AND q.duplicate_of IS NULL AND q.closed_at IS NULL
+        ORDER BY trending.score DESC, q.created_at DESC
+        LIMIT $3
+        ",
+        )
+        .bind(TRENDING_HALF_LIFE_HOURS)
+        .bind(TRENDING_ANSWER_WEIGHT)
+        .bind(limit)
+        .bind(self.space_id)
+        .fetch_all(&self.db)
+        .await
+        .map_err(|e| DBError::Other(Box::new(e)))?;
+
+        render_questions(&self.render_cache, questions).await
     }
 }
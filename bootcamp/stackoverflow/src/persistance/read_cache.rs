@@ -0,0 +1,635 @@
+use std::{
+    any::Any,
+    collections::HashMap,
+    future::Future,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+use async_trait::async_trait;
+use log::error;
+use sqlx::{postgres::PgListener, types::Uuid, PgPool};
+
+use crate::models::{
+    Answer, AnswerDetail, CacheMetrics, DBError, Question, QuestionDetail, SimilarQuestion,
+};
+use crate::persistance::{
+    answers_dao::AnswersDao, questions_dao::QuestionsDao, unit_of_work::UnitOfWork,
+};
+
+/// Channel the instances sharing a database announce their writes on
+pub const INVALIDATION_CHANNEL: &str = "read_cache_invalidation";
+
+/// Delay before listening again after the connection of the listener failed
+const LISTEN_RETRY_DELAY: Duration = Duration::from_secs(5);
+
+/// Reads that are cached, the space they were made in is part of the key
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum Read {
+    Question(String),
+    Questions,
+    QuestionsPage { offset: i64, limit: i64 },
+    TrendingQuestions(i64),
+    Answer(String),
+    Answers(String),
+}
+
+struct CachedRead {
+    value: Arc<dyn Any + Send + Sync>,
+    stored_at: Instant,
+}
+
+#[derive(Default)]
+struct Entries {
+    /// Bumped by every invalidation, so that a read which started before an invalidation
+    /// does not store what it read
+    generation: u64,
+    reads: HashMap<(Uuid, Read), CachedRead>,
+}
+
+/// Reads of questions and answers shared by the DAOs of every space of this instance.
+/// Writes made through the cached DAOs empty the cache of their space once committed,
+/// so that reads following a write never see what was there before it.
+pub struct ReadCache {
+    capacity: usize,
+    ttl: Duration,
+    /// Whether writes are announced to the other instances
+    notify: bool,
+    /// Tells the writes of this instance from those of others on the invalidation channel
+    instance_id: Uuid,
+    entries: Mutex<Entries>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+    invalidations: AtomicU64,
+}
+
+impl ReadCache {
+    pub fn new(capacity: usize, ttl: Duration, notify: bool) -> Self {
+        ReadCache {
+            capacity,
+            ttl,
+            notify,
+            instance_id: Uuid::new_v4(),
+            entries: Mutex::new(Entries::default()),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+            invalidations: AtomicU64::new(0),
+        }
+    }
+
+    pub fn metrics(&self) -> CacheMetrics {
+        CacheMetrics {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            invalidations: self.invalidations.load(Ordering::Relaxed),
+            entries: self.entries.lock().unwrap().reads.len(),
+        }
+    }
+
+    /// Forgets the reads made in a space
+    pub fn invalidate_space(&self, space_id: Uuid) {
+        let mut entries = self.entries.lock().unwrap();
+        entries.generation += 1;
+        entries.reads.retain(|(space, _), _| *space != space_id);
+        self.invalidations.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Forgets every read, when the writes of other instances may have been missed
+    pub fn invalidate_all(&self) {
+        let mut entries = self.entries.lock().unwrap();
+        entries.generation += 1;
+        entries.reads.clear();
+        self.invalidations.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn get<T: Clone + 'static>(&self, key: &(Uuid, Read)) -> Option<T> {
+        let mut entries = self.entries.lock().unwrap();
+        let value = match entries.reads.get(key) {
+            Some(cached) if cached.stored_at.elapsed() < self.ttl => {
+                cached.value.downcast_ref::<T>().cloned()
+            }
+            Some(_) => {
+                entries.reads.remove(key);
+                None
+            }
+            None => None,
+        };
+
+        let counter = if value.is_some() {
+            &self.hits
+        } else {
+            &self.misses
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+
+        value
+    }
+
+    fn generation(&self) -> u64 {
+        self.entries.lock().unwrap().generation
+    }
+
+    /// Stores a read unless the cache was invalidated since `generation`
+    fn insert<T: Send + Sync + 'static>(&self, generation: u64, key: (Uuid, Read), value: T) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        let mut entries = self.entries.lock().unwrap();
+        if entries.generation != generation {
+            return;
+        }
+        if entries.reads.len() >= self.capacity && !entries.reads.contains_key(&key) {
+            let ttl = self.ttl;
+            entries
+                .reads
+                .retain(|_, cached| cached.stored_at.elapsed() < ttl);
+        }
+        if entries.reads.len() >= self.capacity && !entries.reads.contains_key(&key) {
+            let oldest = entries
+                .reads
+                .iter()
+                .min_by_key(|(_, cached)| cached.stored_at)
+                .map(|(key, _)| key.clone());
+            if let Some(oldest) = oldest {
+                entries.reads.remove(&oldest);
+            }
+        }
+        entries.reads.insert(
+            key,
+            CachedRead {
+                value: Arc::new(value),
+                stored_at: Instant::now(),
+            },
+        );
+    }
+
+    async fn read_through<T, F>(&self, key: (Uuid, Read), read: F) -> Result<T, DBError>
+    where
+        T: Clone + Send + Sync + 'static,
+        F: Future<Output = Result<T, DBError>>,
+    {
+        if let Some(value) = self.get(&key) {
+            return Ok(value);
+        }
+
+        // Taken before reading, the read may not see a write committed while it runs
+        let generation = self.generation();
+        let value = read.await?;
+        self.insert(generation, key, value.clone());
+
+        Ok(value)
+    }
+
+    /// Empties the cache of the space once the unit is committed, and tells the other
+    /// instances to do the same. The notification is part of the unit so Postgres only
+    /// delivers it on commit.
+    async fn invalidate_on_commit(
+        self: &Arc<Self>,
+        uow: &mut dyn UnitOfWork,
+        space_id: Uuid,
+    ) -> Result<(), DBError> {
+        if self.notify {
+            sqlx::query("SELECT pg_notify($1, $2)")
+                .bind(INVALIDATION_CHANNEL)
+                .bind(format!("{}/{}", self.instance_id, space_id))
+                .execute(uow.connection().await?)
+                .await
+                .map_err(|e| DBError::Other(Box::new(e)))?;
+        }
+
+        let cache = self.clone();
+        uow.after_commit(Box::new(move || cache.invalidate_space(space_id)));
+
+        Ok(())
+    }
+
+    /// Same as `invalidate_on_commit` for writes spanning every space
+    pub async fn invalidate_all_on_commit(
+        self: &Arc<Self>,
+        uow: &mut dyn UnitOfWork,
+    ) -> Result<(), DBError> {
+        // Other instances read a payload which is not a space as all of them
+        if self.notify {
+            sqlx::query("SELECT pg_notify($1, $2)")
+                .bind(INVALIDATION_CHANNEL)
+                .bind(format!("{}/*", self.instance_id))
+                .execute(uow.connection().await?)
+                .await
+                .map_err(|e| DBError::Other(Box::new(e)))?;
+        }
+
+        let cache = self.clone();
+        uow.after_commit(Box::new(move || cache.invalidate_all()));
+
+        Ok(())
+    }
+
+    /// Applies an invalidation announced on the channel, ignoring those of this instance
+    fn on_notification(&self, payload: &str) {
+        let space_id = payload
+            .split_once('/')
+            .filter(|(instance_id, _)| *instance_id != self.instance_id.to_string())
+            .map(|(_, space_id)| Uuid::parse_str(space_id));
+
+        match space_id {
+            Some(Ok(space_id)) => self.invalidate_space(space_id),
+            Some(Err(_)) => self.invalidate_all(),
+            None => {}
+        }
+    }
+}
+
+/// Background task applying the invalidations announced by the other instances.
+/// The whole cache is emptied whenever notifications may have been missed.
+pub async fn run_invalidation_listener(db: PgPool, cache: Arc<ReadCache>) {
+    loop {
+        if let Err(e) = listen(&db, &cache).await {
+            error!("Failed to listen for cache invalidations: {:?}", e);
+        }
+        cache.invalidate_all();
+        tokio::time::sleep(LISTEN_RETRY_DELAY).await;
+    }
+}
+
+async fn listen(db: &PgPool, cache: &ReadCache) -> Result<(), sqlx::Error> {
+    let mut listener = PgListener::connect_with(db).await?;
+    listener.listen(INVALIDATION_CHANNEL).await?;
+    cache.invalidate_all();
+
+    loop {
+        match listener.try_recv().await? {
+            Some(notification) => cache.on_notification(notification.payload()),
+            // The connection was lost and is reestablished on the next call
+            None => cache.invalidate_all(),
+        }
+    }
+}
+
+/// Serves the hot reads of questions from the cache, other reads go straight to `inner`
+pub struct CachedQuestionsDao {
+    inner: Arc<dyn QuestionsDao + Send + Sync>,
+    cache: Arc<ReadCache>,
+    space_id: Uuid,
+}
+
+impl CachedQuestionsDao {
+    /// `space_id` must be the space `inner` is scoped to
+    pub fn new(
+        inner: Arc<dyn QuestionsDao + Send + Sync>,
+        cache: Arc<ReadCache>,
+        space_id: Uuid,
+    ) -> Self {
+        CachedQuestionsDao {
+            inner,
+            cache,
+            space_id,
+        }
+    }
+
+    fn key(&self, read: Read) -> (Uuid, Read) {
+        (self.space_id, read)
+    }
+}
+
+#[async_trait]
+impl QuestionsDao for CachedQuestionsDao {
+    async fn create_question(
+        &self,
+        uow: &mut dyn UnitOfWork,
+        question: Question,
+        author: String,
+    ) -> Result<QuestionDetail, DBError> {
+        let question = self.inner.create_question(uow, question, author).await?;
+        self.cache.invalidate_on_commit(uow, self.space_id).await?;
+        Ok(question)
+    }
+
+    async fn get_question(&self, question_uuid: String) -> Result<QuestionDetail, DBError> {
+        let key = self.key(Read::Question(question_uuid.clone()));
+        self.cache
+            .read_through(key, self.inner.get_question(question_uuid))
+            .await
+    }
+
+    // Locks are taken in the database, so the cache is bypassed
+    async fn lock_question(
+        &self,
+        uow: &mut dyn UnitOfWork,
+        question_uuid: String,
+    ) -> Result<QuestionDetail, DBError> {
+        self.inner.lock_question(uow, question_uuid).await
+    }
+
+    async fn update_question(
+        &self,
+        uow: &mut dyn UnitOfWork,
+        question_uuid: String,
+        question: Question,
+    ) -> Result<QuestionDetail, DBError> {
+        let question = self
+            .inner
+            .update_question(uow, question_uuid, question)
+            .await?;
+        self.cache.invalidate_on_commit(uow, self.space_id).await?;
+        Ok(question)
+    }
+
+    async fn delete_question(
+        &self,
+        uow: &mut dyn UnitOfWork,
+        question_uuid: String,
+        deleted_by: String,
+    ) -> Result<(), DBError> {
+        self.inner
+            .delete_question(uow, question_uuid, deleted_by)
+            .await?;
+        self.cache.invalidate_on_commit(uow, self.space_id).await
+    }
+
+    async fn restore_question(
+        &self,
+        uow: &mut dyn UnitOfWork,
+        question_uuid: String,
+    ) -> Result<QuestionDetail, DBError> {
+        let question = self.inner.restore_question(uow, question_uuid).await?;
+        self.cache.invalidate_on_commit(uow, self.space_id).await?;
+        Ok(question)
+    }
+
+    async fn get_questions(&self) -> Result<Vec<QuestionDetail>, DBError> {
+        let key = self.key(Read::Questions);
+        self.cache
+            .read_through(key, self.inner.get_questions())
+            .await
+    }
+
+    async fn get_questions_page(
+        &self,
+        offset: i64,
+        limit: i64,
+    ) -> Result<Vec<QuestionDetail>, DBError> {
+        let key = self.key(Read::QuestionsPage { offset, limit });
+        self.cache
+            .read_through(key, self.inner.get_questions_page(offset, limit))
+            .await
+    }
+
+    async fn get_questions_by_uuids(
+        &self,
+        question_uuids: Vec<String>,
+    ) -> Result<Vec<QuestionDetail>, DBError> {
+        self.inner.get_questions_by_uuids(question_uuids).await
+    }
+
+    async fn get_questions_needing_attention(
+        &self,
+        offset: i64,
+        limit: i64,
+    ) -> Result<Vec<QuestionDetail>, DBError> {
+        self.inner
+            .get_questions_needing_attention(offset, limit)
+            .await
+    }
+
+    async fn get_related_questions(
+        &self,
+        question_uuid: String,
+        limit: i64,
+    ) -> Result<Vec<SimilarQuestion>, DBError> {
+        self.inner.get_related_questions(question_uuid, limit).await
+    }
+
+    async fn get_similar_questions(
+        &self,
+        question: Question,
+        limit: i64,
+    ) -> Result<Vec<SimilarQuestion>, DBError> {
+        self.inner.get_similar_questions(question, limit).await
+    }
+
+    async fn close_as_duplicate(
+        &self,
+        uow: &mut dyn UnitOfWork,
+        question_uuid: String,
+        duplicate_of: String,
+        closed_by: String,
+    ) -> Result<QuestionDetail, DBError> {
+        let question = self
+            .inner
+            .close_as_duplicate(uow, question_uuid, duplicate_of, closed_by)
+            .await?;
+        self.cache.invalidate_on_commit(uow, self.space_id).await?;
+        Ok(question)
+    }
+
+    /// Views are added in any space, so the whole cache is forgotten. Other instances are
+    /// not told, view counts are allowed to lag behind.
+    async fn add_views(&self, views: HashMap<String, i64>) -> Result<(), DBError> {
+        self.inner.add_views(views).await?;
+        self.cache.invalidate_all();
+        Ok(())
+    }
+
+    async fn get_trending_questions(&self, limit: i64) -> Result<Vec<QuestionDetail>, DBError> {
+        let key = self.key(Read::TrendingQuestions(limit));
+        self.cache
+            .read_through(key, self.inner.get_trending_questions(limit))
+            .await
+    }
+}
+
+/// Serves the reads of answers from the cache. Answers share the cache of the questions,
+/// writing an answer also forgets the questions read in its space.
+pub struct CachedAnswersDao {
+    inner: Arc<dyn AnswersDao + Send + Sync>,
+    cache: Arc<ReadCache>,
+    space_id: Uuid,
+}
+
+impl CachedAnswersDao {
+    /// `space_id` must be the space `inner` is scoped to
+    pub fn new(
+        inner: Arc<dyn AnswersDao + Send + Sync>,
+        cache: Arc<ReadCache>,
+        space_id: Uuid,
+    ) -> Self {
+        CachedAnswersDao {
+            inner,
+            cache,
+            space_id,
+        }
+    }
+
+    fn key(&self, read: Read) -> (Uuid, Read) {
+        (self.space_id, read)
+    }
+}
+
+#[async_trait]
+impl AnswersDao for CachedAnswersDao {
+    async fn create_answer(
+        &self,
+        uow: &mut dyn UnitOfWork,
+        answer: Answer,
+        author: String,
+    ) -> Result<AnswerDetail, DBError> {
+        let answer = self.inner.create_answer(uow, answer, author).await?;
+        self.cache.invalidate_on_commit(uow, self.space_id).await?;
+        Ok(answer)
+    }
+
+    async fn get_answer(&self, answer_uuid: String) -> Result<AnswerDetail, DBError> {
+        let key = self.key(Read::Answer(answer_uuid.clone()));
+        self.cache
+            .read_through(key, self.inner.get_answer(answer_uuid))
+            .await
+    }
+
+    // Locks are taken in the database, so the cache is bypassed
+    async fn lock_answer(
+        &self,
+        uow: &mut dyn UnitOfWork,
+        answer_uuid: String,
+    ) -> Result<AnswerDetail, DBError> {
+        self.inner.lock_answer(uow, answer_uuid).await
+    }
+
+    async fn update_answer(
+        &self,
+        uow: &mut dyn UnitOfWork,
+        answer_uuid: String,
+        content: String,
+    ) -> Result<AnswerDetail, DBError> {
+        let answer = self.inner.update_answer(uow, answer_uuid, content).await?;
+        self.cache.invalidate_on_commit(uow, self.space_id).await?;
+        Ok(answer)
+    }
+
+    async fn delete_answer(
+        &self,
+        uow: &mut dyn UnitOfWork,
+        answer_uuid: String,
+        deleted_by: String,
+    ) -> Result<(), DBError> {
+        self.inner
+            .delete_answer(uow, answer_uuid, deleted_by)
+            .await?;
+        self.cache.invalidate_on_commit(uow, self.space_id).await
+    }
+
+    async fn get_answers(&self, question_uuid: String) -> Result<Vec<AnswerDetail>, DBError> {
+        let key = self.key(Read::Answers(question_uuid.clone()));
+        self.cache
+            .read_through(key, self.inner.get_answers(question_uuid))
+            .await
+    }
+
+    async fn get_answers_of_questions(
+        &self,
+        question_uuids: Vec<String>,
+    ) -> Result<Vec<AnswerDetail>, DBError> {
+        self.inner.get_answers_of_questions(question_uuids).await
+    }
+}
+
+// ***********************************************************
+//                           Tests
+// ***********************************************************
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SPACE: Uuid = Uuid::from_u128(1);
+    const OTHER_SPACE: Uuid = Uuid::from_u128(2);
+
+    fn key(question_uuid: &str) -> (Uuid, Read) {
+        (SPACE, Read::Question(question_uuid.to_owned()))
+    }
+
+    fn store(cache: &ReadCache, key: (Uuid, Read), value: &str) {
+        cache.insert(cache.generation(), key, value.to_owned());
+    }
+
+    #[test]
+    fn read_cache_should_count_hits_and_misses() {
+        let cache = ReadCache::new(10, Duration::from_secs(60), false);
+
+        assert_eq!(cache.get::<String>(&key("1")), None);
+        store(&cache, key("1"), "a");
+        assert_eq!(cache.get::<String>(&key("1")), Some("a".to_owned()));
+
+        assert_eq!(
+            cache.metrics(),
+            CacheMetrics {
+                hits: 1,
+                misses: 1,
+                invalidations: 0,
+                entries: 1,
+            }
+        );
+    }
+
+    #[test]
+    fn read_cache_should_expire_reads() {
+        let cache = ReadCache::new(10, Duration::ZERO, false);
+
+        store(&cache, key("1"), "a");
+
+        assert_eq!(cache.get::<String>(&key("1")), None);
+        assert_eq!(cache.metrics().entries, 0);
+    }
+
+    #[test]
+    fn read_cache_should_be_bounded() {
+        let cache = ReadCache::new(2, Duration::from_secs(60), false);
+
+        store(&cache, key("1"), "a");
+        store(&cache, key("2"), "b");
+        store(&cache, key("3"), "c");
+
+        assert_eq!(cache.metrics().entries, 2);
+        assert_eq!(cache.get::<String>(&key("1")), None);
+        assert_eq!(cache.get::<String>(&key("3")), Some("c".to_owned()));
+    }
+
+    #[test]
+    fn read_cache_should_only_invalidate_the_space_written_to() {
+        let cache = ReadCache::new(10, Duration::from_secs(60), false);
+        let other = (OTHER_SPACE, Read::Questions);
+
+        store(&cache, key("1"), "a");
+        store(&cache, other.clone(), "b");
+        cache.invalidate_space(SPACE);
+
+        assert_eq!(cache.get::<String>(&key("1")), None);
+        assert_eq!(cache.get::<String>(&other), Some("b".to_owned()));
+    }
+
+    #[test]
+    fn read_cache_should_not_store_reads_that_raced_an_invalidation() {
+        let cache = ReadCache::new(10, Duration::from_secs(60), false);
+
+        let generation = cache.generation();
+        cache.invalidate_space(SPACE);
+        cache.insert(generation, key("1"), "stale".to_owned());
+
+        assert_eq!(cache.get::<String>(&key("1")), None);
+    }
+
+    #[test]
+    fn read_cache_should_ignore_its_own_notifications() {
+        let cache = ReadCache::new(10, Duration::from_secs(60), true);
+        store(&cache, key("1"), "a");
+
+        cache.on_notification(&format!("{}/{}", cache.instance_id, SPACE));
+        assert_eq!(cache.get::<String>(&key("1")), Some("a".to_owned()));
+
+        cache.on_notification(&format!("{}/{}", Uuid::new_v4(), SPACE));
+        assert_eq!(cache.get::<String>(&key("1")), None);
+    }
+}
@@ -0,0 +1,137 @@
+use async_trait::async_trait;
+use sqlx::{types::Uuid, PgConnection, PgPool};
+
+use crate::models::{DBError, ReputationReason, UserProfile, ANONYMOUS};
+use crate::persistance::{spaces_dao::DEFAULT_SPACE_ID, unit_of_work::UnitOfWork};
+
+#[async_trait]
+pub trait ReputationDao {
+    async fn get_reputation(&self, user_id: String) -> Result<i64, DBError>;
+    async fn get_user_profile(&self, user_id: String) -> Result<UserProfile, DBError>;
+    /// Charges the author of an answer removed by moderators
+    async fn record_answer_deleted_by_moderator(
+        &self,
+        uow: &mut dyn UnitOfWork,
+        answer_uuid: String,
+    ) -> Result<(), DBError>;
+}
+
+/// Reputation is earned in a space and only counts there.
+pub struct ReputationDaoImpl {
+    db: PgPool,
+    space_id: Uuid,
+}
+
+impl ReputationDaoImpl {
+    pub fn new(db: PgPool) -> Self {
+        Self::in_space(db, DEFAULT_SPACE_ID)
+    }
+
+    pub fn in_space(db: PgPool, space_id: Uuid) -> Self {
+        ReputationDaoImpl { db, space_id }
+    }
+}
+
+/// Appends an entry to the reputation ledger, anonymous activity is not recorded.
+/// Takes a connection so that it can be called inside the transaction creating the post.
+pub async fn record_reputation(
+    conn: &mut PgConnection,
+    space_id: Uuid,
+    user_id: &str,
+    reason: ReputationReason,
+    post_uuid: Uuid,
+) -> Result<(), DBError> {
+    if user_id == ANONYMOUS {
+        return Ok(());
+    }
+
+    sqlx::query(
+        r"
+        INSERT INTO reputation_events ( user_id, reason, points, post_uuid, space_id )
+        VALUES ( $1, $2, $3, $4, $5 )
+        ",
+    )
+    .bind(user_id)
+    .bind(reason.as_str())
+    .bind(reason.points())
+    .bind(post_uuid)
+    .bind(space_id)
+    .execute(conn)
+    .await
+    .map_err(|e| DBError::Other(Box::new(e)))?;
+
+    Ok(())
+}
+
+#[async_trait]
+impl ReputationDao for ReputationDaoImpl {
+    async fn get_reputation(&self, user_id: String) -> Result<i64, DBError> {
+        sqlx::query_scalar::<_, i64>(
+            r"
+        SELECT COALESCE(SUM(points), 0) FROM reputation_events
+        WHERE user_id = $1 AND space_id = $2
+        ",
+        )
+        .bind(user_id)
+        .bind(self.space_id)
+        .fetch_one(&self.db)
+        .await
+        .map_err(|e| DBError::Other(Box::new(e)))
+    }
+
+    async fn get_user_profile(&self, user_id: String) -> Result<UserProfile, DBError> {
+        let (reputation, question_count, answer_count) = sqlx::query_as::<_, (i64, i64, i64)>(
+            r"
+        SELECT
+            (SELECT COALESCE(SUM(points), 0) FROM reputation_events
+                WHERE user_id = $1 AND space_id = $2),
+            (SELECT COUNT(*) FROM questions
+                WHERE author = $1 AND space_id = $2 AND deleted_at IS NULL),
+            (SELECT COUNT(*) FROM answers
+                JOIN questions ON questions.question_uuid = answers.question_uuid
+                WHERE answers.author = $1 AND questions.space_id = $2
+                    AND answers.deleted_at IS NULL)
+        ",
+        )
+        .bind(&user_id)
+        .bind(self.space_id)
+        .fetch_one(&self.db)
+        .await
+        .map_err(|e| DBError::Other(Box::new(e)))?;
+
+        Ok(UserProfile {
+            user_id,
+            reputation,
+            question_count,
+            answer_count,
+        })
+    }
+
+    async fn record_answer_deleted_by_moderator(
+        &self,
+        uow: &mut dyn UnitOfWork,
+        answer_uuid: String,
+    ) -> Result<(), DBError> {
+        let uuid =
+            Uuid::parse_str(&answer_uuid).map_err(|e| DBError::InvalidUUID(e.to_string()))?;
+        let reason = ReputationReason::AnswerDeletedByModerator;
+
+        sqlx::query(
+            r"
+        INSERT INTO reputation_events ( user_id, reason, points, post_uuid, space_id )
+        SELECT answers.author, $2, $3, answers.answer_uuid, questions.space_id FROM answers
+        JOIN questions ON questions.question_uuid = answers.question_uuid
+        WHERE answers.answer_uuid = $1 AND answers.author <> $4
+        ",
+        )
+        .bind(uuid)
+        .bind(reason.as_str())
+        .bind(reason.points())
+        .bind(ANONYMOUS)
+        .execute(uow.connection().await?)
+        .await
+        .map_err(|e| DBError::Other(Box::new(e)))?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,172 @@
+use async_trait::async_trait;
+use sqlx::{types::Uuid, PgPool};
+
+use crate::models::{
+    postgres_error_codes, DBError, Space, SpaceDetail, SpaceMemberDetail, SpaceRole,
+};
+use crate::persistance::unit_of_work::UnitOfWork;
+
+/// Space of the questions asked outside of `/spaces/{slug}`, created by the spaces migration
+pub const DEFAULT_SPACE_ID: Uuid = Uuid::from_u128(1);
+
+#[async_trait]
+pub trait SpacesDao {
+    /// Fails with `DBError::InvalidUUID` if the slug is taken
+    async fn create_space(
+        &self,
+        uow: &mut dyn UnitOfWork,
+        space: Space,
+    ) -> Result<SpaceDetail, DBError>;
+    async fn get_space(&self, slug: String) -> Result<SpaceDetail, DBError>;
+    /// Role of a user in a space, `None` if they are not a member
+    async fn get_role(
+        &self,
+        space_id: String,
+        user_id: String,
+    ) -> Result<Option<SpaceRole>, DBError>;
+    /// Adds a member to a space, or changes the role of an existing one
+    async fn set_member(
+        &self,
+        uow: &mut dyn UnitOfWork,
+        space_id: String,
+        user_id: String,
+        role: SpaceRole,
+    ) -> Result<SpaceMemberDetail, DBError>;
+    /// Returns the membership that was removed, if any
+    async fn remove_member(
+        &self,
+        uow: &mut dyn UnitOfWork,
+        space_id: String,
+        user_id: String,
+    ) -> Result<Option<SpaceMemberDetail>, DBError>;
+}
+
+pub struct SpacesDaoImpl {
+    db: PgPool,
+}
+
+impl SpacesDaoImpl {
+    pub fn new(db: PgPool) -> Self {
+        SpacesDaoImpl { db }
+    }
+}
+
+#[async_trait]
+impl SpacesDao for SpacesDaoImpl {
+    async fn create_space(
+        &self,
+        uow: &mut dyn UnitOfWork,
+        space: Space,
+    ) -> Result<SpaceDetail, DBError> {
+        sqlx::query_as::<_, SpaceDetail>(
+            r"
+        INSERT INTO spaces ( slug, name, open )
+        VALUES ( $1, $2, $3 )
+        RETURNING *
+        ",
+        )
+        .bind(space.slug)
+        .bind(space.name)
+        .bind(space.open)
+        .fetch_one(uow.connection().await?)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::Database(ref db_err)
+                if db_err.code().as_deref() == Some(postgres_error_codes::UNIQUE_VIOLATION) =>
+            {
+                DBError::InvalidUUID(e.to_string())
+            }
+            _ => DBError::Other(Box::new(e)),
+        })
+    }
+
+    async fn get_space(&self, slug: String) -> Result<SpaceDetail, DBError> {
+        sqlx::query_as::<_, SpaceDetail>(
+            r"
+        SELECT * FROM spaces WHERE slug = $1
+        ",
+        )
+        .bind(slug)
+        .fetch_one(&self.db)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::RowNotFound => DBError::InvalidUUID(e.to_string()),
+            _ => DBError::Other(Box::new(e)),
+        })
+    }
+
+    async fn get_role(
+        &self,
+        space_id: String,
+        user_id: String,
+    ) -> Result<Option<SpaceRole>, DBError> {
+        let uuid = Uuid::parse_str(&space_id).map_err(|e| DBError::InvalidUUID(e.to_string()))?;
+
+        let role = sqlx::query_scalar::<_, String>(
+            r"
+        SELECT role FROM space_members WHERE space_id = $1 AND user_id = $2
+        ",
+        )
+        .bind(uuid)
+        .bind(user_id)
+        .fetch_optional(&self.db)
+        .await
+        .map_err(|e| DBError::Other(Box::new(e)))?;
+
+        Ok(role.as_deref().and_then(SpaceRole::parse))
+    }
+
+    async fn set_member(
+        &self,
+        uow: &mut dyn UnitOfWork,
+        space_id: String,
+        user_id: String,
+        role: SpaceRole,
+    ) -> Result<SpaceMemberDetail, DBError> {
+        let uuid = Uuid::parse_str(&space_id).map_err(|e| DBError::InvalidUUID(e.to_string()))?;
+
+        sqlx::query_as::<_, SpaceMemberDetail>(
+            r"
+        INSERT INTO space_members ( space_id, user_id, role )
+        VALUES ( $1, $2, $3 )
+        ON CONFLICT ( space_id, user_id ) DO UPDATE SET role = EXCLUDED.role
+        RETURNING *
+        ",
+        )
+        .bind(uuid)
+        .bind(user_id)
+        .bind(role.as_str())
+        .fetch_one(uow.connection().await?)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::Database(ref db_err)
+                if db_err.code().as_deref()
+                    == Some(postgres_error_codes::FOREIGN_KEY_VIOLATION) =>
+            {
+                DBError::InvalidUUID(e.to_string())
+            }
+            _ => DBError::Other(Box::new(e)),
+        })
+    }
+
+    async fn remove_member(
+        &self,
+        uow: &mut dyn UnitOfWork,
+        space_id: String,
+        user_id: String,
+    ) -> Result<Option<SpaceMemberDetail>, DBError> {
+        let uuid = Uuid::parse_str(&space_id).map_err(|e| DBError::InvalidUUID(e.to_string()))?;
+
+        sqlx::query_as::<_, SpaceMemberDetail>(
+            r"
+        DELETE FROM space_members WHERE space_id = $1 AND user_id = $2
+        RETURNING *
+        ",
+        )
+        .bind(uuid)
+        .bind(user_id)
+        .fetch_optional(uow.connection().await?)
+        .await
+        .map_err(|e| DBError::Other(Box::new(e)))
+    }
+}
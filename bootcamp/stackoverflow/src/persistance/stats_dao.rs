@@ -0,0 +1,108 @@
+use async_trait::async_trait;
+use sqlx::{
+    types::{time::PrimitiveDateTime, Uuid},
+    PgPool,
+};
+
+use crate::models::{DBError, DailyPosts, Stats};
+use crate::persistance::spaces_dao::DEFAULT_SPACE_ID;
+
+#[async_trait]
+pub trait StatsDao {
+    /// Figures of the space as of the last refresh, with the posts of the last `days` days
+    async fn get_stats(&self, days: i64) -> Result<Stats, DBError>;
+    /// Recomputes the statistics of every space from the posts
+    async fn refresh_stats(&self) -> Result<(), DBError>;
+}
+
+/// Reads the statistics of a single space from the materialised views
+pub struct StatsDaoImpl {
+    db: PgPool,
+    space_id: Uuid,
+}
+
+impl StatsDaoImpl {
+    pub fn new(db: PgPool) -> Self {
+        Self::in_space(db, DEFAULT_SPACE_ID)
+    }
+
+    pub fn in_space(db: PgPool, space_id: Uuid) -> Self {
+        StatsDaoImpl { db, space_id }
+    }
+}
+
+#[async_trait]
+impl StatsDao for StatsDaoImpl {
+    async fn get_stats(&self, days: i64) -> Result<Stats, DBError> {
+        let (
+            questions,
+            answers,
+            unanswered_questions,
+            median_seconds_to_first_answer,
+            refreshed_at,
+        ) = sqlx::query_as::<_, (i64, i64, i64, Option<f64>, PrimitiveDateTime)>(
+            r"
+        SELECT COUNT(*),
+            COALESCE(SUM(answer_count), 0)::BIGINT,
+            COUNT(*) FILTER (WHERE answer_count = 0 AND NOT closed),
+            percentile_cont(0.5) WITHIN GROUP (
+                ORDER BY EXTRACT(EPOCH FROM first_answer_at - created_at)::FLOAT8
+            ),
+            ( SELECT refreshed_at FROM stats_refreshes )
+        FROM question_stats
+        WHERE space_id = $1
+        ",
+        )
+        .bind(self.space_id)
+        .fetch_one(&self.db)
+        .await
+        .map_err(|e| DBError::Other(Box::new(e)))?;
+
+        let posts_per_day = sqlx::query_as::<_, DailyPosts>(
+            r"
+        SELECT to_char(days.day, 'YYYY-MM-DD') AS day,
+            COALESCE(p.questions, 0) AS questions,
+            COALESCE(p.answers, 0) AS answers
+        FROM generate_series(CURRENT_DATE - ($2::INT - 1), CURRENT_DATE, INTERVAL '1 day')
+            AS days(day)
+        LEFT JOIN daily_posts p ON p.day = days.day::DATE AND p.space_id = $1
+        ORDER BY days.day
+        ",
+        )
+        .bind(self.space_id)
+        .bind(days)
+        .fetch_all(&self.db)
+        .await
+        .map_err(|e| DBError::Other(Box::new(e)))?;
+
+        Ok(Stats {
+            questions,
+            answers,
+            unanswered_questions,
+            median_seconds_to_first_answer,
+            posts_per_day,
+            refreshed_at: format!("{:?}", refreshed_at),
+        })
+    }
+
+    async fn refresh_stats(&self) -> Result<(), DBError> {
+        // Refreshed together so that the views agree with each other and with `refreshed_at`,
+        // concurrently so that reads are not blocked meanwhile
+        let mut tx = self
+            .db
+            .begin()
+            .await
+            .map_err(|e| DBError::Other(Box::new(e)))?;
+
+        for view in ["question_stats", "daily_posts", "stats_refreshes"] {
+            sqlx::query(&format!("REFRESH MATERIALIZED VIEW CONCURRENTLY {view}"))
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| DBError::Other(Box::new(e)))?;
+        }
+
+        tx.commit().await.map_err(|e| DBError::Other(Box::new(e)))?;
+
+        Ok(())
+    }
+}
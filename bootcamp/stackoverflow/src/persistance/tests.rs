@@ -1,4 +1,6 @@
 mod answers_tests {
+    use std::time::Duration;
+
     use sqlx::PgPool;
 
     use crate::{
@@ -6,18 +8,24 @@ mod answers_tests {
         persistance::{
             answers_dao::{AnswersDao, AnswersDaoImpl},
             questions_dao::{QuestionsDao, QuestionsDaoImpl},
+            unit_of_work::{Autocommit, Database, DatabaseImpl},
         },
     };
 
     #[sqlx::test]
     async fn create_answer_should_fail_with_malformed_uuid(pool: PgPool) -> Result<(), String> {
+        let mut uow = Autocommit::new(pool.clone());
         let answer_doa = AnswersDaoImpl::new(pool);
 
         let result = answer_doa
-            .create_answer(Answer {
-                question_uuid: "malformed".to_owned(),
-                content: "test content".to_owned(),
-            })
+            .create_answer(
+                &mut uow,
+                Answer {
+                    question_uuid: "malformed".to_owned(),
+                    content: "test content".to_owned(),
+                },
+                "user".to_owned(),
+            )
             .await;
 
         if result.is_ok() {
@@ -39,13 +47,18 @@ mod answers_tests {
 
     #[sqlx::test]
     async fn create_answer_should_fail_with_non_existent_uuid(pool: PgPool) -> Result<(), String> {
+        let mut uow = Autocommit::new(pool.clone());
         let answer_doa = AnswersDaoImpl::new(pool);
 
         let result = answer_doa
-            .create_answer(Answer {
-                question_uuid: "a22abcd2-22ab-2222-a22b-2abc2a2b22cc".to_owned(),
-                content: "test content".to_owned(),
-            })
+            .create_answer(
+                &mut uow,
+                Answer {
+                    question_uuid: "a22abcd2-22ab-2222-a22b-2abc2a2b22cc".to_owned(),
+                    content: "test content".to_owned(),
+                },
+                "user".to_owned(),
+            )
             .await;
 
         if result.is_ok() {
@@ -69,15 +82,20 @@ mod answers_tests {
     async fn create_answer_should_fail_if_database_error_occurs(
         pool: PgPool,
     ) -> Result<(), String> {
+        let mut uow = Autocommit::new(pool.clone());
         let answer_doa = AnswersDaoImpl::new(pool.clone());
 
         pool.close().await;
 
         let result = answer_doa
-            .create_answer(Answer {
-                question_uuid: "a22abcd2-22ab-2222-a22b-2abc2a2b22cc".to_owned(),
-                content: "test content".to_owned(),
-            })
+            .create_answer(
+                &mut uow,
+                Answer {
+                    question_uuid: "a22abcd2-22ab-2222-a22b-2abc2a2b22cc".to_owned(),
+                    content: "test content".to_owned(),
+                },
+                "user".to_owned(),
+            )
             .await;
 
         if result.is_ok() {
@@ -99,37 +117,93 @@ mod answers_tests {
 
     #[sqlx::test]
     async fn create_answer_should_succeed(pool: PgPool) -> Result<(), String> {
+        let mut uow = Autocommit::new(pool.clone());
         let question_doa = QuestionsDaoImpl::new(pool.clone());
         let answer_doa = AnswersDaoImpl::new(pool);
 
         let result = question_doa
-            .create_question(Question {
-                title: "test title".to_owned(),
-                description: "test description".to_owned(),
-            })
+            .create_question(
+                &mut uow,
+                Question {
+                    title: "test title".to_owned(),
+                    description: "test description".to_owned(),
+                },
+                "user".to_owned(),
+            )
             .await
             .map_err(|e| format!("{:?}", e))?;
 
         let result = answer_doa
-            .create_answer(Answer {
-                question_uuid: result.question_uuid,
-                content: "test content".to_owned(),
-            })
+            .create_answer(
+                &mut uow,
+                Answer {
+                    question_uuid: result.question_uuid,
+                    content: "test content".to_owned(),
+                },
+                "user".to_owned(),
+            )
             .await
             .map_err(|e| format!("{:?}", e))?;
 
-        if result.content != "test content".to_owned() {
+        if result.content != "test content" {
             return Err("Incorrect answer content".to_owned());
         }
 
         Ok(())
     }
 
+    #[sqlx::test]
+    async fn update_answer_should_render_new_content(pool: PgPool) -> Result<(), String> {
+        let mut uow = Autocommit::new(pool.clone());
+        let question_doa = QuestionsDaoImpl::new(pool.clone());
+        let answer_doa = AnswersDaoImpl::new(pool);
+
+        let question = question_doa
+            .create_question(
+                &mut uow,
+                Question {
+                    title: "test title".to_owned(),
+                    description: "test description".to_owned(),
+                },
+                "user".to_owned(),
+            )
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+
+        let answer = answer_doa
+            .create_answer(
+                &mut uow,
+                Answer {
+                    question_uuid: question.question_uuid,
+                    content: "test content".to_owned(),
+                },
+                "user".to_owned(),
+            )
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+
+        let result = answer_doa
+            .update_answer(&mut uow, answer.answer_uuid, "`new` content".to_owned())
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+
+        if result.content != "`new` content"
+            || result.content_html != "<p><code>new</code> content</p>\n"
+        {
+            return Err(format!("Answer was not updated: {:?}", result));
+        }
+
+        Ok(())
+    }
+
     #[sqlx::test]
     async fn delete_answer_should_fail_with_malformed_uuid(pool: PgPool) -> Result<(), String> {
+        let mut uow = Autocommit::new(pool.clone());
         let answer_doa = AnswersDaoImpl::new(pool);
 
-        let result = answer_doa.delete_answer("malformed".to_owned()).await;
+        let result = answer_doa
+            .delete_answer(&mut uow, "malformed".to_owned(), "user".to_owned())
+            .await;
 
         if result.is_ok() {
             return Err(format!(
@@ -152,12 +226,17 @@ mod answers_tests {
     async fn delete_answer_should_fail_if_database_error_occurs(
         pool: PgPool,
     ) -> Result<(), String> {
+        let mut uow = Autocommit::new(pool.clone());
         let answer_doa = AnswersDaoImpl::new(pool.clone());
 
         pool.close().await;
 
         let result = answer_doa
-            .delete_answer("a22abcd2-22ab-2222-a22b-2abc2a2b22cc".to_owned())
+            .delete_answer(
+                &mut uow,
+                "a22abcd2-22ab-2222-a22b-2abc2a2b22cc".to_owned(),
+                "user".to_owned(),
+            )
             .await;
 
         if result.is_ok() {
@@ -179,27 +258,36 @@ mod answers_tests {
 
     #[sqlx::test]
     async fn delete_answer_should_succeed(pool: PgPool) -> Result<(), String> {
+        let mut uow = Autocommit::new(pool.clone());
         let question_doa = QuestionsDaoImpl::new(pool.clone());
         let answer_doa = AnswersDaoImpl::new(pool);
 
         let question = question_doa
-            .create_question(Question {
-                title: "test title".to_owned(),
-                description: "test description".to_owned(),
-            })
+            .create_question(
+                &mut uow,
+                Question {
+                    title: "test title".to_owned(),
+                    description: "test description".to_owned(),
+                },
+                "user".to_owned(),
+            )
             .await
             .map_err(|e| format!("{:?}", e))?;
 
         let result = answer_doa
-            .create_answer(Answer {
-                question_uuid: question.question_uuid.clone(),
-                content: "test content".to_owned(),
-            })
+            .create_answer(
+                &mut uow,
+                Answer {
+                    question_uuid: question.question_uuid.clone(),
+                    content: "test content".to_owned(),
+                },
+                "user".to_owned(),
+            )
             .await
             .map_err(|e| format!("{:?}", e))?;
 
         answer_doa
-            .delete_answer(result.answer_uuid)
+            .delete_answer(&mut uow, result.answer_uuid, "user".to_owned())
             .await
             .map_err(|e| format!("{:?}", e))?;
 
@@ -208,13 +296,80 @@ mod answers_tests {
             .await
             .map_err(|e| format!("{:?}", e))?;
 
-        if results.len() != 0 {
+        if !results.is_empty() {
             return Err("Answer was not deleted".to_owned());
         }
 
         Ok(())
     }
 
+    #[sqlx::test]
+    async fn get_answers_should_hide_answers_of_deleted_question(
+        pool: PgPool,
+    ) -> Result<(), String> {
+        let mut uow = Autocommit::new(pool.clone());
+        let question_doa = QuestionsDaoImpl::new(pool.clone());
+        let answer_doa = AnswersDaoImpl::new(pool);
+
+        let question = question_doa
+            .create_question(
+                &mut uow,
+                Question {
+                    title: "test title".to_owned(),
+                    description: "test description".to_owned(),
+                },
+                "user".to_owned(),
+            )
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+
+        answer_doa
+            .create_answer(
+                &mut uow,
+                Answer {
+                    question_uuid: question.question_uuid.clone(),
+                    content: "test content".to_owned(),
+                },
+                "user".to_owned(),
+            )
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+
+        question_doa
+            .delete_question(&mut uow, question.question_uuid.clone(), "user".to_owned())
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+
+        let results = answer_doa
+            .get_answers(question.question_uuid.clone())
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+
+        if !results.is_empty() {
+            return Err("Answers of a deleted question were returned".to_owned());
+        }
+
+        let result = answer_doa
+            .create_answer(
+                &mut uow,
+                Answer {
+                    question_uuid: question.question_uuid,
+                    content: "test content".to_owned(),
+                },
+                "user".to_owned(),
+            )
+            .await;
+
+        if let Err(DBError::InvalidUUID(_)) = result {
+            Ok(())
+        } else {
+            Err(format!(
+                "Expected an invalid UUID error but got the following result: {:?}",
+                result
+            ))
+        }
+    }
+
     #[sqlx::test]
     async fn get_answers_should_fail_with_malformed_uuid(pool: PgPool) -> Result<(), String> {
         let answer_doa = AnswersDaoImpl::new(pool);
@@ -267,22 +422,31 @@ mod answers_tests {
 
     #[sqlx::test]
     async fn get_answers_should_succeed(pool: PgPool) -> Result<(), String> {
+        let mut uow = Autocommit::new(pool.clone());
         let question_doa = QuestionsDaoImpl::new(pool.clone());
         let answer_doa = AnswersDaoImpl::new(pool);
 
         let question = question_doa
-            .create_question(Question {
-                title: "test title".to_owned(),
-                description: "test description".to_owned(),
-            })
+            .create_question(
+                &mut uow,
+                Question {
+                    title: "test title".to_owned(),
+                    description: "test description".to_owned(),
+                },
+                "user".to_owned(),
+            )
             .await
             .map_err(|e| format!("{:?}", e))?;
 
         let result = answer_doa
-            .create_answer(Answer {
-                question_uuid: question.question_uuid.clone(),
-                content: "test content".to_owned(),
-            })
+            .create_answer(
+                &mut uow,
+                Answer {
+                    question_uuid: question.question_uuid.clone(),
+                    content: "test content".to_owned(),
+                },
+                "user".to_owned(),
+            )
             .await
             .map_err(|e| format!("{:?}", e))?;
 
@@ -295,35 +459,275 @@ mod answers_tests {
             return Err("Incorrect number of results returned.".to_owned());
         }
 
-        if results.get(0).unwrap().answer_uuid != result.answer_uuid {
+        if results.first().unwrap().answer_uuid != result.answer_uuid {
             return Err("Incorrect answer returned.".to_owned());
         }
 
         Ok(())
     }
+
+    #[sqlx::test]
+    async fn get_answers_of_questions_should_return_answers_of_every_question(
+        pool: PgPool,
+    ) -> Result<(), String> {
+        let mut uow = Autocommit::new(pool.clone());
+        let question_doa = QuestionsDaoImpl::new(pool.clone());
+        let answer_doa = AnswersDaoImpl::new(pool);
+
+        let mut question_uuids = Vec::new();
+        for title in ["first title", "second title", "unanswered title"] {
+            let question = question_doa
+                .create_question(
+                    &mut uow,
+                    Question {
+                        title: title.to_owned(),
+                        description: "test description".to_owned(),
+                    },
+                    "user".to_owned(),
+                )
+                .await
+                .map_err(|e| format!("{:?}", e))?;
+            question_uuids.push(question.question_uuid);
+        }
+
+        for question_uuid in &question_uuids[..2] {
+            answer_doa
+                .create_answer(
+                    &mut uow,
+                    Answer {
+                        question_uuid: question_uuid.clone(),
+                        content: "test content".to_owned(),
+                    },
+                    "user".to_owned(),
+                )
+                .await
+                .map_err(|e| format!("{:?}", e))?;
+        }
+
+        let results = answer_doa
+            .get_answers_of_questions(question_uuids.clone())
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+
+        let answered = results
+            .iter()
+            .map(|answer| &answer.question_uuid)
+            .collect::<Vec<_>>();
+        if answered != vec![&question_uuids[0], &question_uuids[1]] {
+            return Err(format!("Incorrect answers returned: {:?}", results));
+        }
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn create_answer_should_wait_for_concurrent_question_deletion(
+        pool: PgPool,
+    ) -> Result<(), String> {
+        let mut uow = Autocommit::new(pool.clone());
+        let database = DatabaseImpl::new(pool.clone());
+        let question_doa = QuestionsDaoImpl::new(pool.clone());
+        let answer_doa = AnswersDaoImpl::new(pool.clone());
+
+        let question = question_doa
+            .create_question(
+                &mut uow,
+                Question {
+                    title: "test title".to_owned(),
+                    description: "test description".to_owned(),
+                },
+                "user".to_owned(),
+            )
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+
+        let mut deletion = database.begin().await.map_err(|e| format!("{:?}", e))?;
+        question_doa
+            .delete_question(
+                deletion.as_mut(),
+                question.question_uuid.clone(),
+                "user".to_owned(),
+            )
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+
+        let answering = tokio::spawn(async move {
+            let database = DatabaseImpl::new(pool);
+            let mut creation = database.begin().await?;
+            let answer = answer_doa
+                .create_answer(
+                    creation.as_mut(),
+                    Answer {
+                        question_uuid: question.question_uuid,
+                        content: "test content".to_owned(),
+                    },
+                    "user".to_owned(),
+                )
+                .await?;
+            creation.commit().await.map(|_| answer)
+        });
+
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        if answering.is_finished() {
+            return Err("The answer did not wait for the deletion to complete".to_owned());
+        }
+
+        deletion.commit().await.map_err(|e| format!("{:?}", e))?;
+        let result = answering.await.map_err(|e| format!("{:?}", e))?;
+
+        if let Err(DBError::InvalidUUID(_)) = result {
+            Ok(())
+        } else {
+            Err(format!(
+                "Expected an invalid UUID error but got the following result: {:?}",
+                result
+            ))
+        }
+    }
+
+    #[sqlx::test]
+    async fn lock_question_should_hold_writers_until_commit(pool: PgPool) -> Result<(), String> {
+        let mut uow = Autocommit::new(pool.clone());
+        let database = DatabaseImpl::new(pool.clone());
+        let question_doa = QuestionsDaoImpl::new(pool.clone());
+
+        let question = question_doa
+            .create_question(
+                &mut uow,
+                Question {
+                    title: "test title".to_owned(),
+                    description: "test description".to_owned(),
+                },
+                "user".to_owned(),
+            )
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+
+        let mut check = database.begin().await.map_err(|e| format!("{:?}", e))?;
+        let locked = question_doa
+            .lock_question(check.as_mut(), question.question_uuid.clone())
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+        if locked.author != "user" {
+            return Err(format!("Unexpected locked question: {:?}", locked));
+        }
+
+        let question_uuid = question.question_uuid.clone();
+        let deleting = tokio::spawn(async move {
+            let database = DatabaseImpl::new(pool.clone());
+            let mut deletion = database.begin().await?;
+            QuestionsDaoImpl::new(pool)
+                .delete_question(deletion.as_mut(), question_uuid, "other".to_owned())
+                .await?;
+            deletion.commit().await
+        });
+
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        if deleting.is_finished() {
+            return Err("The deletion did not wait for the lock to be released".to_owned());
+        }
+
+        check.commit().await.map_err(|e| format!("{:?}", e))?;
+        deleting
+            .await
+            .map_err(|e| format!("{:?}", e))?
+            .map_err(|e| format!("{:?}", e))?;
+
+        let result = question_doa
+            .lock_question(&mut uow, question.question_uuid)
+            .await;
+        if let Err(DBError::InvalidUUID(_)) = result {
+            Ok(())
+        } else {
+            Err(format!(
+                "Expected an invalid UUID error but got the following result: {:?}",
+                result
+            ))
+        }
+    }
+
+    #[sqlx::test]
+    async fn uncommitted_unit_of_work_should_discard_all_writes(
+        pool: PgPool,
+    ) -> Result<(), String> {
+        let database = DatabaseImpl::new(pool.clone());
+        let question_doa = QuestionsDaoImpl::new(pool.clone());
+        let answer_doa = AnswersDaoImpl::new(pool);
+
+        let mut uow = database.begin().await.map_err(|e| format!("{:?}", e))?;
+        let question = question_doa
+            .create_question(
+                uow.as_mut(),
+                Question {
+                    title: "test title".to_owned(),
+                    description: "test description".to_owned(),
+                },
+                "user".to_owned(),
+            )
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+        answer_doa
+            .create_answer(
+                uow.as_mut(),
+                Answer {
+                    question_uuid: question.question_uuid.clone(),
+                    content: "test content".to_owned(),
+                },
+                "user".to_owned(),
+            )
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+        drop(uow);
+
+        let questions = question_doa
+            .get_questions()
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+        let answers = answer_doa
+            .get_answers_of_questions(vec![question.question_uuid])
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+
+        if !questions.is_empty() || !answers.is_empty() {
+            return Err("Writes of a dropped unit of work were kept".to_owned());
+        }
+
+        Ok(())
+    }
 }
 
 mod questions_tests {
+    use std::collections::HashMap;
+
     use sqlx::PgPool;
 
     use crate::{
-        models::{DBError, Question},
-        persistance::questions_dao::{QuestionsDao, QuestionsDaoImpl},
+        models::{Answer, DBError, Question},
+        persistance::{
+            answers_dao::{AnswersDao, AnswersDaoImpl},
+            questions_dao::{QuestionsDao, QuestionsDaoImpl},
+            unit_of_work::Autocommit,
+        },
     };
 
     #[sqlx::test]
     async fn create_question_should_fail_if_database_error_occurs(
         pool: PgPool,
     ) -> Result<(), String> {
+        let mut uow = Autocommit::new(pool.clone());
         let doa = QuestionsDaoImpl::new(pool.clone());
 
         pool.close().await;
 
         let result = doa
-            .create_question(Question {
-                title: "test title".to_owned(),
-                description: "test description".to_owned(),
-            })
+            .create_question(
+                &mut uow,
+                Question {
+                    title: "test title".to_owned(),
+                    description: "test description".to_owned(),
+                },
+                "user".to_owned(),
+            )
             .await;
 
         if result.is_ok() {
@@ -345,30 +749,163 @@ mod questions_tests {
 
     #[sqlx::test]
     async fn create_question_should_succeed(pool: PgPool) -> Result<(), String> {
+        let mut uow = Autocommit::new(pool.clone());
         let doa = QuestionsDaoImpl::new(pool);
 
         let result = doa
-            .create_question(Question {
-                title: "test title".to_owned(),
-                description: "test description".to_owned(),
-            })
+            .create_question(
+                &mut uow,
+                Question {
+                    title: "test title".to_owned(),
+                    description: "test description".to_owned(),
+                },
+                "user".to_owned(),
+            )
             .await
             .map_err(|e| format!("{:?}", e))?;
 
-        if result.title != "test title".to_owned()
-            || result.description != "test description".to_owned()
-        {
+        if result.title != "test title" || result.description != "test description" {
             return Err("Incorrect title or description".to_owned());
         }
 
         Ok(())
     }
 
+    #[sqlx::test]
+    async fn get_questions_page_should_skip_offset(pool: PgPool) -> Result<(), String> {
+        let mut uow = Autocommit::new(pool.clone());
+        let doa = QuestionsDaoImpl::new(pool);
+
+        for title in ["first title", "second title", "third title"] {
+            doa.create_question(
+                &mut uow,
+                Question {
+                    title: title.to_owned(),
+                    description: "test description".to_owned(),
+                },
+                "user".to_owned(),
+            )
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+        }
+
+        let results = doa
+            .get_questions_page(1, 5)
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+
+        let titles = results.iter().map(|q| q.title.as_str()).collect::<Vec<_>>();
+        if titles != vec!["second title", "third title"] {
+            return Err(format!("Incorrect page returned: {:?}", titles));
+        }
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn get_questions_by_uuids_should_fail_with_malformed_uuid(
+        pool: PgPool,
+    ) -> Result<(), String> {
+        let doa = QuestionsDaoImpl::new(pool);
+
+        let result = doa
+            .get_questions_by_uuids(vec!["malformed".to_owned()])
+            .await;
+
+        if let Err(DBError::InvalidUUID(_)) = result {
+            Ok(())
+        } else {
+            Err(format!(
+                "Expected an invalid UUID error but got the following result: {:?}",
+                result
+            ))
+        }
+    }
+
+    #[sqlx::test]
+    async fn update_question_should_render_new_description(pool: PgPool) -> Result<(), String> {
+        let mut uow = Autocommit::new(pool.clone());
+        let doa = QuestionsDaoImpl::new(pool);
+
+        let result = doa
+            .create_question(
+                &mut uow,
+                Question {
+                    title: "test title".to_owned(),
+                    description: "*test* description".to_owned(),
+                },
+                "user".to_owned(),
+            )
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+
+        if result.description_html != "<p><em>test</em> description</p>\n" {
+            return Err(format!("Incorrect HTML: {}", result.description_html));
+        }
+
+        let result = doa
+            .update_question(
+                &mut uow,
+                result.question_uuid,
+                Question {
+                    title: "new title".to_owned(),
+                    description: "**new** description".to_owned(),
+                },
+            )
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+
+        if result.title != "new title"
+            || result.description_html != "<p><strong>new</strong> description</p>\n"
+        {
+            return Err(format!("Question was not updated: {:?}", result));
+        }
+
+        let results = doa.get_questions().await.map_err(|e| format!("{:?}", e))?;
+
+        if results != vec![result] {
+            return Err("Stale question returned after update".to_owned());
+        }
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn update_question_should_fail_with_non_existent_uuid(
+        pool: PgPool,
+    ) -> Result<(), String> {
+        let mut uow = Autocommit::new(pool.clone());
+        let doa = QuestionsDaoImpl::new(pool);
+
+        let result = doa
+            .update_question(
+                &mut uow,
+                "b068cd2f-edac-479e-98f1-c5f91008dcbd".to_owned(),
+                Question {
+                    title: "test title".to_owned(),
+                    description: "test description".to_owned(),
+                },
+            )
+            .await;
+
+        if let Err(DBError::InvalidUUID(_)) = result {
+            Ok(())
+        } else {
+            Err(format!(
+                "Expected an invalid UUID error but got the following result: {:?}",
+                result
+            ))
+        }
+    }
+
     #[sqlx::test]
     async fn delete_question_should_fail_with_malformed_uuid(pool: PgPool) -> Result<(), String> {
+        let mut uow = Autocommit::new(pool.clone());
         let doa = QuestionsDaoImpl::new(pool);
 
-        let result = doa.delete_question("malformed".to_owned()).await;
+        let result = doa
+            .delete_question(&mut uow, "malformed".to_owned(), "user".to_owned())
+            .await;
 
         if result.is_ok() {
             return Err(format!(
@@ -391,12 +928,17 @@ mod questions_tests {
     async fn delete_question_should_fail_if_database_error_occurs(
         pool: PgPool,
     ) -> Result<(), String> {
+        let mut uow = Autocommit::new(pool.clone());
         let doa = QuestionsDaoImpl::new(pool.clone());
 
         pool.close().await;
 
         let result = doa
-            .delete_question("a22abcd2-22ab-2222-a22b-2abc2a2b22cc".to_owned())
+            .delete_question(
+                &mut uow,
+                "a22abcd2-22ab-2222-a22b-2abc2a2b22cc".to_owned(),
+                "user".to_owned(),
+            )
             .await;
 
         if result.is_ok() {
@@ -418,23 +960,28 @@ mod questions_tests {
 
     #[sqlx::test]
     async fn delete_question_should_succeed(pool: PgPool) -> Result<(), String> {
+        let mut uow = Autocommit::new(pool.clone());
         let doa = QuestionsDaoImpl::new(pool);
 
         let result = doa
-            .create_question(Question {
-                title: "test title".to_owned(),
-                description: "test description".to_owned(),
-            })
+            .create_question(
+                &mut uow,
+                Question {
+                    title: "test title".to_owned(),
+                    description: "test description".to_owned(),
+                },
+                "user".to_owned(),
+            )
             .await
             .map_err(|e| format!("{:?}", e))?;
 
-        doa.delete_question(result.question_uuid)
+        doa.delete_question(&mut uow, result.question_uuid, "user".to_owned())
             .await
             .map_err(|e| format!("{:?}", e))?;
 
         let results = doa.get_questions().await.map_err(|e| format!("{:?}", e))?;
 
-        if results.len() != 0 {
+        if !results.is_empty() {
             return Err("Question was not deleted".to_owned());
         }
 
@@ -442,14 +989,85 @@ mod questions_tests {
     }
 
     #[sqlx::test]
-    async fn get_questions_should_fail_if_database_error_occurs(
+    async fn restore_question_should_fail_with_non_existent_uuid(
         pool: PgPool,
     ) -> Result<(), String> {
-        let doa = QuestionsDaoImpl::new(pool.clone());
-
-        pool.close().await;
+        let mut uow = Autocommit::new(pool.clone());
+        let doa = QuestionsDaoImpl::new(pool);
 
-        let result = doa.get_questions().await;
+        let result = doa
+            .restore_question(&mut uow, "a22abcd2-22ab-2222-a22b-2abc2a2b22cc".to_owned())
+            .await;
+
+        if let Err(DBError::InvalidUUID(_)) = result {
+            Ok(())
+        } else {
+            Err(format!(
+                "Expected an invalid UUID error but got the following result: {:?}",
+                result
+            ))
+        }
+    }
+
+    #[sqlx::test]
+    async fn restore_question_should_succeed(pool: PgPool) -> Result<(), String> {
+        let mut uow = Autocommit::new(pool.clone());
+        let doa = QuestionsDaoImpl::new(pool.clone());
+
+        let result = doa
+            .create_question(
+                &mut uow,
+                Question {
+                    title: "test title".to_owned(),
+                    description: "test description".to_owned(),
+                },
+                "user".to_owned(),
+            )
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+
+        doa.delete_question(&mut uow, result.question_uuid.clone(), "user".to_owned())
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+
+        let deleted_by: Option<String> =
+            sqlx::query_scalar("SELECT deleted_by FROM questions WHERE question_uuid = $1::uuid")
+                .bind(&result.question_uuid)
+                .fetch_one(&pool)
+                .await
+                .map_err(|e| format!("{:?}", e))?;
+
+        if deleted_by.as_deref() != Some("user") {
+            return Err("Question was not soft deleted".to_owned());
+        }
+
+        let restored = doa
+            .restore_question(&mut uow, result.question_uuid.clone())
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+
+        if restored != result {
+            return Err("Incorrect question restored".to_owned());
+        }
+
+        let results = doa.get_questions().await.map_err(|e| format!("{:?}", e))?;
+
+        if results.len() != 1 {
+            return Err("Restored question is not listed".to_owned());
+        }
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn get_questions_should_fail_if_database_error_occurs(
+        pool: PgPool,
+    ) -> Result<(), String> {
+        let doa = QuestionsDaoImpl::new(pool.clone());
+
+        pool.close().await;
+
+        let result = doa.get_questions().await;
 
         if result.is_ok() {
             return Err(format!(
@@ -470,13 +1088,18 @@ mod questions_tests {
 
     #[sqlx::test]
     async fn get_questions_should_succeed(pool: PgPool) -> Result<(), String> {
+        let mut uow = Autocommit::new(pool.clone());
         let doa = QuestionsDaoImpl::new(pool);
 
         let result = doa
-            .create_question(Question {
-                title: "test title".to_owned(),
-                description: "test description".to_owned(),
-            })
+            .create_question(
+                &mut uow,
+                Question {
+                    title: "test title".to_owned(),
+                    description: "test description".to_owned(),
+                },
+                "user".to_owned(),
+            )
             .await
             .map_err(|e| format!("{:?}", e))?;
 
@@ -486,10 +1109,2711 @@ mod questions_tests {
             return Err("Incorrect number of results returned.".to_owned());
         }
 
-        if results.get(0).unwrap().question_uuid != result.question_uuid {
+        if results.first().unwrap().question_uuid != result.question_uuid {
             return Err("Incorrect question returned.".to_owned());
         }
 
         Ok(())
     }
+
+    async fn ask(
+        doa: &QuestionsDaoImpl,
+        uow: &mut Autocommit,
+        title: &str,
+        description: &str,
+    ) -> Result<String, String> {
+        doa.create_question(
+            uow,
+            Question {
+                title: title.to_owned(),
+                description: description.to_owned(),
+            },
+            "user".to_owned(),
+        )
+        .await
+        .map(|question| question.question_uuid)
+        .map_err(|e| format!("{:?}", e))
+    }
+
+    #[sqlx::test]
+    async fn get_related_questions_should_rank_similar_questions(
+        pool: PgPool,
+    ) -> Result<(), String> {
+        let mut uow = Autocommit::new(pool.clone());
+        let doa = QuestionsDaoImpl::new(pool);
+
+        let question = ask(
+            &doa,
+            &mut uow,
+            "How to sort a vector in Rust",
+            "I have a Vec<i32>",
+        )
+        .await?;
+        let closest = ask(
+            &doa,
+            &mut uow,
+            "How to sort a vector in Rust?",
+            "I have a Vec<u8>",
+        )
+        .await?;
+        let close = ask(&doa, &mut uow, "Sort a vector of structs", "By a field").await?;
+        ask(
+            &doa,
+            &mut uow,
+            "Postgres connection refused",
+            "Docker compose setup",
+        )
+        .await?;
+
+        let results = doa
+            .get_related_questions(question, 10)
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+
+        let uuids: Vec<String> = results.into_iter().map(|q| q.question_uuid).collect();
+        if uuids != vec![closest, close] {
+            return Err(format!("Unexpected related questions: {:?}", uuids));
+        }
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn get_related_questions_should_fail_with_non_existent_uuid(
+        pool: PgPool,
+    ) -> Result<(), String> {
+        let doa = QuestionsDaoImpl::new(pool);
+
+        let result = doa
+            .get_related_questions("b068cd2f-edac-479e-98f1-c5f91008dcbd".to_owned(), 10)
+            .await;
+
+        if let Err(DBError::InvalidUUID(_)) = result {
+            Ok(())
+        } else {
+            Err(format!(
+                "Expected an invalid UUID error but got the following result: {:?}",
+                result
+            ))
+        }
+    }
+
+    #[sqlx::test]
+    async fn get_similar_questions_should_match_drafts(pool: PgPool) -> Result<(), String> {
+        let mut uow = Autocommit::new(pool.clone());
+        let doa = QuestionsDaoImpl::new(pool);
+
+        let question = ask(
+            &doa,
+            &mut uow,
+            "How to sort a vector in Rust",
+            "I have a Vec<i32>",
+        )
+        .await?;
+        ask(
+            &doa,
+            &mut uow,
+            "Postgres connection refused",
+            "Docker compose setup",
+        )
+        .await?;
+
+        let results = doa
+            .get_similar_questions(
+                Question {
+                    title: "Sorting a vector in Rust".to_owned(),
+                    description: "".to_owned(),
+                },
+                10,
+            )
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+
+        if results.len() != 1 || results[0].question_uuid != question {
+            return Err(format!("Unexpected similar questions: {:?}", results));
+        }
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn close_as_duplicate_should_point_to_original(pool: PgPool) -> Result<(), String> {
+        let mut uow = Autocommit::new(pool.clone());
+        let doa = QuestionsDaoImpl::new(pool);
+
+        let original = ask(&doa, &mut uow, "How to sort a vector in Rust", "").await?;
+        let duplicate = ask(&doa, &mut uow, "How to sort a vector in Rust?", "").await?;
+        let second_duplicate = ask(&doa, &mut uow, "Sorting vectors in Rust", "").await?;
+
+        let result = doa
+            .close_as_duplicate(
+                &mut uow,
+                duplicate.clone(),
+                original.clone(),
+                "user".to_owned(),
+            )
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+
+        if result.duplicate_of.as_ref() != Some(&original) {
+            return Err(format!("Question was not closed: {:?}", result));
+        }
+
+        // Closing as a duplicate of a duplicate points to the original
+        let result = doa
+            .close_as_duplicate(&mut uow, second_duplicate, duplicate, "user".to_owned())
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+
+        if result.duplicate_of.as_ref() != Some(&original) {
+            return Err(format!(
+                "Question does not point to the original: {:?}",
+                result
+            ));
+        }
+
+        // Duplicates are not suggested as related questions
+        let results = doa
+            .get_related_questions(original.clone(), 10)
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+
+        if !results.is_empty() {
+            return Err(format!("Duplicates suggested as related: {:?}", results));
+        }
+
+        // A question cannot be a duplicate of itself
+        let result = doa
+            .close_as_duplicate(&mut uow, original.clone(), original, "user".to_owned())
+            .await;
+
+        if let Err(DBError::InvalidUUID(_)) = result {
+            Ok(())
+        } else {
+            Err(format!(
+                "Expected an invalid UUID error but got the following result: {:?}",
+                result
+            ))
+        }
+    }
+
+    #[sqlx::test]
+    async fn close_as_duplicate_should_not_form_cycles(pool: PgPool) -> Result<(), String> {
+        let mut uow = Autocommit::new(pool.clone());
+        let doa = QuestionsDaoImpl::new(pool);
+
+        let a = ask(&doa, &mut uow, "A", "").await?;
+        let b = ask(&doa, &mut uow, "B", "").await?;
+        let c = ask(&doa, &mut uow, "C", "").await?;
+
+        doa.close_as_duplicate(&mut uow, c.clone(), a.clone(), "user".to_owned())
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+        doa.close_as_duplicate(&mut uow, a.clone(), b.clone(), "user".to_owned())
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+
+        // The duplicate of A follows it to B
+        let result = doa
+            .get_question(c.clone())
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+        if result.duplicate_of.as_ref() != Some(&b) {
+            return Err(format!("Duplicate was not repointed: {:?}", result));
+        }
+
+        // C resolves to B, which cannot be a duplicate of itself
+        let result = doa
+            .close_as_duplicate(&mut uow, b.clone(), c, "user".to_owned())
+            .await;
+        if !matches!(result, Err(DBError::InvalidUUID(_))) {
+            return Err(format!(
+                "Expected an invalid UUID error but got the following result: {:?}",
+                result
+            ));
+        }
+
+        let result = doa.get_question(b).await.map_err(|e| format!("{:?}", e))?;
+        if result.duplicate_of.is_some() {
+            return Err(format!("Original was closed: {:?}", result));
+        }
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn add_views_should_accumulate_view_counts(pool: PgPool) -> Result<(), String> {
+        let mut uow = Autocommit::new(pool.clone());
+        let doa = QuestionsDaoImpl::new(pool.clone());
+
+        let question = ask(&doa, &mut uow, "title", "description").await?;
+
+        doa.add_views(HashMap::from([(question.clone(), 2)]))
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+        doa.add_views(HashMap::from([(question.clone(), 3)]))
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+
+        let result = doa
+            .get_question(question)
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+        if result.view_count != 5 {
+            return Err(format!("Unexpected view count: {}", result.view_count));
+        }
+
+        // Both flushes land in the bucket of the current hour
+        let buckets: Vec<i64> = sqlx::query_scalar("SELECT views FROM question_views")
+            .fetch_all(&pool)
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+        if buckets != vec![5] {
+            return Err(format!("Unexpected view buckets: {:?}", buckets));
+        }
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn get_trending_questions_should_rank_by_decayed_activity(
+        pool: PgPool,
+    ) -> Result<(), String> {
+        let mut uow = Autocommit::new(pool.clone());
+        let doa = QuestionsDaoImpl::new(pool.clone());
+        let answers_doa = AnswersDaoImpl::new(pool.clone());
+
+        let viewed = ask(&doa, &mut uow, "Viewed", "").await?;
+        let answered = ask(&doa, &mut uow, "Answered", "").await?;
+        let viewed_long_ago = ask(&doa, &mut uow, "Viewed long ago", "").await?;
+        ask(&doa, &mut uow, "Ignored", "").await?;
+
+        doa.add_views(HashMap::from([(viewed.clone(), 20)]))
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+        answers_doa
+            .create_answer(
+                &mut uow,
+                Answer {
+                    question_uuid: answered.clone(),
+                    content: "content".to_owned(),
+                },
+                "user".to_owned(),
+            )
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+        // Two half-lives ago, 30 views weigh less than a fresh answer
+        sqlx::query(
+            r"
+            INSERT INTO question_views ( question_uuid, hour, views )
+            VALUES ( $1::uuid, CURRENT_TIMESTAMP - INTERVAL '48 hours', 30 )
+            ",
+        )
+        .bind(&viewed_long_ago)
+        .execute(&pool)
+        .await
+        .map_err(|e| format!("{:?}", e))?;
+
+        let results = doa
+            .get_trending_questions(10)
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+
+        let uuids: Vec<String> = results.into_iter().map(|q| q.question_uuid).collect();
+        if uuids != vec![viewed, answered, viewed_long_ago] {
+            return Err(format!("Unexpected trending questions: {:?}", uuids));
+        }
+
+        Ok(())
+    }
+}
+
+mod webhooks_tests {
+    use std::time::Duration;
+
+    use sqlx::PgPool;
+
+    use crate::{
+        models::{Answer, DBError, Question, Webhook, WebhookEvent},
+        persistance::{
+            answers_dao::{AnswersDao, AnswersDaoImpl},
+            questions_dao::{QuestionsDao, QuestionsDaoImpl},
+            unit_of_work::Autocommit,
+            webhooks_dao::{WebhooksDao, WebhooksDaoImpl},
+        },
+    };
+
+    #[sqlx::test]
+    async fn create_webhook_should_fail_if_database_error_occurs(
+        pool: PgPool,
+    ) -> Result<(), String> {
+        let mut uow = Autocommit::new(pool.clone());
+        let doa = WebhooksDaoImpl::new(pool.clone());
+
+        pool.close().await;
+
+        let result = doa
+            .create_webhook(
+                &mut uow,
+                Webhook {
+                    url: "http://localhost/hook".to_owned(),
+                    events: vec![WebhookEvent::QuestionCreated],
+                },
+            )
+            .await;
+
+        if let Err(DBError::Other(_)) = result {
+            Ok(())
+        } else {
+            Err(format!(
+                "Expected an Other error but got the following result: {:?}",
+                result
+            ))
+        }
+    }
+
+    #[sqlx::test]
+    async fn create_webhook_should_succeed(pool: PgPool) -> Result<(), String> {
+        let mut uow = Autocommit::new(pool.clone());
+        let doa = WebhooksDaoImpl::new(pool);
+
+        let result = doa
+            .create_webhook(
+                &mut uow,
+                Webhook {
+                    url: "http://localhost/hook".to_owned(),
+                    events: vec![WebhookEvent::QuestionCreated, WebhookEvent::AnswerCreated],
+                },
+            )
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+
+        if result.url != "http://localhost/hook"
+            || result.events != vec!["question.created", "answer.created"]
+        {
+            return Err("Incorrect url or events".to_owned());
+        }
+
+        if result.secret.is_empty() {
+            return Err("Webhook secret was not generated".to_owned());
+        }
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn delete_webhook_should_drop_its_pending_deliveries(pool: PgPool) -> Result<(), String> {
+        let mut uow = Autocommit::new(pool.clone());
+        let doa = WebhooksDaoImpl::new(pool.clone());
+
+        let webhook = doa
+            .create_webhook(
+                &mut uow,
+                Webhook {
+                    url: "http://localhost/hook".to_owned(),
+                    events: vec![WebhookEvent::QuestionCreated],
+                },
+            )
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+        QuestionsDaoImpl::new(pool.clone())
+            .create_question(
+                &mut uow,
+                Question {
+                    title: "test title".to_owned(),
+                    description: "test description".to_owned(),
+                },
+                "user".to_owned(),
+            )
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+
+        let deleted = doa
+            .delete_webhook(&mut uow, webhook.webhook_uuid.clone())
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+        if deleted.map(|webhook| webhook.webhook_uuid) != Some(webhook.webhook_uuid.clone()) {
+            return Err("Webhook was not deleted".to_owned());
+        }
+
+        let deliveries = doa
+            .get_due_deliveries(10, Duration::from_secs(60))
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+        if !deliveries.is_empty() {
+            return Err("Deliveries of a deleted webhook are still pending".to_owned());
+        }
+
+        // Deleting again changes nothing
+        let deleted = doa
+            .delete_webhook(&mut uow, webhook.webhook_uuid)
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+        if deleted.is_some() {
+            return Err("Webhook was deleted twice".to_owned());
+        }
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn created_posts_should_only_be_enqueued_for_subscribers(
+        pool: PgPool,
+    ) -> Result<(), String> {
+        let mut uow = Autocommit::new(pool.clone());
+        let webhooks_doa = WebhooksDaoImpl::new(pool.clone());
+        let question_doa = QuestionsDaoImpl::new(pool.clone());
+        let answer_doa = AnswersDaoImpl::new(pool.clone());
+
+        webhooks_doa
+            .create_webhook(
+                &mut uow,
+                Webhook {
+                    url: "http://localhost/answers".to_owned(),
+                    events: vec![WebhookEvent::AnswerCreated],
+                },
+            )
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+
+        let question = question_doa
+            .create_question(
+                &mut uow,
+                Question {
+                    title: "test title".to_owned(),
+                    description: "test description".to_owned(),
+                },
+                "user".to_owned(),
+            )
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+
+        let results = webhooks_doa
+            .get_due_deliveries(10, Duration::from_secs(60))
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+
+        if !results.is_empty() {
+            return Err("Question event was delivered to an answers-only webhook".to_owned());
+        }
+
+        let answer = answer_doa
+            .create_answer(
+                &mut uow,
+                Answer {
+                    question_uuid: question.question_uuid,
+                    content: "test content".to_owned(),
+                },
+                "user".to_owned(),
+            )
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+
+        let results = webhooks_doa
+            .get_due_deliveries(10, Duration::from_secs(60))
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+
+        if results.len() != 1 {
+            return Err("Incorrect number of deliveries enqueued.".to_owned());
+        }
+
+        let delivery = results.first().unwrap();
+        if delivery.event != "answer.created" || !delivery.payload.contains(&answer.answer_uuid) {
+            return Err("Incorrect delivery enqueued.".to_owned());
+        }
+
+        // Claimed deliveries are not handed out twice during their lease
+        let results = webhooks_doa
+            .get_due_deliveries(10, Duration::from_secs(60))
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+
+        if !results.is_empty() {
+            return Err("Delivery was claimed twice".to_owned());
+        }
+
+        // Claims whose lease expired are handed out again
+        sqlx::query("UPDATE webhook_deliveries SET next_attempt_at = CURRENT_TIMESTAMP")
+            .execute(&pool)
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+        let results = webhooks_doa
+            .get_due_deliveries(10, Duration::from_secs(60))
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+
+        if results.len() != 1 {
+            return Err("Expired claim was not handed out again".to_owned());
+        }
+
+        Ok(())
+    }
+}
+
+mod flags_tests {
+    use sqlx::PgPool;
+
+    use crate::{
+        models::{DBError, Flag, FlagResolution, PostKind, Question},
+        persistance::{
+            flags_dao::{FlagsDao, FlagsDaoImpl},
+            questions_dao::{QuestionsDao, QuestionsDaoImpl},
+            unit_of_work::Autocommit,
+        },
+    };
+
+    #[sqlx::test]
+    async fn create_flag_should_fail_with_non_existent_uuid(pool: PgPool) -> Result<(), String> {
+        let mut uow = Autocommit::new(pool.clone());
+        let doa = FlagsDaoImpl::new(pool);
+
+        let result = doa
+            .create_flag(
+                &mut uow,
+                Flag {
+                    post_kind: PostKind::Answer,
+                    post_uuid: "a22abcd2-22ab-2222-a22b-2abc2a2b22cc".to_owned(),
+                    reason: "spam".to_owned(),
+                },
+                "user".to_owned(),
+            )
+            .await;
+
+        if let Err(DBError::InvalidUUID(_)) = result {
+            Ok(())
+        } else {
+            Err(format!(
+                "Expected an invalid UUID error but got the following result: {:?}",
+                result
+            ))
+        }
+    }
+
+    #[sqlx::test]
+    async fn flags_should_be_queued_until_resolved(pool: PgPool) -> Result<(), String> {
+        let mut uow = Autocommit::new(pool.clone());
+        let question_doa = QuestionsDaoImpl::new(pool.clone());
+        let doa = FlagsDaoImpl::new(pool);
+
+        let question = question_doa
+            .create_question(
+                &mut uow,
+                Question {
+                    title: "test title".to_owned(),
+                    description: "test description".to_owned(),
+                },
+                "user".to_owned(),
+            )
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+
+        let flag = doa
+            .create_flag(
+                &mut uow,
+                Flag {
+                    post_kind: PostKind::Question,
+                    post_uuid: question.question_uuid.clone(),
+                    reason: "spam".to_owned(),
+                },
+                "user".to_owned(),
+            )
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+
+        if flag.post_kind != PostKind::Question || flag.post_uuid != question.question_uuid {
+            return Err("Incorrect flagged post".to_owned());
+        }
+
+        let results = doa.get_open_flags().await.map_err(|e| format!("{:?}", e))?;
+
+        if results != vec![flag.clone()] {
+            return Err("Flag is not in the moderation queue".to_owned());
+        }
+
+        let resolved = doa
+            .resolve_flag(
+                &mut uow,
+                flag.flag_uuid.clone(),
+                FlagResolution::Dismiss,
+                "admin".to_owned(),
+            )
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+
+        if resolved.status != "resolved" || resolved.resolution.as_deref() != Some("dismiss") {
+            return Err("Flag was not resolved".to_owned());
+        }
+
+        let results = doa.get_open_flags().await.map_err(|e| format!("{:?}", e))?;
+
+        if !results.is_empty() {
+            return Err("Resolved flag is still in the moderation queue".to_owned());
+        }
+
+        // A flag can only be resolved once
+        let result = doa
+            .resolve_flag(
+                &mut uow,
+                flag.flag_uuid,
+                FlagResolution::Dismiss,
+                "admin".to_owned(),
+            )
+            .await;
+
+        if let Err(DBError::InvalidUUID(_)) = result {
+            Ok(())
+        } else {
+            Err(format!(
+                "Expected an invalid UUID error but got the following result: {:?}",
+                result
+            ))
+        }
+    }
+}
+
+mod reputation_tests {
+    use sqlx::PgPool;
+
+    use crate::{
+        models::{Answer, Question, UserProfile, ANONYMOUS},
+        persistance::{
+            answers_dao::{AnswersDao, AnswersDaoImpl},
+            questions_dao::{QuestionsDao, QuestionsDaoImpl},
+            reputation_dao::{ReputationDao, ReputationDaoImpl},
+            unit_of_work::Autocommit,
+        },
+    };
+
+    #[sqlx::test]
+    async fn get_reputation_should_default_to_zero(pool: PgPool) -> Result<(), String> {
+        let doa = ReputationDaoImpl::new(pool);
+
+        let result = doa
+            .get_reputation("user".to_owned())
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+
+        if result != 0 {
+            return Err(format!("Expected no reputation but got {result}"));
+        }
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn posting_should_earn_reputation(pool: PgPool) -> Result<(), String> {
+        let mut uow = Autocommit::new(pool.clone());
+        let question_doa = QuestionsDaoImpl::new(pool.clone());
+        let answer_doa = AnswersDaoImpl::new(pool.clone());
+        let doa = ReputationDaoImpl::new(pool);
+
+        let question = question_doa
+            .create_question(
+                &mut uow,
+                Question {
+                    title: "test title".to_owned(),
+                    description: "test description".to_owned(),
+                },
+                "user".to_owned(),
+            )
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+
+        answer_doa
+            .create_answer(
+                &mut uow,
+                Answer {
+                    question_uuid: question.question_uuid,
+                    content: "test content".to_owned(),
+                },
+                "user".to_owned(),
+            )
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+
+        let result = doa
+            .get_user_profile("user".to_owned())
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+
+        if result
+            != (UserProfile {
+                user_id: "user".to_owned(),
+                reputation: 15,
+                question_count: 1,
+                answer_count: 1,
+            })
+        {
+            return Err(format!("Incorrect user profile: {:?}", result));
+        }
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn anonymous_posts_should_not_earn_reputation(pool: PgPool) -> Result<(), String> {
+        let mut uow = Autocommit::new(pool.clone());
+        let question_doa = QuestionsDaoImpl::new(pool.clone());
+        let doa = ReputationDaoImpl::new(pool);
+
+        question_doa
+            .create_question(
+                &mut uow,
+                Question {
+                    title: "test title".to_owned(),
+                    description: "test description".to_owned(),
+                },
+                ANONYMOUS.to_owned(),
+            )
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+
+        let result = doa
+            .get_reputation(ANONYMOUS.to_owned())
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+
+        if result != 0 {
+            return Err(format!("Expected no reputation but got {result}"));
+        }
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn record_answer_deleted_by_moderator_should_penalise_author(
+        pool: PgPool,
+    ) -> Result<(), String> {
+        let mut uow = Autocommit::new(pool.clone());
+        let question_doa = QuestionsDaoImpl::new(pool.clone());
+        let answer_doa = AnswersDaoImpl::new(pool.clone());
+        let doa = ReputationDaoImpl::new(pool);
+
+        let question = question_doa
+            .create_question(
+                &mut uow,
+                Question {
+                    title: "test title".to_owned(),
+                    description: "test description".to_owned(),
+                },
+                "asker".to_owned(),
+            )
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+
+        let answer = answer_doa
+            .create_answer(
+                &mut uow,
+                Answer {
+                    question_uuid: question.question_uuid,
+                    content: "test content".to_owned(),
+                },
+                "user".to_owned(),
+            )
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+
+        doa.record_answer_deleted_by_moderator(&mut uow, answer.answer_uuid)
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+
+        let result = doa
+            .get_reputation("user".to_owned())
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+
+        if result != -5 {
+            return Err(format!("Expected a reputation of -5 but got {result}"));
+        }
+
+        Ok(())
+    }
+}
+
+mod follows_tests {
+    use sqlx::PgPool;
+
+    use crate::{
+        models::{Answer, DBError, Question},
+        persistance::{
+            answers_dao::{AnswersDao, AnswersDaoImpl},
+            follows_dao::{FollowsDao, FollowsDaoImpl},
+            questions_dao::{QuestionsDao, QuestionsDaoImpl},
+            unit_of_work::Autocommit,
+        },
+    };
+
+    async fn ask(pool: &PgPool, uow: &mut Autocommit, author: &str) -> Result<String, String> {
+        QuestionsDaoImpl::new(pool.clone())
+            .create_question(
+                uow,
+                Question {
+                    title: "title".to_owned(),
+                    description: "description".to_owned(),
+                },
+                author.to_owned(),
+            )
+            .await
+            .map(|question| question.question_uuid)
+            .map_err(|e| format!("{:?}", e))
+    }
+
+    async fn answer(
+        pool: &PgPool,
+        uow: &mut Autocommit,
+        question_uuid: &str,
+        author: &str,
+    ) -> Result<String, String> {
+        AnswersDaoImpl::new(pool.clone())
+            .create_answer(
+                uow,
+                Answer {
+                    question_uuid: question_uuid.to_owned(),
+                    content: "content".to_owned(),
+                },
+                author.to_owned(),
+            )
+            .await
+            .map(|answer| answer.answer_uuid)
+            .map_err(|e| format!("{:?}", e))
+    }
+
+    #[sqlx::test]
+    async fn follow_question_should_fail_with_non_existent_uuid(
+        pool: PgPool,
+    ) -> Result<(), String> {
+        let mut uow = Autocommit::new(pool.clone());
+        let doa = FollowsDaoImpl::new(pool);
+
+        let result = doa
+            .follow_question(
+                &mut uow,
+                "toto".to_owned(),
+                "b068cd2f-edac-479e-98f1-c5f91008dcbd".to_owned(),
+            )
+            .await;
+
+        if let Err(DBError::InvalidUUID(_)) = result {
+            Ok(())
+        } else {
+            Err(format!(
+                "Expected an invalid UUID error but got the following result: {:?}",
+                result
+            ))
+        }
+    }
+
+    #[sqlx::test]
+    async fn answers_should_reach_followers_until_read(pool: PgPool) -> Result<(), String> {
+        let mut uow = Autocommit::new(pool.clone());
+        let doa = FollowsDaoImpl::new(pool.clone());
+
+        let question = ask(&pool, &mut uow, "author").await?;
+        for user in ["toto", "author"] {
+            // Following twice is not an error
+            for _ in 0..2 {
+                doa.follow_question(&mut uow, user.to_owned(), question.clone())
+                    .await
+                    .map_err(|e| format!("{:?}", e))?;
+            }
+        }
+
+        let first = answer(&pool, &mut uow, &question, "author").await?;
+        let second = answer(&pool, &mut uow, &question, "titi").await?;
+
+        let inbox = doa
+            .get_inbox("toto".to_owned())
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+        let answers: Vec<&str> = inbox.iter().map(|i| i.answer_uuid.as_str()).collect();
+        if answers != vec![first.as_str(), second.as_str()] {
+            return Err(format!("Unexpected inbox of a follower: {:?}", inbox));
+        }
+
+        // Authors are not notified of their own answers, non followers are not notified
+        let author_inbox = doa
+            .get_inbox("author".to_owned())
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+        if author_inbox.len() != 1 || author_inbox[0].answer_uuid != second {
+            return Err(format!(
+                "Unexpected inbox of the author: {:?}",
+                author_inbox
+            ));
+        }
+        let titi_inbox = doa
+            .get_inbox("titi".to_owned())
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+        if !titi_inbox.is_empty() {
+            return Err(format!(
+                "Unexpected inbox of a non follower: {:?}",
+                titi_inbox
+            ));
+        }
+
+        // Items of other users cannot be marked as read
+        let marked = doa
+            .mark_inbox_read(
+                &mut uow,
+                "toto".to_owned(),
+                vec![
+                    inbox[0].item_uuid.clone(),
+                    author_inbox[0].item_uuid.clone(),
+                ],
+            )
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+        if marked != 1 {
+            return Err(format!("Unexpected number of marked items: {}", marked));
+        }
+        let inbox = doa
+            .get_inbox("toto".to_owned())
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+        if inbox.len() != 1 || inbox[0].answer_uuid != second {
+            return Err(format!("Unexpected inbox after marking: {:?}", inbox));
+        }
+
+        // Unfollowed questions no longer notify
+        doa.unfollow_question(&mut uow, "toto".to_owned(), question.clone())
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+        answer(&pool, &mut uow, &question, "titi").await?;
+        let inbox = doa
+            .get_inbox("toto".to_owned())
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+        if inbox.len() != 1 {
+            return Err(format!("Unexpected inbox after unfollowing: {:?}", inbox));
+        }
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn bookmarks_should_hide_deleted_questions(pool: PgPool) -> Result<(), String> {
+        let mut uow = Autocommit::new(pool.clone());
+        let doa = FollowsDaoImpl::new(pool.clone());
+
+        let kept = ask(&pool, &mut uow, "author").await?;
+        let deleted = ask(&pool, &mut uow, "author").await?;
+        for question in [&kept, &deleted] {
+            doa.bookmark_question(&mut uow, "toto".to_owned(), question.clone())
+                .await
+                .map_err(|e| format!("{:?}", e))?;
+        }
+        QuestionsDaoImpl::new(pool.clone())
+            .delete_question(&mut uow, deleted, "author".to_owned())
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+
+        let bookmarks = doa
+            .get_bookmarks("toto".to_owned())
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+        if bookmarks.len() != 1 || bookmarks[0].question_uuid != kept {
+            return Err(format!("Unexpected bookmarks: {:?}", bookmarks));
+        }
+
+        doa.remove_bookmark(&mut uow, "toto".to_owned(), kept)
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+        let bookmarks = doa
+            .get_bookmarks("toto".to_owned())
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+        if !bookmarks.is_empty() {
+            return Err(format!(
+                "Unexpected bookmarks after removal: {:?}",
+                bookmarks
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+mod attachments_tests {
+    use std::{
+        ops::Range,
+        time::{Duration, SystemTime},
+    };
+
+    use async_trait::async_trait;
+    use sqlx::PgPool;
+
+    use crate::{
+        attachments::{sweep, SweepConfig},
+        models::{Attachment, BlobError, DBError, Question},
+        persistance::{
+            attachments_dao::{AttachmentsDao, AttachmentsDaoImpl},
+            blob_store::{BlobStore, LocalBlobStore, StoredBlob},
+            questions_dao::{QuestionsDao, QuestionsDaoImpl},
+            unit_of_work::Autocommit,
+        },
+    };
+
+    async fn ask(pool: &PgPool, uow: &mut Autocommit) -> Result<String, String> {
+        QuestionsDaoImpl::new(pool.clone())
+            .create_question(
+                uow,
+                Question {
+                    title: "title".to_owned(),
+                    description: "description".to_owned(),
+                },
+                "author".to_owned(),
+            )
+            .await
+            .map(|question| question.question_uuid)
+            .map_err(|e| format!("{:?}", e))
+    }
+
+    fn attachment(question_uuid: &str, sha256: &str) -> Attachment {
+        Attachment {
+            question_uuid: question_uuid.to_owned(),
+            file_name: "notes.txt".to_owned(),
+            content_type: "text/plain".to_owned(),
+            size: 5,
+            sha256: sha256.to_owned(),
+        }
+    }
+
+    #[sqlx::test]
+    async fn create_attachment_should_fail_with_non_existent_uuid(
+        pool: PgPool,
+    ) -> Result<(), String> {
+        let mut uow = Autocommit::new(pool.clone());
+        let doa = AttachmentsDaoImpl::new(pool);
+
+        let result = doa
+            .create_attachment(
+                &mut uow,
+                attachment("b068cd2f-edac-479e-98f1-c5f91008dcbd", &"a".repeat(64)),
+                "author".to_owned(),
+            )
+            .await;
+
+        if let Err(DBError::InvalidUUID(_)) = result {
+            Ok(())
+        } else {
+            Err(format!(
+                "Expected an invalid UUID error but got the following result: {:?}",
+                result
+            ))
+        }
+    }
+
+    #[sqlx::test]
+    async fn get_attachments_should_list_attachments_of_the_question(
+        pool: PgPool,
+    ) -> Result<(), String> {
+        let mut uow = Autocommit::new(pool.clone());
+        let doa = AttachmentsDaoImpl::new(pool.clone());
+
+        let question = ask(&pool, &mut uow).await?;
+        let other = ask(&pool, &mut uow).await?;
+
+        let created = doa
+            .create_attachment(
+                &mut uow,
+                attachment(&question, &"a".repeat(64)),
+                "author".to_owned(),
+            )
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+        doa.create_attachment(
+            &mut uow,
+            attachment(&other, &"b".repeat(64)),
+            "author".to_owned(),
+        )
+        .await
+        .map_err(|e| format!("{:?}", e))?;
+
+        let read = doa
+            .get_attachment(created.attachment_uuid.clone())
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+        if read != created || read.uploaded_by != "author" || read.size != 5 {
+            return Err(format!("Unexpected attachment: {:?}", read));
+        }
+
+        let attachments = doa
+            .get_attachments(question)
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+        if attachments != vec![created] {
+            return Err(format!("Unexpected attachments: {:?}", attachments));
+        }
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn sweep_should_delete_blobs_of_deleted_questions_only(
+        pool: PgPool,
+    ) -> Result<(), String> {
+        let mut uow = Autocommit::new(pool.clone());
+        let doa = AttachmentsDaoImpl::new(pool.clone());
+        let dir = tempfile::tempdir().map_err(|e| format!("{:?}", e))?;
+        let blob_store = LocalBlobStore::new(dir.path());
+
+        let kept = ask(&pool, &mut uow).await?;
+        let deleted = ask(&pool, &mut uow).await?;
+
+        // The same content attached to both questions is stored once
+        let shared = blob_store
+            .put(b"shared")
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+        let only_deleted = blob_store
+            .put(b"only deleted")
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+        blob_store
+            .put(b"never attached")
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+        for (question, sha256) in [
+            (&kept, &shared),
+            (&deleted, &shared),
+            (&deleted, &only_deleted),
+        ] {
+            doa.create_attachment(&mut uow, attachment(question, sha256), "author".to_owned())
+                .await
+                .map_err(|e| format!("{:?}", e))?;
+        }
+
+        QuestionsDaoImpl::new(pool.clone())
+            .delete_question(&mut uow, deleted.clone(), "author".to_owned())
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+
+        // Attachments of deleted questions are hidden but kept during the retention period
+        let attachments = doa
+            .get_attachments(deleted.clone())
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+        if !attachments.is_empty() {
+            return Err(format!("Unexpected attachments: {:?}", attachments));
+        }
+        let config = SweepConfig {
+            interval: Duration::from_secs(60),
+            retention: Duration::from_secs(60 * 60),
+            grace_period: Duration::ZERO,
+        };
+        let swept = sweep(&doa, &blob_store, &config)
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+        if swept != 1 {
+            return Err(format!(
+                "Expected the never attached blob only, got {}",
+                swept
+            ));
+        }
+
+        let config = SweepConfig {
+            retention: Duration::ZERO,
+            ..config
+        };
+        let swept = sweep(&doa, &blob_store, &config)
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+        if swept != 1 {
+            return Err(format!(
+                "Expected the blob of the deleted question, got {}",
+                swept
+            ));
+        }
+
+        let referenced = doa
+            .get_referenced_digests(vec![shared.clone(), only_deleted.clone()])
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+        if referenced != vec![shared.clone()] {
+            return Err(format!("Unexpected referenced digests: {:?}", referenced));
+        }
+        let remaining: Vec<String> = blob_store
+            .list()
+            .await
+            .map_err(|e| format!("{:?}", e))?
+            .into_iter()
+            .map(|blob| blob.digest)
+            .collect();
+        if remaining != vec![shared] {
+            return Err(format!("Unexpected remaining blobs: {:?}", remaining));
+        }
+
+        Ok(())
+    }
+    /// Stores `content` again right after listing, as an upload racing the sweeper would
+    struct StoredAgainWhileListed {
+        store: LocalBlobStore,
+        content: &'static [u8],
+    }
+
+    #[async_trait]
+    impl BlobStore for StoredAgainWhileListed {
+        async fn put(&self, content: &[u8]) -> Result<String, BlobError> {
+            self.store.put(content).await
+        }
+        async fn get(&self, digest: &str, range: Option<Range<u64>>) -> Result<Vec<u8>, BlobError> {
+            self.store.get(digest, range).await
+        }
+        async fn delete(&self, digest: &str, cutoff: SystemTime) -> Result<bool, BlobError> {
+            self.store.delete(digest, cutoff).await
+        }
+        async fn list(&self) -> Result<Vec<StoredBlob>, BlobError> {
+            let blobs = self.store.list().await?;
+            tokio::time::sleep(Duration::from_millis(1)).await;
+            self.store.put(self.content).await?;
+            Ok(blobs)
+        }
+    }
+
+    #[sqlx::test]
+    async fn sweep_should_keep_blobs_stored_again_while_sweeping(
+        pool: PgPool,
+    ) -> Result<(), String> {
+        let doa = AttachmentsDaoImpl::new(pool.clone());
+        let dir = tempfile::tempdir().map_err(|e| format!("{:?}", e))?;
+        let blob_store = StoredAgainWhileListed {
+            store: LocalBlobStore::new(dir.path()),
+            content: b"uploaded again",
+        };
+        let digest = blob_store
+            .put(b"uploaded again")
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+
+        let config = SweepConfig {
+            interval: Duration::from_secs(60),
+            retention: Duration::from_secs(60 * 60),
+            grace_period: Duration::ZERO,
+        };
+        let swept = sweep(&doa, &blob_store, &config)
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+        if swept != 0 {
+            return Err(format!("Expected no swept blob, got {}", swept));
+        }
+        blob_store
+            .get(&digest, None)
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+
+        Ok(())
+    }
+}
+
+mod audit_tests {
+    use serde_json::json;
+    use sqlx::PgPool;
+
+    use crate::{
+        models::{AuditAction, AuditEntry, AuditQuery, DBError},
+        persistance::{
+            audit_dao::{AuditDao, AuditDaoImpl},
+            unit_of_work::{Autocommit, Database, DatabaseImpl},
+        },
+    };
+
+    fn entry(actor: &str, action: AuditAction, resource_id: &str) -> AuditEntry {
+        AuditEntry {
+            actor: actor.to_owned(),
+            action,
+            resource_id: resource_id.to_owned(),
+            before: Some(json!({ "title": "before" })),
+            after: None,
+            request_id: Some("request".to_owned()),
+        }
+    }
+
+    #[sqlx::test]
+    async fn record_should_only_keep_committed_entries(pool: PgPool) -> Result<(), String> {
+        let database = DatabaseImpl::new(pool.clone());
+        let doa = AuditDaoImpl::new(pool);
+
+        let mut uow = database.begin().await.map_err(|e| format!("{:?}", e))?;
+        doa.record(
+            uow.as_mut(),
+            entry("toto", AuditAction::QuestionDeleted, "1"),
+        )
+        .await
+        .map_err(|e| format!("{:?}", e))?;
+        drop(uow);
+
+        let mut uow = database.begin().await.map_err(|e| format!("{:?}", e))?;
+        doa.record(
+            uow.as_mut(),
+            entry("titi", AuditAction::QuestionDeleted, "2"),
+        )
+        .await
+        .map_err(|e| format!("{:?}", e))?;
+        uow.commit().await.map_err(|e| format!("{:?}", e))?;
+
+        let records = doa
+            .get_audit_log(AuditQuery::default(), 10)
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+        if records.len() != 1
+            || records[0].actor != "titi"
+            || records[0].action != "question.deleted"
+            || records[0].before != Some(json!({ "title": "before" }))
+            || records[0].after.is_some()
+            || records[0].request_id.as_deref() != Some("request")
+        {
+            return Err(format!("Unexpected records: {:?}", records));
+        }
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn get_audit_log_should_filter_records(pool: PgPool) -> Result<(), String> {
+        let mut uow = Autocommit::new(pool.clone());
+        let doa = AuditDaoImpl::new(pool);
+
+        for (actor, resource_id) in [("toto", "1"), ("toto", "2"), ("titi", "1")] {
+            doa.record(
+                &mut uow,
+                entry(actor, AuditAction::QuestionUpdated, resource_id),
+            )
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+        }
+
+        let count = |query: AuditQuery| {
+            let doa = &doa;
+            async move {
+                doa.get_audit_log(query, 10)
+                    .await
+                    .map(|records| records.len())
+                    .map_err(|e| format!("{:?}", e))
+            }
+        };
+
+        let by_actor = count(AuditQuery {
+            actor: Some("toto".to_owned()),
+            ..AuditQuery::default()
+        })
+        .await?;
+        let by_resource = count(AuditQuery {
+            resource_id: Some("1".to_owned()),
+            ..AuditQuery::default()
+        })
+        .await?;
+        let by_both = count(AuditQuery {
+            actor: Some("toto".to_owned()),
+            resource_id: Some("1".to_owned()),
+            ..AuditQuery::default()
+        })
+        .await?;
+        let in_range = count(AuditQuery {
+            since: Some("2000-01-01 00:00:00".to_owned()),
+            until: Some("2999-01-01T00:00:00".to_owned()),
+            ..AuditQuery::default()
+        })
+        .await?;
+        let in_future = count(AuditQuery {
+            since: Some("2999-01-01".to_owned()),
+            ..AuditQuery::default()
+        })
+        .await?;
+        if (by_actor, by_resource, by_both, in_range, in_future) != (2, 2, 1, 3, 0) {
+            return Err(format!(
+                "Unexpected counts: {:?}",
+                (by_actor, by_resource, by_both, in_range, in_future)
+            ));
+        }
+
+        let result = doa
+            .get_audit_log(
+                AuditQuery {
+                    until: Some("not a date".to_owned()),
+                    ..AuditQuery::default()
+                },
+                10,
+            )
+            .await;
+        if let Err(DBError::InvalidUUID(_)) = result {
+            Ok(())
+        } else {
+            Err(format!(
+                "Expected an invalid UUID error but got the following result: {:?}",
+                result
+            ))
+        }
+    }
+
+    #[sqlx::test]
+    async fn audit_log_should_reject_changes(pool: PgPool) -> Result<(), String> {
+        let mut uow = Autocommit::new(pool.clone());
+        let doa = AuditDaoImpl::new(pool.clone());
+
+        doa.record(&mut uow, entry("toto", AuditAction::AnswerDeleted, "1"))
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+
+        for statement in [
+            "UPDATE audit_log SET actor = 'titi'",
+            "DELETE FROM audit_log",
+            "TRUNCATE audit_log",
+        ] {
+            if sqlx::query(statement).execute(&pool).await.is_ok() {
+                return Err(format!("{} should have been rejected", statement));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+mod spaces_tests {
+    use std::{collections::HashMap, time::Duration};
+
+    use sqlx::{types::Uuid, PgPool};
+
+    use crate::{
+        models::{
+            Answer, Attachment, DBError, Flag, FlagResolution, PostKind, Question, QuestionDetail,
+            SimilarQuestion, Space, SpaceRole, UserProfile, Webhook, WebhookEvent,
+        },
+        persistance::{
+            answers_dao::{AnswersDao, AnswersDaoImpl},
+            attachments_dao::{AttachmentsDao, AttachmentsDaoImpl},
+            flags_dao::{FlagsDao, FlagsDaoImpl},
+            follows_dao::{FollowsDao, FollowsDaoImpl},
+            questions_dao::{QuestionsDao, QuestionsDaoImpl},
+            reputation_dao::{ReputationDao, ReputationDaoImpl},
+            spaces_dao::{SpacesDao, SpacesDaoImpl, DEFAULT_SPACE_ID},
+            unit_of_work::Autocommit,
+            webhooks_dao::{WebhooksDao, WebhooksDaoImpl},
+        },
+    };
+
+    async fn create_space(pool: &PgPool, slug: &str) -> Result<Uuid, String> {
+        let space = SpacesDaoImpl::new(pool.clone())
+            .create_space(
+                &mut Autocommit::new(pool.clone()),
+                Space {
+                    slug: slug.to_owned(),
+                    name: slug.to_owned(),
+                    open: false,
+                },
+            )
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+
+        Uuid::parse_str(&space.space_id).map_err(|e| e.to_string())
+    }
+
+    fn question() -> Question {
+        Question {
+            title: "How do I reverse a linked list".to_owned(),
+            description: "In place, without recursion".to_owned(),
+        }
+    }
+
+    fn expect_invalid_uuid<T: std::fmt::Debug>(
+        what: &str,
+        result: Result<T, DBError>,
+    ) -> Result<(), String> {
+        match result {
+            Err(DBError::InvalidUUID(_)) => Ok(()),
+            result => Err(format!(
+                "Expected {what} to fail with an invalid UUID error but got: {:?}",
+                result
+            )),
+        }
+    }
+
+    #[sqlx::test]
+    async fn create_space_should_fail_with_taken_slug(pool: PgPool) -> Result<(), String> {
+        let doa = SpacesDaoImpl::new(pool.clone());
+
+        let result = doa
+            .create_space(
+                &mut Autocommit::new(pool),
+                Space {
+                    slug: "default".to_owned(),
+                    name: "Another default".to_owned(),
+                    open: true,
+                },
+            )
+            .await;
+
+        expect_invalid_uuid("create_space", result)
+    }
+
+    #[sqlx::test]
+    async fn members_should_be_added_updated_and_removed(pool: PgPool) -> Result<(), String> {
+        let mut uow = Autocommit::new(pool.clone());
+        let doa = SpacesDaoImpl::new(pool.clone());
+        let space_id = create_space(&pool, "team").await?.to_string();
+
+        let role = |user_id: &str| doa.get_role(space_id.clone(), user_id.to_owned());
+
+        if role("toto")
+            .await
+            .map_err(|e| format!("{:?}", e))?
+            .is_some()
+        {
+            return Err("A user was a member before being added".to_owned());
+        }
+
+        for expected in [SpaceRole::Reader, SpaceRole::Writer] {
+            let member = doa
+                .set_member(&mut uow, space_id.clone(), "toto".to_owned(), expected)
+                .await
+                .map_err(|e| format!("{:?}", e))?;
+            let role = role("toto").await.map_err(|e| format!("{:?}", e))?;
+            if member.role != expected || role != Some(expected) {
+                return Err(format!("Expected {:?} but got {:?}", expected, role));
+            }
+        }
+
+        if role("titi")
+            .await
+            .map_err(|e| format!("{:?}", e))?
+            .is_some()
+        {
+            return Err("Another user became a member".to_owned());
+        }
+
+        let removed = doa
+            .remove_member(&mut uow, space_id.clone(), "toto".to_owned())
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+        if removed.map(|member| member.role) != Some(SpaceRole::Writer) {
+            return Err("The removed membership was not returned".to_owned());
+        }
+        let removed = doa
+            .remove_member(&mut uow, space_id.clone(), "toto".to_owned())
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+        if removed.is_some()
+            || role("toto")
+                .await
+                .map_err(|e| format!("{:?}", e))?
+                .is_some()
+        {
+            return Err("The user was still a member once removed".to_owned());
+        }
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn questions_should_not_leak_across_spaces(pool: PgPool) -> Result<(), String> {
+        let mut uow = Autocommit::new(pool.clone());
+        let team = QuestionsDaoImpl::in_space(pool.clone(), create_space(&pool, "team").await?);
+        let default = QuestionsDaoImpl::new(pool.clone());
+
+        let secret = team
+            .create_question(&mut uow, question(), "toto".to_owned())
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+        let public = default
+            .create_question(&mut uow, question(), "toto".to_owned())
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+        team.add_views(HashMap::from([(secret.question_uuid.clone(), 5)]))
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+
+        let uuids = |questions: Vec<QuestionDetail>| {
+            questions
+                .into_iter()
+                .map(|q| q.question_uuid)
+                .collect::<Vec<_>>()
+        };
+        let similar = |questions: Vec<SimilarQuestion>| {
+            questions
+                .into_iter()
+                .map(|q| q.question_uuid)
+                .collect::<Vec<_>>()
+        };
+        let only_public = vec![public.question_uuid.clone()];
+        let e = |e: DBError| format!("{:?}", e);
+
+        let listings = [
+            (
+                "get_questions",
+                uuids(default.get_questions().await.map_err(e)?),
+            ),
+            (
+                "get_questions_page",
+                uuids(default.get_questions_page(0, 10).await.map_err(e)?),
+            ),
+            (
+                "get_questions_by_uuids",
+                uuids(
+                    default
+                        .get_questions_by_uuids(vec![
+                            secret.question_uuid.clone(),
+                            public.question_uuid.clone(),
+                        ])
+                        .await
+                        .map_err(e)?,
+                ),
+            ),
+            (
+                "get_similar_questions",
+                similar(
+                    default
+                        .get_similar_questions(question(), 10)
+                        .await
+                        .map_err(e)?,
+                ),
+            ),
+        ];
+        for (what, listed) in listings {
+            if listed != only_public {
+                return Err(format!(
+                    "{what} listed questions of another space: {:?}",
+                    listed
+                ));
+            }
+        }
+
+        let related = default
+            .get_related_questions(public.question_uuid.clone(), 10)
+            .await
+            .map_err(e)?;
+        let trending = default.get_trending_questions(10).await.map_err(e)?;
+        if !related.is_empty() || !trending.is_empty() {
+            return Err("Questions of another space were related or trending".to_owned());
+        }
+        if uuids(team.get_trending_questions(10).await.map_err(e)?)
+            != vec![secret.question_uuid.clone()]
+        {
+            return Err("Views of the space were not counted".to_owned());
+        }
+
+        expect_invalid_uuid(
+            "get_question",
+            default.get_question(secret.question_uuid.clone()).await,
+        )?;
+        expect_invalid_uuid(
+            "update_question",
+            default
+                .update_question(&mut uow, secret.question_uuid.clone(), question())
+                .await,
+        )?;
+        expect_invalid_uuid(
+            "restore_question",
+            default
+                .restore_question(&mut uow, secret.question_uuid.clone())
+                .await,
+        )?;
+        expect_invalid_uuid(
+            "close_as_duplicate",
+            default
+                .close_as_duplicate(
+                    &mut uow,
+                    public.question_uuid.clone(),
+                    secret.question_uuid.clone(),
+                    "toto".to_owned(),
+                )
+                .await,
+        )?;
+        default
+            .delete_question(&mut uow, secret.question_uuid.clone(), "toto".to_owned())
+            .await
+            .map_err(e)?;
+
+        team.get_question(secret.question_uuid.clone())
+            .await
+            .map_err(|_| "The question was deleted from another space".to_owned())?;
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn answers_should_not_leak_across_spaces(pool: PgPool) -> Result<(), String> {
+        let mut uow = Autocommit::new(pool.clone());
+        let space_id = create_space(&pool, "team").await?;
+        let team_questions = QuestionsDaoImpl::in_space(pool.clone(), space_id);
+        let team = AnswersDaoImpl::in_space(pool.clone(), space_id);
+        let default = AnswersDaoImpl::new(pool.clone());
+        let e = |e: DBError| format!("{:?}", e);
+
+        let secret = team_questions
+            .create_question(&mut uow, question(), "toto".to_owned())
+            .await
+            .map_err(e)?;
+        let answer = |question_uuid: &str| Answer {
+            question_uuid: question_uuid.to_owned(),
+            content: "Use three pointers".to_owned(),
+        };
+
+        expect_invalid_uuid(
+            "create_answer",
+            default
+                .create_answer(&mut uow, answer(&secret.question_uuid), "titi".to_owned())
+                .await,
+        )?;
+
+        let secret_answer = team
+            .create_answer(&mut uow, answer(&secret.question_uuid), "titi".to_owned())
+            .await
+            .map_err(e)?;
+
+        expect_invalid_uuid(
+            "get_answer",
+            default.get_answer(secret_answer.answer_uuid.clone()).await,
+        )?;
+        expect_invalid_uuid(
+            "update_answer",
+            default
+                .update_answer(
+                    &mut uow,
+                    secret_answer.answer_uuid.clone(),
+                    "Leaked".to_owned(),
+                )
+                .await,
+        )?;
+        let listed = default
+            .get_answers(secret.question_uuid.clone())
+            .await
+            .map_err(e)?;
+        let batched = default
+            .get_answers_of_questions(vec![secret.question_uuid.clone()])
+            .await
+            .map_err(e)?;
+        if !listed.is_empty() || !batched.is_empty() {
+            return Err("Answers of another space were listed".to_owned());
+        }
+
+        default
+            .delete_answer(
+                &mut uow,
+                secret_answer.answer_uuid.clone(),
+                "titi".to_owned(),
+            )
+            .await
+            .map_err(e)?;
+        team.get_answer(secret_answer.answer_uuid)
+            .await
+            .map_err(|_| "The answer was deleted from another space".to_owned())?;
+
+        Ok(())
+    }
+
+    fn profile_of(user_id: &str, reputation: i64, questions: i64, answers: i64) -> UserProfile {
+        UserProfile {
+            user_id: user_id.to_owned(),
+            reputation,
+            question_count: questions,
+            answer_count: answers,
+        }
+    }
+
+    #[sqlx::test]
+    async fn reputation_should_not_leak_across_spaces(pool: PgPool) -> Result<(), String> {
+        let mut uow = Autocommit::new(pool.clone());
+        let space_id = create_space(&pool, "team").await?;
+        let team = ReputationDaoImpl::in_space(pool.clone(), space_id);
+        let default = ReputationDaoImpl::new(pool.clone());
+        let e = |e: DBError| format!("{:?}", e);
+
+        let secret = QuestionsDaoImpl::in_space(pool.clone(), space_id)
+            .create_question(&mut uow, question(), "toto".to_owned())
+            .await
+            .map_err(e)?;
+        let secret_answer = AnswersDaoImpl::in_space(pool.clone(), space_id)
+            .create_answer(
+                &mut uow,
+                Answer {
+                    question_uuid: secret.question_uuid,
+                    content: "Use three pointers".to_owned(),
+                },
+                "toto".to_owned(),
+            )
+            .await
+            .map_err(e)?;
+        let profile = default
+            .get_user_profile("toto".to_owned())
+            .await
+            .map_err(e)?;
+        if profile != profile_of("toto", 0, 0, 0) {
+            return Err(format!(
+                "Posts of another space were counted: {:?}",
+                profile
+            ));
+        }
+        let profile = team.get_user_profile("toto".to_owned()).await.map_err(e)?;
+        if profile != profile_of("toto", 15, 1, 1) {
+            return Err(format!("Incorrect user profile: {:?}", profile));
+        }
+
+        // The moderator penalty is charged in the space of the answer
+        default
+            .record_answer_deleted_by_moderator(&mut uow, secret_answer.answer_uuid)
+            .await
+            .map_err(e)?;
+        let reputations = (
+            default.get_reputation("toto".to_owned()).await.map_err(e)?,
+            team.get_reputation("toto".to_owned()).await.map_err(e)?,
+        );
+        if reputations != (0, 0) {
+            return Err(format!(
+                "Expected the penalty in the team space: {:?}",
+                reputations
+            ));
+        }
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn follows_attachments_and_flags_should_not_leak_across_spaces(
+        pool: PgPool,
+    ) -> Result<(), String> {
+        let mut uow = Autocommit::new(pool.clone());
+        let space_id = create_space(&pool, "team").await?;
+        let e = |e: DBError| format!("{:?}", e);
+
+        let secret = QuestionsDaoImpl::in_space(pool.clone(), space_id)
+            .create_question(&mut uow, question(), "toto".to_owned())
+            .await
+            .map_err(e)?;
+        let secret_answer = AnswersDaoImpl::in_space(pool.clone(), space_id)
+            .create_answer(
+                &mut uow,
+                Answer {
+                    question_uuid: secret.question_uuid.clone(),
+                    content: "Use three pointers".to_owned(),
+                },
+                "titi".to_owned(),
+            )
+            .await
+            .map_err(e)?;
+
+        let team_follows = FollowsDaoImpl::in_space(pool.clone(), space_id);
+        let default_follows = FollowsDaoImpl::new(pool.clone());
+        expect_invalid_uuid(
+            "follow_question",
+            default_follows
+                .follow_question(&mut uow, "toto".to_owned(), secret.question_uuid.clone())
+                .await,
+        )?;
+        expect_invalid_uuid(
+            "bookmark_question",
+            default_follows
+                .bookmark_question(&mut uow, "toto".to_owned(), secret.question_uuid.clone())
+                .await,
+        )?;
+        team_follows
+            .bookmark_question(&mut uow, "toto".to_owned(), secret.question_uuid.clone())
+            .await
+            .map_err(e)?;
+        if !default_follows
+            .get_bookmarks("toto".to_owned())
+            .await
+            .map_err(e)?
+            .is_empty()
+        {
+            return Err("Bookmarks of another space were listed".to_owned());
+        }
+        default_follows
+            .remove_bookmark(&mut uow, "toto".to_owned(), secret.question_uuid.clone())
+            .await
+            .map_err(e)?;
+        if team_follows
+            .get_bookmarks("toto".to_owned())
+            .await
+            .map_err(e)?
+            .len()
+            != 1
+        {
+            return Err("A bookmark was removed from another space".to_owned());
+        }
+
+        let default_attachments = AttachmentsDaoImpl::new(pool.clone());
+        let attachment = Attachment {
+            question_uuid: secret.question_uuid.clone(),
+            file_name: "notes.txt".to_owned(),
+            content_type: "text/plain".to_owned(),
+            size: 5,
+            sha256: "00".repeat(32),
+        };
+        expect_invalid_uuid(
+            "create_attachment",
+            default_attachments
+                .create_attachment(&mut uow, attachment.clone(), "toto".to_owned())
+                .await,
+        )?;
+        let secret_attachment = AttachmentsDaoImpl::in_space(pool.clone(), space_id)
+            .create_attachment(&mut uow, attachment, "toto".to_owned())
+            .await
+            .map_err(e)?;
+        expect_invalid_uuid(
+            "get_attachment",
+            default_attachments
+                .get_attachment(secret_attachment.attachment_uuid)
+                .await,
+        )?;
+        if !default_attachments
+            .get_attachments(secret.question_uuid.clone())
+            .await
+            .map_err(e)?
+            .is_empty()
+        {
+            return Err("Attachments of another space were listed".to_owned());
+        }
+
+        let team_flags = FlagsDaoImpl::in_space(pool.clone(), space_id);
+        let default_flags = FlagsDaoImpl::new(pool.clone());
+        let flag = |post_kind, post_uuid: &str| Flag {
+            post_kind,
+            post_uuid: post_uuid.to_owned(),
+            reason: "spam".to_owned(),
+        };
+        expect_invalid_uuid(
+            "create_flag",
+            default_flags
+                .create_flag(
+                    &mut uow,
+                    flag(PostKind::Question, &secret.question_uuid),
+                    "titi".to_owned(),
+                )
+                .await,
+        )?;
+        let secret_flag = team_flags
+            .create_flag(
+                &mut uow,
+                flag(PostKind::Answer, &secret_answer.answer_uuid),
+                "titi".to_owned(),
+            )
+            .await
+            .map_err(e)?;
+        if !default_flags.get_open_flags().await.map_err(e)?.is_empty() {
+            return Err("Flags of another space were in the moderation queue".to_owned());
+        }
+        expect_invalid_uuid(
+            "resolve_flag",
+            default_flags
+                .resolve_flag(
+                    &mut uow,
+                    secret_flag.flag_uuid.clone(),
+                    FlagResolution::Dismiss,
+                    "moderator".to_owned(),
+                )
+                .await,
+        )?;
+        if team_flags.get_open_flags().await.map_err(e)? != vec![secret_flag] {
+            return Err("The flag was not in the moderation queue of its space".to_owned());
+        }
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn webhooks_should_only_receive_events_of_their_space(
+        pool: PgPool,
+    ) -> Result<(), String> {
+        let mut uow = Autocommit::new(pool.clone());
+        let space_id = create_space(&pool, "team").await?;
+        let e = |e: DBError| format!("{:?}", e);
+
+        let webhook = WebhooksDaoImpl::in_space(pool.clone(), space_id)
+            .create_webhook(
+                &mut uow,
+                Webhook {
+                    url: "http://localhost/team".to_owned(),
+                    events: vec![WebhookEvent::QuestionCreated],
+                },
+            )
+            .await
+            .map_err(e)?;
+
+        // Webhooks cannot be deleted from another space
+        let deleted = WebhooksDaoImpl::new(pool.clone())
+            .delete_webhook(&mut uow, webhook.webhook_uuid.clone())
+            .await
+            .map_err(e)?;
+        if deleted.is_some() {
+            return Err("Webhook was deleted from another space".to_owned());
+        }
+
+        QuestionsDaoImpl::in_space(pool.clone(), DEFAULT_SPACE_ID)
+            .create_question(&mut uow, question(), "toto".to_owned())
+            .await
+            .map_err(e)?;
+        let secret = QuestionsDaoImpl::in_space(pool.clone(), space_id)
+            .create_question(&mut uow, question(), "toto".to_owned())
+            .await
+            .map_err(e)?;
+
+        let deliveries = WebhooksDaoImpl::new(pool)
+            .get_due_deliveries(10, Duration::from_secs(60))
+            .await
+            .map_err(e)?;
+        if deliveries.len() != 1
+            || deliveries[0].url != webhook.url
+            || !deliveries[0].payload.contains(&secret.question_uuid)
+        {
+            return Err(format!("Unexpected deliveries: {:?}", deliveries));
+        }
+
+        Ok(())
+    }
+}
+
+mod stats_tests {
+    use sqlx::PgPool;
+
+    use crate::{
+        models::{Answer, Question, Space},
+        persistance::{
+            answers_dao::{AnswersDao, AnswersDaoImpl},
+            questions_dao::{QuestionsDao, QuestionsDaoImpl},
+            spaces_dao::{SpacesDao, SpacesDaoImpl},
+            stats_dao::{StatsDao, StatsDaoImpl},
+            unit_of_work::Autocommit,
+        },
+    };
+
+    async fn ask(doa: &QuestionsDaoImpl, pool: &PgPool, title: &str) -> Result<String, String> {
+        doa.create_question(
+            &mut Autocommit::new(pool.clone()),
+            Question {
+                title: title.to_owned(),
+                description: "test description".to_owned(),
+            },
+            "toto".to_owned(),
+        )
+        .await
+        .map(|question| question.question_uuid)
+        .map_err(|e| format!("{:?}", e))
+    }
+
+    /// Answers `minutes` after the question was asked
+    async fn answer(pool: &PgPool, question_uuid: &str, minutes: i32) -> Result<(), String> {
+        let answer = AnswersDaoImpl::new(pool.clone())
+            .create_answer(
+                &mut Autocommit::new(pool.clone()),
+                Answer {
+                    question_uuid: question_uuid.to_owned(),
+                    content: "test content".to_owned(),
+                },
+                "titi".to_owned(),
+            )
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+
+        sqlx::query(
+            r"
+        UPDATE answers a SET created_at = q.created_at + make_interval(mins => $2)
+        FROM questions q
+        WHERE a.answer_uuid = $1::UUID AND q.question_uuid = a.question_uuid
+        ",
+        )
+        .bind(answer.answer_uuid)
+        .bind(minutes)
+        .execute(pool)
+        .await
+        .map_err(|e| e.to_string())?;
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn get_stats_should_only_change_on_refresh(pool: PgPool) -> Result<(), String> {
+        let doa = StatsDaoImpl::new(pool.clone());
+        ask(&QuestionsDaoImpl::new(pool.clone()), &pool, "Asked").await?;
+
+        let stats = doa.get_stats(1).await.map_err(|e| format!("{:?}", e))?;
+        if stats.questions != 0 {
+            return Err(format!("Expected stale figures but got {:?}", stats));
+        }
+
+        doa.refresh_stats().await.map_err(|e| format!("{:?}", e))?;
+
+        let refreshed = doa.get_stats(1).await.map_err(|e| format!("{:?}", e))?;
+        if refreshed.questions != 1 || refreshed.refreshed_at < stats.refreshed_at {
+            return Err(format!("Expected fresh figures but got {:?}", refreshed));
+        }
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn get_stats_should_aggregate_the_posts_of_the_space(pool: PgPool) -> Result<(), String> {
+        let mut uow = Autocommit::new(pool.clone());
+        let questions_doa = QuestionsDaoImpl::new(pool.clone());
+
+        let quick = ask(&questions_doa, &pool, "Answered quickly").await?;
+        answer(&pool, &quick, 2).await?;
+        answer(&pool, &quick, 30).await?;
+        let slow = ask(&questions_doa, &pool, "Answered slowly").await?;
+        answer(&pool, &slow, 10).await?;
+        let slowest = ask(&questions_doa, &pool, "Answered at last").await?;
+        answer(&pool, &slowest, 60).await?;
+        ask(&questions_doa, &pool, "Unanswered").await?;
+        let duplicate = ask(&questions_doa, &pool, "Closed").await?;
+        questions_doa
+            .close_as_duplicate(&mut uow, duplicate, quick.clone(), "mod".to_owned())
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+        let deleted = ask(&questions_doa, &pool, "Deleted").await?;
+        answer(&pool, &deleted, 1).await?;
+        questions_doa
+            .delete_question(&mut uow, deleted, "toto".to_owned())
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+
+        // Posts of other spaces are not counted
+        let space = SpacesDaoImpl::new(pool.clone())
+            .create_space(
+                &mut uow,
+                Space {
+                    slug: "team".to_owned(),
+                    name: "Team".to_owned(),
+                    open: true,
+                },
+            )
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+        let space_id = sqlx::types::Uuid::parse_str(&space.space_id).map_err(|e| e.to_string())?;
+        ask(
+            &QuestionsDaoImpl::in_space(pool.clone(), space_id),
+            &pool,
+            "Elsewhere",
+        )
+        .await?;
+
+        let doa = StatsDaoImpl::new(pool.clone());
+        doa.refresh_stats().await.map_err(|e| format!("{:?}", e))?;
+        let stats = doa.get_stats(7).await.map_err(|e| format!("{:?}", e))?;
+
+        if (stats.questions, stats.answers, stats.unanswered_questions) != (5, 4, 1) {
+            return Err(format!(
+                "Expected 5 questions, 4 answers and 1 unanswered but got {:?}",
+                stats
+            ));
+        }
+        if stats.median_seconds_to_first_answer != Some(600.0) {
+            return Err(format!(
+                "Expected a median of 10 minutes but got {:?}",
+                stats
+            ));
+        }
+        if stats.posts_per_day.len() != 7 {
+            return Err(format!("Expected 7 days of posts but got {:?}", stats));
+        }
+        // Answers are dated after their question and may fall on the next day, questions may not
+        let questions: i64 = stats.posts_per_day.iter().map(|day| day.questions).sum();
+        if questions != 5 {
+            return Err(format!(
+                "Expected every question in the window but got {:?}",
+                stats
+            ));
+        }
+
+        let stats = StatsDaoImpl::in_space(pool.clone(), space_id)
+            .get_stats(7)
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+        if (stats.questions, stats.median_seconds_to_first_answer) != (1, None) {
+            return Err(format!(
+                "Expected a single unanswered question but got {:?}",
+                stats
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+mod read_cache_tests {
+    use std::{sync::Arc, time::Duration};
+
+    use sqlx::PgPool;
+
+    use crate::{
+        models::{Answer, Question},
+        persistance::{
+            answers_dao::{AnswersDao, AnswersDaoImpl},
+            questions_dao::{QuestionsDao, QuestionsDaoImpl},
+            read_cache::{
+                run_invalidation_listener, CachedAnswersDao, CachedQuestionsDao, ReadCache,
+            },
+            spaces_dao::DEFAULT_SPACE_ID,
+            unit_of_work::{Database, DatabaseImpl},
+        },
+    };
+
+    fn cached_doas(
+        pool: &PgPool,
+        cache: &Arc<ReadCache>,
+    ) -> (CachedQuestionsDao, CachedAnswersDao) {
+        (
+            CachedQuestionsDao::new(
+                Arc::new(QuestionsDaoImpl::new(pool.clone())),
+                cache.clone(),
+                DEFAULT_SPACE_ID,
+            ),
+            CachedAnswersDao::new(
+                Arc::new(AnswersDaoImpl::new(pool.clone())),
+                cache.clone(),
+                DEFAULT_SPACE_ID,
+            ),
+        )
+    }
+
+    fn question(title: &str) -> Question {
+        Question {
+            title: title.to_owned(),
+            description: "test description".to_owned(),
+        }
+    }
+
+    async fn titles(doa: &CachedQuestionsDao) -> Result<Vec<String>, String> {
+        doa.get_questions()
+            .await
+            .map(|questions| questions.into_iter().map(|q| q.title).collect())
+            .map_err(|e| format!("{:?}", e))
+    }
+
+    #[sqlx::test]
+    async fn reads_should_never_be_stale_after_a_committed_write(
+        pool: PgPool,
+    ) -> Result<(), String> {
+        let database = DatabaseImpl::new(pool.clone());
+        let cache = Arc::new(ReadCache::new(100, Duration::from_secs(3600), false));
+        let (questions_doa, answers_doa) = cached_doas(&pool, &cache);
+
+        let mut uow = database.begin().await.map_err(|e| format!("{:?}", e))?;
+        let created = questions_doa
+            .create_question(uow.as_mut(), question("First"), "toto".to_owned())
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+        uow.commit().await.map_err(|e| format!("{:?}", e))?;
+
+        titles(&questions_doa).await?;
+        titles(&questions_doa).await?;
+        let answers = answers_doa
+            .get_answers(created.question_uuid.clone())
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+        if cache.metrics().hits != 1 || !answers.is_empty() {
+            return Err(format!(
+                "Expected the second listing to be a hit but got {:?}",
+                cache.metrics()
+            ));
+        }
+
+        let mut uow = database.begin().await.map_err(|e| format!("{:?}", e))?;
+        questions_doa
+            .update_question(
+                uow.as_mut(),
+                created.question_uuid.clone(),
+                question("Edited"),
+            )
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+        questions_doa
+            .create_question(uow.as_mut(), question("Second"), "toto".to_owned())
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+        answers_doa
+            .create_answer(
+                uow.as_mut(),
+                Answer {
+                    question_uuid: created.question_uuid.clone(),
+                    content: "test content".to_owned(),
+                },
+                "titi".to_owned(),
+            )
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+
+        // Reads before the commit may be cached, they are forgotten on commit
+        titles(&questions_doa).await?;
+        uow.commit().await.map_err(|e| format!("{:?}", e))?;
+
+        let titles = titles(&questions_doa).await?;
+        if titles != vec!["Edited".to_owned(), "Second".to_owned()] {
+            return Err(format!(
+                "Expected the written questions but got {:?}",
+                titles
+            ));
+        }
+        let answers = answers_doa
+            .get_answers(created.question_uuid)
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+        if answers.len() != 1 {
+            return Err(format!("Expected the written answer but got {:?}", answers));
+        }
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn reads_should_stay_cached_after_a_rolled_back_write(
+        pool: PgPool,
+    ) -> Result<(), String> {
+        let database = DatabaseImpl::new(pool.clone());
+        let cache = Arc::new(ReadCache::new(100, Duration::from_secs(3600), false));
+        let (questions_doa, _) = cached_doas(&pool, &cache);
+
+        titles(&questions_doa).await?;
+
+        let mut uow = database.begin().await.map_err(|e| format!("{:?}", e))?;
+        questions_doa
+            .create_question(uow.as_mut(), question("Rolled back"), "toto".to_owned())
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+        drop(uow);
+
+        let titles = titles(&questions_doa).await?;
+        if !titles.is_empty() || cache.metrics().invalidations != 0 {
+            return Err(format!(
+                "Expected the empty listing to stay cached but got {:?} and {:?}",
+                titles,
+                cache.metrics()
+            ));
+        }
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn writes_should_invalidate_the_cache_of_other_instances(
+        pool: PgPool,
+    ) -> Result<(), String> {
+        let database = DatabaseImpl::new(pool.clone());
+        let writer_cache = Arc::new(ReadCache::new(100, Duration::from_secs(3600), true));
+        let reader_cache = Arc::new(ReadCache::new(100, Duration::from_secs(3600), true));
+        let (writer_doa, _) = cached_doas(&pool, &writer_cache);
+        let (reader_doa, _) = cached_doas(&pool, &reader_cache);
+
+        // The listener empties the cache once it listens
+        let listener = tokio::spawn(run_invalidation_listener(
+            pool.clone(),
+            reader_cache.clone(),
+        ));
+        while reader_cache.metrics().invalidations == 0 {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        titles(&reader_doa).await?;
+
+        let mut uow = database.begin().await.map_err(|e| format!("{:?}", e))?;
+        writer_doa
+            .create_question(uow.as_mut(), question("Elsewhere"), "toto".to_owned())
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+        uow.commit().await.map_err(|e| format!("{:?}", e))?;
+
+        let mut titles_read = Vec::new();
+        for _ in 0..100 {
+            titles_read = titles(&reader_doa).await?;
+            if !titles_read.is_empty() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        listener.abort();
+
+        if titles_read != vec!["Elsewhere".to_owned()] {
+            return Err(format!(
+                "Expected the other instance to see the write but got {:?}",
+                titles_read
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+mod jobs_tests {
+    use std::time::Duration;
+
+    use sqlx::{
+        types::time::{OffsetDateTime, PrimitiveDateTime},
+        PgPool,
+    };
+
+    use crate::{
+        models::{Answer, DBError, Question},
+        persistance::{
+            answers_dao::{AnswersDao, AnswersDaoImpl},
+            jobs_dao::{JobsDao, JobsDaoImpl},
+            questions_dao::{QuestionsDao, QuestionsDaoImpl},
+            unit_of_work::{Autocommit, Database, DatabaseImpl, UnitOfWork},
+        },
+    };
+
+    const DAY: Duration = Duration::from_secs(24 * 60 * 60);
+
+    /// Question asked `days` days ago
+    async fn ask(pool: &PgPool, title: &str, days: i32) -> Result<String, String> {
+        let question = QuestionsDaoImpl::new(pool.clone())
+            .create_question(
+                &mut Autocommit::new(pool.clone()),
+                Question {
+                    title: title.to_owned(),
+                    description: "test description".to_owned(),
+                },
+                "toto".to_owned(),
+            )
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+
+        sqlx::query(
+            r"
+        UPDATE questions SET created_at = created_at - make_interval(days => $2)
+        WHERE question_uuid = $1::UUID
+        ",
+        )
+        .bind(&question.question_uuid)
+        .bind(days)
+        .execute(pool)
+        .await
+        .map_err(|e| e.to_string())?;
+
+        Ok(question.question_uuid)
+    }
+
+    async fn answer(pool: &PgPool, question_uuid: &str) -> Result<(), DBError> {
+        AnswersDaoImpl::new(pool.clone())
+            .create_answer(
+                &mut Autocommit::new(pool.clone()),
+                Answer {
+                    question_uuid: question_uuid.to_owned(),
+                    content: "test content".to_owned(),
+                },
+                "titi".to_owned(),
+            )
+            .await
+            .map(|_| ())
+    }
+
+    /// Midnight UTC, `days` days after the epoch
+    fn midnight(days: i64) -> PrimitiveDateTime {
+        let time = OffsetDateTime::from_unix_timestamp(days * 24 * 60 * 60).unwrap();
+        PrimitiveDateTime::new(time.date(), time.time())
+    }
+
+    async fn claim(doa: &JobsDaoImpl, uow: &mut dyn UnitOfWork, days: i64) -> Result<bool, String> {
+        doa.claim_run(uow, "test", midnight(days))
+            .await
+            .map_err(|e| format!("{:?}", e))
+    }
+
+    #[sqlx::test]
+    async fn close_inactive_questions_should_close_questions_without_recent_answers(
+        pool: PgPool,
+    ) -> Result<(), String> {
+        let doa = JobsDaoImpl::new(pool.clone());
+        let inactive = ask(&pool, "Inactive", 10).await?;
+        let answered = ask(&pool, "Answered lately", 10).await?;
+        answer(&pool, &answered)
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+        ask(&pool, "Recent", 1).await?;
+
+        let closed = doa
+            .close_inactive_questions(&mut Autocommit::new(pool.clone()), 5 * DAY)
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+        if closed != 1 {
+            return Err(format!(
+                "Expected a single question closed but got {closed}"
+            ));
+        }
+
+        match answer(&pool, &inactive).await {
+            Err(DBError::InvalidUUID(_)) => {}
+            other => {
+                return Err(format!(
+                    "Expected the closed question not to be answered but got {:?}",
+                    other
+                ))
+            }
+        }
+
+        let closed = doa
+            .close_inactive_questions(&mut Autocommit::new(pool.clone()), 5 * DAY)
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+        if closed != 0 {
+            return Err(format!("Expected nothing left to close but got {closed}"));
+        }
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn flag_unanswered_questions_should_list_questions_until_answered(
+        pool: PgPool,
+    ) -> Result<(), String> {
+        let doa = JobsDaoImpl::new(pool.clone());
+        let questions_doa = QuestionsDaoImpl::new(pool.clone());
+        let unanswered = ask(&pool, "Unanswered", 2).await?;
+        let answered = ask(&pool, "Answered", 2).await?;
+        answer(&pool, &answered)
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+        ask(&pool, "Recent", 0).await?;
+
+        let flagged = doa
+            .flag_unanswered_questions(&mut Autocommit::new(pool.clone()), DAY)
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+        if flagged != 1 {
+            return Err(format!(
+                "Expected a single question flagged but got {flagged}"
+            ));
+        }
+
+        let listed = questions_doa
+            .get_questions_needing_attention(0, 10)
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+        if listed.len() != 1 || listed[0].question_uuid != unanswered {
+            return Err(format!(
+                "Expected the unanswered question but got {:?}",
+                listed
+            ));
+        }
+
+        answer(&pool, &unanswered)
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+        let listed = questions_doa
+            .get_questions_needing_attention(0, 10)
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+        if !listed.is_empty() {
+            return Err(format!(
+                "Expected answered questions off the list but got {:?}",
+                listed
+            ));
+        }
+
+        doa.flag_unanswered_questions(&mut Autocommit::new(pool.clone()), DAY)
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+        let left = sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM needs_attention")
+            .fetch_one(&pool)
+            .await
+            .map_err(|e| e.to_string())?;
+        if left != 0 {
+            return Err(format!(
+                "Expected the next run to prune the list but {left} were left"
+            ));
+        }
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn archive_questions_should_move_questions_with_their_answers(
+        pool: PgPool,
+    ) -> Result<(), String> {
+        let doa = JobsDaoImpl::new(pool.clone());
+        let questions_doa = QuestionsDaoImpl::new(pool.clone());
+        let old = ask(&pool, "Old", 10).await?;
+        answer(&pool, &old).await.map_err(|e| format!("{:?}", e))?;
+        let original = ask(&pool, "Old original", 10).await?;
+        let duplicate = ask(&pool, "Recent duplicate", 1).await?;
+        questions_doa
+            .close_as_duplicate(
+                &mut Autocommit::new(pool.clone()),
+                duplicate,
+                original.clone(),
+                "toto".to_owned(),
+            )
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+
+        let archived = doa
+            .archive_questions(&mut Autocommit::new(pool.clone()), 5 * DAY)
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+        if archived != 1 {
+            return Err(format!(
+                "Expected a single question archived but got {archived}"
+            ));
+        }
+
+        match questions_doa.get_question(old.clone()).await {
+            Err(DBError::InvalidUUID(_)) => {}
+            other => {
+                return Err(format!(
+                    "Expected the question to be gone but got {:?}",
+                    other
+                ))
+            }
+        }
+        questions_doa
+            .get_question(original)
+            .await
+            .map_err(|e| format!("Expected the original of a kept duplicate to stay: {:?}", e))?;
+
+        let (title, answers) = sqlx::query_as::<_, (String, i32)>(
+            r"
+        SELECT question->>'title', jsonb_array_length(answers)
+        FROM questions_archive WHERE question_uuid = $1::UUID
+        ",
+        )
+        .bind(&old)
+        .fetch_one(&pool)
+        .await
+        .map_err(|e| e.to_string())?;
+        if (title.as_str(), answers) != ("Old", 1) {
+            return Err(format!(
+                "Expected the question and its answer but got {title} and {answers}"
+            ));
+        }
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn claim_run_should_let_a_single_unit_run_each_scheduled_time(
+        pool: PgPool,
+    ) -> Result<(), String> {
+        let doa = JobsDaoImpl::new(pool.clone());
+        let database = DatabaseImpl::new(pool.clone());
+        let mut running = database.begin().await.map_err(|e| format!("{:?}", e))?;
+        let mut other = database.begin().await.map_err(|e| format!("{:?}", e))?;
+        if !claim(&doa, running.as_mut(), 19).await? {
+            return Err("Expected the first claim to succeed".to_owned());
+        }
+        if claim(&doa, other.as_mut(), 20).await? {
+            return Err("Expected the job to be claimed while it runs".to_owned());
+        }
+        drop(other);
+
+        doa.record_run(running.as_mut(), "test", midnight(19), midnight(19), Ok(2))
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+        running.commit().await.map_err(|e| format!("{:?}", e))?;
+
+        let mut late = database.begin().await.map_err(|e| format!("{:?}", e))?;
+        if claim(&doa, late.as_mut(), 19).await? {
+            return Err("Expected a time that ran not to be claimed again".to_owned());
+        }
+        // Rolling back by dropping the unit would only release the lock later on
+        late.commit().await.map_err(|e| format!("{:?}", e))?;
+        let mut next = database.begin().await.map_err(|e| format!("{:?}", e))?;
+        if !claim(&doa, next.as_mut(), 20).await? {
+            return Err("Expected the next time to be claimed".to_owned());
+        }
+        drop(next);
+
+        let runs = doa.get_job_runs(10).await.map_err(|e| format!("{:?}", e))?;
+        if runs.len() != 1 || !runs[0].succeeded || runs[0].affected != 2 {
+            return Err(format!("Expected the recorded run but got {:?}", runs));
+        }
+
+        Ok(())
+    }
 }
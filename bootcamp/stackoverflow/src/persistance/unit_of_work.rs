@@ -0,0 +1,119 @@
+use async_trait::async_trait;
+use sqlx::{PgConnection, PgPool, Postgres, Transaction};
+
+use crate::models::DBError;
+
+/// Writes of one or more DAOs that become visible all at once.
+/// DAO methods taking a unit run their queries in it, nothing is kept unless it is committed.
+/// Dropping a unit without committing rolls it back.
+#[async_trait]
+pub trait UnitOfWork: Send {
+    /// Connection the DAOs run their queries on
+    async fn connection(&mut self) -> Result<&mut PgConnection, DBError>;
+    async fn commit(self: Box<Self>) -> Result<(), DBError>;
+    /// Runs `callback` once the writes are visible to other connections,
+    /// it is dropped without running if the unit is rolled back
+    fn after_commit(&mut self, callback: Box<dyn FnOnce() + Send>);
+}
+
+/// Starts units of work, handlers depend on it rather than on a pool so it can be mocked
+#[async_trait]
+pub trait Database {
+    async fn begin(&self) -> Result<Box<dyn UnitOfWork>, DBError>;
+}
+
+pub struct DatabaseImpl {
+    db: PgPool,
+}
+
+impl DatabaseImpl {
+    pub fn new(db: PgPool) -> Self {
+        DatabaseImpl { db }
+    }
+}
+
+#[async_trait]
+impl Database for DatabaseImpl {
+    async fn begin(&self) -> Result<Box<dyn UnitOfWork>, DBError> {
+        let tx = self
+            .db
+            .begin()
+            .await
+            .map_err(|e| DBError::Other(Box::new(e)))?;
+
+        Ok(Box::new(PgUnitOfWork {
+            tx,
+            after_commit: Vec::new(),
+        }))
+    }
+}
+
+struct PgUnitOfWork {
+    tx: Transaction<'static, Postgres>,
+    after_commit: Vec<Box<dyn FnOnce() + Send>>,
+}
+
+#[async_trait]
+impl UnitOfWork for PgUnitOfWork {
+    async fn connection(&mut self) -> Result<&mut PgConnection, DBError> {
+        Ok(&mut self.tx)
+    }
+
+    async fn commit(self: Box<Self>) -> Result<(), DBError> {
+        self.tx
+            .commit()
+            .await
+            .map_err(|e| DBError::Other(Box::new(e)))?;
+
+        for callback in self.after_commit {
+            callback();
+        }
+
+        Ok(())
+    }
+
+    fn after_commit(&mut self, callback: Box<dyn FnOnce() + Send>) {
+        self.after_commit.push(callback);
+    }
+}
+
+/// Commits every DAO call on its own, for tests which are not about atomicity.
+/// The connection is only acquired on first use so that closing the pool makes DAO calls fail.
+#[cfg(test)]
+pub struct Autocommit {
+    db: PgPool,
+    conn: Option<sqlx::pool::PoolConnection<Postgres>>,
+}
+
+#[cfg(test)]
+impl Autocommit {
+    pub fn new(db: PgPool) -> Self {
+        Autocommit { db, conn: None }
+    }
+}
+
+#[cfg(test)]
+#[async_trait]
+impl UnitOfWork for Autocommit {
+    async fn connection(&mut self) -> Result<&mut PgConnection, DBError> {
+        if self.conn.is_none() {
+            let conn = self
+                .db
+                .acquire()
+                .await
+                .map_err(|e| DBError::Other(Box::new(e)))?;
+            self.conn = Some(conn);
+        }
+
+        Ok(self.conn.as_mut().expect("Connection was just acquired"))
+    }
+
+    async fn commit(self: Box<Self>) -> Result<(), DBError> {
+        Ok(())
+    }
+
+    /// Writes were already committed by the DAO call registering the callback
+    fn after_commit(&mut self, callback: Box<dyn FnOnce() + Send>) {
+        callback();
+    }
+}
@@ -0,0 +1,238 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+use serde::Serialize;
+use sqlx::{types::Json, types::Uuid, PgConnection, PgPool};
+
+use crate::models::{DBError, Webhook, WebhookDelivery, WebhookDetail, WebhookEvent};
+use crate::persistance::{spaces_dao::DEFAULT_SPACE_ID, unit_of_work::UnitOfWork};
+
+#[async_trait]
+pub trait WebhooksDao {
+    /// Subscribes to the events of the space of the DAO
+    async fn create_webhook(
+        &self,
+        uow: &mut dyn UnitOfWork,
+        webhook: Webhook,
+    ) -> Result<WebhookDetail, DBError>;
+    /// Unsubscribes a webhook of the space of the DAO along with its pending deliveries
+    async fn delete_webhook(
+        &self,
+        uow: &mut dyn UnitOfWork,
+        webhook_uuid: String,
+    ) -> Result<Option<WebhookDetail>, DBError>;
+    /// Claims up to `limit` pending deliveries whose next attempt is due. Other workers skip
+    /// them for `lease`, which must outlast their delivery.
+    async fn get_due_deliveries(
+        &self,
+        limit: i64,
+        lease: Duration,
+    ) -> Result<Vec<WebhookDelivery>, DBError>;
+    async fn mark_delivered(&self, delivery_uuid: String) -> Result<(), DBError>;
+    async fn reschedule_delivery(
+        &self,
+        delivery_uuid: String,
+        error: String,
+        delay: Duration,
+    ) -> Result<(), DBError>;
+    async fn dead_letter_delivery(
+        &self,
+        delivery_uuid: String,
+        error: String,
+    ) -> Result<(), DBError>;
+}
+
+/// Webhooks are created in a single space, deliveries are made for all spaces
+pub struct WebhooksDaoImpl {
+    db: PgPool,
+    space_id: Uuid,
+}
+
+impl WebhooksDaoImpl {
+    pub fn new(db: PgPool) -> Self {
+        Self::in_space(db, DEFAULT_SPACE_ID)
+    }
+
+    pub fn in_space(db: PgPool, space_id: Uuid) -> Self {
+        WebhooksDaoImpl { db, space_id }
+    }
+}
+
+/// Writes one outbox row per webhook of the space subscribed to `event`.
+/// Takes a connection so that it can be called inside the transaction creating the resource.
+pub async fn enqueue_event<T: Serialize + Sync>(
+    conn: &mut PgConnection,
+    space_id: Uuid,
+    event: WebhookEvent,
+    data: &T,
+) -> Result<(), DBError> {
+    let payload = serde_json::json!({
+        "event": event.as_str(),
+        "data": data,
+    });
+
+    sqlx::query(
+        r"
+        INSERT INTO webhook_deliveries ( webhook_uuid, event, payload )
+        SELECT webhook_uuid, $1, $2 FROM webhooks WHERE $1 = ANY(events) AND space_id = $3
+        ",
+    )
+    .bind(event.as_str())
+    .bind(Json(payload))
+    .bind(space_id)
+    .execute(conn)
+    .await
+    .map_err(|e| DBError::Other(Box::new(e)))?;
+
+    Ok(())
+}
+
+#[async_trait]
+impl WebhooksDao for WebhooksDaoImpl {
+    async fn create_webhook(
+        &self,
+        uow: &mut dyn UnitOfWork,
+        webhook: Webhook,
+    ) -> Result<WebhookDetail, DBError> {
+        let events: Vec<&str> = webhook.events.iter().map(WebhookEvent::as_str).collect();
+
+        sqlx::query_as::<_, WebhookDetail>(
+            r"
+        INSERT INTO webhooks ( url, events, space_id )
+        VALUES ( $1, $2, $3 )
+        RETURNING *
+        ",
+        )
+        .bind(webhook.url)
+        .bind(events)
+        .bind(self.space_id)
+        .fetch_one(uow.connection().await?)
+        .await
+        .map_err(|e| DBError::Other(Box::new(e)))
+    }
+
+    async fn delete_webhook(
+        &self,
+        uow: &mut dyn UnitOfWork,
+        webhook_uuid: String,
+    ) -> Result<Option<WebhookDetail>, DBError> {
+        let uuid =
+            Uuid::parse_str(&webhook_uuid).map_err(|e| DBError::InvalidUUID(e.to_string()))?;
+
+        sqlx::query_as::<_, WebhookDetail>(
+            r"
+        DELETE FROM webhooks WHERE webhook_uuid = $1 AND space_id = $2
+        RETURNING *
+        ",
+        )
+        .bind(uuid)
+        .bind(self.space_id)
+        .fetch_optional(uow.connection().await?)
+        .await
+        .map_err(|e| DBError::Other(Box::new(e)))
+    }
+
+    async fn get_due_deliveries(
+        &self,
+        limit: i64,
+        lease: Duration,
+    ) -> Result<Vec<WebhookDelivery>, DBError> {
+        // Claimed rows are pushed past the lease so that other workers skip them while they
+        // are being delivered.
+        sqlx::query_as::<_, WebhookDelivery>(
+            r"
+        WITH claimed AS (
+            UPDATE webhook_deliveries
+            SET next_attempt_at = CURRENT_TIMESTAMP + make_interval(secs => $2)
+            WHERE delivery_uuid IN (
+                SELECT delivery_uuid FROM webhook_deliveries
+                WHERE status = 'pending' AND next_attempt_at <= CURRENT_TIMESTAMP
+                ORDER BY next_attempt_at
+                LIMIT $1
+                FOR UPDATE SKIP LOCKED
+            )
+            RETURNING *
+        )
+        SELECT claimed.delivery_uuid, webhooks.url, webhooks.secret,
+               claimed.event, claimed.payload, claimed.attempts
+        FROM claimed JOIN webhooks ON webhooks.webhook_uuid = claimed.webhook_uuid
+        ",
+        )
+        .bind(limit)
+        .bind(lease.as_secs_f64())
+        .fetch_all(&self.db)
+        .await
+        .map_err(|e| DBError::Other(Box::new(e)))
+    }
+
+    async fn mark_delivered(&self, delivery_uuid: String) -> Result<(), DBError> {
+        let uuid =
+            Uuid::parse_str(&delivery_uuid).map_err(|e| DBError::InvalidUUID(e.to_string()))?;
+
+        sqlx::query(
+            r"
+        UPDATE webhook_deliveries
+        SET status = 'delivered', attempts = attempts + 1, last_error = NULL
+        WHERE delivery_uuid = $1
+        ",
+        )
+        .bind(uuid)
+        .execute(&self.db)
+        .await
+        .map_err(|e| DBError::Other(Box::new(e)))?;
+
+        Ok(())
+    }
+
+    async fn reschedule_delivery(
+        &self,
+        delivery_uuid: String,
+        error: String,
+        delay: Duration,
+    ) -> Result<(), DBError> {
+        let uuid =
+            Uuid::parse_str(&delivery_uuid).map_err(|e| DBError::InvalidUUID(e.to_string()))?;
+
+        sqlx::query(
+            r"
+        UPDATE webhook_deliveries
+        SET attempts = attempts + 1,
+            last_error = $2,
+            next_attempt_at = CURRENT_TIMESTAMP + make_interval(secs => $3)
+        WHERE delivery_uuid = $1
+        ",
+        )
+        .bind(uuid)
+        .bind(error)
+        .bind(delay.as_secs_f64())
+        .execute(&self.db)
+        .await
+        .map_err(|e| DBError::Other(Box::new(e)))?;
+
+        Ok(())
+    }
+
+    async fn dead_letter_delivery(
+        &self,
+        delivery_uuid: String,
+        error: String,
+    ) -> Result<(), DBError> {
+        let uuid =
+            Uuid::parse_str(&delivery_uuid).map_err(|e| DBError::InvalidUUID(e.to_string()))?;
+
+        sqlx::query(
+            r"
+        UPDATE webhook_deliveries
+        SET status = 'dead', attempts = attempts + 1, last_error = $2
+        WHERE delivery_uuid = $1
+        ",
+        )
+        .bind(uuid)
+        .bind(error)
+        .execute(&self.db)
+        .await
+        .map_err(|e| DBError::Other(Box::new(e)))?;
+
+        Ok(())
+    }
+}
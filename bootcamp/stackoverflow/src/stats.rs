@@ -0,0 +1,21 @@
+use std::{sync::Arc, time::Duration};
+
+use log::error;
+
+use crate::persistance::stats_dao::StatsDao;
+
+/// Statistics lag behind the posts by at most this interval
+pub const REFRESH_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+/// Background task refreshing the statistics every `interval` until the runtime shuts down,
+/// starting right away so that they are fresh after a deployment
+pub async fn run_stats_refresher(stats_dao: Arc<dyn StatsDao + Send + Sync>, interval: Duration) {
+    let mut interval = tokio::time::interval(interval);
+
+    loop {
+        interval.tick().await;
+        if let Err(e) = stats_dao.refresh_stats().await {
+            error!("Failed to refresh statistics: {:?}", e);
+        }
+    }
+}
@@ -0,0 +1,110 @@
+use std::{
+    collections::HashMap,
+    mem,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use log::error;
+
+use crate::{models::DBError, persistance::questions_dao::QuestionsDao};
+
+/// Views lost on a crash are at most the ones of the last interval
+pub const FLUSH_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Views are counted in memory and written in batches, so that reading a question
+/// does not cost a database write
+#[derive(Default)]
+pub struct ViewCounter {
+    pending: Mutex<HashMap<String, i64>>,
+}
+
+impl ViewCounter {
+    pub fn record(&self, question_uuid: &str) {
+        let mut pending = self.pending.lock().unwrap();
+        *pending.entry(question_uuid.to_owned()).or_default() += 1;
+    }
+
+    /// Views counted since the last call, keyed by question UUID
+    pub fn take(&self) -> HashMap<String, i64> {
+        mem::take(&mut *self.pending.lock().unwrap())
+    }
+
+    /// Puts back views that could not be written so that the next flush retries them
+    fn restore(&self, views: HashMap<String, i64>) {
+        let mut pending = self.pending.lock().unwrap();
+        for (question_uuid, count) in views {
+            *pending.entry(question_uuid).or_default() += count;
+        }
+    }
+}
+
+/// Writes the pending views, returns the number of questions that were viewed
+pub async fn flush(
+    views: &ViewCounter,
+    questions_dao: &(dyn QuestionsDao + Send + Sync),
+) -> Result<usize, DBError> {
+    let pending = views.take();
+    if pending.is_empty() {
+        return Ok(0);
+    }
+
+    let count = pending.len();
+    match questions_dao.add_views(pending.clone()).await {
+        Ok(()) => Ok(count),
+        Err(e) => {
+            views.restore(pending);
+            Err(e)
+        }
+    }
+}
+
+/// Background task flushing the view counter every `interval` until the runtime shuts down
+pub async fn run_view_flusher(
+    views: Arc<ViewCounter>,
+    questions_dao: Arc<dyn QuestionsDao + Send + Sync>,
+    interval: Duration,
+) {
+    let mut interval = tokio::time::interval(interval);
+
+    loop {
+        interval.tick().await;
+        if let Err(e) = flush(&views, questions_dao.as_ref()).await {
+            error!("Failed to flush question views: {:?}", e);
+        }
+    }
+}
+
+// ***********************************************************
+//                           Tests
+// ***********************************************************
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn view_counter_should_aggregate_views_until_taken() {
+        let views = ViewCounter::default();
+        views.record("first");
+        views.record("second");
+        views.record("first");
+
+        assert_eq!(
+            views.take(),
+            HashMap::from([("first".to_owned(), 2), ("second".to_owned(), 1)])
+        );
+        assert!(views.take().is_empty());
+    }
+
+    #[test]
+    fn view_counter_should_merge_restored_views() {
+        let views = ViewCounter::default();
+        views.record("first");
+        let failed = views.take();
+        views.record("first");
+        views.restore(failed);
+
+        assert_eq!(views.take(), HashMap::from([("first".to_owned(), 2)]));
+    }
+}
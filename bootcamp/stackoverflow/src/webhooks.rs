@@ -0,0 +1,464 @@
+use std::{
+    net::{IpAddr, SocketAddr},
+    sync::Arc,
+    time::Duration,
+};
+
+use hmac::{Hmac, Mac};
+use log::{error, warn};
+use reqwest::{
+    dns::{Addrs, Name, Resolve, Resolving},
+    Url,
+};
+use sha2::Sha256;
+
+use crate::{
+    models::{DBError, WebhookDelivery},
+    persistance::webhooks_dao::WebhooksDao,
+};
+
+pub const EVENT_HEADER: &str = "X-Webhook-Event";
+pub const DELIVERY_HEADER: &str = "X-Webhook-Delivery";
+pub const SIGNATURE_HEADER: &str = "X-Webhook-Signature";
+
+#[derive(Debug, Clone)]
+pub struct DeliveryConfig {
+    pub poll_interval: Duration,
+    pub batch_size: i64,
+    /// Number of failed attempts after which a delivery is moved to the dead-letter state
+    pub max_attempts: i32,
+    pub base_backoff: Duration,
+    pub max_backoff: Duration,
+    pub request_timeout: Duration,
+    /// Whether deliveries may reach loopback, link-local and private addresses
+    pub allow_private_targets: bool,
+}
+
+impl Default for DeliveryConfig {
+    fn default() -> Self {
+        DeliveryConfig {
+            poll_interval: Duration::from_secs(1),
+            batch_size: 50,
+            max_attempts: 8,
+            base_backoff: Duration::from_secs(2),
+            max_backoff: Duration::from_secs(60 * 60),
+            request_timeout: Duration::from_secs(10),
+            allow_private_targets: false,
+        }
+    }
+}
+
+impl DeliveryConfig {
+    /// How long a batch stays claimed: each of its deliveries may wait for the request timeout,
+    /// and one more covers recording the outcomes.
+    pub fn lease(&self) -> Duration {
+        let requests = u32::try_from(self.batch_size.max(0) + 1).unwrap_or(u32::MAX);
+        self.request_timeout.saturating_mul(requests)
+    }
+}
+
+/// Whether an address may receive webhooks when private targets are not allowed
+pub fn is_public(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            let [a, b, ..] = ip.octets();
+            // 100.64.0.0/10 is shared by the hosts behind a carrier-grade NAT
+            let shared = a == 100 && (b & 0b1100_0000) == 64;
+            !(ip.is_loopback()
+                || ip.is_private()
+                || ip.is_link_local()
+                || ip.is_unspecified()
+                || ip.is_broadcast()
+                || shared)
+        }
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(ip) => is_public(IpAddr::V4(ip)),
+            None => {
+                !(ip.is_loopback()
+                    || ip.is_unspecified()
+                    || ip.is_unique_local()
+                    || ip.is_unicast_link_local())
+            }
+        },
+    }
+}
+
+/// Parses the url of a webhook, which must be http(s). Unless `allow_private_targets`, hosts
+/// given by address must be public and hosts given by name are checked when resolved.
+pub fn parse_url(url: &str, allow_private_targets: bool) -> Result<Url, String> {
+    let parsed = Url::parse(url).map_err(|e| format!("Invalid webhook url {url}: {e}"))?;
+    if !matches!(parsed.scheme(), "http" | "https") {
+        return Err(format!("Webhook url {url} is not http(s)"));
+    }
+
+    let Some(host) = parsed.host_str() else {
+        return Err(format!("Webhook url {url} has no host"));
+    };
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+    let private = !allow_private_targets
+        && match host.parse::<IpAddr>() {
+            Ok(ip) => !is_public(ip),
+            Err(_) => {
+                let domain = host.trim_end_matches('.');
+                domain == "localhost" || domain.ends_with(".localhost")
+            }
+        };
+    if private {
+        return Err(format!("Webhook url {url} targets a private address"));
+    }
+
+    Ok(parsed)
+}
+
+/// Resolves host names to their public addresses only, so that a name pointing to an internal
+/// host cannot be used to reach it
+struct PublicResolver;
+
+impl Resolve for PublicResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        Box::pin(async move {
+            let addrs: Vec<SocketAddr> = tokio::net::lookup_host((name.as_str(), 0))
+                .await?
+                .filter(|addr| is_public(addr.ip()))
+                .collect();
+            if addrs.is_empty() {
+                return Err(format!("{} has no public address", name.as_str()).into());
+            }
+
+            Ok(Box::new(addrs.into_iter()) as Addrs)
+        })
+    }
+}
+
+/// HMAC-SHA256 of the request body, hex encoded and prefixed with the algorithm name
+pub fn sign(secret: &str, body: &[u8]) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any size");
+    mac.update(body);
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+/// Delay before the next attempt, given the number of attempts already made
+pub fn backoff(config: &DeliveryConfig, attempts: i32) -> Duration {
+    let exponent = attempts.clamp(0, 31) as u32;
+    config
+        .base_backoff
+        .saturating_mul(2u32.saturating_pow(exponent))
+        .min(config.max_backoff)
+}
+
+/// Background task delivering the webhook outbox until the runtime shuts down
+pub async fn run_delivery_worker(
+    webhooks_dao: Arc<dyn WebhooksDao + Send + Sync>,
+    config: DeliveryConfig,
+) {
+    let mut client = reqwest::Client::builder().timeout(config.request_timeout);
+    if !config.allow_private_targets {
+        client = client.dns_resolver(Arc::new(PublicResolver));
+    }
+    let client = client.build().expect("Failed to build webhook HTTP client");
+    let mut interval = tokio::time::interval(config.poll_interval);
+
+    loop {
+        interval.tick().await;
+        if let Err(e) = deliver_due(webhooks_dao.as_ref(), &client, &config).await {
+            error!("Failed to deliver webhooks: {:?}", e);
+        }
+    }
+}
+
+/// Attempts every delivery currently due once, returns how many were attempted
+pub async fn deliver_due(
+    webhooks_dao: &(dyn WebhooksDao + Send + Sync),
+    client: &reqwest::Client,
+    config: &DeliveryConfig,
+) -> Result<usize, DBError> {
+    let deliveries = webhooks_dao
+        .get_due_deliveries(config.batch_size, config.lease())
+        .await?;
+    let count = deliveries.len();
+
+    for delivery in deliveries {
+        let delivery_uuid = delivery.delivery_uuid.clone();
+        let attempts = delivery.attempts + 1;
+
+        match send(client, &delivery, config.allow_private_targets).await {
+            Ok(()) => webhooks_dao.mark_delivered(delivery_uuid).await?,
+            Err(e) if attempts >= config.max_attempts => {
+                warn!("Webhook delivery {delivery_uuid} dead-lettered: {e}");
+                webhooks_dao.dead_letter_delivery(delivery_uuid, e).await?
+            }
+            Err(e) => {
+                let delay = backoff(config, attempts - 1);
+                warn!("Webhook delivery {delivery_uuid} failed, retrying in {delay:?}: {e}");
+                webhooks_dao
+                    .reschedule_delivery(delivery_uuid, e, delay)
+                    .await?
+            }
+        }
+    }
+
+    Ok(count)
+}
+
+async fn send(
+    client: &reqwest::Client,
+    delivery: &WebhookDelivery,
+    allow_private_targets: bool,
+) -> Result<(), String> {
+    // Also refuses the webhooks registered before their urls were checked
+    let url = parse_url(&delivery.url, allow_private_targets)?;
+    let body = delivery.payload.clone().into_bytes();
+    let signature = sign(&delivery.secret, &body);
+
+    let response = client
+        .post(url)
+        .header(reqwest::header::CONTENT_TYPE, "application/json")
+        .header(EVENT_HEADER, &delivery.event)
+        .header(DELIVERY_HEADER, &delivery.delivery_uuid)
+        .header(SIGNATURE_HEADER, signature)
+        .body(body)
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+
+    if response.status().is_success() {
+        Ok(())
+    } else {
+        Err(format!("Receiver responded with {}", response.status()))
+    }
+}
+
+// ***********************************************************
+//                           Tests
+// ***********************************************************
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        models::{Question, Webhook, WebhookEvent},
+        persistance::{
+            questions_dao::{QuestionsDao, QuestionsDaoImpl},
+            unit_of_work::Autocommit,
+            webhooks_dao::WebhooksDaoImpl,
+        },
+    };
+    use axum::{extract::State, http::HeaderMap, http::StatusCode, routing::post, Router};
+    use sqlx::PgPool;
+    use tokio::sync::mpsc;
+
+    type Received = (HeaderMap, String);
+
+    /// Spawns a local receiver answering every request with `status`
+    async fn spawn_receiver(status: StatusCode) -> (String, mpsc::UnboundedReceiver<Received>) {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        let app = Router::new()
+            .route(
+                "/hook",
+                post(
+                    move |State(sender): State<mpsc::UnboundedSender<Received>>,
+                          headers: HeaderMap,
+                          body: String| async move {
+                        sender.send((headers, body)).unwrap();
+                        status
+                    },
+                ),
+            )
+            .with_state(sender);
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        (format!("http://{addr}/hook"), receiver)
+    }
+
+    async fn delivery_state(pool: &PgPool) -> (String, i32) {
+        sqlx::query_as::<_, (String, i32)>("SELECT status, attempts FROM webhook_deliveries")
+            .fetch_one(pool)
+            .await
+            .unwrap()
+    }
+
+    async fn make_due(pool: &PgPool) {
+        sqlx::query("UPDATE webhook_deliveries SET next_attempt_at = CURRENT_TIMESTAMP")
+            .execute(pool)
+            .await
+            .unwrap();
+    }
+
+    async fn subscribe_and_ask(pool: &PgPool, url: String) -> String {
+        let webhooks_dao = WebhooksDaoImpl::new(pool.clone());
+        let webhook = webhooks_dao
+            .create_webhook(
+                &mut Autocommit::new(pool.clone()),
+                Webhook {
+                    url,
+                    events: vec![WebhookEvent::QuestionCreated],
+                },
+            )
+            .await
+            .unwrap();
+
+        QuestionsDaoImpl::new(pool.clone())
+            .create_question(
+                &mut Autocommit::new(pool.clone()),
+                Question {
+                    title: "test title".to_owned(),
+                    description: "test description".to_owned(),
+                },
+                "user".to_owned(),
+            )
+            .await
+            .unwrap();
+
+        webhook.secret
+    }
+
+    #[test]
+    fn backoff_should_grow_exponentially_up_to_max() {
+        let config = DeliveryConfig {
+            base_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(10),
+            ..Default::default()
+        };
+
+        assert_eq!(backoff(&config, 0), Duration::from_secs(1));
+        assert_eq!(backoff(&config, 1), Duration::from_secs(2));
+        assert_eq!(backoff(&config, 3), Duration::from_secs(8));
+        assert_eq!(backoff(&config, 4), Duration::from_secs(10));
+        assert_eq!(backoff(&config, 100), Duration::from_secs(10));
+    }
+
+    #[test]
+    fn lease_should_outlast_a_batch_of_timeouts() {
+        let config = DeliveryConfig {
+            batch_size: 50,
+            request_timeout: Duration::from_secs(10),
+            ..Default::default()
+        };
+
+        assert!(config.lease() >= Duration::from_secs(500));
+    }
+
+    #[test]
+    fn parse_url_should_reject_private_targets() {
+        for url in [
+            "http://localhost/hook",
+            "http://api.localhost/hook",
+            "http://127.0.0.1/hook",
+            "http://10.1.2.3/hook",
+            "http://192.168.0.1/hook",
+            "http://169.254.169.254/latest/meta-data",
+            "http://100.64.0.1/hook",
+            "http://0.0.0.0/hook",
+            "http://[::1]/hook",
+            "http://[fd00::1]/hook",
+            "http://[fe80::1]/hook",
+            "http://[::ffff:127.0.0.1]/hook",
+        ] {
+            assert!(parse_url(url, false).is_err(), "{url} should be rejected");
+            assert!(parse_url(url, true).is_ok(), "{url} should be allowed");
+        }
+    }
+
+    #[test]
+    fn parse_url_should_accept_public_http_targets() {
+        for url in [
+            "https://example.com/hook",
+            "http://93.184.216.34:8080/hook",
+            "http://[2606:2800:220:1::]/hook",
+        ] {
+            assert!(parse_url(url, false).is_ok(), "{url} should be accepted");
+        }
+        for url in ["ftp://example.com/hook", "not a url", "file:///etc/passwd"] {
+            assert!(parse_url(url, true).is_err(), "{url} should be rejected");
+        }
+    }
+
+    #[sqlx::test]
+    async fn deliver_due_should_refuse_private_targets(pool: PgPool) {
+        let (url, mut received) = spawn_receiver(StatusCode::OK).await;
+        subscribe_and_ask(&pool, url).await;
+        let dao = WebhooksDaoImpl::new(pool.clone());
+
+        let attempted = deliver_due(&dao, &reqwest::Client::new(), &DeliveryConfig::default())
+            .await
+            .unwrap();
+        assert_eq!(attempted, 1);
+
+        assert!(received.try_recv().is_err());
+        assert_eq!(delivery_state(&pool).await, ("pending".to_owned(), 1));
+    }
+
+    #[sqlx::test]
+    async fn deliver_due_should_send_signed_payload(pool: PgPool) {
+        let (url, mut received) = spawn_receiver(StatusCode::OK).await;
+        let secret = subscribe_and_ask(&pool, url).await;
+        let dao = WebhooksDaoImpl::new(pool.clone());
+        let config = DeliveryConfig {
+            allow_private_targets: true,
+            ..Default::default()
+        };
+
+        let attempted = deliver_due(&dao, &reqwest::Client::new(), &config)
+            .await
+            .unwrap();
+        assert_eq!(attempted, 1);
+
+        let (headers, body) = received.recv().await.unwrap();
+        assert_eq!(headers[EVENT_HEADER], "question.created");
+        assert_eq!(
+            headers[SIGNATURE_HEADER],
+            sign(&secret, body.as_bytes()).as_str()
+        );
+        let body: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(body["data"]["title"], "test title");
+
+        assert_eq!(delivery_state(&pool).await, ("delivered".to_owned(), 1));
+
+        // Nothing left to deliver
+        let attempted = deliver_due(&dao, &reqwest::Client::new(), &config)
+            .await
+            .unwrap();
+        assert_eq!(attempted, 0);
+    }
+
+    #[sqlx::test]
+    async fn deliver_due_should_retry_then_dead_letter(pool: PgPool) {
+        let (url, mut received) = spawn_receiver(StatusCode::INTERNAL_SERVER_ERROR).await;
+        subscribe_and_ask(&pool, url).await;
+        let dao = WebhooksDaoImpl::new(pool.clone());
+        let config = DeliveryConfig {
+            max_attempts: 2,
+            allow_private_targets: true,
+            ..Default::default()
+        };
+
+        deliver_due(&dao, &reqwest::Client::new(), &config)
+            .await
+            .unwrap();
+        received.recv().await.unwrap();
+        assert_eq!(delivery_state(&pool).await, ("pending".to_owned(), 1));
+
+        // Backoff has not elapsed yet
+        let attempted = deliver_due(&dao, &reqwest::Client::new(), &config)
+            .await
+            .unwrap();
+        assert_eq!(attempted, 0);
+
+        make_due(&pool).await;
+        deliver_due(&dao, &reqwest::Client::new(), &config)
+            .await
+            .unwrap();
+        received.recv().await.unwrap();
+        assert_eq!(delivery_state(&pool).await, ("dead".to_owned(), 2));
+
+        make_due(&pool).await;
+        let attempted = deliver_due(&dao, &reqwest::Client::new(), &config)
+            .await
+            .unwrap();
+        assert_eq!(attempted, 0);
+    }
+}